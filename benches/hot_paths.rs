@@ -0,0 +1,116 @@
+use bazuka::blockchain::Blockchain;
+use bazuka::core::{Amount, Hasher, ZkHasher};
+use bazuka::crypto::merkle::MerkleTree;
+use bazuka::db::{KvStore, LevelDbKvStore, RamKvStore, StringKey, WriteOp};
+use bazuka::test_utils::*;
+use bazuka::zk::{ZkDataLocator, ZkDataPairs, ZkScalar, ZkStateModel};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+const BLOCK_TXS: usize = 100;
+
+fn block_application(c: &mut Criterion) {
+    let mut rng = ChaChaRng::seed_from_u64(0);
+    let validator = validator_wallet();
+    let alice = wallet("ALICE");
+    let chain = funded_chain(&validator, &[(&alice, Amount(1_000_000_000))]);
+    let txs = random_transactions(&mut rng, &chain, &alice, BLOCK_TXS).unwrap();
+    let block = mine_block(&mut chain.fork_on_ram(), &validator, &txs).unwrap();
+
+    let mut group = c.benchmark_group("block_application");
+    group.throughput(Throughput::Elements(block.body.len() as u64));
+    group.bench_function("extend", |b| {
+        b.iter_batched(
+            || chain.fork_on_ram(),
+            |mut fork| fork.extend(1, std::slice::from_ref(&block)).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn signature_verification(c: &mut Criterion) {
+    let mut rng = ChaChaRng::seed_from_u64(0);
+    let alice = wallet("ALICE");
+    let chain = funded_chain(&validator_wallet(), &[(&alice, Amount(1_000_000))]);
+    let tx = random_transactions(&mut rng, &chain, &alice, 1)
+        .unwrap()
+        .remove(0)
+        .tx;
+    c.bench_function("signature_verification", |b| {
+        b.iter(|| assert!(tx.verify_signature()))
+    });
+}
+
+fn merkle_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle_tree");
+    for leaves in [16u64, 256, 4096] {
+        let data = (0..leaves)
+            .map(|i| <Hasher as bazuka::core::hash::Hash>::hash(&i.to_le_bytes()))
+            .collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(leaves));
+        group.bench_with_input(BenchmarkId::from_parameter(leaves), &data, |b, data| {
+            b.iter(|| MerkleTree::<Hasher>::new(data.clone()).root())
+        });
+    }
+    group.finish();
+}
+
+fn kv_store_batch_writes(c: &mut Criterion) {
+    const BATCH: u64 = 1000;
+    let ops = (0..BATCH)
+        .map(|i| WriteOp::Put(StringKey::new(&format!("KEY-{:010}", i)), i.into()))
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("kv_store_batch_writes");
+    group.throughput(Throughput::Elements(BATCH));
+    group.bench_function("ram", |b| {
+        b.iter_batched(
+            RamKvStore::new,
+            |mut db| db.update(&ops).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    let dir = tempdir::TempDir::new("bazuka-bench").unwrap();
+    let mut db = LevelDbKvStore::new(dir.path(), 64).unwrap();
+    group.bench_function("leveldb", |b| b.iter(|| db.update(&ops).unwrap()));
+    group.finish();
+}
+
+fn zk_state_compression(c: &mut Criterion) {
+    let model = ZkStateModel::List {
+        log4_size: 5,
+        item_type: Box::new(ZkStateModel::Struct {
+            field_types: vec![ZkStateModel::Scalar, ZkStateModel::Scalar],
+        }),
+    };
+    let mut group = c.benchmark_group("zk_state_compression");
+    for items in [16u64, 256] {
+        let data = ZkDataPairs(
+            (0..items)
+                .flat_map(|i| {
+                    [
+                        (ZkDataLocator(vec![i, 0]), ZkScalar::from(i)),
+                        (ZkDataLocator(vec![i, 1]), ZkScalar::from(i * 2)),
+                    ]
+                })
+                .collect(),
+        );
+        group.throughput(Throughput::Elements(items));
+        group.bench_with_input(BenchmarkId::from_parameter(items), &data, |b, data| {
+            b.iter(|| model.compress::<ZkHasher>(data).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    block_application,
+    signature_verification,
+    merkle_tree,
+    kv_store_batch_writes,
+    zk_state_compression
+);
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+use bazuka::core::{Hasher, Signer};
+use bazuka::crypto::dilithium::Dilithium;
+use bazuka::crypto::SignatureScheme;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const MSG: &[u8] = b"a transaction worth of bytes to be signed by a wallet";
+
+fn bench_scheme<S: SignatureScheme>(c: &mut Criterion, name: &str) {
+    let (pk, sk) = S::generate_keys(b"ABC");
+    let sig = S::sign(&sk, MSG);
+    // Sizes as they would be stored in a block
+    println!(
+        "{}: public-key {} bytes, signature {} bytes",
+        name,
+        bincode::serialize(&pk).unwrap().len(),
+        bincode::serialize(&sig).unwrap().len()
+    );
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("sign", |b| b.iter(|| S::sign(&sk, MSG)));
+    group.bench_function("verify", |b| b.iter(|| assert!(S::verify(&pk, MSG, &sig))));
+    group.finish();
+}
+
+fn signatures(c: &mut Criterion) {
+    bench_scheme::<Signer>(c, "ed25519");
+    bench_scheme::<Dilithium<Hasher>>(c, "dilithium");
+}
+
+criterion_group!(benches, signatures);
+criterion_main!(benches);
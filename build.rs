@@ -0,0 +1,99 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    grpc::compile();
+}
+
+/// Generates the service stubs of `proto/bazuka.proto`. Messages are defined by hand
+/// in `src/node/grpc/proto.rs`, so that building does not require `protoc`.
+#[cfg(feature = "grpc")]
+mod grpc {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    const PROTO: &str = "crate::node::grpc::proto";
+
+    fn method(name: &str, route: &str, input: &str, output: &str, streaming: bool) -> Method {
+        let builder = Method::builder()
+            .name(name)
+            .route_name(route)
+            .input_type(format!("{}::{}", PROTO, input))
+            .output_type(format!("{}::{}", PROTO, output))
+            .codec_path("tonic::codec::ProstCodec");
+        if streaming {
+            builder.server_streaming().build()
+        } else {
+            builder.build()
+        }
+    }
+
+    pub fn compile() {
+        let service = Service::builder()
+            .name("Node")
+            .package("bazuka.v1")
+            .method(method(
+                "get_stats",
+                "GetStats",
+                "GetStatsRequest",
+                "GetStatsResponse",
+                false,
+            ))
+            .method(method(
+                "get_account",
+                "GetAccount",
+                "GetAccountRequest",
+                "GetAccountResponse",
+                false,
+            ))
+            .method(method(
+                "get_balance",
+                "GetBalance",
+                "GetBalanceRequest",
+                "GetBalanceResponse",
+                false,
+            ))
+            .method(method(
+                "get_headers",
+                "GetHeaders",
+                "GetHeadersRequest",
+                "GetHeadersResponse",
+                false,
+            ))
+            .method(method(
+                "get_blocks",
+                "GetBlocks",
+                "GetBlocksRequest",
+                "GetBlocksResponse",
+                false,
+            ))
+            .method(method(
+                "get_mempool",
+                "GetMempool",
+                "GetMempoolRequest",
+                "GetMempoolResponse",
+                false,
+            ))
+            .method(method(
+                "transact",
+                "Transact",
+                "TransactRequest",
+                "TransactResponse",
+                false,
+            ))
+            .method(method(
+                "stream_blocks",
+                "StreamBlocks",
+                "StreamBlocksRequest",
+                "Block",
+                true,
+            ))
+            .method(method(
+                "stream_transactions",
+                "StreamTransactions",
+                "StreamTransactionsRequest",
+                "MempoolTransaction",
+                true,
+            ))
+            .build();
+        Builder::new().compile(&[service]);
+    }
+}
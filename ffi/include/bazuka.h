@@ -0,0 +1,51 @@
+/* C interface to the Bazuka wallet, implemented in ffi/src/lib.rs.
+ *
+ * Strings returned by these functions are owned by the caller and must be
+ * released through bazuka_string_free(). Functions returning pointers return
+ * NULL on failure, in which case bazuka_last_error() describes the error. */
+
+#ifndef BAZUKA_H
+#define BAZUKA_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct BazukaWallet BazukaWallet;
+
+/* Last error happened on the calling thread, or NULL. Owned by the library. */
+const char *bazuka_last_error(void);
+
+void bazuka_string_free(char *s);
+
+/* New random 24-word mnemonic phrase. Wallets are restored from 24-word
+ * phrases as well as the 12-word ones of older wallets. */
+char *bazuka_mnemonic_generate(void);
+
+/* Account `index` of the wallet with the given mnemonic phrase. Must be
+ * released through bazuka_wallet_free(). */
+BazukaWallet *bazuka_wallet_from_mnemonic(const char *mnemonic, uint32_t index);
+
+void bazuka_wallet_free(BazukaWallet *wallet);
+
+char *bazuka_wallet_address(const BazukaWallet *wallet);
+
+bool bazuka_address_is_valid(const char *address);
+
+/* Hex-encoded signature of `len` bytes at `msg` */
+char *bazuka_wallet_sign(const BazukaWallet *wallet, const uint8_t *msg, size_t len);
+
+/* Signed payment of `amount` Ziesha base units to `dst`, as the JSON `tx` field
+ * of the /transact endpoint. `nonce` is the account's current nonce plus one. */
+char *bazuka_wallet_send(const BazukaWallet *wallet, const char *dst, uint64_t amount,
+                         uint64_t fee, uint32_t nonce, const char *memo);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
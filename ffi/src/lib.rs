@@ -0,0 +1,243 @@
+//! C interface to the Bazuka wallet, so that apps which cannot link Rust code
+//! (Swift, Kotlin, ...) can create keys and sign transactions with the exact same
+//! code as the node. Declarations are in `include/bazuka.h`.
+//!
+//! Strings returned by the library are owned by the caller and must be released
+//! through `bazuka_string_free`. Functions returning pointers return `NULL` on
+//! failure, in which case `bazuka_last_error` describes what went wrong.
+
+use bazuka::core::{Address, GeneralTransaction, Money, ParseAddressError};
+use bazuka::wallet::{TxBuilder, Wallet, WalletCollection, WalletType};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error<E: ToString>(err: E) {
+    let msg = CString::new(err.to_string().replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(msg));
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    match CString::new(s) {
+        Ok(s) => s.into_raw(),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+unsafe fn as_str<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("unexpected null string".into());
+    }
+    CStr::from_ptr(s).to_str().map_err(|e| e.to_string())
+}
+
+/// Keys of a single account of a wallet
+pub struct BazukaWallet(TxBuilder);
+
+/// Description of the last error happened on the calling thread, or `NULL`. The
+/// string is owned by the library and valid until the next failing call.
+#[no_mangle]
+pub extern "C" fn bazuka_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// # Safety
+///
+/// `s` must be a string returned by this library, or `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn bazuka_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// New random 24-word mnemonic phrase. Wallets are restored from 24-word phrases as
+/// well as the 12-word ones of older wallets.
+#[no_mangle]
+pub extern "C" fn bazuka_mnemonic_generate() -> *mut c_char {
+    match rand_mnemonic::rngs::OsRng::new() {
+        Ok(mut rng) => into_c_string(
+            WalletCollection::create(&mut rng, None)
+                .mnemonic()
+                .to_string(),
+        ),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Account `index` of the wallet with the given mnemonic phrase, the same account
+/// `bazuka wallet` uses for index 0. Must be released through `bazuka_wallet_free`.
+///
+/// # Safety
+///
+/// `mnemonic` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bazuka_wallet_from_mnemonic(
+    mnemonic: *const c_char,
+    index: u32,
+) -> *mut BazukaWallet {
+    let mnemonic = match as_str(mnemonic).and_then(|m| {
+        m.parse::<bip39::Mnemonic>()
+            .map_err(|e| format!("invalid mnemonic: {}", e))
+    }) {
+        Ok(m) => m,
+        Err(e) => {
+            set_last_error(e);
+            return ptr::null_mut();
+        }
+    };
+    let wallet = Wallet::new(WalletType::User(index as usize), mnemonic);
+    Box::into_raw(Box::new(BazukaWallet(wallet.tx_builder())))
+}
+
+/// # Safety
+///
+/// `wallet` must be returned by `bazuka_wallet_from_mnemonic` and not freed yet, or
+/// `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn bazuka_wallet_free(wallet: *mut BazukaWallet) {
+    if !wallet.is_null() {
+        drop(Box::from_raw(wallet));
+    }
+}
+
+/// # Safety
+///
+/// `wallet` must be a valid wallet pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bazuka_wallet_address(wallet: *const BazukaWallet) -> *mut c_char {
+    into_c_string((*wallet).0.get_address().to_string())
+}
+
+/// Whether `address` is a well-formed account address
+///
+/// # Safety
+///
+/// `address` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bazuka_address_is_valid(address: *const c_char) -> bool {
+    match as_str(address) {
+        Ok(addr) => addr.parse::<Address>().is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Hex-encoded signature of the given bytes
+///
+/// # Safety
+///
+/// `wallet` must be a valid wallet pointer and `msg` must point to `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bazuka_wallet_sign(
+    wallet: *const BazukaWallet,
+    msg: *const u8,
+    len: usize,
+) -> *mut c_char {
+    let msg = if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(msg, len)
+    };
+    into_c_string((*wallet).0.sign(msg).to_string())
+}
+
+/// Signed payment of `amount` Ziesha (in base units, with a fee of `fee`) to `dst`,
+/// encoded as the JSON `tx` field expected by the `/transact` endpoint of nodes.
+/// `nonce` is the current nonce of the account plus one.
+///
+/// # Safety
+///
+/// `wallet` must be a valid wallet pointer, `dst` and `memo` must be valid
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn bazuka_wallet_send(
+    wallet: *const BazukaWallet,
+    dst: *const c_char,
+    amount: u64,
+    fee: u64,
+    nonce: u32,
+    memo: *const c_char,
+) -> *mut c_char {
+    let result = (|| {
+        let dst: Address = as_str(dst)?
+            .parse()
+            .map_err(|e: ParseAddressError| format!("invalid destination: {}", e))?;
+        let memo = as_str(memo)?.to_string();
+        let tx = (*wallet).0.create_transaction(
+            memo,
+            dst,
+            Money::ziesha(amount),
+            Money::ziesha(fee),
+            nonce,
+        );
+        serde_json::to_string(&GeneralTransaction::TransactionAndDelta(tx))
+            .map_err(|e| e.to_string())
+    })();
+    match result {
+        Ok(json) => into_c_string(json),
+        Err(e) => {
+            set_last_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        let owned = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { bazuka_string_free(s) };
+        owned
+    }
+
+    #[test]
+    fn test_wallet_signs_transactions() {
+        let mnemonic = CString::new(take_string(bazuka_mnemonic_generate())).unwrap();
+        let wallet = unsafe { bazuka_wallet_from_mnemonic(mnemonic.as_ptr(), 0) };
+        assert!(!wallet.is_null());
+
+        let expected = Wallet::new(
+            WalletType::User(0),
+            mnemonic.to_str().unwrap().parse().unwrap(),
+        )
+        .tx_builder()
+        .get_address();
+        let addr = take_string(unsafe { bazuka_wallet_address(wallet) });
+        assert_eq!(addr, expected.to_string());
+        let addr = CString::new(addr).unwrap();
+        assert!(unsafe { bazuka_address_is_valid(addr.as_ptr()) });
+
+        let memo = CString::new("Hi!").unwrap();
+        let tx = take_string(unsafe {
+            bazuka_wallet_send(wallet, addr.as_ptr(), 100, 2, 1, memo.as_ptr())
+        });
+        let tx: GeneralTransaction = serde_json::from_str(&tx).unwrap();
+        assert!(tx.verify_signature());
+        assert_eq!(tx.sender().to_string(), expected.to_string());
+
+        unsafe { bazuka_wallet_free(wallet) };
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let invalid = CString::new("not a mnemonic").unwrap();
+        assert!(unsafe { bazuka_wallet_from_mnemonic(invalid.as_ptr(), 0) }.is_null());
+        assert!(!bazuka_last_error().is_null());
+        assert!(!unsafe { bazuka_address_is_valid(invalid.as_ptr()) });
+        assert!(!unsafe { bazuka_address_is_valid(ptr::null()) });
+    }
+}
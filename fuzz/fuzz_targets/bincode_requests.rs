@@ -0,0 +1,15 @@
+#![no_main]
+
+//! Bodies of the `/bincode/*` endpoints, which are sent by untrusted peers
+
+use bazuka::client::messages::*;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = bincode::deserialize::<PostBlockRequest>(data);
+    let _ = bincode::deserialize::<TransactRequest>(data);
+    let _ = bincode::deserialize::<HandshakeRequest>(data);
+    let _ = bincode::deserialize::<PostValidatorClaimRequest>(data);
+    let _ = bincode::deserialize::<PostMpnSolutionRequest>(data);
+    let _ = bincode::deserialize::<PostMpnWorkerRequest>(data);
+});
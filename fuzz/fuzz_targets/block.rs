@@ -0,0 +1,16 @@
+#![no_main]
+
+use bazuka::core::Block;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(block) = bincode::deserialize::<Block>(data) {
+        block.header.hash();
+        block.merkle_tree();
+        for tx in block.body.iter() {
+            tx.verify_signature();
+            tx.hash();
+            tx.size();
+        }
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use bazuka::core::Header;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(header) = bincode::deserialize::<Header>(data) {
+        header.hash();
+    }
+});
@@ -0,0 +1,24 @@
+#![no_main]
+
+//! Query strings and JSON bodies parsed by `node_service`
+
+use bazuka::client::messages::*;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(qs) = std::str::from_utf8(data) {
+        let _ = serde_qs::from_str::<GetBlocksRequest>(qs);
+        let _ = serde_qs::from_str::<GetHeadersRequest>(qs);
+        let _ = serde_qs::from_str::<GetExplorerBlocksRequest>(qs);
+        let _ = serde_qs::from_str::<GetExplorerMpnAccountsRequest>(qs);
+        let _ = serde_qs::from_str::<GetAccountRequest>(qs)
+            .map(|req| req.address.parse::<bazuka::core::Address>());
+        let _ = serde_qs::from_str::<GetMpnAccountRequest>(qs);
+        let _ = serde_qs::from_str::<GetBalanceRequest>(qs)
+            .map(|req| req.token_id.parse::<bazuka::core::TokenId>());
+        let _ = serde_qs::from_str::<GetDelegationsRequest>(qs);
+        let _ = serde_qs::from_str::<GetMempoolRequest>(qs);
+    }
+    let _ = serde_json::from_slice::<TransactRequest>(data);
+    let _ = serde_json::from_slice::<FaucetRequest>(data);
+});
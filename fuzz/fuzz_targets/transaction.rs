@@ -0,0 +1,20 @@
+#![no_main]
+
+use bazuka::core::{GeneralTransaction, Transaction, TransactionAndDelta};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(tx) = bincode::deserialize::<Transaction>(data) {
+        tx.verify_signature();
+        tx.hash();
+        tx.size();
+    }
+    if let Ok(tx) = bincode::deserialize::<TransactionAndDelta>(data) {
+        tx.tx.verify_signature();
+    }
+    if let Ok(tx) = bincode::deserialize::<GeneralTransaction>(data) {
+        tx.verify_signature();
+        tx.sender();
+        tx.nonce();
+    }
+});
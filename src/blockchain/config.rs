@@ -1,6 +1,6 @@
-use crate::core::{Address, Block, Ratio, TokenId};
+use crate::core::{hash::Hash, Address, Block, Hasher, Ratio, TokenId};
 use crate::mpn::MpnConfig;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 #[derive(Clone)]
 pub struct BlockchainConfig {
@@ -8,9 +8,16 @@ pub struct BlockchainConfig {
     pub genesis: Block,
     pub reward_ratio: u64,
     pub max_block_size: usize,
+    /// Maximum execution cost of a single contract transaction, see `execution_cost`
+    pub max_tx_execution_cost: u64,
+    /// Maximum execution cost of all the non-MPN transactions of a block
+    pub max_block_execution_cost: u64,
     pub ziesha_token_id: TokenId,
     pub mpn_config: MpnConfig,
     pub testnet_height_limit: Option<u64>,
+    /// Known header hashes by block number. Headers conflicting with them are invalid
+    /// and the chain is never reorganized below the latest one it has passed.
+    pub checkpoints: BTreeMap<u64, <Hasher as Hash>::Output>,
     pub max_memo_length: usize,
     pub slot_duration: u32,
     pub slot_per_epoch: u32,
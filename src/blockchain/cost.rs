@@ -0,0 +1,86 @@
+//! Execution cost of transactions, in abstract units. Verifying zk proofs dominates
+//! the time it takes to apply contract updates, followed by checking the signatures
+//! of contract payments and writing the state cells of the delta.
+
+use crate::core::{ContractId, ContractUpdate, Transaction, TransactionData};
+
+/// Verifying a single zk proof
+pub const PROOF_COST: u64 = 1000;
+/// Checking a single deposit or withdraw of a contract update
+pub const PAYMENT_COST: u64 = 10;
+/// Writing a single state cell of a contract
+pub const STATE_CELL_COST: u64 = 1;
+
+/// Execution cost of a transaction. Only contract transactions are metered, the rest
+/// cost nothing.
+pub fn execution_cost(tx: &Transaction) -> u64 {
+    match &tx.data {
+        TransactionData::CreateContract { state, .. } => {
+            state.as_ref().map(|s| s.0.len() as u64).unwrap_or_default() * STATE_CELL_COST
+        }
+        TransactionData::UpdateContract { updates, delta, .. } => {
+            let payments = updates
+                .iter()
+                .map(|update| match update {
+                    ContractUpdate::Deposit { deposits, .. } => deposits.len() as u64,
+                    ContractUpdate::Withdraw { withdraws, .. } => withdraws.len() as u64,
+                    ContractUpdate::FunctionCall { .. } => 0,
+                })
+                .sum::<u64>();
+            let cells = delta.as_ref().map(|d| d.0.len() as u64).unwrap_or_default();
+            (updates.len() as u64)
+                .saturating_mul(PROOF_COST)
+                .saturating_add(payments.saturating_mul(PAYMENT_COST))
+                .saturating_add(cells.saturating_mul(STATE_CELL_COST))
+        }
+        _ => 0,
+    }
+}
+
+/// Whether the transaction updates the given contract. MPN updates are required in
+/// every block and their size is fixed by the chain config, so they are not metered.
+pub fn updates_contract(tx: &Transaction, contract_id: &ContractId) -> bool {
+    matches!(&tx.data, TransactionData::UpdateContract { contract_id: id, .. } if id == contract_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Amount, Money, Signature};
+    use crate::zk::{ZkCompressedState, ZkDataLocator, ZkDeltaPairs, ZkProof, ZkScalar};
+
+    #[test]
+    fn test_execution_cost() {
+        let tx = |data| Transaction {
+            memo: String::new(),
+            src: None,
+            data,
+            nonce: 0,
+            fee: Money::ziesha(0),
+            sig: Signature::Unsigned,
+        };
+        let call = ContractUpdate::FunctionCall {
+            function_id: 0,
+            next_state: ZkCompressedState::default(),
+            proof: ZkProof::Dummy(true),
+            fee: Money::ziesha(0),
+        };
+        let delta = ZkDeltaPairs(
+            (0..5)
+                .map(|i| (ZkDataLocator(vec![i]), Some(ZkScalar::from(Amount(i)))))
+                .collect(),
+        );
+        assert_eq!(
+            execution_cost(&tx(TransactionData::UpdateContract {
+                contract_id: Default::default(),
+                updates: vec![call.clone(), call],
+                delta: Some(delta),
+            })),
+            2 * PROOF_COST + 5 * STATE_CELL_COST
+        );
+        assert_eq!(
+            execution_cost(&tx(TransactionData::RegularSend { entries: vec![] })),
+            0
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use super::ReplayLogError;
 use crate::core::ConvertRatioError;
 use crate::db::{keys::ParseDbKeyError, KvStoreError};
 use crate::zk::{StateManagerError, ZkError};
@@ -19,6 +20,22 @@ pub enum BlockchainError {
     Inconsistency,
     #[error("block not found")]
     BlockNotFound,
+    #[error("block has been pruned")]
+    BlockPruned,
+    #[error("fork point is older than the retained rollback data")]
+    ReorgTooDeep,
+    #[error("replay log error happened: {0}")]
+    ReplayLogError(#[from] ReplayLogError),
+    #[error("cannot reorganize the chain below a checkpoint")]
+    ReorgBelowCheckpoint,
+    #[error("header does not match the checkpoint at its height")]
+    CheckpointMismatch,
+    #[error("address index is disabled on this node")]
+    AddressIndexDisabled,
+    #[error("snapshots can only be imported into a chain holding the genesis block")]
+    SnapshotOnNonEmptyChain,
+    #[error("snapshot invalid")]
+    InvalidSnapshot,
     #[error("cannot extend from the genesis block")]
     ExtendFromGenesis,
     #[error("cannot extend from very future blocks")]
@@ -31,12 +48,24 @@ pub enum BlockchainError {
     InvalidMerkleRoot,
     #[error("transaction nonce invalid")]
     InvalidTransactionNonce,
+    #[error("transaction nonce is already used")]
+    TransactionNonceUsed,
+    #[error("transaction nonce is too far ahead of the pending transactions of the sender")]
+    TransactionNonceGap,
+    #[error("transaction is already pending")]
+    TransactionAlreadyPending,
+    #[error("replacing a pending transaction requires a higher fee")]
+    ReplacementFeeTooLow,
+    #[error("sender has too many pending transactions")]
+    TooManyPendingTransactions,
     #[error("block timestamp is in past")]
     InvalidEpochSlot,
     #[error("miner reward not present")]
     MinerRewardNotFound,
     #[error("illegal access to treasury funds")]
     IllegalTreasuryAccess,
+    #[error("transaction destination is not a valid address")]
+    InvalidDestinationAddress,
     #[error("miner reward transaction is invalid")]
     InvalidMinerReward,
     #[error("contract not found")]
@@ -49,6 +78,10 @@ pub enum BlockchainError {
     IncorrectZkProof,
     #[error("block too big")]
     BlockTooBig,
+    #[error("transaction execution cost is too high")]
+    ExecutionCostTooHigh,
+    #[error("block execution cost is too high")]
+    BlockExecutionCostTooHigh,
     #[error("no blocks to roll back")]
     NoBlocksToRollback,
     #[error("zk error happened: {0}")]
@@ -89,6 +122,10 @@ pub enum BlockchainError {
     TokenBadNameSymbol,
     #[error("only ziesha fees are accepted!")]
     OnlyZieshaFeesAccepted,
+    #[error("transaction fee is lower than the minimum relay fee")]
+    FeeTooLow,
+    #[error("contract transactions of this kind are not accepted by the node")]
+    ContractTransactionNotAccepted,
     #[error("transaction memo is too long")]
     MemoTooLong,
     #[error("Wrong validator has built the block!")]
@@ -1,12 +1,21 @@
-use super::{Blockchain, BlockchainError, TransactionStats};
+use super::{updates_contract, Blockchain, BlockchainError, TransactionStats};
 use crate::core::{
-    Address, Amount, GeneralAddress, GeneralTransaction, MpnDeposit, MpnWithdraw, NonceGroup,
-    Signature, TokenId, TransactionAndDelta,
+    hash::Hash, Address, Amount, ContractId, ContractUpdate, GeneralAddress, GeneralTransaction,
+    Hasher, MpnDeposit, MpnWithdraw, NonceGroup, Signature, TokenId, Transaction,
+    TransactionAndDelta, TransactionData,
 };
 use crate::db::KvStore;
 use crate::zk::MpnTransaction;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Number of recently rejected transactions whose rejection reason is remembered
+const MAX_REJECTIONS: usize = 1024;
+/// Transactions this far ahead of the next nonce of their sender are rejected
+const MAX_NONCE_GAP: u32 = 16;
+/// Maximum number of transactions of a single sender waiting for missing nonces
+const MAX_QUEUED_TXS: usize = 16;
 
 // Allow transaction senders to commit on the time they submitted their transaction, as a
 // solution for selecting the next tx from the sender in case there are txs with equal nonces.
@@ -44,10 +53,29 @@ impl Nonced for TransactionAndDelta {
     }
 }
 
+// Fees not paid in Ziesha are worth nothing to the validators
+fn ziesha_fee(tx: &GeneralTransaction) -> u64 {
+    let fee = tx.fee();
+    if fee.token_id == TokenId::Ziesha {
+        fee.amount.into()
+    } else {
+        0
+    }
+}
+
+// Compares fee-per-byte of two transactions without losing precision.
+fn cmp_fee_rate(a: &GeneralTransaction, b: &GeneralTransaction) -> Ordering {
+    (ziesha_fee(a) as u128 * b.size() as u128).cmp(&(ziesha_fee(b) as u128 * a.size() as u128))
+}
+
+/// Transactions of a single sender. The ones right after the nonce of the sender
+/// are ready to be included in blocks, the ones after a gap are queued until the
+/// missing nonces arrive.
 #[derive(Debug, Clone)]
 pub struct SingleMempool {
     nonce: u32,
     txs: VecDeque<(GeneralTransaction, TransactionStats)>,
+    future: BTreeMap<u32, (GeneralTransaction, TransactionStats)>,
 }
 
 impl SingleMempool {
@@ -55,30 +83,70 @@ impl SingleMempool {
         Self {
             nonce,
             txs: Default::default(),
+            future: Default::default(),
         }
     }
     fn len(&self) -> usize {
-        self.txs.len()
-    }
-    fn first_tx(&self) -> Option<&(GeneralTransaction, TransactionStats)> {
-        self.txs.front()
+        self.txs.len() + self.future.len()
     }
     fn first_nonce(&self) -> Option<u32> {
-        self.first_tx().map(|(tx, _)| tx.nonce())
+        self.txs.front().map(|(tx, _)| tx.nonce())
     }
-    fn last_nonce(&self) -> Option<u32> {
-        self.txs.back().map(|(tx, _)| tx.nonce())
+    fn next_nonce(&self) -> u32 {
+        self.txs
+            .back()
+            .map(|(tx, _)| tx.nonce())
+            .unwrap_or(self.nonce)
+            + 1
     }
-    fn applicable(&self, tx: &GeneralTransaction) -> bool {
-        if let Some(last_nonce) = self.last_nonce() {
-            tx.nonce() == last_nonce + 1
-        } else {
-            self.nonce + 1 == tx.nonce()
+    /// The transaction with the highest nonce, either queued or ready
+    fn last_tx(&self) -> Option<&(GeneralTransaction, TransactionStats)> {
+        self.future.values().next_back().or_else(|| self.txs.back())
+    }
+    fn pop_last(&mut self) {
+        if self.future.pop_last().is_none() {
+            self.txs.pop_back();
         }
     }
+    fn get(&self, nonce: u32) -> Option<&GeneralTransaction> {
+        self.txs
+            .iter()
+            .chain(self.future.values())
+            .map(|(tx, _)| tx)
+            .find(|tx| tx.nonce() == nonce)
+    }
+    /// Whether `tx` is either ready or close enough to be queued
+    fn accepts(&self, tx: &GeneralTransaction) -> bool {
+        let next_nonce = self.next_nonce();
+        tx.nonce() == next_nonce
+            || (tx.nonce() > next_nonce
+                && tx.nonce() - next_nonce <= MAX_NONCE_GAP
+                && self.future.len() < MAX_QUEUED_TXS)
+    }
     fn insert(&mut self, tx: GeneralTransaction, stats: TransactionStats) {
-        if self.applicable(&tx) {
+        if !self.accepts(&tx) {
+            return;
+        }
+        if tx.nonce() == self.next_nonce() {
             self.txs.push_back((tx, stats));
+            self.promote();
+        } else {
+            self.future.insert(tx.nonce(), (tx, stats));
+        }
+    }
+    /// Replaces the pending transaction with the same nonce
+    fn replace(&mut self, tx: GeneralTransaction, stats: TransactionStats) {
+        let nonce = tx.nonce();
+        if let Some(pending) = self.txs.iter_mut().find(|(t, _)| t.nonce() == nonce) {
+            *pending = (tx, stats);
+        } else if let Some(pending) = self.future.get_mut(&nonce) {
+            *pending = (tx, stats);
+        }
+    }
+    /// Moves the queued transactions which are no longer after a gap
+    fn promote(&mut self) {
+        while let Some(entry) = self.future.remove(&self.next_nonce()) {
+            self.txs.push_back(entry);
         }
     }
     fn update_nonce(&mut self, nonce: u32) {
@@ -89,44 +157,118 @@ impl SingleMempool {
                 break;
             }
         }
+        // The chain nonce might have gone back, e.g. by a rollback
         if self.first_nonce() != Some(nonce + 1) {
-            self.txs.clear();
+            for (tx, stats) in self.txs.drain(..) {
+                self.future.insert(tx.nonce(), (tx, stats));
+            }
         }
+        self.future = self.future.split_off(&(nonce + 1));
         self.nonce = nonce;
+        self.promote();
     }
-    fn reset(&mut self, nonce: u32) {
-        if nonce == 0 {
-            self.txs.clear();
-            return;
+}
+
+/// Contract transactions a node accepts and relays, so that resource constrained
+/// relays may stay out of the zk traffic. Payments and MPN updates, which every
+/// block needs, are accepted regardless.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct ContractTxPolicy {
+    pub accept_creations: bool,
+    pub accept_updates: bool,
+    /// Contract updates carrying more bytes of proofs than this are not accepted
+    pub max_proof_size: Option<usize>,
+}
+
+impl Default for ContractTxPolicy {
+    fn default() -> Self {
+        Self {
+            accept_creations: true,
+            accept_updates: true,
+            max_proof_size: None,
         }
-        while let Some(last_nonce) = self.last_nonce() {
-            if last_nonce > nonce - 1 {
-                self.txs.pop_back();
-            } else {
-                break;
-            }
+    }
+}
+
+impl ContractTxPolicy {
+    pub fn accepts(&self, tx: &GeneralTransaction, mpn_contract_id: &ContractId) -> bool {
+        let tx = match tx {
+            GeneralTransaction::TransactionAndDelta(tx_delta) => &tx_delta.tx,
+            _ => return true,
+        };
+        if updates_contract(tx, mpn_contract_id) {
+            return true;
         }
-        if self.last_nonce() != Some(nonce - 1) {
-            self.txs.clear();
+        match &tx.data {
+            TransactionData::CreateContract { .. } => self.accept_creations,
+            TransactionData::UpdateContract { updates, .. } => {
+                self.accept_updates
+                    && self
+                        .max_proof_size
+                        .map(|max| proofs_size(updates) <= max)
+                        .unwrap_or(true)
+            }
+            _ => true,
         }
     }
 }
 
+fn proofs_size(updates: &[ContractUpdate]) -> usize {
+    updates
+        .iter()
+        .map(|update| match update {
+            ContractUpdate::Deposit { proof, .. }
+            | ContractUpdate::Withdraw { proof, .. }
+            | ContractUpdate::FunctionCall { proof, .. } => {
+                bincode::serialized_size(proof).unwrap_or(u64::MAX) as usize
+            }
+        })
+        .fold(0usize, |sum, size| sum.saturating_add(size))
+}
+
 #[derive(Clone, Debug)]
 pub struct Mempool {
     min_balance_per_tx: Amount,
+    min_fee: Amount,
+    min_fee_per_byte: Amount,
+    contract_policy: ContractTxPolicy,
+    max_size: usize,
     txs: HashMap<NonceGroup, SingleMempool>,
     rejected: HashMap<GeneralTransaction, TransactionStats>,
+    rejections: VecDeque<(<Hasher as Hash>::Output, String)>,
 }
 
 impl Mempool {
-    pub fn new(min_balance_per_tx: Amount) -> Self {
+    /// A mempool holding at most `max_size` transactions, the ones paying the lowest
+    /// fee per byte are evicted when it is full
+    pub fn new(min_balance_per_tx: Amount, max_size: usize) -> Self {
         Self {
             min_balance_per_tx,
+            min_fee: Amount(0),
+            min_fee_per_byte: Amount(0),
+            contract_policy: Default::default(),
+            max_size,
             txs: Default::default(),
             rejected: Default::default(),
+            rejections: Default::default(),
         }
     }
+    /// Chain transactions paying less than `min_fee` are not relayed
+    pub fn with_min_fee(mut self, min_fee: Amount) -> Self {
+        self.min_fee = min_fee;
+        self
+    }
+    /// Chain transactions are additionally charged for every byte they take, so that
+    /// large transactions (E.g. carrying post-quantum signatures) pay for their space
+    pub fn with_min_fee_per_byte(mut self, min_fee_per_byte: Amount) -> Self {
+        self.min_fee_per_byte = min_fee_per_byte;
+        self
+    }
+    pub fn with_contract_policy(mut self, contract_policy: ContractTxPolicy) -> Self {
+        self.contract_policy = contract_policy;
+        self
+    }
 }
 
 impl Mempool {
@@ -155,6 +297,112 @@ impl Mempool {
     pub fn chain_address_limit(&self, _addr: Address) -> usize {
         100
     }
+    fn chain_nonce<K: KvStore, B: Blockchain<K>>(
+        blockchain: &B,
+        nonce_group: NonceGroup,
+    ) -> Result<u32, BlockchainError> {
+        let mpn_contract_id = blockchain.config().mpn_config.mpn_contract_id;
+        Ok(match nonce_group {
+            NonceGroup::TransactionAndDelta(addr) => blockchain.get_nonce(addr)?,
+            NonceGroup::MpnDeposit(addr) => blockchain.get_deposit_nonce(addr, mpn_contract_id)?,
+            NonceGroup::MpnTransaction(addr) => blockchain.get_mpn_account(addr)?.tx_nonce,
+            NonceGroup::MpnWithdraw(addr) => blockchain.get_mpn_account(addr)?.withdraw_nonce,
+        })
+    }
+    /// Maximum number of pending transactions of a remote sender
+    fn queue_limit<K: KvStore, B: Blockchain<K>>(
+        &self,
+        blockchain: &B,
+        sender: GeneralAddress,
+    ) -> Result<usize, BlockchainError> {
+        let ziesha_balance = match sender {
+            GeneralAddress::ChainAddress(addr) => blockchain.get_balance(addr, TokenId::Ziesha)?,
+            GeneralAddress::MpnAddress(mpn_addr) => {
+                let acc = blockchain.get_mpn_account(mpn_addr)?;
+                acc.tokens
+                    .get(&0)
+                    .map(|m| {
+                        if m.token_id == TokenId::Ziesha {
+                            m.amount
+                        } else {
+                            0.into()
+                        }
+                    })
+                    .unwrap_or_default()
+            }
+        };
+
+        // Allow 1tx in mempool per Ziesha
+        // Min: 1 Max: 1000
+        Ok(std::cmp::max(
+            std::cmp::min(
+                Into::<u64>::into(ziesha_balance) / self.min_balance_per_tx.0,
+                1000,
+            ),
+            1,
+        ) as usize)
+    }
+    /// Checks if `tx` either replaces a pending transaction of its sender, or can be
+    /// queued after them with a limited gap
+    pub fn check_nonce<K: KvStore, B: Blockchain<K>>(
+        &self,
+        blockchain: &B,
+        tx: &GeneralTransaction,
+        is_local: bool,
+    ) -> Result<(), BlockchainError> {
+        let nonce = Self::chain_nonce(blockchain, tx.nonce_group())?;
+        if tx.nonce() <= nonce {
+            return Err(BlockchainError::TransactionNonceUsed);
+        }
+        let mut next_nonce = nonce + 1;
+        if let Some(all) = self.txs.get(&tx.nonce_group()) {
+            for (pending_tx, _) in all.txs.iter() {
+                if pending_tx.nonce() == next_nonce {
+                    next_nonce += 1;
+                }
+            }
+        }
+        let pending = (next_nonce - nonce - 1) as usize;
+        if tx.nonce() > next_nonce + MAX_NONCE_GAP {
+            return Err(BlockchainError::TransactionNonceGap);
+        }
+        if !is_local
+            && tx.nonce() >= next_nonce
+            && pending >= self.queue_limit(blockchain, tx.sender())?
+        {
+            return Err(BlockchainError::TooManyPendingTransactions);
+        }
+        Ok(())
+    }
+    pub fn min_fee(&self) -> Amount {
+        self.min_fee
+    }
+    pub fn min_fee_per_byte(&self) -> Amount {
+        self.min_fee_per_byte
+    }
+    /// Minimum relay fee of `tx`, considering its size
+    pub fn min_fee_for(&self, tx: &Transaction) -> Amount {
+        self.min_fee_for_size(tx.size())
+    }
+    /// Minimum relay fee of a transaction of `size` bytes
+    pub fn min_fee_for_size(&self, size: usize) -> Amount {
+        Amount(
+            u64::from(self.min_fee)
+                .saturating_add(u64::from(self.min_fee_per_byte).saturating_mul(size as u64)),
+        )
+    }
+    /// Checks if `tx` pays at least the minimum relay fee. MPN transactions pay their
+    /// fees to the MPN operator and are not subject to it.
+    pub fn check_fee(&self, tx: &GeneralTransaction) -> Result<(), BlockchainError> {
+        if let GeneralTransaction::TransactionAndDelta(tx_delta) = tx {
+            if tx_delta.tx.fee.token_id != TokenId::Ziesha
+                || tx_delta.tx.fee.amount < self.min_fee_for(&tx_delta.tx)
+            {
+                return Err(BlockchainError::FeeTooLow);
+            }
+        }
+        Ok(())
+    }
     pub fn add_tx<K: KvStore, B: Blockchain<K>>(
         &mut self,
         blockchain: &B,
@@ -185,36 +433,30 @@ impl Mempool {
         if self.rejected.contains_key(&tx) || !tx.verify_signature() {
             return Ok(());
         }
-        let nonce = match tx.nonce_group() {
-            NonceGroup::TransactionAndDelta(addr) => blockchain.get_nonce(addr)?,
-            NonceGroup::MpnDeposit(addr) => blockchain.get_deposit_nonce(addr, mpn_contract_id)?,
-            NonceGroup::MpnTransaction(addr) => blockchain.get_mpn_account(addr)?.tx_nonce,
-            NonceGroup::MpnWithdraw(addr) => blockchain.get_mpn_account(addr)?.withdraw_nonce,
-        };
-        if self
-            .txs
-            .get_mut(&tx.nonce_group())
-            .map(|all| {
-                all.update_nonce(nonce);
-                if is_local && !all.applicable(&tx) {
-                    all.reset(tx.nonce());
-                }
-                if let Some((first_tx, stats)) = all.first_tx() {
-                    // TODO: config.replace_tx_threshold instead of 60
-                    if now > stats.first_seen + 60 && first_tx != &tx {
-                        log::info!(
-                            "{} replaced its transaction on nonce {}",
-                            tx.sender(),
-                            tx.nonce()
-                        );
-                        all.reset(tx.nonce());
-                    }
-                }
-                !all.applicable(&tx)
-            })
-            .unwrap_or_default()
+        // Tell local users about the typo, just drop such transactions when gossiped
+        if !tx.has_valid_destinations() {
+            return if is_local {
+                Err(BlockchainError::InvalidDestinationAddress)
+            } else {
+                Ok(())
+            };
+        }
+        if let Err(err) = self.check_fee(&tx) {
+            return if is_local { Err(err) } else { Ok(()) };
+        }
+        if !self
+            .contract_policy
+            .accepts(&tx, &blockchain.config().mpn_config.mpn_contract_id)
         {
-            return Ok(());
+            return if is_local {
+                Err(BlockchainError::ContractTransactionNotAccepted)
+            } else {
+                Ok(())
+            };
+        }
+        let nonce = Self::chain_nonce(blockchain, tx.nonce_group())?;
+        if let Some(all) = self.txs.get_mut(&tx.nonce_group()) {
+            all.update_nonce(nonce);
         }
 
         // Do not accept old txs in the mempool
@@ -222,43 +464,73 @@ impl Mempool {
             return Ok(());
         }
 
-        let ziesha_balance = match tx.sender() {
-            GeneralAddress::ChainAddress(addr) => blockchain.get_balance(addr, TokenId::Ziesha)?,
-            GeneralAddress::MpnAddress(mpn_addr) => {
-                let acc = blockchain.get_mpn_account(mpn_addr)?;
-                acc.tokens
-                    .get(&0)
-                    .map(|m| {
-                        if m.token_id == TokenId::Ziesha {
-                            m.amount
-                        } else {
-                            0.into()
-                        }
-                    })
-                    .unwrap_or_default()
-            }
-        };
-
-        // Allow 1tx in mempool per Ziesha
-        // Min: 1 Max: 1000
-        let limit = std::cmp::max(
-            std::cmp::min(
-                Into::<u64>::into(ziesha_balance) / self.min_balance_per_tx.0,
-                1000,
-            ),
-            1,
-        ) as usize;
+        let limit = self.queue_limit(blockchain, tx.sender())?;
 
         let all = self
             .txs
             .entry(tx.nonce_group().clone())
             .or_insert(SingleMempool::new(nonce));
 
-        if is_local || all.len() < limit {
-            all.insert(tx.clone(), TransactionStats::new(is_local, now));
+        // A pending transaction is only replaced by one paying a higher fee
+        if let Some(pending) = all.get(tx.nonce()) {
+            let err = if pending == &tx {
+                BlockchainError::TransactionAlreadyPending
+            } else if ziesha_fee(&tx) <= ziesha_fee(pending) {
+                BlockchainError::ReplacementFeeTooLow
+            } else {
+                tracing::info!(
+                    "{} replaced its transaction on nonce {}",
+                    tx.sender(),
+                    tx.nonce()
+                );
+                all.replace(tx, TransactionStats::new(is_local, now));
+                return Ok(());
+            };
+            return if is_local { Err(err) } else { Ok(()) };
+        }
+
+        if !all.accepts(&tx) || (!is_local && all.len() >= limit) {
+            return Ok(());
+        }
+        // Local transactions are kept even if there is no room for them
+        if self.len() >= self.max_size && !self.evict_for(&tx, is_local) && !is_local {
+            return Ok(());
+        }
+        if let Some(all) = self.txs.get_mut(&tx.nonce_group()) {
+            all.insert(tx, TransactionStats::new(is_local, now));
         }
         Ok(())
     }
+    /// Makes room for `tx` by removing the remote transaction with the lowest fee per
+    /// byte. Only the last transaction of a sender is removed, so that the rest of its
+    /// transactions remain applicable. Returns false if `tx` itself is not worth more
+    /// than the transactions in the mempool.
+    fn evict_for(&mut self, tx: &GeneralTransaction, is_local: bool) -> bool {
+        let nonce_group = tx.nonce_group();
+        let cheapest = self
+            .txs
+            .iter()
+            .filter(|(ng, _)| **ng != nonce_group)
+            .filter_map(|(ng, all)| all.last_tx().map(|last| (ng, last)))
+            .filter(|(_, (_, stats))| !stats.is_local)
+            .min_by(|(_, (a, _)), (_, (b, _))| cmp_fee_rate(a, b))
+            .map(|(ng, (last, _))| (ng.clone(), cmp_fee_rate(tx, last)));
+        match cheapest {
+            Some((ng, ord)) if is_local || ord == Ordering::Greater => {
+                if let Some(all) = self.txs.get_mut(&ng) {
+                    all.pop_last();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+    /// The pending transaction of the same sender and nonce as `tx`, if any
+    pub fn pending(&self, tx: &GeneralTransaction) -> Option<&GeneralTransaction> {
+        self.txs
+            .get(&tx.nonce_group())
+            .and_then(|all| all.get(tx.nonce()))
+    }
     pub fn all(&self) -> impl Iterator<Item = &(GeneralTransaction, TransactionStats)> {
         self.txs.iter().map(|(_, c)| c.txs.iter()).flatten()
     }
@@ -337,6 +609,23 @@ impl Mempool {
     pub fn len(&self) -> usize {
         self.txs.values().map(|c| c.len()).sum()
     }
+    /// Remembers why a submitted transaction was not accepted, forgetting the oldest
+    /// rejections when there are too many of them
+    pub fn reject(&mut self, hash: <Hasher as Hash>::Output, reason: String) {
+        self.rejections.retain(|(h, _)| h != &hash);
+        if self.rejections.len() >= MAX_REJECTIONS {
+            self.rejections.pop_front();
+        }
+        self.rejections.push_back((hash, reason));
+    }
+    /// Why the transaction with the given hash was rejected, if it recently was
+    pub fn rejection(&self, hash: &<Hasher as Hash>::Output) -> Option<&str> {
+        self.rejections
+            .iter()
+            .rev()
+            .find(|(h, _)| h == hash)
+            .map(|(_, reason)| reason.as_str())
+    }
 }
 
 #[cfg(test)]
@@ -357,6 +646,190 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn test_mempool_rejects_invalid_destinations() {
+        let chain = KvStoreChain::new(
+            RamKvStore::new(),
+            crate::config::blockchain::get_test_blockchain_config(),
+        )
+        .unwrap();
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let to_zero = GeneralTransaction::TransactionAndDelta(abc.create_transaction(
+            "".into(),
+            Default::default(),
+            Money::ziesha(200),
+            Money::ziesha(0),
+            1,
+        ));
+        let mut mempool = Mempool::new(Amount(1), 100);
+        assert!(matches!(
+            mempool.add_tx(&chain, to_zero.clone(), true, 0),
+            Err(BlockchainError::InvalidDestinationAddress)
+        ));
+        mempool.add_tx(&chain, to_zero, false, 0).unwrap();
+        assert_eq!(mempool.len(), 0);
+        assert!(matches!(
+            chain.check_tx(
+                &abc.create_transaction(
+                    "".into(),
+                    Default::default(),
+                    Money::ziesha(200),
+                    Money::ziesha(0),
+                    1,
+                )
+                .tx
+            ),
+            Err(BlockchainError::InvalidDestinationAddress)
+        ));
+    }
+
+    #[test]
+    fn test_mempool_min_fee() {
+        let chain = KvStoreChain::new(
+            RamKvStore::new(),
+            crate::config::blockchain::get_test_blockchain_config(),
+        )
+        .unwrap();
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let tx = |fee| {
+            GeneralTransaction::TransactionAndDelta(abc.create_transaction(
+                "".into(),
+                abc.get_address(),
+                Money::ziesha(200),
+                Money::ziesha(fee),
+                1,
+            ))
+        };
+        let mut mempool = Mempool::new(Amount(1), 100).with_min_fee(Amount(10));
+        assert!(matches!(
+            mempool.add_tx(&chain, tx(9), true, 0),
+            Err(BlockchainError::FeeTooLow)
+        ));
+        mempool.add_tx(&chain, tx(9), false, 0).unwrap();
+        assert_eq!(mempool.len(), 0);
+        mempool.add_tx(&chain, tx(10), false, 0).unwrap();
+        assert_eq!(mempool.len(), 1);
+    }
+
+    #[test]
+    fn test_mempool_min_fee_per_byte() {
+        let chain = KvStoreChain::new(
+            RamKvStore::new(),
+            crate::config::blockchain::get_test_blockchain_config(),
+        )
+        .unwrap();
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let tx = |fee| {
+            abc.create_transaction(
+                "".into(),
+                abc.get_address(),
+                Money::ziesha(200),
+                Money::ziesha(fee),
+                1,
+            )
+        };
+        let mut mempool = Mempool::new(Amount(1), 100)
+            .with_min_fee(Amount(10))
+            .with_min_fee_per_byte(Amount(2));
+        let min_fee = mempool.min_fee_for(&tx(0).tx);
+        assert_eq!(min_fee, Amount(10 + 2 * tx(0).tx.size() as u64));
+        let min_fee = u64::from(min_fee);
+        assert!(matches!(
+            mempool.add_tx(
+                &chain,
+                GeneralTransaction::TransactionAndDelta(tx(min_fee - 1)),
+                true,
+                0
+            ),
+            Err(BlockchainError::FeeTooLow)
+        ));
+        mempool
+            .add_tx(
+                &chain,
+                GeneralTransaction::TransactionAndDelta(tx(min_fee)),
+                true,
+                0,
+            )
+            .unwrap();
+        assert_eq!(mempool.len(), 1);
+    }
+
+    #[test]
+    fn test_mempool_contract_policy() {
+        let chain = KvStoreChain::new(
+            RamKvStore::new(),
+            crate::config::blockchain::get_test_blockchain_config(),
+        )
+        .unwrap();
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let create = GeneralTransaction::TransactionAndDelta(abc.create_contract(
+            "".into(),
+            crate::zk::ZkContract {
+                state_model: crate::zk::ZkStateModel::Scalar,
+                initial_state: Default::default(),
+                deposit_functions: Vec::new(),
+                withdraw_functions: Vec::new(),
+                functions: Vec::new(),
+            },
+            Default::default(),
+            Money::ziesha(0),
+            1,
+        ));
+        let update = GeneralTransaction::TransactionAndDelta(
+            abc.call_function(
+                "".into(),
+                "0000000000000000000000000000000000000000000000000000000000000000"
+                    .parse()
+                    .unwrap(),
+                0,
+                Default::default(),
+                Default::default(),
+                crate::zk::ZkProof::Dummy(true),
+                Money::ziesha(0),
+                Money::ziesha(0),
+                1,
+            ),
+        );
+        let payment = dummy_tx(&abc, 1);
+        let mpn_contract_id = chain.config().mpn_config.mpn_contract_id;
+        let mpn_update = GeneralTransaction::TransactionAndDelta(abc.call_function(
+            "".into(),
+            mpn_contract_id,
+            0,
+            Default::default(),
+            Default::default(),
+            crate::zk::ZkProof::Dummy(true),
+            Money::ziesha(0),
+            Money::ziesha(0),
+            1,
+        ));
+        let accepts = |policy: &ContractTxPolicy, tx| policy.accepts(tx, &mpn_contract_id);
+
+        let mut policy = ContractTxPolicy::default();
+        assert!(accepts(&policy, &create) && accepts(&policy, &update));
+        policy.max_proof_size = Some(4);
+        assert!(accepts(&policy, &create) && !accepts(&policy, &update));
+        policy.max_proof_size = Some(5);
+        assert!(accepts(&policy, &update));
+        policy.max_proof_size = Some(0);
+        policy.accept_creations = false;
+        policy.accept_updates = false;
+        assert!(!accepts(&policy, &create) && !accepts(&policy, &update));
+        assert!(accepts(&policy, &payment));
+        // Every block needs an MPN update
+        assert!(accepts(&policy, &mpn_update));
+
+        let mut mempool = Mempool::new(Amount(1), 100).with_contract_policy(policy);
+        assert!(matches!(
+            mempool.add_tx(&chain, create.clone(), true, 0),
+            Err(BlockchainError::ContractTransactionNotAccepted)
+        ));
+        mempool.add_tx(&chain, update, false, 0).unwrap();
+        assert_eq!(mempool.len(), 0);
+        mempool.add_tx(&chain, payment, false, 0).unwrap();
+        assert_eq!(mempool.len(), 1);
+    }
+
     #[test]
     fn test_mempool_check_correct_account_nonce() {
         let chain = KvStoreChain::new(
@@ -367,7 +840,7 @@ mod tests {
         let abc = TxBuilder::new(&Vec::from("ABC"));
 
         for i in 0..5 {
-            let mut mempool = Mempool::new(Amount(1));
+            let mut mempool = Mempool::new(Amount(1), 100);
             mempool.add_tx(&chain, dummy_tx(&abc, i), false, 0).unwrap();
 
             let snapshot = mempool.all().collect::<Vec<_>>();
@@ -385,23 +858,25 @@ mod tests {
         .unwrap();
         let abc = TxBuilder::new(&Vec::from("ABC"));
         let other = TxBuilder::new(&Vec::from("DELEGATOR"));
-        let mut mempool = Mempool::new(Amount(1));
+        let mut mempool = Mempool::new(Amount(1), 100);
 
         mempool.add_tx(&chain, dummy_tx(&abc, 1), false, 0).unwrap();
         assert_eq!(mempool.all().collect::<Vec<_>>().len(), 1);
         mempool.add_tx(&chain, dummy_tx(&abc, 2), false, 0).unwrap();
         assert_eq!(mempool.all().collect::<Vec<_>>().len(), 2);
+        // Transactions after a gap are queued until the gap is filled
         mempool.add_tx(&chain, dummy_tx(&abc, 4), false, 0).unwrap();
         assert_eq!(mempool.all().collect::<Vec<_>>().len(), 2);
+        assert_eq!(mempool.len(), 3);
         mempool.add_tx(&chain, dummy_tx(&abc, 3), false, 0).unwrap();
-        assert_eq!(mempool.all().collect::<Vec<_>>().len(), 3);
+        assert_eq!(mempool.all().collect::<Vec<_>>().len(), 4);
         mempool.add_tx(&chain, dummy_tx(&abc, 4), false, 0).unwrap();
         assert_eq!(mempool.all().collect::<Vec<_>>().len(), 4);
 
         mempool
-            .add_tx(&chain, dummy_tx(&other, 10), false, 0)
+            .add_tx(&chain, dummy_tx(&other, 20), false, 0)
             .unwrap();
-        assert_eq!(mempool.all().collect::<Vec<_>>().len(), 4);
+        assert_eq!(mempool.len(), 4);
         mempool
             .add_tx(&chain, dummy_tx(&other, 1), false, 0)
             .unwrap();
@@ -413,6 +888,141 @@ mod tests {
         mempool
             .add_tx(&chain, dummy_tx(&other, 2), false, 0)
             .unwrap();
-        assert_eq!(mempool.all().collect::<Vec<_>>().len(), 6);
+        assert_eq!(mempool.all().collect::<Vec<_>>().len(), 7);
+    }
+
+    #[test]
+    fn test_mempool_replace_by_fee() {
+        let chain = KvStoreChain::new(
+            RamKvStore::new(),
+            crate::config::blockchain::get_test_blockchain_config(),
+        )
+        .unwrap();
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let tx = |nonce, fee| {
+            GeneralTransaction::TransactionAndDelta(abc.create_transaction(
+                "".into(),
+                abc.get_address(),
+                Money::ziesha(200),
+                Money::ziesha(fee),
+                nonce,
+            ))
+        };
+        let fees = |mempool: &Mempool| {
+            mempool
+                .all()
+                .map(|(tx, _)| u64::from(tx.fee().amount))
+                .collect::<Vec<_>>()
+        };
+        let mut mempool = Mempool::new(Amount(1), 100);
+        mempool.add_tx(&chain, tx(1, 5), false, 0).unwrap();
+        mempool.add_tx(&chain, tx(2, 5), false, 0).unwrap();
+
+        assert!(matches!(
+            mempool.add_tx(&chain, tx(1, 5), true, 0),
+            Err(BlockchainError::TransactionAlreadyPending)
+        ));
+        assert!(matches!(
+            mempool.add_tx(&chain, tx(1, 4), true, 0),
+            Err(BlockchainError::ReplacementFeeTooLow)
+        ));
+        mempool.add_tx(&chain, tx(1, 5), false, 0).unwrap();
+        assert_eq!(fees(&mempool), vec![5, 5]);
+
+        // Later transactions of the sender are kept
+        mempool.add_tx(&chain, tx(1, 6), false, 0).unwrap();
+        assert_eq!(fees(&mempool), vec![6, 5]);
+    }
+
+    #[test]
+    fn test_mempool_check_nonce() {
+        let chain = KvStoreChain::new(
+            RamKvStore::new(),
+            crate::config::blockchain::get_test_blockchain_config(),
+        )
+        .unwrap();
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let mut mempool = Mempool::new(Amount(1), 100);
+
+        assert!(matches!(
+            mempool.check_nonce(&chain, &dummy_tx(&abc, 0), false),
+            Err(BlockchainError::TransactionNonceUsed)
+        ));
+        mempool
+            .check_nonce(&chain, &dummy_tx(&abc, 2), false)
+            .unwrap();
+        assert!(matches!(
+            mempool.check_nonce(&chain, &dummy_tx(&abc, 2 + MAX_NONCE_GAP), false),
+            Err(BlockchainError::TransactionNonceGap)
+        ));
+        for i in 1..4 {
+            mempool
+                .check_nonce(&chain, &dummy_tx(&abc, i), false)
+                .unwrap();
+            mempool.add_tx(&chain, dummy_tx(&abc, i), false, 0).unwrap();
+        }
+        assert!(matches!(
+            mempool.check_nonce(&chain, &dummy_tx(&abc, 5 + MAX_NONCE_GAP), false),
+            Err(BlockchainError::TransactionNonceGap)
+        ));
+        // Pending transactions may be replaced
+        mempool
+            .check_nonce(&chain, &dummy_tx(&abc, 2), true)
+            .unwrap();
+
+        let mut mempool = Mempool::new(Amount(u64::MAX), 100);
+        mempool.add_tx(&chain, dummy_tx(&abc, 1), false, 0).unwrap();
+        assert!(matches!(
+            mempool.check_nonce(&chain, &dummy_tx(&abc, 2), false),
+            Err(BlockchainError::TooManyPendingTransactions)
+        ));
+        mempool
+            .check_nonce(&chain, &dummy_tx(&abc, 2), true)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_mempool_evicts_lowest_fee_rate() {
+        let chain = KvStoreChain::new(
+            RamKvStore::new(),
+            crate::config::blockchain::get_test_blockchain_config(),
+        )
+        .unwrap();
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let other = TxBuilder::new(&Vec::from("DELEGATOR"));
+        let tx = |wallet: &TxBuilder, nonce, fee| {
+            GeneralTransaction::TransactionAndDelta(wallet.create_transaction(
+                "".into(),
+                wallet.get_address(),
+                Money::ziesha(200),
+                Money::ziesha(fee),
+                nonce,
+            ))
+        };
+        let pending = |mempool: &Mempool| {
+            let mut fees = mempool
+                .all()
+                .map(|(tx, _)| u64::from(tx.fee().amount))
+                .collect::<Vec<_>>();
+            fees.sort();
+            fees
+        };
+        let mut mempool = Mempool::new(Amount(1), 2);
+
+        mempool.add_tx(&chain, tx(&abc, 1, 1), false, 0).unwrap();
+        mempool.add_tx(&chain, tx(&other, 1, 5), false, 0).unwrap();
+        assert_eq!(pending(&mempool), vec![1, 5]);
+
+        // The last tx of another sender is evicted for a more valuable one
+        mempool.add_tx(&chain, tx(&abc, 2, 10), false, 0).unwrap();
+        assert_eq!(pending(&mempool), vec![1, 10]);
+
+        // Cheaper transactions are not accepted when full
+        mempool.add_tx(&chain, tx(&other, 1, 2), false, 0).unwrap();
+        assert_eq!(pending(&mempool), vec![1, 10]);
+
+        // Local transactions are always accepted
+        mempool.add_tx(&chain, tx(&other, 1, 0), true, 0).unwrap();
+        assert_eq!(pending(&mempool), vec![0, 1]);
     }
 }
@@ -0,0 +1,732 @@
+//! In-memory stand-in for a real chain, for testing code built on top of the
+//! [`Blockchain`] trait (node handlers, heartbeats, sync) without applying real
+//! blocks. Blocks are accepted as given, the state is whatever the test sets, and
+//! any method can be told to fail.
+//!
+//! ```ignore
+//! let mut chain = MockBlockchain::new(get_test_blockchain_config());
+//! chain.set_balance(addr, TokenId::Ziesha, Amount(100));
+//! chain.fail_times("extend", 1, || BlockchainError::InvalidMerkleRoot);
+//! ```
+
+use super::*;
+use crate::core::TokenId;
+use crate::db::RamKvStore;
+use std::sync::Mutex;
+
+type ErrorFn = Box<dyn Fn() -> BlockchainError + Send + Sync>;
+
+struct Failure {
+    /// Number of calls left to fail, forever if `None`
+    remaining: Option<usize>,
+    error: ErrorFn,
+}
+
+pub struct MockBlockchain {
+    config: BlockchainConfig,
+    database: RamKvStore,
+    blocks: Vec<Block>,
+    pruned_height: u64,
+    rollback_pruned_height: u64,
+    balances: HashMap<(Address, TokenId), Amount>,
+    nonces: HashMap<Address, u32>,
+    tokens: HashMap<TokenId, Token>,
+    stakers: HashMap<Address, (Staker, Amount)>,
+    delegates: HashMap<(Address, Address), Amount>,
+    next_reward: Amount,
+    elected: bool,
+    validator_proof: ValidatorProof,
+    block_template_policy: Arc<dyn BlockTemplatePolicy>,
+    block_template_limits: BlockTemplateLimits,
+    failures: Mutex<HashMap<&'static str, Failure>>,
+    calls: Mutex<HashMap<&'static str, usize>>,
+}
+
+impl MockBlockchain {
+    /// Chain holding only the genesis block of `config`. Tokens created in the
+    /// genesis block are known, but no other transaction is applied.
+    pub fn new(config: BlockchainConfig) -> Self {
+        let mut tokens = HashMap::new();
+        for tx in config.genesis.body.iter() {
+            if let TransactionData::CreateToken { token } = &tx.data {
+                let token_id = TokenId::new(tx);
+                let token_id = if token_id == config.ziesha_token_id {
+                    TokenId::Ziesha
+                } else {
+                    token_id
+                };
+                tokens.insert(token_id, token.clone());
+            }
+        }
+        Self {
+            blocks: vec![config.genesis.clone()],
+            config,
+            database: RamKvStore::new(),
+            pruned_height: 0,
+            rollback_pruned_height: 0,
+            balances: HashMap::new(),
+            nonces: HashMap::new(),
+            tokens,
+            stakers: HashMap::new(),
+            delegates: HashMap::new(),
+            next_reward: Amount(0),
+            elected: false,
+            validator_proof: ValidatorProof::Unproven,
+            block_template_policy: Arc::new(DefaultBlockTemplatePolicy::default()),
+            block_template_limits: Default::default(),
+            failures: Mutex::new(HashMap::new()),
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Appends a block without checking anything
+    pub fn push_block(&mut self, block: Block) {
+        self.blocks.push(block);
+    }
+
+    /// Block on top of the current tip, produced by `validator` at `timestamp`
+    pub fn next_block(
+        &self,
+        timestamp: u32,
+        validator: &TxBuilder,
+        body: Vec<Transaction>,
+    ) -> Block {
+        let tip = self.blocks.last().unwrap().header.clone();
+        let mut block = Block {
+            header: Header {
+                parent_hash: tip.hash(),
+                number: tip.number + 1,
+                block_root: Default::default(),
+                proof_of_stake: ProofOfStake {
+                    validator: validator.get_address(),
+                    timestamp,
+                    proof: ValidatorProof::Unproven,
+                },
+            },
+            body,
+        };
+        block.header.block_root = block.merkle_tree().root();
+        block
+    }
+
+    pub fn set_balance(&mut self, addr: Address, token_id: TokenId, amount: Amount) {
+        self.balances.insert((addr, token_id), amount);
+    }
+
+    pub fn set_nonce(&mut self, addr: Address, nonce: u32) {
+        self.nonces.insert(addr, nonce);
+    }
+
+    pub fn set_token(&mut self, token_id: TokenId, token: Token) {
+        self.tokens.insert(token_id, token);
+    }
+
+    pub fn set_staker(&mut self, addr: Address, staker: Staker, stake: Amount) {
+        self.stakers.insert(addr, (staker, stake));
+    }
+
+    pub fn set_delegate(&mut self, delegator: Address, delegatee: Address, amount: Amount) {
+        self.delegates.insert((delegator, delegatee), amount);
+    }
+
+    pub fn set_next_reward(&mut self, amount: Amount) {
+        self.next_reward = amount;
+    }
+
+    /// Whether validator claims are accepted (`is_validator`) and blocks can be
+    /// drafted (`draft_block`)
+    pub fn set_elected(&mut self, elected: bool) {
+        self.elected = elected;
+    }
+
+    /// What `validator_status` returns, whatever the wallet and timestamp
+    pub fn set_validator_proof(&mut self, proof: ValidatorProof) {
+        self.validator_proof = proof;
+    }
+
+    /// Makes every call to `method` fail with the given error
+    pub fn fail<F: Fn() -> BlockchainError + Send + Sync + 'static>(
+        &mut self,
+        method: &'static str,
+        error: F,
+    ) {
+        self.failures.lock().unwrap().insert(
+            method,
+            Failure {
+                remaining: None,
+                error: Box::new(error),
+            },
+        );
+    }
+
+    /// Makes the next `times` calls to `method` fail with the given error
+    pub fn fail_times<F: Fn() -> BlockchainError + Send + Sync + 'static>(
+        &mut self,
+        method: &'static str,
+        times: usize,
+        error: F,
+    ) {
+        self.failures.lock().unwrap().insert(
+            method,
+            Failure {
+                remaining: Some(times),
+                error: Box::new(error),
+            },
+        );
+    }
+
+    /// Stops injecting errors into `method`
+    pub fn succeed(&mut self, method: &'static str) {
+        self.failures.lock().unwrap().remove(method);
+    }
+
+    /// Number of times `method` has been called, including the failed calls
+    pub fn calls(&self, method: &str) -> usize {
+        self.calls
+            .lock()
+            .unwrap()
+            .get(method)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn call(&self, method: &'static str) -> Result<(), BlockchainError> {
+        *self.calls.lock().unwrap().entry(method).or_default() += 1;
+        let mut failures = self.failures.lock().unwrap();
+        if let Some(failure) = failures.get_mut(method) {
+            let err = (failure.error)();
+            match &mut failure.remaining {
+                None => return Err(err),
+                Some(0) => {}
+                Some(remaining) => {
+                    *remaining -= 1;
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the blocks from `from` on with `blocks`, without recording a call
+    fn replace_blocks(&mut self, from: u64, blocks: &[Block]) -> Result<(), BlockchainError> {
+        if from == 0 {
+            return Err(BlockchainError::ExtendFromGenesis);
+        }
+        if from > self.height() {
+            return Err(BlockchainError::ExtendFromFuture);
+        }
+        for (i, block) in blocks.iter().enumerate() {
+            if block.header.number != from + i as u64 {
+                return Err(BlockchainError::InvalidBlockNumber);
+            }
+        }
+        self.blocks.truncate(from as usize);
+        self.blocks.extend(blocks.iter().cloned());
+        Ok(())
+    }
+
+    fn height(&self) -> u64 {
+        self.blocks.len() as u64
+    }
+}
+
+impl Blockchain<RamKvStore> for MockBlockchain {
+    fn epoch_randomness(&self) -> Result<<Hasher as Hash>::Output, BlockchainError> {
+        self.call("epoch_randomness")?;
+        Ok(Default::default())
+    }
+    fn database(&self) -> &RamKvStore {
+        &self.database
+    }
+    fn epoch_slot(&self, timestamp: u32) -> (u32, u32) {
+        let slot_number =
+            timestamp.saturating_sub(self.config.chain_start_timestamp) / self.config.slot_duration;
+        let epoch_number = slot_number / self.config.slot_per_epoch;
+        (epoch_number, slot_number % self.config.slot_per_epoch)
+    }
+    fn get_stake(&self, addr: Address) -> Result<Amount, BlockchainError> {
+        self.call("get_stake")?;
+        Ok(self
+            .stakers
+            .get(&addr)
+            .map(|(_, stake)| *stake)
+            .unwrap_or_default())
+    }
+    fn get_stakers(&self) -> Result<Vec<(Address, Amount)>, BlockchainError> {
+        self.call("get_stakers")?;
+        let mut stakers = self
+            .stakers
+            .iter()
+            .map(|(addr, (_, stake))| (addr.clone(), *stake))
+            .collect::<Vec<_>>();
+        stakers.sort_by_key(|(_, stake)| std::cmp::Reverse(*stake));
+        Ok(stakers)
+    }
+    fn get_auto_delegate_ratio(
+        &self,
+        _delegator: Address,
+        _delegatee: Address,
+    ) -> Result<Ratio, BlockchainError> {
+        self.call("get_auto_delegate_ratio")?;
+        Ok(Ratio(0))
+    }
+    fn get_delegatees(
+        &self,
+        delegator: Address,
+        top: Option<usize>,
+    ) -> Result<Vec<(Address, Amount)>, BlockchainError> {
+        self.call("get_delegatees")?;
+        let mut delegatees = self
+            .delegates
+            .iter()
+            .filter(|((from, _), _)| *from == delegator)
+            .map(|((_, to), amount)| (to.clone(), *amount))
+            .collect::<Vec<_>>();
+        delegatees.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+        delegatees.truncate(top.unwrap_or(usize::MAX));
+        Ok(delegatees)
+    }
+    fn get_account_transactions(
+        &self,
+        address: Address,
+        since: u64,
+        page: usize,
+        page_size: usize,
+    ) -> Result<Vec<(u64, <Hasher as Hash>::Output)>, BlockchainError> {
+        self.call("get_account_transactions")?;
+        Ok(self
+            .blocks
+            .iter()
+            .rev()
+            .take_while(|b| b.header.number >= since)
+            .flat_map(|b| {
+                b.body
+                    .iter()
+                    .rev()
+                    .filter(|tx| tx.involved_addresses().contains(&address))
+                    .map(|tx| (b.header.number, tx.hash()))
+                    .collect::<Vec<_>>()
+            })
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .collect())
+    }
+    fn get_delegators(
+        &self,
+        delegatee: Address,
+        top: Option<usize>,
+    ) -> Result<Vec<(Address, Amount)>, BlockchainError> {
+        self.call("get_delegators")?;
+        let mut delegators = self
+            .delegates
+            .iter()
+            .filter(|((_, to), _)| *to == delegatee)
+            .map(|((from, _), amount)| (from.clone(), *amount))
+            .collect::<Vec<_>>();
+        delegators.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+        delegators.truncate(top.unwrap_or(usize::MAX));
+        Ok(delegators)
+    }
+    fn is_validator(
+        &self,
+        _timestamp: u32,
+        _addr: Address,
+        _proof: ValidatorProof,
+    ) -> Result<bool, BlockchainError> {
+        self.call("is_validator")?;
+        Ok(self.elected)
+    }
+    fn validator_status(
+        &self,
+        _timestamp: u32,
+        _wallet: &TxBuilder,
+    ) -> Result<ValidatorProof, BlockchainError> {
+        self.call("validator_status")?;
+        Ok(self.validator_proof.clone())
+    }
+    fn min_validator_reward(&self, _validator: Address) -> Result<Amount, BlockchainError> {
+        self.call("min_validator_reward")?;
+        Ok(Amount(0))
+    }
+    fn currency_in_circulation(&self) -> Result<Amount, BlockchainError> {
+        self.call("currency_in_circulation")?;
+        let mut sum = Amount(0);
+        for ((_, token_id), amount) in self.balances.iter() {
+            if *token_id == TokenId::Ziesha {
+                sum += *amount;
+            }
+        }
+        Ok(sum)
+    }
+    fn config(&self) -> &BlockchainConfig {
+        &self.config
+    }
+    fn db_checksum(&self) -> Result<String, BlockchainError> {
+        self.call("db_checksum")?;
+        Ok(hex::encode(self.get_tip()?.hash()))
+    }
+    fn get_token(&self, token_id: TokenId) -> Result<Option<Token>, BlockchainError> {
+        self.call("get_token")?;
+        Ok(self.tokens.get(&token_id).cloned())
+    }
+    fn get_balance(&self, addr: Address, token_id: TokenId) -> Result<Amount, BlockchainError> {
+        self.call("get_balance")?;
+        Ok(self
+            .balances
+            .get(&(addr, token_id))
+            .cloned()
+            .unwrap_or_default())
+    }
+    fn get_contract_balance(
+        &self,
+        _contract_id: ContractId,
+        _token_id: TokenId,
+    ) -> Result<Amount, BlockchainError> {
+        self.call("get_contract_balance")?;
+        Ok(Amount(0))
+    }
+    fn get_delegate(
+        &self,
+        delegator: Address,
+        delegatee: Address,
+    ) -> Result<Delegate, BlockchainError> {
+        self.call("get_delegate")?;
+        Ok(Delegate {
+            amount: self
+                .delegates
+                .get(&(delegator, delegatee))
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+    fn get_undelegation(
+        &self,
+        _undelegator: Address,
+        _undelegation_id: UndelegationId,
+    ) -> Result<Option<Undelegation>, BlockchainError> {
+        self.call("get_undelegation")?;
+        Ok(None)
+    }
+    fn get_undelegations(
+        &self,
+        _undelegator: Address,
+        _top: Option<usize>,
+    ) -> Result<Vec<(UndelegationId, Undelegation)>, BlockchainError> {
+        self.call("get_undelegations")?;
+        Ok(vec![])
+    }
+    fn get_staker(&self, addr: Address) -> Result<Option<Staker>, BlockchainError> {
+        self.call("get_staker")?;
+        Ok(self.stakers.get(&addr).map(|(staker, _)| staker.clone()))
+    }
+    fn get_nonce(&self, addr: Address) -> Result<u32, BlockchainError> {
+        self.call("get_nonce")?;
+        Ok(self.nonces.get(&addr).cloned().unwrap_or_default())
+    }
+    fn get_mpn_account(&self, _addr: MpnAddress) -> Result<zk::MpnAccount, BlockchainError> {
+        self.call("get_mpn_account")?;
+        Ok(Default::default())
+    }
+    fn get_mpn_accounts(
+        &self,
+        _page: usize,
+        _page_size: usize,
+    ) -> Result<Vec<(u64, zk::MpnAccount)>, BlockchainError> {
+        self.call("get_mpn_accounts")?;
+        Ok(vec![])
+    }
+    fn get_deposit_nonce(
+        &self,
+        _addr: Address,
+        _contract_id: ContractId,
+    ) -> Result<u32, BlockchainError> {
+        self.call("get_deposit_nonce")?;
+        Ok(0)
+    }
+    fn get_contract_account(
+        &self,
+        _contract_id: ContractId,
+    ) -> Result<ContractAccount, BlockchainError> {
+        self.call("get_contract_account")?;
+        Err(BlockchainError::ContractNotFound)
+    }
+    fn read_state(
+        &self,
+        _contract_id: ContractId,
+        _locator: zk::ZkDataLocator,
+    ) -> Result<zk::ZkScalar, BlockchainError> {
+        self.call("read_state")?;
+        Err(BlockchainError::ContractNotFound)
+    }
+    fn next_reward(&self) -> Result<Amount, BlockchainError> {
+        self.call("next_reward")?;
+        Ok(self.next_reward)
+    }
+    fn get_block_reward(&self, index: u64) -> Result<Option<Amount>, BlockchainError> {
+        self.call("get_block_reward")?;
+        Ok((index > 0 && index < self.height()).then_some(self.next_reward))
+    }
+    /// The longer chain wins
+    fn will_extend(&self, from: u64, headers: &[Header]) -> Result<bool, BlockchainError> {
+        self.call("will_extend")?;
+        Ok(from > 0
+            && from <= self.height()
+            && from.saturating_add(headers.len() as u64) > self.height())
+    }
+    fn extend(&mut self, from: u64, blocks: &[Block]) -> Result<(), BlockchainError> {
+        self.call("extend")?;
+        self.replace_blocks(from, blocks)
+    }
+    fn reorg(&mut self, from: u64, blocks: &[Block]) -> Result<Vec<Transaction>, BlockchainError> {
+        self.call("reorg")?;
+        let abandoned = self
+            .blocks
+            .get(from as usize..)
+            .map(|blocks| blocks.to_vec())
+            .unwrap_or_default();
+        self.replace_blocks(from, blocks)?;
+        Ok(abandoned
+            .into_iter()
+            .flat_map(|b| b.body.into_iter())
+            .filter(|tx| !blocks.iter().any(|b| b.body.contains(tx)))
+            .collect())
+    }
+    fn rollback(&mut self) -> Result<(), BlockchainError> {
+        self.call("rollback")?;
+        if self.height() <= 1 {
+            return Err(BlockchainError::NoBlocksToRollback);
+        }
+        self.blocks.pop();
+        Ok(())
+    }
+    fn prune(&mut self, keep_blocks: u64) -> Result<u64, BlockchainError> {
+        self.call("prune")?;
+        let until = self.height().saturating_sub(keep_blocks);
+        let pruned = until.saturating_sub(self.pruned_height);
+        self.pruned_height = std::cmp::max(self.pruned_height, until);
+        Ok(pruned)
+    }
+    fn recover(&mut self) -> Result<u64, BlockchainError> {
+        self.call("recover")?;
+        Ok(0)
+    }
+    fn set_block_template_policy(&mut self, policy: Arc<dyn BlockTemplatePolicy>) {
+        self.block_template_policy = policy;
+    }
+    fn update_block_template_limits(&mut self, max_txs: Option<usize>, min_fee: Option<Amount>) {
+        if max_txs.is_some() {
+            self.block_template_limits.max_txs = max_txs;
+        }
+        if min_fee.is_some() {
+            self.block_template_limits.min_fee = min_fee;
+        }
+    }
+    fn get_pruned_height(&self) -> Result<u64, BlockchainError> {
+        self.call("get_pruned_height")?;
+        Ok(self.pruned_height)
+    }
+    fn prune_rollbacks(&mut self, keep_blocks: u64) -> Result<u64, BlockchainError> {
+        self.call("prune_rollbacks")?;
+        let until = self.height().saturating_sub(keep_blocks);
+        let pruned = until.saturating_sub(self.rollback_pruned_height);
+        self.rollback_pruned_height = std::cmp::max(self.rollback_pruned_height, until);
+        Ok(pruned)
+    }
+    fn get_rollback_pruned_height(&self) -> Result<u64, BlockchainError> {
+        self.call("get_rollback_pruned_height")?;
+        Ok(std::cmp::max(
+            self.rollback_pruned_height,
+            self.pruned_height,
+        ))
+    }
+    /// A block on top of the tip with all of the given transactions, if elected
+    fn draft_block(
+        &self,
+        timestamp: u32,
+        mempool: &[TransactionAndDelta],
+        wallet: &TxBuilder,
+        _check: bool,
+    ) -> Result<Option<Block>, BlockchainError> {
+        self.call("draft_block")?;
+        if !self.elected {
+            return Ok(None);
+        }
+        Ok(Some(self.next_block(
+            timestamp,
+            wallet,
+            mempool.iter().map(|tx| tx.tx.clone()).collect(),
+        )))
+    }
+    fn get_height(&self) -> Result<u64, BlockchainError> {
+        self.call("get_height")?;
+        Ok(self.height())
+    }
+    fn get_tip(&self) -> Result<Header, BlockchainError> {
+        self.call("get_tip")?;
+        Ok(self.blocks.last().unwrap().header.clone())
+    }
+    fn get_headers(&self, since: u64, count: u64) -> Result<Vec<Header>, BlockchainError> {
+        self.call("get_headers")?;
+        let until = std::cmp::min(self.height(), since.saturating_add(count));
+        Ok((since..until)
+            .map(|i| self.blocks[i as usize].header.clone())
+            .collect())
+    }
+    fn get_blocks(&self, since: u64, count: u64) -> Result<Vec<Block>, BlockchainError> {
+        self.call("get_blocks")?;
+        let since = std::cmp::max(since, self.pruned_height);
+        let until = std::cmp::min(self.height(), since.saturating_add(count));
+        Ok((since..until)
+            .map(|i| self.blocks[i as usize].clone())
+            .collect())
+    }
+    fn get_header(&self, index: u64) -> Result<Header, BlockchainError> {
+        self.call("get_header")?;
+        self.blocks
+            .get(index as usize)
+            .map(|b| b.header.clone())
+            .ok_or(BlockchainError::BlockNotFound)
+    }
+    fn get_block(&self, index: u64) -> Result<Block, BlockchainError> {
+        self.call("get_block")?;
+        if index < self.pruned_height {
+            return Err(BlockchainError::BlockPruned);
+        }
+        self.blocks
+            .get(index as usize)
+            .cloned()
+            .ok_or(BlockchainError::BlockNotFound)
+    }
+    fn get_block_meta(&self, index: u64) -> Result<BlockMeta, BlockchainError> {
+        self.call("get_block_meta")?;
+        self.blocks
+            .get(index as usize)
+            .map(|b| b.meta())
+            .ok_or(BlockchainError::BlockNotFound)
+    }
+    fn get_header_number(
+        &self,
+        hash: &<Hasher as Hash>::Output,
+    ) -> Result<Option<u64>, BlockchainError> {
+        self.call("get_header_number")?;
+        Ok(self
+            .blocks
+            .iter()
+            .position(|b| &b.header.hash() == hash)
+            .map(|i| i as u64))
+    }
+    fn get_transaction_location(
+        &self,
+        hash: &<Hasher as Hash>::Output,
+    ) -> Result<Option<TransactionLocation>, BlockchainError> {
+        self.call("get_transaction_location")?;
+        Ok(self.blocks.iter().enumerate().find_map(|(i, b)| {
+            b.body
+                .iter()
+                .position(|tx| &tx.hash() == hash)
+                .map(|index| TransactionLocation {
+                    block: i as u64,
+                    index: index as u32,
+                })
+        }))
+    }
+    fn get_snapshot(
+        &self,
+        _after: Option<StringKey>,
+        _count: usize,
+    ) -> Result<Vec<(StringKey, Blob)>, BlockchainError> {
+        self.call("get_snapshot")?;
+        Ok(Vec::new())
+    }
+    fn import_snapshot(
+        &mut self,
+        _tip: &Header,
+        _pairs: &[(StringKey, Blob)],
+    ) -> Result<(), BlockchainError> {
+        self.call("import_snapshot")?;
+        Err(BlockchainError::InvalidSnapshot)
+    }
+    fn get_contract(&self, _contract_id: ContractId) -> Result<zk::ZkContract, BlockchainError> {
+        self.call("get_contract")?;
+        Err(BlockchainError::ContractNotFound)
+    }
+    fn check_tx(&self, _tx: &Transaction) -> Result<(), BlockchainError> {
+        self.call("check_tx")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::blockchain::get_test_blockchain_config;
+
+    #[test]
+    fn test_mock_extend_and_rollback() {
+        let validator = TxBuilder::new(&Vec::from("VALIDATOR"));
+        let mut chain = MockBlockchain::new(get_test_blockchain_config());
+        assert_eq!(chain.get_height().unwrap(), 1);
+        assert!(chain
+            .draft_block(60, &[], &validator, true)
+            .unwrap()
+            .is_none());
+
+        chain.set_elected(true);
+        for i in 1..=3 {
+            let block = chain
+                .draft_block(i * 60, &[], &validator, true)
+                .unwrap()
+                .unwrap();
+            assert_eq!(block.header.parent_hash, chain.get_tip().unwrap().hash());
+            chain.extend(i as u64, &[block]).unwrap();
+        }
+        assert_eq!(chain.get_height().unwrap(), 4);
+        assert!(chain
+            .will_extend(2, &chain.get_headers(1, 3).unwrap())
+            .unwrap());
+        assert!(!chain
+            .will_extend(2, &chain.get_headers(1, 2).unwrap())
+            .unwrap());
+
+        chain.rollback().unwrap();
+        assert_eq!(chain.get_tip().unwrap().number, 2);
+        assert!(matches!(
+            chain.extend(5, &[]),
+            Err(BlockchainError::ExtendFromFuture)
+        ));
+
+        assert_eq!(chain.prune(1).unwrap(), 2);
+        assert!(matches!(
+            chain.get_block(1),
+            Err(BlockchainError::BlockPruned)
+        ));
+        assert_eq!(chain.get_headers(0, 10).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_mock_failure_injection() {
+        let mut chain = MockBlockchain::new(get_test_blockchain_config());
+        let addr = TxBuilder::new(&Vec::from("ABC")).get_address();
+        chain.set_balance(addr.clone(), TokenId::Ziesha, Amount(123));
+        assert!(chain.get_token(TokenId::Ziesha).unwrap().is_some());
+
+        chain.fail_times("get_balance", 2, || BlockchainError::Inconsistency);
+        for _ in 0..2 {
+            assert!(matches!(
+                chain.get_balance(addr.clone(), TokenId::Ziesha),
+                Err(BlockchainError::Inconsistency)
+            ));
+        }
+        assert_eq!(
+            chain.get_balance(addr.clone(), TokenId::Ziesha).unwrap(),
+            Amount(123)
+        );
+        assert_eq!(chain.calls("get_balance"), 3);
+
+        chain.fail("get_height", || BlockchainError::BlockNotFound);
+        assert!(chain.get_height().is_err());
+        assert!(chain.get_height().is_err());
+        chain.succeed("get_height");
+        assert_eq!(chain.get_height().unwrap(), 1);
+    }
+}
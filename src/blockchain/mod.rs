@@ -4,17 +4,27 @@ mod mempool;
 pub use mempool::*;
 mod config;
 pub use config::BlockchainConfig;
+mod cost;
+pub use cost::*;
 mod ops;
+mod policy;
+mod replay;
+pub use policy::*;
+pub use replay::*;
+#[cfg(any(test, feature = "test-utils"))]
+mod mock;
+#[cfg(any(test, feature = "test-utils"))]
+pub use mock::MockBlockchain;
 
 use crate::core::{
-    hash::Hash, Address, Amount, Block, ContractAccount, ContractDeposit, ContractId,
+    hash::Hash, Address, Amount, Block, BlockMeta, ContractAccount, ContractDeposit, ContractId,
     ContractUpdate, ContractWithdraw, Delegate, Hasher, Header, Money, MpnAddress, ProofOfStake,
     Ratio, RegularSendEntry, Signature, Staker, Token, TokenId, TokenUpdate, Transaction,
     TransactionAndDelta, TransactionData, Undelegation, UndelegationId, ValidatorProof, Vrf,
     ZkHasher as CoreZkHasher,
 };
 use crate::crypto::VerifiableRandomFunction;
-use crate::db::{keys, KvStore, RamMirrorKvStore, WriteOp};
+use crate::db::{keys, Blob, KvStore, QueryResult, RamMirrorKvStore, StringKey, WriteOp};
 
 use crate::wallet::TxBuilder;
 use crate::zk;
@@ -23,6 +33,7 @@ use rayon::prelude::*;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TransactionValidity {
@@ -48,6 +59,13 @@ impl TransactionStats {
     }
 }
 
+/// Where a transaction has been included in the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionLocation {
+    pub block: u64,
+    pub index: u32,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ZkCompressedStateChange {
     prev_state: zk::ZkCompressedState,
@@ -76,6 +94,15 @@ pub trait Blockchain<K: KvStore> {
         delegatee: Address,
         top: Option<usize>,
     ) -> Result<Vec<(Address, Amount)>, BlockchainError>;
+    /// Block numbers and hashes of the transactions touching `address` in blocks at or
+    /// above `since`, newest first
+    fn get_account_transactions(
+        &self,
+        address: Address,
+        since: u64,
+        page: usize,
+        page_size: usize,
+    ) -> Result<Vec<(u64, <Hasher as Hash>::Output)>, BlockchainError>;
     fn is_validator(
         &self,
         timestamp: u32,
@@ -143,9 +170,26 @@ pub trait Blockchain<K: KvStore> {
         locator: zk::ZkDataLocator,
     ) -> Result<zk::ZkScalar, BlockchainError>;
     fn next_reward(&self) -> Result<Amount, BlockchainError>;
+    /// Reward issued to the validator of the given block and its delegators, on top
+    /// of the fees. `None` for the genesis block.
+    fn get_block_reward(&self, index: u64) -> Result<Option<Amount>, BlockchainError>;
     fn will_extend(&self, from: u64, headers: &[Header]) -> Result<bool, BlockchainError>;
     fn extend(&mut self, from: u64, blocks: &[Block]) -> Result<(), BlockchainError>;
+    /// Rolls back to block `from` and applies `blocks` on top of it atomically. Returns
+    /// the transactions of the abandoned blocks which the new branch does not include.
+    fn reorg(&mut self, from: u64, blocks: &[Block]) -> Result<Vec<Transaction>, BlockchainError>;
     fn rollback(&mut self) -> Result<(), BlockchainError>;
+    fn prune(&mut self, keep_blocks: u64) -> Result<u64, BlockchainError>;
+    fn recover(&mut self) -> Result<u64, BlockchainError>;
+    fn set_block_template_policy(&mut self, policy: Arc<dyn BlockTemplatePolicy>);
+    /// Overrides the limits of the block template policy, `None` keeps the current
+    /// ones
+    fn update_block_template_limits(&mut self, max_txs: Option<usize>, min_fee: Option<Amount>);
+    fn get_pruned_height(&self) -> Result<u64, BlockchainError>;
+    /// Only keep rollback data of the last `keep_blocks` blocks
+    fn prune_rollbacks(&mut self, keep_blocks: u64) -> Result<u64, BlockchainError>;
+    /// Blocks below this height cannot be rolled back anymore
+    fn get_rollback_pruned_height(&self) -> Result<u64, BlockchainError>;
     fn draft_block(
         &self,
         timestamp: u32,
@@ -159,6 +203,32 @@ pub trait Blockchain<K: KvStore> {
     fn get_blocks(&self, since: u64, count: u64) -> Result<Vec<Block>, BlockchainError>;
     fn get_header(&self, index: u64) -> Result<Header, BlockchainError>;
     fn get_block(&self, index: u64) -> Result<Block, BlockchainError>;
+    /// Transaction count and body size of a block, available even if it is pruned
+    fn get_block_meta(&self, index: u64) -> Result<BlockMeta, BlockchainError>;
+    /// Number of the block with the given header hash, if it is on the local chain
+    fn get_header_number(
+        &self,
+        hash: &<Hasher as Hash>::Output,
+    ) -> Result<Option<u64>, BlockchainError>;
+    /// Location of the transaction with the given hash, `None` if it is not on the chain
+    fn get_transaction_location(
+        &self,
+        hash: &<Hasher as Hash>::Output,
+    ) -> Result<Option<TransactionLocation>, BlockchainError>;
+
+    /// Up to `count` key-value pairs of the current state, starting after `after`
+    fn get_snapshot(
+        &self,
+        after: Option<StringKey>,
+        count: usize,
+    ) -> Result<Vec<(StringKey, Blob)>, BlockchainError>;
+    /// Replaces the state of a fresh chain with a snapshot downloaded from a peer,
+    /// which should end at `tip`
+    fn import_snapshot(
+        &mut self,
+        tip: &Header,
+        pairs: &[(StringKey, Blob)],
+    ) -> Result<(), BlockchainError>;
 
     fn get_contract(&self, contract_id: ContractId) -> Result<zk::ZkContract, BlockchainError>;
 
@@ -168,6 +238,10 @@ pub trait Blockchain<K: KvStore> {
 pub struct KvStoreChain<K: KvStore> {
     config: BlockchainConfig,
     database: K,
+    block_template_policy: Arc<dyn BlockTemplatePolicy>,
+    block_template_limits: BlockTemplateLimits,
+    address_index: bool,
+    replay_log: Option<ReplayLog>,
 }
 
 impl<K: KvStore> KvStoreChain<K> {
@@ -178,6 +252,10 @@ impl<K: KvStore> KvStoreChain<K> {
         let mut chain = KvStoreChain::<K> {
             database,
             config: config.clone(),
+            block_template_policy: Arc::new(DefaultBlockTemplatePolicy::default()),
+            block_template_limits: Default::default(),
+            address_index: true,
+            replay_log: None,
         };
         if chain.get_height()? == 0 {
             chain.apply_block(&config.genesis)?;
@@ -188,10 +266,59 @@ impl<K: KvStore> KvStoreChain<K> {
         Ok(chain)
     }
 
+    pub fn with_block_template_policy<P: BlockTemplatePolicy + 'static>(
+        mut self,
+        policy: P,
+    ) -> Self {
+        self.block_template_policy = Arc::new(policy);
+        self
+    }
+
+    /// Whether the transactions touching each address are recorded while applying
+    /// blocks. Blocks applied while the index is disabled are missing from it.
+    pub fn with_address_index(mut self, enabled: bool) -> Self {
+        self.address_index = enabled;
+        self
+    }
+
+    /// Records the blocks applied from now on, along with their state patches
+    pub fn with_replay_log(mut self, log: ReplayLog) -> Self {
+        self.replay_log = Some(log);
+        self
+    }
+
+    /// Applies the block of a replay log entry, returning the state patch it made
+    pub fn replay(&mut self, entry: &ReplayEntry) -> Result<Vec<WriteOp>, BlockchainError> {
+        let (_, mut patches) = ops::reorg(
+            self,
+            entry.block.header.number,
+            std::slice::from_ref(&entry.block),
+            true,
+        )?;
+        patches.pop().ok_or(BlockchainError::Inconsistency)
+    }
+
+    /// Checksum of the state keys only, equal on all nodes at the same tip
+    pub fn state_checksum(&self) -> Result<String, BlockchainError> {
+        let state = self
+            .database
+            .pairs("".into())?
+            .into_iter()
+            .filter(|(k, _)| keys::is_state(k))
+            .collect();
+        Ok(hex::encode(
+            QueryResult::Precalculated(state).checksum::<Hasher>()?,
+        ))
+    }
+
     pub fn fork_on_ram(&self) -> KvStoreChain<RamMirrorKvStore<'_, K>> {
         KvStoreChain {
             database: self.database.mirror(),
             config: self.config.clone(),
+            block_template_policy: self.block_template_policy.clone(),
+            block_template_limits: self.block_template_limits.clone(),
+            address_index: self.address_index,
+            replay_log: None,
         }
     }
 
@@ -260,6 +387,9 @@ impl<K: KvStore> Blockchain<K> for KvStoreChain<K> {
         if index >= self.get_height()? {
             return Err(BlockchainError::BlockNotFound);
         }
+        if index > 0 && index < self.get_pruned_height()? {
+            return Err(BlockchainError::BlockPruned);
+        }
         Ok(match self.database.get(keys::block(index))? {
             Some(b) => b.try_into()?,
             None => {
@@ -268,10 +398,98 @@ impl<K: KvStore> Blockchain<K> for KvStoreChain<K> {
         })
     }
 
+    fn get_block_meta(&self, index: u64) -> Result<BlockMeta, BlockchainError> {
+        if index >= self.get_height()? {
+            return Err(BlockchainError::BlockNotFound);
+        }
+        Ok(match self.database.get(keys::block_meta(index))? {
+            Some(b) => b.try_into()?,
+            // Blocks applied before the metadata was stored
+            None => self.get_block(index)?.meta(),
+        })
+    }
+
+    fn get_header_number(
+        &self,
+        hash: &<Hasher as Hash>::Output,
+    ) -> Result<Option<u64>, BlockchainError> {
+        Ok(match self.database.get(keys::header_number(hash))? {
+            Some(b) => Some(b.try_into()?),
+            None => None,
+        })
+    }
+
+    fn get_transaction_location(
+        &self,
+        hash: &<Hasher as Hash>::Output,
+    ) -> Result<Option<TransactionLocation>, BlockchainError> {
+        Ok(match self.database.get(keys::transaction_location(hash))? {
+            Some(b) => Some(b.try_into()?),
+            None => None,
+        })
+    }
+
     fn rollback(&mut self) -> Result<(), BlockchainError> {
         ops::rollback(self)
     }
 
+    fn prune(&mut self, keep_blocks: u64) -> Result<u64, BlockchainError> {
+        ops::prune(self, keep_blocks)
+    }
+
+    fn prune_rollbacks(&mut self, keep_blocks: u64) -> Result<u64, BlockchainError> {
+        ops::prune_rollbacks(self, keep_blocks)
+    }
+
+    fn recover(&mut self) -> Result<u64, BlockchainError> {
+        ops::recover(self)
+    }
+
+    fn get_snapshot(
+        &self,
+        after: Option<StringKey>,
+        count: usize,
+    ) -> Result<Vec<(StringKey, Blob)>, BlockchainError> {
+        ops::get_snapshot(self, after, count)
+    }
+
+    fn import_snapshot(
+        &mut self,
+        tip: &Header,
+        pairs: &[(StringKey, Blob)],
+    ) -> Result<(), BlockchainError> {
+        ops::import_snapshot(self, tip, pairs)
+    }
+
+    fn set_block_template_policy(&mut self, policy: Arc<dyn BlockTemplatePolicy>) {
+        self.block_template_policy = policy;
+    }
+
+    fn update_block_template_limits(&mut self, max_txs: Option<usize>, min_fee: Option<Amount>) {
+        if max_txs.is_some() {
+            self.block_template_limits.max_txs = max_txs;
+        }
+        if min_fee.is_some() {
+            self.block_template_limits.min_fee = min_fee;
+        }
+    }
+
+    fn get_pruned_height(&self) -> Result<u64, BlockchainError> {
+        Ok(match self.database.get(keys::pruned_height())? {
+            Some(b) => b.try_into()?,
+            None => 0,
+        })
+    }
+
+    fn get_rollback_pruned_height(&self) -> Result<u64, BlockchainError> {
+        // Pruning blocks removes their rollback data too
+        let rollback_pruned = match self.database.get(keys::rollback_pruned_height())? {
+            Some(b) => b.try_into()?,
+            None => 0,
+        };
+        Ok(std::cmp::max(rollback_pruned, self.get_pruned_height()?))
+    }
+
     fn get_tip(&self) -> Result<Header, BlockchainError> {
         let height = self.get_height()?;
         if height == 0 {
@@ -400,7 +618,7 @@ impl<K: KvStore> Blockchain<K> for KvStoreChain<K> {
     }
 
     fn will_extend(&self, from: u64, headers: &[Header]) -> Result<bool, BlockchainError> {
-        if from + headers.len() as u64 <= self.get_height()? {
+        if from.saturating_add(headers.len() as u64) <= self.get_height()? {
             return Ok(false);
         }
 
@@ -408,6 +626,19 @@ impl<K: KvStore> Blockchain<K> for KvStoreChain<K> {
             return Err(BlockchainError::ExtendFromGenesis);
         } else if from > self.get_height()? {
             return Err(BlockchainError::ExtendFromFuture);
+        } else if from < self.get_height()? && from < self.get_rollback_pruned_height()? {
+            return Err(BlockchainError::ReorgTooDeep);
+        }
+
+        if let Some((number, _)) = self
+            .config
+            .checkpoints
+            .range(..self.get_height()?)
+            .next_back()
+        {
+            if from <= *number {
+                return Err(BlockchainError::ReorgBelowCheckpoint);
+            }
         }
 
         let mut last_header = self.get_header(from - 1)?;
@@ -428,35 +659,25 @@ impl<K: KvStore> Blockchain<K> for KvStoreChain<K> {
                 return Err(BlockchainError::InvalidParentHash);
             }
 
+            if let Some(hash) = self.config.checkpoints.get(&h.number) {
+                if &h.hash() != hash {
+                    return Err(BlockchainError::CheckpointMismatch);
+                }
+            }
+
             last_header = h.clone();
         }
 
         Ok(true)
     }
     fn extend(&mut self, from: u64, blocks: &[Block]) -> Result<(), BlockchainError> {
-        let (ops, _) = self.isolated(|chain| {
-            let curr_height = chain.get_height()?;
-
-            if from == 0 {
-                return Err(BlockchainError::ExtendFromGenesis);
-            } else if from > curr_height {
-                return Err(BlockchainError::ExtendFromFuture);
-            }
-
-            while chain.get_height()? > from {
-                chain.rollback()?;
-            }
-
-            for block in blocks.iter() {
-                chain.apply_block(block)?;
-            }
-
-            Ok(())
-        })?;
-
-        self.database.update(&ops)?;
+        self.reorg(from, blocks)?;
         Ok(())
     }
+    fn reorg(&mut self, from: u64, blocks: &[Block]) -> Result<Vec<Transaction>, BlockchainError> {
+        let record = self.replay_log.is_some();
+        Ok(ops::reorg(self, from, blocks, record)?.0)
+    }
     fn get_height(&self) -> Result<u64, BlockchainError> {
         Ok(match self.database.get(keys::height())? {
             Some(b) => b.try_into()?,
@@ -465,7 +686,7 @@ impl<K: KvStore> Blockchain<K> for KvStoreChain<K> {
     }
     fn get_headers(&self, since: u64, count: u64) -> Result<Vec<Header>, BlockchainError> {
         let mut blks: Vec<Header> = Vec::new();
-        let until = std::cmp::min(self.get_height()?, since + count);
+        let until = std::cmp::min(self.get_height()?, since.saturating_add(count));
         for i in since..until {
             blks.push(self.get_header(i)?);
         }
@@ -473,7 +694,7 @@ impl<K: KvStore> Blockchain<K> for KvStoreChain<K> {
     }
     fn get_blocks(&self, since: u64, count: u64) -> Result<Vec<Block>, BlockchainError> {
         let mut blks: Vec<Block> = Vec::new();
-        let until = std::cmp::min(self.get_height()?, since + count);
+        let until = std::cmp::min(self.get_height()?, since.saturating_add(count));
         for i in since..until {
             blks.push(self.get_block(i)?);
         }
@@ -483,6 +704,12 @@ impl<K: KvStore> Blockchain<K> for KvStoreChain<K> {
         let supply = self.get_balance(Default::default(), TokenId::Ziesha)?;
         Ok(supply / self.config.reward_ratio)
     }
+    fn get_block_reward(&self, index: u64) -> Result<Option<Amount>, BlockchainError> {
+        Ok(match self.database.get(keys::block_reward(index))? {
+            Some(b) => Some(b.try_into()?),
+            None => None,
+        })
+    }
     fn draft_block(
         &self,
         timestamp: u32,
@@ -670,6 +897,34 @@ impl<K: KvStore> Blockchain<K> for KvStoreChain<K> {
         Ok(delegatees)
     }
 
+    fn get_account_transactions(
+        &self,
+        address: Address,
+        since: u64,
+        page: usize,
+        page_size: usize,
+    ) -> Result<Vec<(u64, <Hasher as Hash>::Output)>, BlockchainError> {
+        if !self.address_index {
+            return Err(BlockchainError::AddressIndexDisabled);
+        }
+        let mut txs = Vec::new();
+        for (k, v) in self
+            .database
+            .pairs(keys::AccountTxDbKey::prefix(&address).into())?
+            .into_iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+        {
+            let key = keys::AccountTxDbKey::try_from(k)?;
+            // Keys are sorted from the newest block to the oldest
+            if key.block < since {
+                break;
+            }
+            txs.push((key.block, v.try_into()?));
+        }
+        Ok(txs)
+    }
+
     fn epoch_slot(&self, timestamp: u32) -> (u32, u32) {
         // TODO: Error instead of saturating_sub!
         let slot_number =
@@ -4,6 +4,7 @@ pub fn apply_block<K: KvStore>(
     chain: &mut KvStoreChain<K>,
     block: &Block,
 ) -> Result<(), BlockchainError> {
+    let _span = tracing::debug_span!("apply_block", number = block.header.number).entered();
     let (ops, _) = chain.isolated(|chain| {
         let curr_height = chain.get_height()?;
 
@@ -41,6 +42,11 @@ pub fn apply_block<K: KvStore>(
                     .map(|t| -> u64 { t.fee.amount.into() })
                     .sum(),
             );
+            // Remember the issued reward, the treasury it is computed from changes
+            chain.database.update(&[WriteOp::Put(
+                keys::block_reward(block.header.number),
+                chain.next_reward()?.into(),
+            )])?;
             chain.pay_validator_and_delegators(
                 block.header.proof_of_stake.validator.clone(),
                 fee_sum,
@@ -57,7 +63,7 @@ pub fn apply_block<K: KvStore>(
         let mut num_mpn_contract_deposits = 0;
         let mut num_mpn_contract_withdraws = 0;
 
-        for tx in block.body.iter() {
+        for (index, tx) in block.body.iter().enumerate() {
             // Count MPN updates
             if let TransactionData::UpdateContract {
                 contract_id,
@@ -84,6 +90,31 @@ pub fn apply_block<K: KvStore>(
 
             body_size += tx.size();
             chain.apply_tx(tx, is_genesis)?;
+
+            let tx_hash = tx.hash();
+            let mut index_ops = Vec::new();
+            if chain.address_index {
+                index_ops.extend(tx.involved_addresses().into_iter().map(|address| {
+                    WriteOp::Put(
+                        keys::AccountTxDbKey {
+                            address,
+                            block: block.header.number,
+                            index: index as u32,
+                        }
+                        .into(),
+                        tx_hash.into(),
+                    )
+                }));
+            }
+            index_ops.push(WriteOp::Put(
+                keys::transaction_location(&tx_hash),
+                TransactionLocation {
+                    block: block.header.number,
+                    index: index as u32,
+                }
+                .into(),
+            ));
+            chain.database.update(&index_ops)?;
         }
 
         if !is_genesis
@@ -98,6 +129,19 @@ pub fn apply_block<K: KvStore>(
             return Err(BlockchainError::BlockTooBig);
         }
 
+        let mpn_contract_id = chain.config.mpn_config.mpn_contract_id;
+        if !is_genesis
+            && block
+                .body
+                .iter()
+                .filter(|tx| !updates_contract(tx, &mpn_contract_id))
+                .map(execution_cost)
+                .fold(0u64, |sum, cost| sum.saturating_add(cost))
+                > chain.config.max_block_execution_cost
+        {
+            return Err(BlockchainError::BlockExecutionCostTooHigh);
+        }
+
         if curr_height > 0 {
             let mut new_randomness = chain.epoch_randomness()?;
             let tip_epoch = chain
@@ -132,6 +176,11 @@ pub fn apply_block<K: KvStore>(
                 keys::header(block.header.number),
                 block.header.clone().into(),
             ),
+            WriteOp::Put(
+                keys::header_number(&block.header.hash()),
+                block.header.number.into(),
+            ),
+            WriteOp::Put(keys::block_meta(block.header.number), block.meta().into()),
             WriteOp::Put(keys::block(block.header.number), block.into()),
             WriteOp::Put(
                 keys::merkle(block.header.number),
@@ -28,6 +28,23 @@ pub fn apply_tx<K: KvStore>(
             return Err(BlockchainError::MemoTooLong);
         }
 
+        if !internal
+            && !updates_contract(tx, &chain.config.mpn_config.mpn_contract_id)
+            && execution_cost(tx) > chain.config.max_tx_execution_cost
+        {
+            return Err(BlockchainError::ExecutionCostTooHigh);
+        }
+
+        // Internal transactions may pay the treasury, which is the all-zero key
+        if !internal
+            && tx
+                .destinations()
+                .iter()
+                .any(|addr| addr.validate().is_err())
+        {
+            return Err(BlockchainError::InvalidDestinationAddress);
+        }
+
         let tx_src = tx.src.clone().unwrap_or_default(); // Default is treasury account!
 
         let mut acc_nonce = chain.get_nonce(tx_src.clone())?;
@@ -13,5 +13,13 @@ mod select_transactions;
 pub use select_transactions::*;
 mod rollback;
 pub use rollback::*;
+mod reorg;
+pub use reorg::*;
+mod prune;
+pub use prune::*;
+mod recover;
+pub use recover::*;
+mod snapshot;
+pub use snapshot::*;
 mod pay_validator_and_delegators;
 pub use pay_validator_and_delegators::*;
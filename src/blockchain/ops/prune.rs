@@ -0,0 +1,52 @@
+use super::*;
+
+/// Removes bodies, merkle trees and rollback data of all blocks except the genesis
+/// and the last `keep_blocks` ones. Headers and state are kept, so the tip can still
+/// be validated and extended. Returns the number of newly pruned blocks.
+pub fn prune<K: KvStore>(
+    chain: &mut KvStoreChain<K>,
+    keep_blocks: u64,
+) -> Result<u64, BlockchainError> {
+    let height = chain.get_height()?;
+    // Genesis block is kept for checking the chain identity on startup
+    let pruned_height = std::cmp::max(chain.get_pruned_height()?, 1);
+    let target = height.saturating_sub(keep_blocks);
+    if target <= pruned_height {
+        return Ok(0);
+    }
+
+    let mut ops = Vec::new();
+    for index in pruned_height..target {
+        ops.push(WriteOp::Remove(keys::block(index)));
+        ops.push(WriteOp::Remove(keys::merkle(index)));
+        ops.push(WriteOp::Remove(keys::rollback(index)));
+    }
+    ops.push(WriteOp::Put(keys::pruned_height(), target.into()));
+    chain.database.update(&ops)?;
+
+    Ok(target - pruned_height)
+}
+
+/// Removes rollback data of all blocks except the genesis and the last `keep_blocks`
+/// ones, keeping their bodies. Forks deeper than `keep_blocks` can no longer be
+/// switched to. Returns the number of blocks whose rollback data was newly removed.
+pub fn prune_rollbacks<K: KvStore>(
+    chain: &mut KvStoreChain<K>,
+    keep_blocks: u64,
+) -> Result<u64, BlockchainError> {
+    let height = chain.get_height()?;
+    let pruned_height = std::cmp::max(chain.get_rollback_pruned_height()?, 1);
+    let target = height.saturating_sub(keep_blocks);
+    if target <= pruned_height {
+        return Ok(0);
+    }
+
+    let mut ops = Vec::new();
+    for index in pruned_height..target {
+        ops.push(WriteOp::Remove(keys::rollback(index)));
+    }
+    ops.push(WriteOp::Put(keys::rollback_pruned_height(), target.into()));
+    chain.database.update(&ops)?;
+
+    Ok(target - pruned_height)
+}
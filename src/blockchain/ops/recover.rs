@@ -0,0 +1,40 @@
+use super::*;
+
+/// Checks that the header, body and rollback data of the tip block are stored and
+/// readable, unless they were pruned on purpose.
+fn check_tip<K: KvStore>(chain: &KvStoreChain<K>) -> Result<(), BlockchainError> {
+    let tip = chain.get_height()? - 1;
+    let header = chain.get_header(tip)?;
+    if tip >= chain.get_pruned_height()? && chain.get_block(tip)?.header != header {
+        return Err(BlockchainError::Inconsistency);
+    }
+    if tip > 0 && tip >= chain.get_rollback_pruned_height()? {
+        let _: Vec<WriteOp> = chain
+            .database
+            .get(keys::rollback(tip))?
+            .ok_or(BlockchainError::Inconsistency)?
+            .try_into()?;
+    }
+    Ok(())
+}
+
+/// Rolls back blocks from the tip until reaching one whose data is fully stored,
+/// after the node was not shut down cleanly. Returns the number of removed blocks.
+pub fn recover<K: KvStore>(chain: &mut KvStoreChain<K>) -> Result<u64, BlockchainError> {
+    let mut rolled_back = 0;
+    loop {
+        let height = chain.get_height()?;
+        if height == 0 {
+            return Ok(rolled_back);
+        }
+        match check_tip(chain) {
+            Ok(()) => return Ok(rolled_back),
+            Err(e) => {
+                tracing::warn!("Block #{} is not consistent: {}", height - 1, e);
+                chain.rollback()?;
+                tracing::warn!("Rolled back block #{}", height - 1);
+                rolled_back += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use super::*;
+use std::collections::HashSet;
+
+/// Returns the evicted transactions, and the state patch of each applied block if
+/// `record` is set. The blocks are written to the replay log before they are committed.
+pub fn reorg<K: KvStore>(
+    chain: &mut KvStoreChain<K>,
+    from: u64,
+    blocks: &[Block],
+    record: bool,
+) -> Result<(Vec<Transaction>, Vec<Vec<WriteOp>>), BlockchainError> {
+    let (ops, (evicted, patches)) = chain.isolated(|chain| {
+        let curr_height = chain.get_height()?;
+
+        if from == 0 {
+            return Err(BlockchainError::ExtendFromGenesis);
+        } else if from > curr_height {
+            return Err(BlockchainError::ExtendFromFuture);
+        } else if from < curr_height && from < chain.get_rollback_pruned_height()? {
+            return Err(BlockchainError::ReorgTooDeep);
+        }
+
+        if let Some((number, _)) = chain.config.checkpoints.range(..curr_height).next_back() {
+            if from <= *number {
+                return Err(BlockchainError::ReorgBelowCheckpoint);
+            }
+        }
+
+        let abandoned = chain.get_blocks(from, curr_height - from)?;
+
+        while chain.get_height()? > from {
+            chain.rollback()?;
+        }
+
+        let mut patches = Vec::new();
+        for block in blocks.iter() {
+            if record {
+                let (patch, _) = chain.isolated(|chain| chain.apply_block(block))?;
+                chain.database.update(&patch)?;
+                patches.push(patch);
+            } else {
+                chain.apply_block(block)?;
+            }
+        }
+
+        let included = blocks
+            .iter()
+            .flat_map(|b| b.body.iter().map(|tx| tx.hash()))
+            .collect::<HashSet<_>>();
+        let evicted = abandoned
+            .into_iter()
+            .flat_map(|b| b.body.into_iter())
+            .filter(|tx| tx.src.is_some() && !included.contains(&tx.hash()))
+            .collect::<Vec<_>>();
+        Ok((evicted, patches))
+    })?;
+
+    if let Some(log) = &mut chain.replay_log {
+        for (block, patch) in blocks.iter().zip(patches.iter()) {
+            log.append(block, patch)?;
+        }
+    }
+
+    chain.database.update(&ops)?;
+    Ok((evicted, patches))
+}
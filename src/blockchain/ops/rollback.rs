@@ -8,6 +8,10 @@ pub fn rollback<K: KvStore>(chain: &mut KvStoreChain<K>) -> Result<(), Blockchai
             return Err(BlockchainError::NoBlocksToRollback);
         }
 
+        if height - 1 < chain.get_rollback_pruned_height()? {
+            return Err(BlockchainError::BlockPruned);
+        }
+
         let rollback: Vec<WriteOp> = match chain.database.get(keys::rollback(height - 1))? {
             Some(b) => b.try_into()?,
             None => {
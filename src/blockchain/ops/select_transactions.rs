@@ -1,4 +1,48 @@
 use super::*;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+// A chain of consecutive transactions of a single sender, which can only be
+// included in a block as a whole, in nonce order.
+struct Package {
+    len: usize,
+    value: u64,
+    size: usize,
+}
+
+impl Package {
+    // Compares value-per-byte of two packages without losing precision
+    fn cmp_density(&self, other: &Package) -> Ordering {
+        (self.value as u128 * other.size as u128).cmp(&(other.value as u128 * self.size as u128))
+    }
+}
+
+// Finds the prefix of a sender's queue with the highest value-per-byte, so
+// that a high-fee transaction can pull in its low-fee predecessors.
+fn best_package(
+    policy: &dyn BlockTemplatePolicy,
+    queue: &VecDeque<TransactionAndDelta>,
+) -> Option<Package> {
+    let mut best: Option<Package> = None;
+    let (mut value, mut size) = (0u64, 0usize);
+    for (i, tx) in queue.iter().enumerate() {
+        value += policy.value(&tx.tx);
+        size += tx.tx.size();
+        let pkg = Package {
+            len: i + 1,
+            value,
+            size,
+        };
+        if best
+            .as_ref()
+            .map(|b| pkg.cmp_density(b) == Ordering::Greater)
+            .unwrap_or(true)
+        {
+            best = Some(pkg);
+        }
+    }
+    best
+}
 
 pub fn select_transactions<K: KvStore>(
     chain: &KvStoreChain<K>,
@@ -6,56 +50,130 @@ pub fn select_transactions<K: KvStore>(
     txs: &[TransactionAndDelta],
     check: bool,
 ) -> Result<Vec<TransactionAndDelta>, BlockchainError> {
-    let mut sorted = txs
-        .iter()
-        .filter(|t| t.tx.fee.token_id == TokenId::Ziesha)
-        .cloned()
-        .collect::<Vec<_>>();
-    // WARN: Sort will be invalid if not all fees are specified in Ziesha
-    sorted.sort_unstable_by_key(|tx| {
-        let cost = tx.tx.size();
-        let is_mpn = if let TransactionData::UpdateContract { contract_id, .. } = &tx.tx.data {
+    let policy = chain.block_template_policy.clone();
+    let limits = &chain.block_template_limits;
+    let min_fee = limits.min_fee.unwrap_or_else(|| policy.min_fee());
+    let max_txs = limits.max_txs.or_else(|| policy.max_txs());
+    let is_mpn = |tx: &Transaction| {
+        if let TransactionData::UpdateContract { contract_id, .. } = &tx.data {
             *contract_id == chain.config.mpn_config.mpn_contract_id
         } else {
             false
-        };
-        (
-            is_mpn,
-            Into::<u64>::into(tx.tx.fee.amount) / cost as u64,
-            -(tx.tx.nonce as i32),
-        )
-    });
-    if !check {
-        return Ok(sorted);
+        }
+    };
+
+    // MPN updates always come first, blocks are not valid without them
+    let mut mpn_txs = Vec::new();
+    let mut queues: HashMap<Option<Address>, Vec<TransactionAndDelta>> = HashMap::new();
+    // WARN: Packing will be invalid if not all fees are specified in Ziesha
+    for tx in txs.iter().filter(|t| t.tx.fee.token_id == TokenId::Ziesha) {
+        if is_mpn(&tx.tx) {
+            mpn_txs.push(tx.clone());
+        } else if tx.tx.fee.amount >= min_fee && policy.accept(&tx.tx) {
+            queues
+                .entry(tx.tx.src.clone())
+                .or_default()
+                .push(tx.clone());
+        }
     }
+    mpn_txs.sort_unstable_by_key(|tx| tx.tx.nonce);
+    let mut queues = queues
+        .into_iter()
+        .map(|(src, mut q)| {
+            if check {
+                // Transactions with already used nonces will never be applied
+                if let Some(src) = src {
+                    let nonce = chain.get_nonce(src)?;
+                    q.retain(|tx| tx.tx.nonce > nonce);
+                }
+            }
+            q.sort_unstable_by_key(|tx| tx.tx.nonce);
+            Ok(VecDeque::from(q))
+        })
+        .collect::<Result<Vec<_>, BlockchainError>>()?;
+
     let (_, result) = chain.isolated(|chain| {
         // Safe to consider a 0 fee-sum
-        chain.pay_validator_and_delegators(validator, Amount(0))?;
+        if check {
+            chain.pay_validator_and_delegators(validator, Amount(0))?;
+        }
 
         let mut result = Vec::new();
         let mut block_sz = 0usize;
-        for tx in sorted.into_iter().rev() {
+        let mut block_cost = 0u64;
+        let limit_reached = |result: &Vec<TransactionAndDelta>| {
+            max_txs
+                .map(|max_txs| result.len() >= max_txs)
+                .unwrap_or(false)
+        };
+
+        // Returns false if the transaction could not be included
+        let mut try_include = |chain: &mut KvStoreChain<RamMirrorKvStore<'_, K>>,
+                               result: &mut Vec<TransactionAndDelta>,
+                               tx: &TransactionAndDelta|
+         -> Result<bool, BlockchainError> {
+            if !check {
+                result.push(tx.clone());
+                return Ok(true);
+            }
             match chain.isolated(|chain| chain.apply_tx(&tx.tx, false)) {
                 Ok((ops, _)) => {
                     let block_diff = tx.tx.size();
+                    let cost_diff = if is_mpn(&tx.tx) {
+                        0
+                    } else {
+                        execution_cost(&tx.tx)
+                    };
                     if block_sz + block_diff <= chain.config.max_block_size
+                        && block_cost.saturating_add(cost_diff)
+                            <= chain.config.max_block_execution_cost
                         && tx.tx.verify_signature()
                     {
                         block_sz += block_diff;
+                        block_cost += cost_diff;
                         chain.database.update(&ops)?;
-                        result.push(tx);
+                        result.push(tx.clone());
+                        Ok(true)
+                    } else {
+                        Ok(false)
                     }
                 }
                 Err(e) => {
-                    let is_mpn =
-                        if let TransactionData::UpdateContract { contract_id, .. } = &tx.tx.data {
-                            *contract_id == chain.config.mpn_config.mpn_contract_id
-                        } else {
-                            false
-                        };
-                    if is_mpn {
-                        log::error!("MPN transaction rejected: {}", e);
+                    if is_mpn(&tx.tx) {
+                        tracing::error!("MPN transaction rejected: {}", e);
                     }
+                    Ok(false)
+                }
+            }
+        };
+
+        for tx in mpn_txs.iter() {
+            if limit_reached(&result) {
+                return Ok(result);
+            }
+            try_include(chain, &mut result, tx)?;
+        }
+
+        loop {
+            let best = queues
+                .iter()
+                .enumerate()
+                .filter_map(|(i, q)| best_package(policy.as_ref(), q).map(|p| (i, p)))
+                .max_by(|(_, a), (_, b)| a.cmp_density(b));
+            let (index, pkg) = if let Some(best) = best {
+                best
+            } else {
+                break;
+            };
+            for _ in 0..pkg.len {
+                if limit_reached(&result) {
+                    return Ok(result);
+                }
+                let tx = queues[index].pop_front().unwrap();
+                if !try_include(chain, &mut result, &tx)? {
+                    // Later nonces of this sender can't be applied anymore
+                    queues[index].clear();
+                    break;
                 }
             }
         }
@@ -0,0 +1,110 @@
+use super::*;
+
+/// Bodies, merkle trees and rollback data of blocks are left out of snapshots,
+/// except the ones of the tip, which are needed for rolling it back.
+fn in_snapshot(key: &StringKey, tip: u64) -> bool {
+    if *key == keys::pruned_height() || *key == keys::rollback_pruned_height() {
+        return false;
+    }
+    if ["BLK-", "MRK-", "RLK-"]
+        .iter()
+        .any(|prefix| key.0.starts_with(prefix))
+    {
+        return *key == keys::block(tip)
+            || *key == keys::merkle(tip)
+            || *key == keys::rollback(tip);
+    }
+    true
+}
+
+/// Returns up to `count` key-value pairs of the current state, starting after the
+/// key `after`. Headers are included, so the receiver can check them against its
+/// genesis block.
+pub fn get_snapshot<K: KvStore>(
+    chain: &KvStoreChain<K>,
+    after: Option<StringKey>,
+    count: usize,
+) -> Result<Vec<(StringKey, Blob)>, BlockchainError> {
+    let tip = chain.get_height()?.saturating_sub(1);
+    Ok(chain
+        .database
+        .pairs_from(after.clone().unwrap_or_else(|| "".into()))?
+        .into_iter()
+        .filter(|(k, _)| Some(k) != after.as_ref() && in_snapshot(k, tip))
+        .take(count)
+        .collect())
+}
+
+/// Replaces the state of a chain holding only the genesis block with the given
+/// snapshot. The header chain of the snapshot should extend our own genesis block
+/// and end at `tip`. The state itself cannot be checked against the headers, so the
+/// peer serving the snapshot is trusted. All blocks except the genesis and the tip
+/// are marked as pruned.
+pub fn import_snapshot<K: KvStore>(
+    chain: &mut KvStoreChain<K>,
+    tip: &Header,
+    pairs: &[(StringKey, Blob)],
+) -> Result<(), BlockchainError> {
+    if chain.get_height()? > 1 {
+        return Err(BlockchainError::SnapshotOnNonEmptyChain);
+    }
+
+    // Pairs are sorted by key, and so the headers by their number
+    let headers = pairs
+        .iter()
+        .filter(|(k, _)| k.0.starts_with("HDR-"))
+        .map(|(_, v)| v.clone().try_into())
+        .collect::<Result<Vec<Header>, _>>()
+        .map_err(|_| BlockchainError::InvalidSnapshot)?;
+    if headers.first() != Some(&chain.get_header(0)?)
+        || headers.last() != Some(tip)
+        || headers.iter().zip(0..).any(|(h, i)| h.number != i)
+    {
+        return Err(BlockchainError::InvalidSnapshot);
+    }
+    if headers.len() > 1 && !chain.will_extend(1, &headers[1..]).unwrap_or(false) {
+        return Err(BlockchainError::InvalidSnapshot);
+    }
+
+    let (ops, _) = chain.isolated(|chain| {
+        let stale = chain
+            .database
+            .pairs("".into())?
+            .into_iter()
+            .map(|(k, _)| k)
+            .filter(|k| *k != keys::block(0) && *k != keys::merkle(0))
+            .map(WriteOp::Remove)
+            .collect::<Vec<_>>();
+        chain.database.update(&stale)?;
+        chain.database.update(
+            &pairs
+                .iter()
+                .map(|(k, v)| WriteOp::Put(k.clone(), v.clone()))
+                .collect::<Vec<_>>(),
+        )?;
+
+        let height = chain.get_height()?;
+        if height != headers.len() as u64 || chain.get_tip()? != *tip {
+            return Err(BlockchainError::InvalidSnapshot);
+        }
+        let tip = height - 1;
+        if tip > 0 {
+            let tip_block: Option<Block> = match chain.database.get(keys::block(tip))? {
+                Some(b) => Some(b.try_into()?),
+                None => None,
+            };
+            if tip_block.map(|b| b.header).as_ref() != headers.last()
+                || chain.database.get(keys::rollback(tip))?.is_none()
+            {
+                return Err(BlockchainError::InvalidSnapshot);
+            }
+        }
+
+        chain
+            .database
+            .update(&[WriteOp::Put(keys::pruned_height(), tip.into())])?;
+        Ok(())
+    })?;
+    chain.database.update(&ops)?;
+    Ok(())
+}
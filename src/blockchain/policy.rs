@@ -0,0 +1,66 @@
+use crate::core::{Address, Amount, Transaction};
+use std::collections::HashSet;
+
+/// Decides which mempool transactions a validator is willing to put in its
+/// blocks and in which order. MPN updates are always included regardless of
+/// the policy, since blocks are invalid without them.
+pub trait BlockTemplatePolicy: Send + Sync {
+    /// Maximum number of transactions in a block
+    fn max_txs(&self) -> Option<usize> {
+        None
+    }
+    /// Transactions paying less than this fee are ignored
+    fn min_fee(&self) -> Amount {
+        Amount(0)
+    }
+    /// Whether the transaction may be included at all
+    fn accept(&self, _tx: &Transaction) -> bool {
+        true
+    }
+    /// Blocks are packed to maximize the total value of their transactions,
+    /// transactions with more value per byte are preferred
+    fn value(&self, tx: &Transaction) -> u64 {
+        tx.fee.amount.into()
+    }
+}
+
+/// Limits changed at runtime, overriding the ones of the block template policy
+/// without replacing the rest of it
+#[derive(Debug, Clone, Default)]
+pub struct BlockTemplateLimits {
+    pub max_txs: Option<usize>,
+    pub min_fee: Option<Amount>,
+}
+
+/// Fee maximizing packing with optional limits on transaction count, fee
+/// and senders.
+#[derive(Debug, Clone, Default)]
+pub struct DefaultBlockTemplatePolicy {
+    pub max_txs: Option<usize>,
+    pub min_fee: Amount,
+    /// Only accept transactions from these senders, if specified
+    pub whitelist: Option<HashSet<Address>>,
+    pub blacklist: HashSet<Address>,
+}
+
+impl BlockTemplatePolicy for DefaultBlockTemplatePolicy {
+    fn max_txs(&self) -> Option<usize> {
+        self.max_txs
+    }
+    fn min_fee(&self) -> Amount {
+        self.min_fee
+    }
+    fn accept(&self, tx: &Transaction) -> bool {
+        match &tx.src {
+            Some(src) => {
+                !self.blacklist.contains(src)
+                    && self
+                        .whitelist
+                        .as_ref()
+                        .map(|w| w.contains(src))
+                        .unwrap_or(true)
+            }
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use super::*;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ReplayLogError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("replay log entry is corrupted: {0}")]
+    Corrupted(#[from] bincode::Error),
+    #[error("entry {0} of the replay log is not linked to the previous entries")]
+    BrokenLink(u64),
+}
+
+/// A block applied to the chain, along with the changes it made to the state
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplayEntry {
+    pub block: Block,
+    pub patch: Vec<WriteOp>,
+    /// Hash of the previous entry, the block and the patch
+    pub hash: <Hasher as Hash>::Output,
+}
+
+impl ReplayEntry {
+    fn new(prev: &<Hasher as Hash>::Output, block: Block, patch: Vec<WriteOp>) -> Self {
+        let hash = Self::link(prev, &block, &patch);
+        Self { block, patch, hash }
+    }
+    fn link(
+        prev: &<Hasher as Hash>::Output,
+        block: &Block,
+        patch: &[WriteOp],
+    ) -> <Hasher as Hash>::Output {
+        let mut preimage = prev.to_vec();
+        preimage.extend(bincode::serialize(&(block, patch)).unwrap());
+        Hasher::hash(&preimage)
+    }
+    pub fn is_linked_to(&self, prev: &<Hasher as Hash>::Output) -> bool {
+        Self::link(prev, &self.block, &self.patch) == self.hash
+    }
+}
+
+/// Append-only log of the blocks applied to the chain and their state patches,
+/// replaying it on a fresh chain reproduces the state step by step. Each entry is
+/// stored as its length followed by its bincode encoding.
+pub struct ReplayLog {
+    file: File,
+    last_hash: <Hasher as Hash>::Output,
+}
+
+impl ReplayLog {
+    /// Opens the log for appending, creating it if it does not exist. An entry left
+    /// half-written by a crash is discarded.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ReplayLogError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)?;
+        let mut reader = ReplayLogReader::new(file.try_clone()?);
+        while reader.next_entry()?.is_some() {}
+        file.set_len(reader.offset)?;
+        file.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            file,
+            last_hash: reader.last_hash,
+        })
+    }
+
+    pub fn append(&mut self, block: &Block, patch: &[WriteOp]) -> Result<(), ReplayLogError> {
+        let entry = ReplayEntry::new(&self.last_hash, block.clone(), patch.to_vec());
+        let bytes = bincode::serialize(&entry)?;
+        let mut frame = (bytes.len() as u64).to_le_bytes().to_vec();
+        frame.extend(bytes);
+        self.file.write_all(&frame)?;
+        self.file.sync_data()?;
+        self.last_hash = entry.hash;
+        Ok(())
+    }
+}
+
+/// Reads the entries of a replay log in order, checking that they are linked
+pub struct ReplayLogReader<R: Read> {
+    reader: BufReader<R>,
+    last_hash: <Hasher as Hash>::Output,
+    offset: u64,
+    index: u64,
+}
+
+impl ReplayLogReader<File> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ReplayLogError> {
+        Ok(Self::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> ReplayLogReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            last_hash: Default::default(),
+            offset: 0,
+            index: 0,
+        }
+    }
+
+    /// The next entry, `None` at the end of the log or at a half-written entry
+    pub fn next_entry(&mut self) -> Result<Option<ReplayEntry>, ReplayLogError> {
+        let mut len = [0u8; 8];
+        let mut bytes = Vec::new();
+        match self.reader.read_exact(&mut len) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            res => res?,
+        }
+        let len = u64::from_le_bytes(len);
+        if (&mut self.reader).take(len).read_to_end(&mut bytes)? as u64 != len {
+            return Ok(None);
+        }
+        let entry: ReplayEntry = bincode::deserialize(&bytes)?;
+        if !entry.is_linked_to(&self.last_hash) {
+            return Err(ReplayLogError::BrokenLink(self.index));
+        }
+        self.last_hash = entry.hash;
+        self.offset += 8 + len;
+        self.index += 1;
+        Ok(Some(entry))
+    }
+}
+
+impl<R: Read> Iterator for ReplayLogReader<R> {
+    type Item = Result<ReplayEntry, ReplayLogError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
@@ -951,6 +951,162 @@ fn test_chain_should_draft_all_valid_transactions() {
     rollback_till_empty(&mut chain).unwrap();
 }
 
+#[test]
+fn test_block_template_policy() {
+    let wallet_miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let wallet1 = TxBuilder::new(&Vec::from("ABCD"));
+    let wallet2 = TxBuilder::new(&Vec::from("CBAD"));
+
+    let mut conf = blockchain::get_test_blockchain_config();
+    conf.genesis.body.push(Transaction {
+        memo: "".into(),
+        src: None,
+        data: TransactionData::RegularSend {
+            entries: vec![RegularSendEntry {
+                dst: wallet1.get_address(),
+                amount: Money::ziesha(10_000_000),
+            }],
+        },
+        nonce: 0,
+        fee: Money::ziesha(0),
+        sig: Signature::Unsigned,
+    });
+
+    let mempool = (1..=3)
+        .map(|nonce| {
+            wallet1.create_transaction(
+                "".into(),
+                wallet2.get_address(),
+                Money::ziesha(1000),
+                Money::ziesha(10),
+                nonce,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let chain = KvStoreChain::new(db::RamKvStore::new(), conf.clone())
+        .unwrap()
+        .with_block_template_policy(DefaultBlockTemplatePolicy {
+            max_txs: Some(2),
+            ..Default::default()
+        });
+    let draft = chain
+        .draft_block(1650000000, &mempool, &wallet_miner, true)
+        .unwrap()
+        .unwrap();
+    assert_eq!(2, draft.body.len());
+
+    let chain = KvStoreChain::new(db::RamKvStore::new(), conf.clone())
+        .unwrap()
+        .with_block_template_policy(DefaultBlockTemplatePolicy {
+            min_fee: Amount(11),
+            ..Default::default()
+        });
+    let draft = chain
+        .draft_block(1650000000, &mempool, &wallet_miner, true)
+        .unwrap()
+        .unwrap();
+    assert!(draft.body.is_empty());
+
+    let chain = KvStoreChain::new(db::RamKvStore::new(), conf)
+        .unwrap()
+        .with_block_template_policy(DefaultBlockTemplatePolicy {
+            blacklist: [wallet1.get_address()].into_iter().collect(),
+            ..Default::default()
+        });
+    let draft = chain
+        .draft_block(1650000000, &mempool, &wallet_miner, true)
+        .unwrap()
+        .unwrap();
+    assert!(draft.body.is_empty());
+}
+
+#[test]
+fn test_fee_maximizing_selection_respects_nonces() {
+    let wallet_miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let wallet1 = TxBuilder::new(&Vec::from("ABCD"));
+    let wallet2 = TxBuilder::new(&Vec::from("CBAD"));
+
+    let mut conf = blockchain::get_test_blockchain_config();
+    conf.genesis.body.push(Transaction {
+        memo: "".into(),
+        src: None,
+        data: TransactionData::RegularSend {
+            entries: vec![
+                RegularSendEntry {
+                    dst: wallet1.get_address(),
+                    amount: Money::ziesha(10_000_000),
+                },
+                RegularSendEntry {
+                    dst: wallet2.get_address(),
+                    amount: Money::ziesha(10_000_000),
+                },
+            ],
+        },
+        nonce: 0,
+        fee: Money::ziesha(0),
+        sig: Signature::Unsigned,
+    });
+
+    let chain = KvStoreChain::new(db::RamKvStore::new(), conf)
+        .unwrap()
+        .with_block_template_policy(DefaultBlockTemplatePolicy {
+            max_txs: Some(2),
+            ..Default::default()
+        });
+
+    let t1 = wallet1.create_transaction(
+        "".into(),
+        wallet2.get_address(),
+        Money::ziesha(1000),
+        Money::ziesha(1),
+        1,
+    );
+    let t2 = wallet1.create_transaction(
+        "".into(),
+        wallet2.get_address(),
+        Money::ziesha(1000),
+        Money::ziesha(100),
+        2,
+    );
+    let t3 = wallet2.create_transaction(
+        "".into(),
+        wallet1.get_address(),
+        Money::ziesha(1000),
+        Money::ziesha(40),
+        1,
+    );
+
+    // The high fee of t2 pays for its low fee predecessor t1
+    let draft = chain
+        .draft_block(
+            1650000000,
+            &[t3, t2.clone(), t1.clone()],
+            &wallet_miner,
+            true,
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(draft.body, vec![t1.tx, t2.tx]);
+}
+
+#[test]
+fn test_consensus_params_override() {
+    let conf = blockchain::get_test_blockchain_config_with(blockchain::ConsensusParams {
+        slot_duration: 10,
+        slot_per_epoch: 3,
+        reward_ratio: 1000,
+    });
+    let chain = KvStoreChain::new(db::RamKvStore::new(), conf).unwrap();
+    assert_eq!(chain.epoch_slot(65), (2, 0));
+    assert_eq!(chain.epoch_slot(59), (1, 2));
+
+    let treasury = chain
+        .get_balance(Default::default(), TokenId::Ziesha)
+        .unwrap();
+    assert_eq!(chain.next_reward().unwrap(), Amount(treasury.0 / 1000));
+}
+
 #[test]
 fn test_chain_should_rollback_applied_block() {
     let wallet_miner = TxBuilder::new(&Vec::from("VALIDATOR"));
@@ -1015,6 +1171,27 @@ fn test_chain_should_rollback_applied_block() {
     let height = chain.get_height().unwrap();
     assert_eq!(3, height);
 
+    let t1_hash = mempool[0].tx.hash();
+    let t2_hash = mempool[1].tx.hash();
+    assert_eq!(
+        chain
+            .get_account_transactions(wallet2.get_address(), 0, 0, 10)
+            .unwrap(),
+        vec![(2, t2_hash), (1, t1_hash)]
+    );
+    assert_eq!(
+        chain
+            .get_account_transactions(wallet2.get_address(), 0, 1, 1)
+            .unwrap(),
+        vec![(1, t1_hash)]
+    );
+    assert_eq!(
+        chain
+            .get_account_transactions(wallet2.get_address(), 2, 0, 10)
+            .unwrap(),
+        vec![(2, t2_hash)]
+    );
+
     let last_block = chain.get_block(height - 1).unwrap();
     assert_eq!(
         last_block.body[0].data.clone(),
@@ -1054,6 +1231,583 @@ fn test_chain_should_rollback_applied_block() {
         .unwrap();
     assert_eq!(Amount(1_000_000), balance);
     assert_eq!(0, nonce);
+    assert_eq!(
+        chain
+            .get_account_transactions(wallet2.get_address(), 0, 0, 10)
+            .unwrap(),
+        vec![(1, t1_hash)]
+    );
 
     rollback_till_empty(&mut chain).unwrap();
 }
+
+#[test]
+fn test_custom_genesis_block() {
+    let validator = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let alice = TxBuilder::new(&Vec::from("ABC"));
+    let state_model = zk::ZkStateModel::Struct {
+        field_types: vec![zk::ZkStateModel::Scalar, zk::ZkStateModel::Scalar],
+    };
+    let state = zk::ZkDataPairs([(zk::ZkDataLocator(vec![0]), zk::ZkScalar::from(5))].into());
+    let mut builder = zk::ZkStateBuilder::<crate::core::ZkHasher>::new(state_model.clone());
+    builder.batch_set(&state.as_delta()).unwrap();
+    let contract = zk::ZkContract {
+        state_model,
+        initial_state: builder.compress().unwrap(),
+        deposit_functions: vec![],
+        withdraw_functions: vec![],
+        functions: vec![],
+    };
+    let genesis = blockchain::get_custom_genesis_block(&blockchain::GenesisParams {
+        chain_start_timestamp: 1000,
+        validator: validator.get_address(),
+        validator_vrf: validator.get_vrf_public_key(),
+        validator_commission: Ratio(12),
+        validator_stake: Amount(1_000_000),
+        allocations: vec![(alice.get_address(), Amount(5_000))],
+        contracts: vec![(contract.clone(), state)],
+    });
+    let contract_id = ContractId::new(genesis.body.last().unwrap());
+    let chain = KvStoreChain::new(
+        db::RamKvStore::new(),
+        blockchain::get_custom_blockchain_config(genesis),
+    )
+    .unwrap();
+
+    assert_eq!(chain.get_height().unwrap(), 1);
+    assert_eq!(
+        chain
+            .get_balance(alice.get_address(), TokenId::Ziesha)
+            .unwrap(),
+        Amount(5_000)
+    );
+    assert_eq!(
+        chain.get_stake(validator.get_address()).unwrap(),
+        Amount(1_000_000)
+    );
+    assert!(chain.get_staker(validator.get_address()).unwrap().is_some());
+    assert_eq!(chain.get_contract(contract_id).unwrap(), contract);
+    assert_eq!(
+        chain
+            .get_contract_account(contract_id)
+            .unwrap()
+            .compressed_state,
+        contract.initial_state
+    );
+}
+
+#[test]
+fn test_prune_keeps_tip_usable() {
+    let miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let mut chain = KvStoreChain::new(
+        db::RamKvStore::new(),
+        blockchain::get_test_blockchain_config(),
+    )
+    .unwrap();
+
+    for i in 0..5 {
+        let blk = chain
+            .draft_block(i * 60 + 30, &[], &miner, true)
+            .unwrap()
+            .unwrap();
+        chain.extend(chain.get_height().unwrap(), &[blk]).unwrap();
+    }
+    assert_eq!(chain.get_height().unwrap(), 6);
+
+    assert_eq!(chain.prune(2).unwrap(), 3);
+    assert_eq!(chain.prune(2).unwrap(), 0);
+    assert_eq!(chain.get_pruned_height().unwrap(), 4);
+
+    assert!(matches!(
+        chain.get_block(3),
+        Err(BlockchainError::BlockPruned)
+    ));
+    assert!(chain.get_block(0).is_ok());
+    assert!(chain.get_header(3).is_ok());
+    assert!(chain.get_block(4).is_ok());
+
+    chain.rollback().unwrap();
+    chain.rollback().unwrap();
+    assert!(matches!(
+        chain.rollback(),
+        Err(BlockchainError::BlockPruned)
+    ));
+
+    let blk = chain.draft_block(330, &[], &miner, true).unwrap().unwrap();
+    chain.extend(4, &[blk]).unwrap();
+    assert_eq!(chain.get_height().unwrap(), 5);
+}
+
+#[test]
+fn test_prune_rollbacks_bounds_reorg_depth() {
+    let miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let mut chain = KvStoreChain::new(
+        db::RamKvStore::new(),
+        blockchain::get_test_blockchain_config(),
+    )
+    .unwrap();
+
+    let mut blocks = Vec::new();
+    for i in 0..5 {
+        let blk = chain
+            .draft_block(i * 60 + 30, &[], &miner, true)
+            .unwrap()
+            .unwrap();
+        chain
+            .extend(chain.get_height().unwrap(), std::slice::from_ref(&blk))
+            .unwrap();
+        blocks.push(blk);
+    }
+    assert_eq!(chain.get_height().unwrap(), 6);
+
+    assert_eq!(chain.prune_rollbacks(2).unwrap(), 3);
+    assert_eq!(chain.prune_rollbacks(2).unwrap(), 0);
+    assert_eq!(chain.get_rollback_pruned_height().unwrap(), 4);
+
+    // Block bodies are kept
+    assert_eq!(chain.get_pruned_height().unwrap(), 0);
+    assert!(chain.get_block(2).is_ok());
+
+    // Forks older than the retained rollback data are rejected, even if longer
+    let mut fork = blocks[2..]
+        .iter()
+        .map(|b| b.header.clone())
+        .collect::<Vec<_>>();
+    fork.push(fork[fork.len() - 1].clone());
+    assert!(matches!(
+        chain.will_extend(3, &fork),
+        Err(BlockchainError::ReorgTooDeep)
+    ));
+
+    chain.rollback().unwrap();
+    chain.rollback().unwrap();
+    assert!(matches!(
+        chain.rollback(),
+        Err(BlockchainError::BlockPruned)
+    ));
+
+    chain.extend(4, &blocks[3..]).unwrap();
+    assert_eq!(chain.get_height().unwrap(), 6);
+}
+
+#[test]
+fn test_recover_rolls_back_inconsistent_tip() {
+    let miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let mut chain = KvStoreChain::new(
+        db::RamKvStore::new(),
+        blockchain::get_test_blockchain_config(),
+    )
+    .unwrap();
+
+    for i in 0..3 {
+        let blk = chain
+            .draft_block(i * 60 + 30, &[], &miner, true)
+            .unwrap()
+            .unwrap();
+        chain.extend(chain.get_height().unwrap(), &[blk]).unwrap();
+    }
+    assert_eq!(chain.get_height().unwrap(), 4);
+    assert_eq!(chain.recover().unwrap(), 0);
+
+    // Body of the tip is lost
+    chain
+        .database
+        .update(&[WriteOp::Remove(keys::block(3))])
+        .unwrap();
+    assert_eq!(chain.recover().unwrap(), 1);
+    assert_eq!(chain.get_height().unwrap(), 3);
+    assert_eq!(chain.recover().unwrap(), 0);
+
+    let blk = chain.draft_block(210, &[], &miner, true).unwrap().unwrap();
+    chain.extend(3, &[blk]).unwrap();
+    assert_eq!(chain.get_height().unwrap(), 4);
+
+    // Data removed on purpose is not a sign of an unclean shutdown
+    chain.prune_rollbacks(0).unwrap();
+    assert_eq!(chain.recover().unwrap(), 0);
+    chain.prune(0).unwrap();
+    assert_eq!(chain.recover().unwrap(), 0);
+    assert_eq!(chain.get_height().unwrap(), 4);
+}
+
+#[test]
+fn test_header_number_lookup() {
+    let miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let mut chain = KvStoreChain::new(
+        db::RamKvStore::new(),
+        blockchain::get_test_blockchain_config(),
+    )
+    .unwrap();
+
+    for i in 0..3 {
+        let blk = chain
+            .draft_block(i * 60 + 30, &[], &miner, true)
+            .unwrap()
+            .unwrap();
+        chain.extend(chain.get_height().unwrap(), &[blk]).unwrap();
+    }
+    let hashes = (0..4)
+        .map(|i| chain.get_header(i).unwrap().hash())
+        .collect::<Vec<_>>();
+
+    for (i, hash) in hashes.iter().enumerate() {
+        assert_eq!(chain.get_header_number(hash).unwrap(), Some(i as u64));
+    }
+
+    // Blocks removed by a rollback are no longer known
+    chain.rollback().unwrap();
+    assert_eq!(chain.get_header_number(&hashes[3]).unwrap(), None);
+    assert_eq!(chain.get_header_number(&hashes[2]).unwrap(), Some(2));
+}
+
+#[test]
+fn test_disabled_address_index() {
+    let miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let wallet1 = TxBuilder::new(&Vec::from("ABC"));
+    let wallet2 = TxBuilder::new(&Vec::from("CBA"));
+
+    let mut conf = blockchain::get_test_blockchain_config();
+    conf.genesis.body.push(Transaction {
+        memo: "".into(),
+        src: None,
+        data: TransactionData::RegularSend {
+            entries: vec![RegularSendEntry {
+                dst: wallet1.get_address(),
+                amount: Money::ziesha(10_000_000),
+            }],
+        },
+        nonce: 0,
+        fee: Money::ziesha(0),
+        sig: Signature::Unsigned,
+    });
+    let mut chain = KvStoreChain::new(db::RamKvStore::new(), conf.clone())
+        .unwrap()
+        .with_address_index(false);
+
+    let tx = wallet1.create_transaction(
+        "".into(),
+        wallet2.get_address(),
+        Money::ziesha(1_000_000),
+        Money::ziesha(0),
+        1,
+    );
+    let draft = chain
+        .draft_block(1650000000, &[tx], &miner, true)
+        .unwrap()
+        .unwrap();
+    chain.apply_block(&draft).unwrap();
+
+    assert!(chain
+        .database
+        .get(
+            keys::AccountTxDbKey {
+                address: wallet2.get_address(),
+                block: 1,
+                index: 0,
+            }
+            .into()
+        )
+        .unwrap()
+        .is_none());
+    assert!(matches!(
+        chain.get_account_transactions(wallet2.get_address(), 0, 0, 10),
+        Err(BlockchainError::AddressIndexDisabled)
+    ));
+
+    // Indexes are not part of the state
+    let mut indexed = KvStoreChain::new(db::RamKvStore::new(), conf).unwrap();
+    indexed.apply_block(&draft).unwrap();
+    assert_ne!(indexed.db_checksum().unwrap(), chain.db_checksum().unwrap());
+    assert_eq!(
+        indexed.state_checksum().unwrap(),
+        chain.state_checksum().unwrap()
+    );
+}
+
+#[test]
+fn test_reorg_returns_evicted_transactions() {
+    let miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let wallet1 = TxBuilder::new(&Vec::from("ABC"));
+    let wallet2 = TxBuilder::new(&Vec::from("CBA"));
+
+    let mut conf = blockchain::get_test_blockchain_config();
+    conf.genesis.body.push(Transaction {
+        memo: "".into(),
+        src: None,
+        data: TransactionData::RegularSend {
+            entries: vec![RegularSendEntry {
+                dst: wallet1.get_address(),
+                amount: Money::ziesha(10_000_000),
+            }],
+        },
+        nonce: 0,
+        fee: Money::ziesha(0),
+        sig: Signature::Unsigned,
+    });
+    let mut chain = KvStoreChain::new(db::RamKvStore::new(), conf.clone()).unwrap();
+    let mut fork_chain = KvStoreChain::new(db::RamKvStore::new(), conf).unwrap();
+
+    let txs = (1..=2)
+        .map(|nonce| {
+            wallet1.create_transaction(
+                "".into(),
+                wallet2.get_address(),
+                Money::ziesha(1_000_000),
+                Money::ziesha(0),
+                nonce,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let blk = chain.draft_block(60, &txs, &miner, true).unwrap().unwrap();
+    assert_eq!(blk.body.len(), 2);
+    chain.extend(1, &[blk]).unwrap();
+
+    // The competing branch is longer and only includes the first transaction
+    let mut fork = Vec::new();
+    for (timestamp, mempool) in [(30, &txs[..1]), (90, &[][..])] {
+        let blk = fork_chain
+            .draft_block(timestamp, mempool, &miner, true)
+            .unwrap()
+            .unwrap();
+        fork_chain
+            .extend(fork_chain.get_height().unwrap(), std::slice::from_ref(&blk))
+            .unwrap();
+        fork.push(blk);
+    }
+
+    // Failed reorgs leave the chain untouched
+    assert!(chain.reorg(1, &fork[1..]).is_err());
+    assert_eq!(chain.get_height().unwrap(), 2);
+
+    let evicted = chain.reorg(1, &fork).unwrap();
+    assert_eq!(evicted, vec![txs[1].tx.clone()]);
+    assert_eq!(chain.get_height().unwrap(), 3);
+    assert_eq!(chain.get_tip().unwrap(), fork_chain.get_tip().unwrap());
+    assert!(chain.check_tx(&txs[1].tx).is_ok());
+
+    // Plain extensions evict nothing
+    let blk = chain.draft_block(120, &[], &miner, true).unwrap().unwrap();
+    assert!(chain.reorg(3, &[blk]).unwrap().is_empty());
+}
+
+#[test]
+fn test_replay_log_reproduces_state() {
+    use std::io::Write;
+    let miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let path = std::env::temp_dir().join(format!("bazuka-replay-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut chain = KvStoreChain::new(
+        db::RamKvStore::new(),
+        blockchain::get_test_blockchain_config(),
+    )
+    .unwrap()
+    .with_replay_log(ReplayLog::open(&path).unwrap());
+    for i in 0..3 {
+        let blk = chain
+            .draft_block(i * 60 + 60, &[], &miner, true)
+            .unwrap()
+            .unwrap();
+        chain.extend(chain.get_height().unwrap(), &[blk]).unwrap();
+    }
+
+    // A longer fork replaces the last two blocks
+    let mut branch = Vec::new();
+    {
+        let mut fork = chain.fork_on_ram();
+        fork.rollback().unwrap();
+        fork.rollback().unwrap();
+        for i in 0..3 {
+            let blk = fork
+                .draft_block(i * 60 + 90, &[], &miner, true)
+                .unwrap()
+                .unwrap();
+            fork.extend(fork.get_height().unwrap(), std::slice::from_ref(&blk))
+                .unwrap();
+            branch.push(blk);
+        }
+    }
+    chain.reorg(2, &branch).unwrap();
+    assert_eq!(chain.get_height().unwrap(), 5);
+
+    // Half-written entries of a crash are dropped when the log is reopened
+    drop(chain);
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(&[100, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3])
+        .unwrap();
+    drop(ReplayLog::open(&path).unwrap());
+
+    let mut replayed = KvStoreChain::new(
+        db::RamKvStore::new(),
+        blockchain::get_test_blockchain_config(),
+    )
+    .unwrap();
+    let entries = ReplayLogReader::open(&path)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(entries.len(), 6);
+    for entry in entries.iter() {
+        assert_eq!(replayed.replay(entry).unwrap(), entry.patch);
+    }
+    assert_eq!(replayed.get_height().unwrap(), 5);
+    assert_eq!(replayed.get_tip().unwrap(), branch[2].header);
+
+    // Tampering with an entry breaks the links of the log
+    let mut bytes = std::fs::read(&path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    std::fs::write(&path, bytes).unwrap();
+    assert!(ReplayLogReader::open(&path)
+        .unwrap()
+        .any(|entry| entry.is_err()));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_checkpoints() {
+    let miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let mut chain = KvStoreChain::new(
+        db::RamKvStore::new(),
+        blockchain::get_test_blockchain_config(),
+    )
+    .unwrap();
+    let mut blocks = Vec::new();
+    for i in 0..3 {
+        let blk = chain
+            .draft_block(i * 60 + 60, &[], &miner, true)
+            .unwrap()
+            .unwrap();
+        chain
+            .extend(chain.get_height().unwrap(), std::slice::from_ref(&blk))
+            .unwrap();
+        blocks.push(blk);
+    }
+
+    // Competing branch forking right after block 1
+    let mut fork = Vec::new();
+    {
+        let mut fork_chain = chain.fork_on_ram();
+        fork_chain.rollback().unwrap();
+        fork_chain.rollback().unwrap();
+        for i in 0..3 {
+            let blk = fork_chain
+                .draft_block(i * 60 + 90, &[], &miner, true)
+                .unwrap()
+                .unwrap();
+            fork_chain
+                .extend(fork_chain.get_height().unwrap(), std::slice::from_ref(&blk))
+                .unwrap();
+            fork.push(blk);
+        }
+    }
+    let fork_headers = fork.iter().map(|b| b.header.clone()).collect::<Vec<_>>();
+
+    let mut conf = blockchain::get_test_blockchain_config();
+    conf.checkpoints.insert(2, blocks[1].header.hash());
+    let mut chain = KvStoreChain::new(db::RamKvStore::new(), conf.clone()).unwrap();
+
+    // Headers conflicting with the checkpoint are rejected
+    assert!(matches!(
+        chain.will_extend(1, &[blocks[0].header.clone(), fork_headers[0].clone()]),
+        Err(BlockchainError::CheckpointMismatch)
+    ));
+    chain.extend(1, &blocks[..2]).unwrap();
+
+    // Reorgs replacing the checkpoint are rejected, even if longer
+    assert!(matches!(
+        chain.will_extend(2, &fork_headers),
+        Err(BlockchainError::ReorgBelowCheckpoint)
+    ));
+    assert!(matches!(
+        chain.reorg(2, &fork),
+        Err(BlockchainError::ReorgBelowCheckpoint)
+    ));
+
+    // Reorgs above it are still followed
+    chain.extend(3, &blocks[2..]).unwrap();
+    assert_eq!(chain.get_height().unwrap(), 4);
+    let mut conf = blockchain::get_test_blockchain_config();
+    conf.checkpoints.insert(1, blocks[0].header.hash());
+    let mut chain = KvStoreChain::new(db::RamKvStore::new(), conf).unwrap();
+    chain.extend(1, &blocks).unwrap();
+    assert!(chain.will_extend(2, &fork_headers).unwrap());
+    chain.reorg(2, &fork).unwrap();
+    assert_eq!(chain.get_tip().unwrap(), fork_headers[2]);
+}
+
+#[test]
+fn test_block_meta() {
+    let miner = TxBuilder::new(&Vec::from("VALIDATOR"));
+    let wallet1 = TxBuilder::new(&Vec::from("ABC"));
+    let wallet2 = TxBuilder::new(&Vec::from("CBA"));
+
+    let mut conf = blockchain::get_test_blockchain_config();
+    conf.genesis.body.push(Transaction {
+        memo: "".into(),
+        src: None,
+        data: TransactionData::RegularSend {
+            entries: vec![RegularSendEntry {
+                dst: wallet1.get_address(),
+                amount: Money::ziesha(10_000_000),
+            }],
+        },
+        nonce: 0,
+        fee: Money::ziesha(0),
+        sig: Signature::Unsigned,
+    });
+    let mut chain = KvStoreChain::new(db::RamKvStore::new(), conf).unwrap();
+
+    let txs = (1..=3)
+        .map(|nonce| {
+            wallet1.create_transaction(
+                "".into(),
+                wallet2.get_address(),
+                Money::ziesha(1_000_000),
+                Money::ziesha(0),
+                nonce,
+            )
+        })
+        .collect::<Vec<_>>();
+    let blk = chain.draft_block(60, &txs, &miner, true).unwrap().unwrap();
+    chain.extend(1, std::slice::from_ref(&blk)).unwrap();
+    for i in 0..3 {
+        let blk = chain
+            .draft_block(i * 60 + 120, &[], &miner, true)
+            .unwrap()
+            .unwrap();
+        chain.extend(chain.get_height().unwrap(), &[blk]).unwrap();
+    }
+
+    let meta = chain.get_block_meta(1).unwrap();
+    assert_eq!(meta, blk.meta());
+    assert_eq!(meta.tx_count, 3);
+    assert_eq!(
+        meta.body_size,
+        blk.body.iter().map(|tx| tx.size() as u64).sum::<u64>() + 8
+    );
+
+    // Kept after the body is pruned
+    chain.prune(1).unwrap();
+    assert!(matches!(
+        chain.get_block(1),
+        Err(BlockchainError::BlockPruned)
+    ));
+    assert_eq!(chain.get_block_meta(1).unwrap(), meta);
+
+    chain.rollback().unwrap();
+    assert!(matches!(
+        chain.get_block_meta(4),
+        Err(BlockchainError::BlockNotFound)
+    ));
+    assert!(chain
+        .database
+        .get(crate::db::keys::block_meta(4))
+        .unwrap()
+        .is_none());
+}
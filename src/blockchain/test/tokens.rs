@@ -1,4 +1,5 @@
 use super::*;
+use crate::core::TokenUpdate;
 
 #[test]
 fn test_token_balances() {
@@ -129,5 +130,30 @@ fn test_token_balances() {
         Amount(0)
     );
 
+    // Token ops without a dedicated wallet method go through the generic builder
+    let mint = alice
+        .transaction(TransactionData::UpdateToken {
+            token_id,
+            update: TokenUpdate::Mint {
+                amount: Amount(100),
+            },
+        })
+        .fee(Money::ziesha(0))
+        .nonce(4)
+        .build();
+    assert_eq!(mint.tx.src, Some(alice.get_address()));
+    chain
+        .apply_block(
+            &chain
+                .draft_block(500, &[mint], &miner, false)
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        chain.get_balance(alice.get_address(), token_id).unwrap(),
+        Amount(100)
+    );
+
     rollback_till_empty(&mut chain).unwrap();
 }
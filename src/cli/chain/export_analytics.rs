@@ -0,0 +1,380 @@
+use crate::cli::{datadir::DataDir, BazukaConfig};
+use bazuka::blockchain::Blockchain;
+use bazuka::{
+    blockchain::KvStoreChain,
+    config::blockchain::get_blockchain_config,
+    core::{Address, Block, ContractUpdate, TokenId, TokenUpdate, TransactionData},
+    db::{KvStore, RamKvStore, ReadOnlyLevelDbKvStore},
+};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Column {
+    Int(&'static str),
+    Text(&'static str),
+}
+
+enum Cell {
+    Int(u64),
+    Text(String),
+}
+
+const BLOCK_COLUMNS: &[Column] = &[
+    Column::Int("number"),
+    Column::Text("hash"),
+    Column::Text("parent_hash"),
+    Column::Int("timestamp"),
+    Column::Text("validator"),
+    Column::Int("tx_count"),
+    Column::Int("size"),
+];
+
+const TRANSACTION_COLUMNS: &[Column] = &[
+    Column::Int("block_number"),
+    Column::Int("index"),
+    Column::Text("hash"),
+    Column::Text("src"),
+    Column::Int("nonce"),
+    Column::Text("kind"),
+    Column::Text("fee_token"),
+    Column::Int("fee_amount"),
+    Column::Int("size"),
+    Column::Text("memo"),
+];
+
+const BALANCE_COLUMNS: &[Column] = &[
+    Column::Int("block_number"),
+    Column::Text("address"),
+    Column::Text("token_id"),
+    Column::Int("balance"),
+];
+
+#[cfg(feature = "analytics-parquet")]
+const PARQUET_BATCH_SIZE: usize = 8192;
+
+enum TableWriter {
+    Csv(csv::Writer<File>),
+    #[cfg(feature = "analytics-parquet")]
+    Parquet {
+        columns: &'static [Column],
+        writer: parquet::arrow::ArrowWriter<File>,
+        rows: Vec<Vec<Cell>>,
+    },
+}
+
+impl TableWriter {
+    fn create(
+        dir: &Path,
+        name: &str,
+        columns: &'static [Column],
+        format: ExportFormat,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        match format {
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_path(dir.join(format!("{}.csv", name)))?;
+                writer.write_record(columns.iter().map(|c| match c {
+                    Column::Int(name) | Column::Text(name) => name,
+                }))?;
+                Ok(Self::Csv(writer))
+            }
+            #[cfg(feature = "analytics-parquet")]
+            ExportFormat::Parquet => {
+                let file = File::create(dir.join(format!("{}.parquet", name)))?;
+                let writer =
+                    parquet::arrow::ArrowWriter::try_new(file, Self::arrow_schema(columns), None)?;
+                Ok(Self::Parquet {
+                    columns,
+                    writer,
+                    rows: Vec::new(),
+                })
+            }
+            #[cfg(not(feature = "analytics-parquet"))]
+            ExportFormat::Parquet => {
+                Err("Bazuka was built without the `analytics-parquet` feature!".into())
+            }
+        }
+    }
+
+    fn write(&mut self, row: Vec<Cell>) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Self::Csv(writer) => {
+                writer.write_record(row.into_iter().map(|c| match c {
+                    Cell::Int(v) => v.to_string(),
+                    Cell::Text(v) => v,
+                }))?;
+            }
+            #[cfg(feature = "analytics-parquet")]
+            Self::Parquet { rows, .. } => {
+                rows.push(row);
+                if rows.len() >= PARQUET_BATCH_SIZE {
+                    self.flush_batch()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Self::Csv(mut writer) => writer.flush()?,
+            #[cfg(feature = "analytics-parquet")]
+            mut parquet @ Self::Parquet { .. } => {
+                parquet.flush_batch()?;
+                if let Self::Parquet { writer, .. } = parquet {
+                    writer.close()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "analytics-parquet")]
+    fn arrow_schema(columns: &[Column]) -> std::sync::Arc<arrow_schema::Schema> {
+        use arrow_schema::{DataType, Field, Schema};
+        std::sync::Arc::new(Schema::new(
+            columns
+                .iter()
+                .map(|c| match c {
+                    Column::Int(name) => Field::new(*name, DataType::UInt64, false),
+                    Column::Text(name) => Field::new(*name, DataType::Utf8, false),
+                })
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    #[cfg(feature = "analytics-parquet")]
+    fn flush_batch(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+        if let Self::Parquet {
+            columns,
+            writer,
+            rows,
+        } = self
+        {
+            if rows.is_empty() {
+                return Ok(());
+            }
+            let arrays = columns
+                .iter()
+                .enumerate()
+                .map(|(i, c)| -> ArrayRef {
+                    match c {
+                        Column::Int(_) => std::sync::Arc::new(UInt64Array::from_iter_values(
+                            rows.iter().map(|r| match &r[i] {
+                                Cell::Int(v) => *v,
+                                Cell::Text(_) => unreachable!(),
+                            }),
+                        )),
+                        Column::Text(_) => std::sync::Arc::new(StringArray::from_iter_values(
+                            rows.iter().map(|r| match &r[i] {
+                                Cell::Text(v) => v.as_str(),
+                                Cell::Int(_) => unreachable!(),
+                            }),
+                        )),
+                    }
+                })
+                .collect::<Vec<_>>();
+            writer.write(&RecordBatch::try_new(Self::arrow_schema(columns), arrays)?)?;
+            rows.clear();
+        }
+        Ok(())
+    }
+}
+
+fn kind(data: &TransactionData) -> &'static str {
+    match data {
+        TransactionData::UpdateStaker { .. } => "update_staker",
+        TransactionData::Delegate { .. } => "delegate",
+        TransactionData::Undelegate { .. } => "undelegate",
+        TransactionData::AutoDelegate { .. } => "auto_delegate",
+        TransactionData::RegularSend { .. } => "regular_send",
+        TransactionData::CreateContract { .. } => "create_contract",
+        TransactionData::UpdateContract { .. } => "update_contract",
+        TransactionData::CreateToken { .. } => "create_token",
+        TransactionData::UpdateToken { .. } => "update_token",
+    }
+}
+
+/// Accounts whose balance might have changed by applying `block`
+fn touched_balances(block: &Block) -> BTreeMap<(String, String), (Address, TokenId)> {
+    let mut touched = vec![(
+        block.header.proof_of_stake.validator.clone(),
+        TokenId::Ziesha,
+    )];
+    for tx in block.body.iter() {
+        if let Some(src) = &tx.src {
+            touched.push((src.clone(), tx.fee.token_id));
+            touched.push((src.clone(), TokenId::Ziesha));
+        }
+        match &tx.data {
+            TransactionData::RegularSend { entries } => {
+                for entry in entries {
+                    touched.push((entry.dst.clone(), entry.amount.token_id));
+                    if let Some(src) = &tx.src {
+                        touched.push((src.clone(), entry.amount.token_id));
+                    }
+                }
+            }
+            TransactionData::CreateToken { .. } => {
+                if let Some(src) = &tx.src {
+                    touched.push((src.clone(), TokenId::new(tx)));
+                }
+            }
+            TransactionData::UpdateToken {
+                token_id,
+                update: TokenUpdate::Mint { .. },
+            } => {
+                if let Some(src) = &tx.src {
+                    touched.push((src.clone(), *token_id));
+                }
+            }
+            TransactionData::UpdateContract { updates, .. } => {
+                for update in updates {
+                    match update {
+                        ContractUpdate::Deposit { deposits, .. } => {
+                            for deposit in deposits {
+                                touched.push((deposit.src.clone(), deposit.amount.token_id));
+                                touched.push((deposit.src.clone(), deposit.fee.token_id));
+                            }
+                        }
+                        ContractUpdate::Withdraw { withdraws, .. } => {
+                            for withdraw in withdraws {
+                                touched.push((withdraw.dst.clone(), withdraw.amount.token_id));
+                            }
+                        }
+                        ContractUpdate::FunctionCall { .. } => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    // Keyed by the printed values so that the output is sorted and deterministic
+    touched
+        .into_iter()
+        .map(|(addr, token_id)| ((addr.to_string(), token_id.to_string()), (addr, token_id)))
+        .collect()
+}
+
+fn export_block<K: KvStore, B: Blockchain<K>>(
+    block: &Block,
+    replayed: &B,
+    blocks: &mut TableWriter,
+    transactions: &mut TableWriter,
+    balances: &mut TableWriter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let number = block.header.number;
+    blocks.write(vec![
+        Cell::Int(number),
+        Cell::Text(hex::encode(block.header.hash())),
+        Cell::Text(hex::encode(block.header.parent_hash)),
+        Cell::Int(block.header.proof_of_stake.timestamp as u64),
+        Cell::Text(block.header.proof_of_stake.validator.to_string()),
+        Cell::Int(block.body.len() as u64),
+        Cell::Int(bincode::serialize(block)?.len() as u64),
+    ])?;
+    for (index, tx) in block.body.iter().enumerate() {
+        transactions.write(vec![
+            Cell::Int(number),
+            Cell::Int(index as u64),
+            Cell::Text(hex::encode(tx.hash())),
+            Cell::Text(tx.src.as_ref().map(|s| s.to_string()).unwrap_or_default()),
+            Cell::Int(tx.nonce as u64),
+            Cell::Text(kind(&tx.data).into()),
+            Cell::Text(tx.fee.token_id.to_string()),
+            Cell::Int(tx.fee.amount.into()),
+            Cell::Int(tx.size() as u64),
+            Cell::Text(tx.memo.clone()),
+        ])?;
+    }
+    for ((addr_str, token_str), (addr, token_id)) in touched_balances(block) {
+        balances.write(vec![
+            Cell::Int(number),
+            Cell::Text(addr_str),
+            Cell::Text(token_str),
+            Cell::Int(replayed.get_balance(addr, token_id)?.into()),
+        ])?;
+    }
+    Ok(())
+}
+
+pub fn export_analytics(
+    output: PathBuf,
+    format: ExportFormat,
+    from: Option<u64>,
+    to: Option<u64>,
+    conf: &BazukaConfig,
+) {
+    let rdb = ReadOnlyLevelDbKvStore::read_only(&DataDir::new(&conf.db).db(), 64).unwrap();
+    let db = rdb.snapshot();
+    let stored = KvStoreChain::new(db, get_blockchain_config()).unwrap();
+
+    let height = stored.get_height().unwrap();
+    let from = from.unwrap_or(0);
+    let to = to.map(|to| to.min(height - 1)).unwrap_or(height - 1);
+
+    std::fs::create_dir_all(&output).unwrap();
+    let mut blocks = TableWriter::create(&output, "blocks", BLOCK_COLUMNS, format).unwrap();
+    let mut transactions =
+        TableWriter::create(&output, "transactions", TRANSACTION_COLUMNS, format).unwrap();
+    let mut balances = TableWriter::create(&output, "balances", BALANCE_COLUMNS, format).unwrap();
+
+    // Balances at a past height are only known by replaying the chain up to it
+    let mut replayed = KvStoreChain::new(RamKvStore::new(), get_blockchain_config()).unwrap();
+    for index in 0..=to {
+        let block = stored.get_block(index).unwrap_or_else(|e| {
+            panic!(
+                "Cannot read block {} ({}), pruned nodes cannot export analytics!",
+                index, e
+            )
+        });
+        if index > 0 {
+            replayed
+                .extend(index, std::slice::from_ref(&block))
+                .unwrap();
+        }
+        if index >= from {
+            export_block(
+                &block,
+                &replayed,
+                &mut blocks,
+                &mut transactions,
+                &mut balances,
+            )
+            .unwrap();
+        }
+        if index % 1000 == 0 {
+            println!("Exported {}/{} blocks...", index, to);
+        }
+    }
+
+    blocks.finish().unwrap();
+    transactions.finish().unwrap();
+    balances.finish().unwrap();
+    println!(
+        "Exported blocks {} to {} into {}",
+        from,
+        to,
+        output.display()
+    );
+}
@@ -1,4 +1,4 @@
-use crate::cli::BazukaConfig;
+use crate::cli::{datadir::DataDir, BazukaConfig};
 use bazuka::blockchain::Blockchain;
 use bazuka::db::KvStore;
 use bazuka::{
@@ -12,7 +12,7 @@ pub fn health_check(conf: &BazukaConfig) {
     let mpn_contract_id = bazuka::config::blockchain::get_blockchain_config()
         .mpn_config
         .mpn_contract_id;
-    let rdb = ReadOnlyLevelDbKvStore::read_only(&conf.db, 64).unwrap();
+    let rdb = ReadOnlyLevelDbKvStore::read_only(&DataDir::new(&conf.db).db(), 64).unwrap();
     let db = rdb.snapshot();
     let chain = KvStoreChain::new(db, bazuka::config::blockchain::get_blockchain_config()).unwrap();
     let mut fork = chain.fork_on_ram();
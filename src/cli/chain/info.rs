@@ -0,0 +1,48 @@
+use crate::cli::{datadir::DataDir, BazukaConfig};
+use bazuka::blockchain::Blockchain;
+use bazuka::{blockchain::KvStoreChain, db::ReadOnlyLevelDbKvStore};
+use colored::Colorize;
+
+pub fn info(conf: &BazukaConfig) {
+    let rdb = ReadOnlyLevelDbKvStore::read_only(&DataDir::new(&conf.db).db(), 64).unwrap();
+    let db = rdb.snapshot();
+    let chain = KvStoreChain::new(db, bazuka::config::blockchain::get_blockchain_config()).unwrap();
+    let tip = chain.get_tip().unwrap();
+    let (epoch, slot) = chain.epoch_slot(tip.proof_of_stake.timestamp);
+    println!(
+        "{} {}",
+        "Height:".bright_yellow(),
+        chain.get_height().unwrap()
+    );
+    println!("{} {}", "Tip:".bright_yellow(), hex::encode(tip.hash()));
+    println!(
+        "{} {} (Epoch: {} Slot: {})",
+        "Tip timestamp:".bright_yellow(),
+        tip.proof_of_stake.timestamp,
+        epoch,
+        slot
+    );
+    println!(
+        "{} {}",
+        "Genesis:".bright_yellow(),
+        hex::encode(chain.get_header(0).unwrap().hash())
+    );
+    println!(
+        "{} {}",
+        "Pruned height:".bright_yellow(),
+        chain.get_pruned_height().unwrap()
+    );
+    println!(
+        "{} {}",
+        "Stakers:".bright_yellow(),
+        chain.get_stakers().unwrap().len()
+    );
+    println!(
+        "{} {}",
+        "Currency in circulation:".bright_yellow(),
+        chain
+            .currency_in_circulation()
+            .unwrap()
+            .display_by_decimals(bazuka::config::UNIT_ZEROS)
+    );
+}
@@ -0,0 +1,72 @@
+use crate::cli::{datadir::DataDir, BazukaConfig};
+use bazuka::blockchain::Blockchain;
+use bazuka::{
+    blockchain::KvStoreChain,
+    config::blockchain::get_blockchain_config,
+    core::{Address, Amount, ContractAccount, ContractId, Staker, TokenId},
+    db::ReadOnlyLevelDbKvStore,
+    zk::ZkContract,
+};
+use serde::Serialize;
+use std::fmt::Debug;
+
+#[derive(Serialize, Debug)]
+struct AccountInfo {
+    address: String,
+    nonce: u32,
+    balance: Amount,
+    stake: Amount,
+    staker: Option<Staker>,
+}
+
+#[derive(Serialize, Debug)]
+struct ContractInfo {
+    contract: ZkContract,
+    account: ContractAccount,
+    balance: Amount,
+}
+
+pub enum InspectTarget {
+    Block(u64),
+    Header(u64),
+    Account(Address),
+    Contract(ContractId),
+}
+
+fn print<T: Serialize + Debug>(value: &T, json: bool) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value).unwrap());
+    } else {
+        println!("{:#?}", value);
+    }
+}
+
+pub fn inspect(target: InspectTarget, json: bool, conf: &BazukaConfig) {
+    let rdb = ReadOnlyLevelDbKvStore::read_only(&DataDir::new(&conf.db).db(), 64).unwrap();
+    let db = rdb.snapshot();
+    let chain = KvStoreChain::new(db, get_blockchain_config()).unwrap();
+    match target {
+        InspectTarget::Block(index) => print(&chain.get_block(index).unwrap(), json),
+        InspectTarget::Header(index) => print(&chain.get_header(index).unwrap(), json),
+        InspectTarget::Account(address) => print(
+            &AccountInfo {
+                address: address.to_string(),
+                nonce: chain.get_nonce(address.clone()).unwrap(),
+                balance: chain.get_balance(address.clone(), TokenId::Ziesha).unwrap(),
+                stake: chain.get_stake(address.clone()).unwrap(),
+                staker: chain.get_staker(address).unwrap(),
+            },
+            json,
+        ),
+        InspectTarget::Contract(contract_id) => print(
+            &ContractInfo {
+                contract: chain.get_contract(contract_id).unwrap(),
+                account: chain.get_contract_account(contract_id).unwrap(),
+                balance: chain
+                    .get_contract_balance(contract_id, TokenId::Ziesha)
+                    .unwrap(),
+            },
+            json,
+        ),
+    }
+}
@@ -1,7 +1,15 @@
-pub mod db_query;
+pub mod export_analytics;
 pub mod health_check;
+pub mod info;
+pub mod inspect;
+pub mod replay;
 pub mod rollback;
+pub mod verify;
 
-pub use db_query::*;
+pub use export_analytics::*;
 pub use health_check::*;
+pub use info::*;
+pub use inspect::*;
+pub use replay::*;
 pub use rollback::*;
+pub use verify::*;
@@ -0,0 +1,107 @@
+use crate::cli::{datadir::DataDir, BazukaConfig};
+use bazuka::blockchain::Blockchain;
+use bazuka::{
+    blockchain::{KvStoreChain, ReplayLogReader},
+    config::blockchain::get_blockchain_config,
+    db::{Blob, RamKvStore, WriteOp},
+};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub fn replay(log: Option<PathBuf>, verbose: bool, conf: &BazukaConfig) {
+    let path = log.unwrap_or_else(|| DataDir::new(&conf.db).replay_log());
+    let reader = ReplayLogReader::open(&path)
+        .unwrap_or_else(|e| panic!("Cannot open the replay log at {:?} ({})!", path, e));
+    let mut replayed = KvStoreChain::new(RamKvStore::new(), get_blockchain_config()).unwrap();
+
+    let mut count = 0;
+    for entry in reader {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                println!(
+                    "{} Entry {} cannot be read: {}",
+                    "FAIL".bright_red(),
+                    count,
+                    e
+                );
+                return;
+            }
+        };
+        let number = entry.block.header.number;
+        let patch = match replayed.replay(&entry) {
+            Ok(patch) => patch,
+            Err(e) => {
+                println!(
+                    "{} Block {} of entry {} cannot be applied: {}",
+                    "FAIL".bright_red(),
+                    number,
+                    count,
+                    e
+                );
+                return;
+            }
+        };
+        if patch != entry.patch {
+            println!(
+                "{} Block {} of entry {} leads to a different state:",
+                "FAIL".bright_red(),
+                number,
+                count
+            );
+            print_diff(&entry.patch, &patch);
+            return;
+        }
+        if verbose {
+            println!(
+                "Block {} ({}) -> {} changes",
+                number,
+                hex::encode(entry.block.header.hash()),
+                patch.len()
+            );
+        }
+        count += 1;
+    }
+
+    println!(
+        "{} Replayed {} entries, height is {} and state checksum is {}.",
+        "PASS".bright_green(),
+        count,
+        replayed.get_height().unwrap(),
+        replayed.db_checksum().unwrap()
+    );
+}
+
+fn print_diff(recorded: &[WriteOp], replayed: &[WriteOp]) {
+    let changes = |patch: &[WriteOp]| {
+        patch
+            .iter()
+            .map(|op| match op {
+                WriteOp::Put(k, v) => (k.0.clone(), Some(v.clone())),
+                WriteOp::Remove(k) => (k.0.clone(), None),
+            })
+            .collect::<HashMap<_, _>>()
+    };
+    let recorded = changes(recorded);
+    let replayed = changes(replayed);
+    let mut keys = recorded.keys().chain(replayed.keys()).collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let (a, b) = (recorded.get(key), replayed.get(key));
+        if a != b {
+            let describe = |change: Option<&Option<Blob>>| match change {
+                Some(Some(v)) => format!("put {}", hex::encode(&v.0)),
+                Some(None) => "removed".into(),
+                None => "untouched".into(),
+            };
+            println!(
+                "  {}: recorded {}, replayed {}",
+                key,
+                describe(a),
+                describe(b)
+            );
+        }
+    }
+}
@@ -1,12 +1,13 @@
-use crate::cli::BazukaConfig;
+use crate::cli::{datadir::DataDir, BazukaConfig};
 use bazuka::blockchain::Blockchain;
 use bazuka::{
     blockchain::KvStoreChain, config::blockchain::get_blockchain_config, db::LevelDbKvStore,
 };
 
 pub async fn rollback(conf: &BazukaConfig) {
+    let datadir = DataDir::lock(&conf.db).unwrap();
     let mut chain = KvStoreChain::new(
-        LevelDbKvStore::new(&conf.db, 64).unwrap(),
+        LevelDbKvStore::new(&datadir.db(), 64).unwrap(),
         get_blockchain_config(),
     )
     .unwrap();
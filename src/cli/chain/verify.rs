@@ -0,0 +1,80 @@
+use crate::cli::{datadir::DataDir, BazukaConfig};
+use bazuka::blockchain::{Blockchain, BlockchainError};
+use bazuka::{
+    blockchain::KvStoreChain, config::blockchain::get_blockchain_config, db::RamKvStore,
+    db::ReadOnlyLevelDbKvStore,
+};
+use colored::Colorize;
+
+pub fn verify(conf: &BazukaConfig) {
+    let rdb = ReadOnlyLevelDbKvStore::read_only(&DataDir::new(&conf.db).db(), 64).unwrap();
+    let db = rdb.snapshot();
+    let stored = KvStoreChain::new(db, get_blockchain_config()).unwrap();
+    let mut replayed = KvStoreChain::new(RamKvStore::new(), get_blockchain_config()).unwrap();
+
+    let height = stored.get_height().unwrap();
+    for index in 1..height {
+        let block = match stored.get_block(index) {
+            Ok(block) => block,
+            Err(BlockchainError::BlockPruned) => {
+                println!(
+                    "{} Block {} is pruned, blocks below {} cannot be replayed.",
+                    "FAIL".bright_red(),
+                    index,
+                    stored.get_pruned_height().unwrap()
+                );
+                return;
+            }
+            Err(e) => panic!("{}", e),
+        };
+        if let Err(e) = replayed.extend(index, &[block]) {
+            println!(
+                "{} Block {} cannot be applied: {}",
+                "FAIL".bright_red(),
+                index,
+                e
+            );
+            return;
+        }
+        if index % 1000 == 0 {
+            println!("Replayed {}/{} blocks...", index, height - 1);
+        }
+    }
+
+    // Only the state is compared, blocks, rollback data and indexes depend on how
+    // the node was configured
+    if replayed.state_checksum().unwrap() == stored.state_checksum().unwrap() {
+        println!(
+            "{} Replayed {} blocks, state matches the stored tip.",
+            "PASS".bright_green(),
+            height - 1
+        );
+        return;
+    }
+
+    // Walk back both chains until their states match again, the block right after
+    // that point is the first one leading to a different state.
+    let mut stored = stored.fork_on_ram();
+    let mut replayed = replayed.fork_on_ram();
+    let mut divergent = height - 1;
+    while divergent > 0 {
+        if stored.rollback().is_err() {
+            println!(
+                "{} State diverges from the stored state at or before block {}, rollback data of older blocks is pruned.",
+                "FAIL".bright_red(),
+                divergent
+            );
+            return;
+        }
+        replayed.rollback().unwrap();
+        if stored.state_checksum().unwrap() == replayed.state_checksum().unwrap() {
+            break;
+        }
+        divergent -= 1;
+    }
+    println!(
+        "{} State diverges from the stored state at block {}.",
+        "FAIL".bright_red(),
+        divergent
+    );
+}
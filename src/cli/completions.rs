@@ -0,0 +1,75 @@
+use super::Cli;
+use structopt::clap::{AppSettings, Shell};
+use structopt::StructOpt;
+
+const BIN_NAME: &str = "bazuka";
+
+pub fn completions(shell: Shell) {
+    Cli::clap().gen_completions_to(BIN_NAME, shell, &mut std::io::stdout());
+}
+
+/// Help text of a (sub)command, e.g. `["wallet", "send"]`
+fn help_text(path: &[String]) -> String {
+    let args = std::iter::once(BIN_NAME.to_string())
+        .chain(path.iter().cloned())
+        .chain(std::iter::once("--help".to_string()));
+    match Cli::clap()
+        .global_setting(AppSettings::ColorNever)
+        .get_matches_from_safe(args)
+    {
+        Err(e) => e.message,
+        Ok(_) => String::new(),
+    }
+}
+
+fn subcommands(help: &str) -> Vec<String> {
+    help.lines()
+        .skip_while(|l| !l.starts_with("SUBCOMMANDS:"))
+        .skip(1)
+        .take_while(|l| l.starts_with(' '))
+        .filter(|l| !l.starts_with("            ")) // Wrapped descriptions
+        .filter_map(|l| l.split_whitespace().next())
+        .filter(|name| *name != "help")
+        .map(String::from)
+        .collect()
+}
+
+fn escape_roff(line: &str) -> String {
+    let line = line.replace('\\', "\\e");
+    if line.starts_with('.') || line.starts_with('\'') {
+        format!("\\&{}", line)
+    } else {
+        line
+    }
+}
+
+fn write_sections(path: Vec<String>, out: &mut String) {
+    let help = help_text(&path);
+    let title = std::iter::once(BIN_NAME)
+        .chain(path.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    out.push_str(&format!(".SS {}\n.nf\n", title));
+    for line in help.lines() {
+        out.push_str(&escape_roff(line));
+        out.push('\n');
+    }
+    out.push_str(".fi\n");
+    for sub in subcommands(&help) {
+        let mut sub_path = path.clone();
+        sub_path.push(sub);
+        write_sections(sub_path, out);
+    }
+}
+
+/// Prints a roff man page containing help of all the subcommands
+pub fn man() {
+    let mut out = format!(
+        ".TH BAZUKA 1 \"\" \"{} {}\"\n.SH NAME\n{} \\- Node software for Ziesha Network\n.SH COMMANDS\n",
+        BIN_NAME,
+        env!("CARGO_PKG_VERSION"),
+        BIN_NAME
+    );
+    write_sections(Vec::new(), &mut out);
+    print!("{}", out);
+}
@@ -0,0 +1,168 @@
+use bazuka::client::messages::{RestartInfo, RestartReason};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Version of the data directory layout, bumped whenever the on-disk format changes
+const LAYOUT_VERSION: u32 = 1;
+
+const DB_DIR: &str = "db";
+const EXPLORER_DB_DIR: &str = "explorer";
+const METRICS_DB_DIR: &str = "metrics";
+const MEMPOOL_DB_DIR: &str = "mempool";
+const BAN_DB_DIR: &str = "bans";
+const REPLAY_LOG_FILE: &str = "replay.log";
+const LOCK_FILE: &str = "LOCK.bazuka";
+const VERSION_FILE: &str = "VERSION";
+const DIRTY_FILE: &str = "DIRTY";
+const STARTS_FILE: &str = "STARTS";
+
+#[derive(Error, Debug)]
+pub enum DataDirError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("data directory {0} is already in use by another node")]
+    Locked(PathBuf),
+    #[error("data directory was created by a newer version of bazuka (layout v{0})")]
+    NewerVersion(u32),
+    #[error("data directory version marker is corrupted")]
+    InvalidVersion,
+    #[error("start history is corrupted: {0}")]
+    InvalidStarts(#[from] serde_json::Error),
+}
+
+/// Node data directory (`db` path of the config), laid out as:
+///
+/// - `db/`: chain database
+/// - `explorer/`: explorer index, when the node is run with `--explorer`
+/// - `metrics/`: recent samples of the node status
+/// - `mempool/`: pending transactions, reloaded when the node is restarted
+/// - `bans/`: bans of misbehaving peers, reloaded when the node is restarted
+/// - `replay.log`: applied blocks and their state patches, when the node is run with
+///   `--replay-log`
+/// - `LOCK.bazuka`: held while a node is writing to the directory
+/// - `VERSION`: layout version the directory was written with
+/// - `DIRTY`: exists while a node is running, left behind if it does not shut down
+///   cleanly
+/// - `STARTS`: number of starts and crashes of the node, and when it was last started
+///
+/// Older data directories store the database files directly in the root, those are
+/// still opened in place.
+pub struct DataDir {
+    root: PathBuf,
+    _lock: Option<File>,
+}
+
+impl DataDir {
+    /// Opens the data directory without locking it, for read-only access
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            _lock: None,
+        }
+    }
+
+    /// Opens the data directory for writing, creating it if it does not exist. Fails if
+    /// another process holds the lock or the layout version is not supported.
+    pub fn lock<P: AsRef<Path>>(root: P) -> Result<Self, DataDirError> {
+        let root = root.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root)?;
+
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(root.join(LOCK_FILE))?;
+        if lock.try_lock().is_err() {
+            return Err(DataDirError::Locked(root));
+        }
+
+        let version_path = root.join(VERSION_FILE);
+        if version_path.exists() {
+            let version: u32 = std::fs::read_to_string(&version_path)?
+                .trim()
+                .parse()
+                .map_err(|_| DataDirError::InvalidVersion)?;
+            if version > LAYOUT_VERSION {
+                return Err(DataDirError::NewerVersion(version));
+            }
+        }
+        std::fs::write(&version_path, LAYOUT_VERSION.to_string())?;
+
+        Ok(Self {
+            root,
+            _lock: Some(lock),
+        })
+    }
+
+    /// Marks the directory as in use by a running node. Returns true if the previous
+    /// node did not shut down cleanly.
+    pub fn mark_dirty(&self) -> Result<bool, DataDirError> {
+        let path = self.root.join(DIRTY_FILE);
+        let was_dirty = path.exists();
+        std::fs::write(path, [])?;
+        Ok(was_dirty)
+    }
+
+    pub fn mark_clean(&self) -> Result<(), DataDirError> {
+        std::fs::remove_file(self.root.join(DIRTY_FILE))?;
+        Ok(())
+    }
+
+    /// Records a start of the node at `now`, `dirty` being what `mark_dirty` returned
+    pub fn record_start(&self, now: u32, dirty: bool) -> Result<RestartInfo, DataDirError> {
+        let path = self.root.join(STARTS_FILE);
+        let previous = if path.exists() {
+            Some(serde_json::from_str::<RestartInfo>(
+                &std::fs::read_to_string(&path)?,
+            )?)
+        } else {
+            None
+        };
+        let reason = if dirty {
+            RestartReason::Crash
+        } else if previous.is_some() {
+            RestartReason::Restart
+        } else {
+            RestartReason::FirstStart
+        };
+        let info = RestartInfo {
+            reason,
+            started_at: now,
+            previous_start: previous.as_ref().map(|p| p.started_at),
+            starts: previous.as_ref().map(|p| p.starts).unwrap_or(0) + 1,
+            crashes: previous.as_ref().map(|p| p.crashes).unwrap_or(0) + dirty as u32,
+        };
+        std::fs::write(path, serde_json::to_string(&info)?)?;
+        Ok(info)
+    }
+
+    pub fn explorer_db(&self) -> PathBuf {
+        self.root.join(EXPLORER_DB_DIR)
+    }
+
+    pub fn metrics_db(&self) -> PathBuf {
+        self.root.join(METRICS_DB_DIR)
+    }
+
+    pub fn mempool_db(&self) -> PathBuf {
+        self.root.join(MEMPOOL_DB_DIR)
+    }
+
+    pub fn ban_db(&self) -> PathBuf {
+        self.root.join(BAN_DB_DIR)
+    }
+
+    pub fn replay_log(&self) -> PathBuf {
+        self.root.join(REPLAY_LOG_FILE)
+    }
+
+    pub fn db(&self) -> PathBuf {
+        // LevelDB databases always have a CURRENT file
+        if self.root.join("CURRENT").exists() {
+            self.root.clone()
+        } else {
+            self.root.join(DB_DIR)
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod prune;
+pub mod query;
+
+pub use prune::*;
+pub use query::*;
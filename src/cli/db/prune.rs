@@ -0,0 +1,15 @@
+use crate::cli::{datadir::DataDir, BazukaConfig};
+use bazuka::blockchain::{Blockchain, KvStoreChain};
+use bazuka::config;
+use bazuka::db::LevelDbKvStore;
+
+pub fn prune(keep_blocks: u64, conf: &BazukaConfig) {
+    let datadir = DataDir::lock(&conf.db).unwrap();
+    let mut chain = KvStoreChain::new(
+        LevelDbKvStore::new(&datadir.db(), 64).unwrap(),
+        config::blockchain::get_blockchain_config(),
+    )
+    .unwrap();
+    let pruned = chain.prune(keep_blocks).unwrap();
+    println!("Pruned {} blocks!", pruned);
+}
@@ -0,0 +1,3 @@
+pub mod new;
+
+pub use new::*;
@@ -0,0 +1,141 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use bazuka::config::blockchain::{get_custom_genesis_block, GenesisParams};
+use bazuka::config::UNIT_ZEROS;
+use bazuka::core::{Address, Amount, ContractId, Decimal, Ratio, TransactionData};
+use colored::Colorize;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct GenesisAllocation {
+    address: String,
+    amount: String,
+}
+
+/// Paths to a bincode-encoded `ZkContract` and, optionally, its initial `ZkDataPairs`
+#[derive(Deserialize)]
+struct GenesisContract {
+    contract: PathBuf,
+    state: Option<PathBuf>,
+}
+
+/// Human-editable genesis description, amounts are in Ziesha (e.g. "12.5")
+#[derive(Deserialize)]
+struct GenesisSpec {
+    chain_start_timestamp: Option<u32>,
+    validator: String,
+    validator_vrf: String,
+    validator_commission: Option<u8>,
+    validator_stake: String,
+    #[serde(default)]
+    allocations: Vec<GenesisAllocation>,
+    #[serde(default)]
+    contracts: Vec<GenesisContract>,
+}
+
+fn parse_amount(s: &str) -> Amount {
+    s.parse::<Decimal>()
+        .unwrap_or_else(|_| panic!("Invalid amount: {}", s))
+        .to_amount(UNIT_ZEROS)
+}
+
+fn parse_address(s: &str) -> Address {
+    s.parse()
+        .unwrap_or_else(|_| panic!("Invalid address: {}", s))
+}
+
+fn read_bincode<T: serde::de::DeserializeOwned>(path: &Path) -> T {
+    bincode::deserialize(
+        &std::fs::read(path).unwrap_or_else(|e| panic!("Cannot read {}: {}", path.display(), e)),
+    )
+    .unwrap_or_else(|e| panic!("Invalid file {}: {}", path.display(), e))
+}
+
+fn prompt(msg: &str) -> String {
+    print!("{} ", msg.bright_yellow());
+    std::io::stdout().flush().unwrap();
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line).unwrap();
+    line.trim().to_string()
+}
+
+fn ask_spec() -> GenesisSpec {
+    let chain_start_timestamp = prompt("Chain start timestamp (Empty for now):");
+    let validator = prompt("Validator address:");
+    let validator_vrf = prompt("Validator VRF public key:");
+    let validator_commission = prompt("Validator commission out of 255 (Empty for 12):");
+    let validator_stake = prompt("Validator stake:");
+    let mut allocations = Vec::new();
+    loop {
+        let address = prompt("Allocation address (Empty to finish):");
+        if address.is_empty() {
+            break;
+        }
+        let amount = prompt("Allocation amount:");
+        allocations.push(GenesisAllocation { address, amount });
+    }
+    GenesisSpec {
+        chain_start_timestamp: (!chain_start_timestamp.is_empty())
+            .then(|| chain_start_timestamp.parse().expect("Invalid timestamp!")),
+        validator,
+        validator_vrf,
+        validator_commission: (!validator_commission.is_empty())
+            .then(|| validator_commission.parse().expect("Invalid commission!")),
+        validator_stake,
+        allocations,
+        contracts: vec![],
+    }
+}
+
+pub fn new(spec: Option<PathBuf>, output: &Path) {
+    let spec: GenesisSpec = match spec {
+        Some(path) => serde_yaml::from_reader(std::fs::File::open(path).unwrap()).unwrap(),
+        None => ask_spec(),
+    };
+    let params = GenesisParams {
+        chain_start_timestamp: spec
+            .chain_start_timestamp
+            .unwrap_or_else(bazuka::utils::local_timestamp),
+        validator: parse_address(&spec.validator),
+        validator_vrf: spec
+            .validator_vrf
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid VRF public key: {}", spec.validator_vrf)),
+        validator_commission: Ratio(spec.validator_commission.unwrap_or(12)),
+        validator_stake: parse_amount(&spec.validator_stake),
+        allocations: spec
+            .allocations
+            .iter()
+            .map(|a| (parse_address(&a.address), parse_amount(&a.amount)))
+            .collect(),
+        contracts: spec
+            .contracts
+            .iter()
+            .map(|c| {
+                (
+                    read_bincode(&c.contract),
+                    c.state.as_deref().map(read_bincode).unwrap_or_default(),
+                )
+            })
+            .collect(),
+    };
+
+    let genesis = get_custom_genesis_block(&params);
+    std::fs::write(output, bincode::serialize(&genesis).unwrap()).unwrap();
+    println!(
+        "{} {}",
+        "Genesis written to:".bright_yellow(),
+        output.display()
+    );
+    println!(
+        "{} {}",
+        "Genesis hash:".bright_yellow(),
+        hex::encode(genesis.header.hash())
+    );
+    for tx in genesis.body.iter() {
+        if let TransactionData::CreateContract { .. } = tx.data {
+            println!("{} {}", "Contract-Id:".bright_yellow(), ContractId::new(tx));
+        }
+    }
+}
@@ -1,50 +1,55 @@
-use super::{BazukaConfig, DEFAULT_PORT};
+use super::wallet::WalletFile;
+use super::{BazukaConfig, DEFAULT_PORT, DEFAULT_TESTNET_PORT};
 use bazuka::{client::PeerAddress, wallet::WalletCollection};
 use bip39::Mnemonic;
-use colored::Colorize;
 use std::{
     net::SocketAddr,
     path::{Path, PathBuf},
 };
 
+#[allow(clippy::too_many_arguments)]
 pub async fn init(
     bootstrap: Vec<PeerAddress>,
     mnemonic: Option<Mnemonic>,
     external: Option<PeerAddress>,
     listen: Option<SocketAddr>,
     db: Option<PathBuf>,
+    testnet: bool,
     conf: Option<BazukaConfig>,
     conf_path: &PathBuf,
     wallet: Option<WalletCollection>,
-    wallet_path: &PathBuf,
+    wallet_file: &WalletFile,
 ) -> () {
-    if wallet.is_none() {
-        let w = WalletCollection::create(&mut rand_mnemonic::thread_rng(), mnemonic);
-        w.save(wallet_path).unwrap();
-        println!("Wallet generated!");
-        println!("{} {}", "Mnemonic phrase:".bright_yellow(), w.mnemonic());
-        println!(
-            "{}",
-            "WRITE DOWN YOUR MNEMONIC PHRASE IN A SAFE PLACE!"
-                .italic()
-                .bold()
-                .bright_green()
-        );
-    } else {
-        println!("Wallet is already initialized!");
-    }
+    super::wallet::init(mnemonic, wallet, wallet_file);
 
     if conf.is_none() {
+        let (port, default_db) = if testnet {
+            (DEFAULT_TESTNET_PORT, ".bazuka-testnet")
+        } else {
+            (DEFAULT_PORT, ".bazuka")
+        };
         let public_ip = bazuka::client::utils::get_public_ip().await.unwrap();
         std::fs::write(
             conf_path,
             serde_yaml::to_string(&BazukaConfig {
                 bootstrap,
-                listen: listen.unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], DEFAULT_PORT))),
+                listen: listen.unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], port))),
                 external: external
-                    .unwrap_or_else(|| PeerAddress(SocketAddr::from((public_ip, DEFAULT_PORT)))),
-                db: db.unwrap_or_else(|| home::home_dir().unwrap().join(Path::new(".bazuka"))),
+                    .unwrap_or_else(|| PeerAddress(SocketAddr::from((public_ip, port)))),
+                db: db.unwrap_or_else(|| home::home_dir().unwrap().join(Path::new(default_db))),
                 mpn_workers: vec![],
+                testnet,
+                runtime: Default::default(),
+                #[cfg(feature = "node")]
+                webhooks: Vec::new(),
+                #[cfg(feature = "node")]
+                telemetry: None,
+                #[cfg(feature = "node")]
+                cluster: None,
+                #[cfg(feature = "node")]
+                access_list: None,
+                #[cfg(feature = "node")]
+                contract_policy: None,
             })
             .unwrap(),
         )
@@ -1,46 +1,62 @@
 #[cfg(feature = "node")]
 use {
     bazuka::blockchain::Blockchain,
-    bazuka::client::{messages::SocialProfiles, Limit, NodeRequest},
+    bazuka::client::{
+        messages::{SocialProfiles, UpdateConfigRequest},
+        Limit, NodeRequest,
+    },
     bazuka::common::*,
     bazuka::db::KvStore,
-    bazuka::node::{node_create, Firewall},
+    bazuka::node::{systemd, transport, ConnectionLimiter, Firewall, NodeBuilder, NodeOptions},
     hyper::server::conn::AddrStream,
     hyper::service::{make_service_fn, service_fn},
-    hyper::{Body, Client, Request, Response, Server, StatusCode},
+    hyper::{Body, Method, Request, Response, Server, StatusCode},
     std::sync::Arc,
-    tokio::sync::mpsc,
+    tokio::sync::{mpsc, watch},
 };
 
 #[cfg(feature = "client")]
 use {
-    bazuka::client::{NodeError, PeerAddress},
-    bazuka::config,
-    bazuka::core::{Address, Decimal, GeneralAddress, MpnAddress, TokenId},
+    bazuka::client::{messages::Offense, NodeError, PeerAddress},
+    bazuka::core::{Address, Amount, ContractId, Decimal, GeneralAddress, MpnAddress, TokenId},
     bazuka::mpn::MpnWorker,
     bazuka::wallet::WalletCollection,
     colored::Colorize,
     serde::{Deserialize, Serialize},
+    std::collections::HashMap,
     std::net::SocketAddr,
     std::path::{Path, PathBuf},
+    std::sync::OnceLock,
     structopt::StructOpt,
     tokio::try_join,
+    tracing_subscriber::EnvFilter,
 };
 
 pub mod chain;
+pub mod completions;
+pub mod datadir;
+pub mod db;
+pub mod genesis;
 pub mod init;
 pub mod wallet;
 pub use init::*;
 
 #[cfg(feature = "node")]
 pub mod node;
+#[cfg(feature = "node")]
+pub mod simulate;
 
 #[cfg(feature = "client")]
 const DEFAULT_PORT: u16 = 8765;
+const DEFAULT_TESTNET_PORT: u16 = 18765;
 const BAZUKA_NOT_INITILIZED: &str = "Bazuka is not initialized";
 
 const CURRENT_NETWORK: &str = "koobideh-kebab";
 
+/// Connections a remote client may keep open at the same time, unless configured
+#[cfg(feature = "node")]
+const DEFAULT_MAX_CONNECTIONS_PER_IP: usize = 16;
+
 #[cfg(feature = "client")]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BazukaConfigMpnWorker {
@@ -68,17 +84,153 @@ pub struct BazukaConfig {
     bootstrap: Vec<PeerAddress>,
     db: PathBuf,
     mpn_workers: Vec<BazukaConfigMpnWorker>,
+    /// Join the public test network instead of the main network
+    #[serde(default)]
+    testnet: bool,
+    #[serde(default)]
+    runtime: BazukaRuntimeConfig,
+    /// External endpoints notified of new blocks, reorgs, address activity and bans
+    #[cfg(feature = "node")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    webhooks: Vec<bazuka::node::WebhookOptions>,
+    /// Anonymous node stats (Version, height, peer count and OS) are reported to
+    /// this endpoint when set
+    #[cfg(feature = "node")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    telemetry: Option<String>,
+    /// Other nodes of the operator, allowed to use the admin endpoints of this node
+    #[cfg(feature = "node")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cluster: Option<bazuka::node::ClusterOptions>,
+    /// Networks allowed or denied to send requests to the node
+    #[cfg(feature = "node")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    access_list: Option<bazuka::node::AccessListOptions>,
+    /// Contract transactions accepted and relayed by the node, all of them if not set
+    #[cfg(feature = "node")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    contract_policy: Option<bazuka::blockchain::ContractTxPolicy>,
+}
+
+/// Settings of a running node that are reloaded from the config file on SIGHUP
+#[cfg(feature = "client")]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct BazukaRuntimeConfig {
+    log_level: Option<String>,
+    num_peers: Option<usize>,
+    request_limit_per_minute: Option<usize>,
+    traffic_limit_per_15m: Option<u64>,
+    /// Largest request body accepted from remote clients, in bytes
+    max_body_size: Option<u64>,
+    /// Connections a remote client may keep open at the same time
+    max_connections_per_ip: Option<usize>,
+    max_block_txs: Option<usize>,
+    min_fee: Option<Amount>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    punishments: HashMap<Offense, u32>,
+    max_punish: Option<u32>,
 }
 
 #[cfg(feature = "client")]
 impl BazukaConfig {
     fn random_node(&self) -> PeerAddress {
-        PeerAddress(SocketAddr::from(([0, 0, 0, 0], DEFAULT_PORT)))
+        PeerAddress(SocketAddr::from(([0, 0, 0, 0], self.listen.port())))
         /*self.bootstrap
         .choose(&mut rand::thread_rng())
         .unwrap_or(&PeerAddress(SocketAddr::from(([0, 0, 0, 0], DEFAULT_PORT))))
         .clone()*/
     }
+
+    /// Applies `BAZUKA_*` environment variable overrides on top of the config file.
+    /// When there is no config file, a config is built from the environment alone,
+    /// given that at least `BAZUKA_LISTEN` and `BAZUKA_EXTERNAL` are provided.
+    fn with_env(conf: Option<BazukaConfig>) -> Option<BazukaConfig> {
+        let listen = env_var::<SocketAddr>("BAZUKA_LISTEN");
+        let external = env_var::<PeerAddress>("BAZUKA_EXTERNAL");
+        let db = env_var::<PathBuf>("BAZUKA_DB");
+        let bootstrap = std::env::var("BAZUKA_BOOTSTRAP").ok().map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    s.parse::<PeerAddress>()
+                        .unwrap_or_else(|e| panic!("Invalid BAZUKA_BOOTSTRAP entry {}: {}", s, e))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let mut conf = match conf {
+            Some(conf) => conf,
+            None => BazukaConfig {
+                listen: listen?,
+                external: external?,
+                bootstrap: Vec::new(),
+                db: db
+                    .clone()
+                    .unwrap_or_else(|| home::home_dir().unwrap().join(Path::new(".bazuka"))),
+                mpn_workers: Vec::new(),
+                testnet: false,
+                runtime: Default::default(),
+                #[cfg(feature = "node")]
+                webhooks: Vec::new(),
+                #[cfg(feature = "node")]
+                telemetry: None,
+                #[cfg(feature = "node")]
+                cluster: None,
+                #[cfg(feature = "node")]
+                access_list: None,
+                #[cfg(feature = "node")]
+                contract_policy: None,
+            },
+        };
+        if let Some(listen) = listen {
+            conf.listen = listen;
+        }
+        if let Some(external) = external {
+            conf.external = external;
+        }
+        if let Some(db) = db {
+            conf.db = db;
+        }
+        if let Some(bootstrap) = bootstrap {
+            conf.bootstrap = bootstrap;
+        }
+        Some(conf)
+    }
+}
+
+#[cfg(feature = "client")]
+fn config_path() -> PathBuf {
+    env_var::<PathBuf>("BAZUKA_CONFIG")
+        .unwrap_or_else(|| home::home_dir().unwrap().join(Path::new(".bazuka.yaml")))
+}
+
+#[cfg(feature = "client")]
+fn read_config(path: &Path) -> Result<Option<BazukaConfig>, serde_yaml::Error> {
+    let conf = match std::fs::File::open(path) {
+        Ok(f) => Some(serde_yaml::from_reader(f)?),
+        Err(_) => None,
+    };
+    Ok(BazukaConfig::with_env(conf))
+}
+
+#[cfg(feature = "client")]
+fn env_var<T: std::str::FromStr>(name: &str) -> Option<T>
+where
+    T::Err: std::fmt::Display,
+{
+    std::env::var(name).ok().map(|v| {
+        v.parse()
+            .unwrap_or_else(|e| panic!("Invalid value for {}: {}", name, e))
+    })
+}
+
+#[cfg(feature = "client")]
+fn parse_block_count(v: &str) -> Result<u64, String> {
+    match v.parse::<u64>().map_err(|e| e.to_string())? {
+        0 => Err("at least one block should be kept".into()),
+        count => Ok(count),
+    }
 }
 
 #[derive(StructOpt)]
@@ -121,6 +273,30 @@ enum WalletOptions {
         amount: Decimal,
         #[structopt(long, default_value = "0")]
         fee: Decimal,
+        /// Label the sent transaction in the wallet metadata
+        #[structopt(long)]
+        label: Option<String>,
+    },
+    /// Label a transaction, or remove its label when no label is given
+    Label {
+        #[structopt(long)]
+        tx: String,
+        #[structopt(long)]
+        label: Option<String>,
+    },
+    /// Keep the money received by a transaction out of new sends
+    ExcludeInflow {
+        #[structopt(long)]
+        tx: String,
+        #[structopt(long)]
+        amount: Decimal,
+        #[structopt(long)]
+        token_id: Option<TokenId>,
+    },
+    /// Allow spending an excluded inflow again
+    IncludeInflow {
+        #[structopt(long)]
+        tx: String,
     },
     /// Register your validator
     RegisterValidator {
@@ -164,15 +340,35 @@ enum WalletOptions {
         #[structopt(long, default_value = "0")]
         fee: Decimal,
     },
+    /// Generate a new wallet seeded from OS randomness
+    Init {
+        /// Restore the wallet backed up by this mnemonic phrase instead
+        #[structopt(long)]
+        mnemonic: Option<bip39::Mnemonic>,
+    },
+    /// Show the mnemonic phrase which backs up the wallet
+    Backup {},
     /// Resets wallet nonces
     Reset {},
     /// Get info and balances of the wallet
     Info {
         #[structopt(long)]
         validator: bool,
+        /// Also list balances of this many accounts derived from the mnemonic
+        #[structopt(long, default_value = "1")]
+        accounts: usize,
     },
     /// Resend pending transactions
     ResendPending {},
+    /// Encrypt the wallet with a passphrase and store it in the keystore
+    KeystoreSave {
+        #[structopt(long)]
+        name: String,
+        #[structopt(long)]
+        overwrite: bool,
+    },
+    /// List the wallets in the keystore
+    KeystoreList {},
 }
 
 #[derive(StructOpt)]
@@ -191,8 +387,63 @@ enum NodeCliOptions {
         dev: bool,
         #[structopt(long)]
         small_mpn: bool,
+        /// Run a local regtest chain where blocks are generated on demand
+        #[structopt(long)]
+        regtest: bool,
+        /// Run a private network on the genesis block built by `genesis new`
+        #[structopt(long)]
+        genesis: Option<PathBuf>,
+        /// Only keep bodies and rollback data of the given number of latest blocks
+        #[structopt(long, parse(try_from_str = parse_block_count))]
+        prune: Option<u64>,
+        /// Only keep rollback data of the given number of latest blocks, which also
+        /// bounds the deepest reorg the node will follow
+        #[structopt(long, parse(try_from_str = parse_block_count))]
+        rollback_retention: Option<u64>,
+        /// Do not record which transactions touch each address, which disables the
+        /// account history endpoints
+        #[structopt(long)]
+        no_address_index: bool,
+        /// Record every applied block and its state patch in the data directory, for
+        /// reproducing the state with `chain replay`
+        #[structopt(long)]
+        replay_log: bool,
+        /// Bootstrap a fresh node from the state of its highest peer, instead of
+        /// replaying all blocks
+        #[structopt(long)]
+        snapshot_sync: bool,
+        /// Seconds between the node status summaries written to the log
+        #[structopt(long)]
+        status_interval: Option<u64>,
+        /// Run on the public test network
+        #[structopt(long)]
+        testnet: bool,
+        /// Serve test coins of the first user account through the /faucet endpoint
+        /// (testnet only)
+        #[structopt(long)]
+        faucet: bool,
+        /// Index the chain and serve a block explorer on /explorer (needs the `explorer`
+        /// build feature)
+        #[structopt(long)]
+        explorer: bool,
+        /// Serve the gRPC API on the given address (needs the `grpc` build feature)
+        #[structopt(long)]
+        grpc: Option<SocketAddr>,
+        /// Publish block and transaction hashes on the given ZeroMQ endpoint, E.g
+        /// `tcp://127.0.0.1:28332` (needs the `zmq` build feature)
+        #[structopt(long)]
+        zmq: Option<String>,
+        /// Forward the port of the node on the router through UPnP (needs the `upnp`
+        /// build feature)
+        #[structopt(long)]
+        upnp: bool,
+        /// Talk to other nodes over libp2p, listening on the given address, which the
+        /// external address should point to (needs the `libp2p` build feature)
+        #[structopt(long)]
+        libp2p: Option<SocketAddr>,
     },
-    /// Get status of a node
+    /// Print height, peers, sync progress, mempool size and wallet balance of the
+    /// node, followed by all of its stats
     Status {},
     /// Add a new mpn worker
     AddMpnWorker { mpn_address: MpnAddress },
@@ -202,48 +453,168 @@ enum NodeCliOptions {
 #[allow(clippy::large_enum_variant)]
 #[cfg(feature = "client")]
 enum ChainCliOptions {
+    /// Print height, tip and supply of the local blockchain
+    Info {},
     /// Rollback the blockchain
     Rollback {},
-    /// Query the underlying database
-    DbQuery { prefix: String },
     /// Check health of the blockchain
     HealthCheck {},
+    /// Replay all blocks from genesis and compare the result with the stored state
+    Verify {},
+    /// Apply the blocks of a replay log one by one and compare the state patches with
+    /// the recorded ones
+    Replay {
+        /// Replay log of the node, defaults to the one in the data directory
+        #[structopt(long)]
+        log: Option<PathBuf>,
+        /// Print each replayed block
+        #[structopt(long)]
+        verbose: bool,
+    },
+    /// Export blocks, transactions and balances over time as flat tables
+    ExportAnalytics {
+        /// Directory to write the tables into
+        #[structopt(long)]
+        output: PathBuf,
+        /// Table format, csv or parquet
+        #[structopt(long, default_value = "csv")]
+        format: chain::ExportFormat,
+        /// First block to export
+        #[structopt(long)]
+        from: Option<u64>,
+        /// Last block to export
+        #[structopt(long)]
+        to: Option<u64>,
+    },
+    /// Print blocks, headers, accounts or contracts stored in the database
+    Inspect {
+        /// Print as JSON
+        #[structopt(long)]
+        json: bool,
+        #[structopt(subcommand)]
+        target: InspectCliOptions,
+    },
+}
+
+#[derive(StructOpt)]
+#[cfg(feature = "client")]
+enum InspectCliOptions {
+    /// Block with the given height
+    Block { index: u64 },
+    /// Header of the block with the given height
+    Header { index: u64 },
+    /// Account state of an address
+    Account { address: Address },
+    /// Contract definition and state
+    Contract { contract_id: ContractId },
+}
+
+#[derive(StructOpt)]
+#[cfg(feature = "client")]
+enum GenesisCliOptions {
+    /// Build a genesis block, either interactively or from a YAML spec file
+    New {
+        #[structopt(long)]
+        spec: Option<PathBuf>,
+        #[structopt(long, default_value = "genesis.dat")]
+        output: PathBuf,
+    },
+}
+
+#[derive(StructOpt)]
+#[cfg(feature = "client")]
+enum DbCliOptions {
+    /// Query the underlying database
+    Query { prefix: String },
+    /// Delete bodies and rollback data of old blocks to save disk space
+    Prune {
+        #[structopt(long, parse(try_from_str = parse_block_count))]
+        keep_blocks: u64,
+    },
 }
 
 #[derive(StructOpt)]
-#[allow(clippy::large_enum_variant)]
 #[cfg(feature = "client")]
 #[structopt(name = "Bazuka!", about = "Node software for Ziesha Network")]
+struct Cli {
+    /// Log filter directives, e.g. `info` or `bazuka::node=debug` (Overrides RUST_LOG)
+    #[structopt(long, global = true)]
+    log_level: Option<String>,
+    /// Print logs as JSON objects
+    #[structopt(long, global = true)]
+    json_logs: bool,
+    /// Use the wallet with this name from the keystore instead of the wallet file. It
+    /// is decrypted in memory with the passphrase in BAZUKA_KEYSTORE_PASSPHRASE, or
+    /// the one asked for.
+    #[structopt(long, global = true)]
+    keystore: Option<String>,
+    #[structopt(subcommand)]
+    command: CliOptions,
+}
+
+#[derive(StructOpt)]
+#[allow(clippy::large_enum_variant)]
+#[cfg(feature = "client")]
 enum CliOptions {
     #[cfg(not(feature = "client"))]
     Init,
     #[cfg(feature = "client")]
     /// Initialize node/wallet
     Init {
-        #[structopt(long)]
+        #[structopt(long, env = "BAZUKA_BOOTSTRAP", use_delimiter = true)]
         bootstrap: Vec<PeerAddress>,
         #[structopt(long)]
         mnemonic: Option<bip39::Mnemonic>,
-        #[structopt(long)]
+        #[structopt(long, env = "BAZUKA_LISTEN")]
         listen: Option<SocketAddr>,
-        #[structopt(long)]
+        #[structopt(long, env = "BAZUKA_EXTERNAL")]
         external: Option<PeerAddress>,
-        #[structopt(long)]
+        #[structopt(long, env = "BAZUKA_DB")]
         db: Option<PathBuf>,
+        /// Use the public test network, its ports and data directory
+        #[structopt(long)]
+        testnet: bool,
     },
 
     #[cfg(feature = "node")]
     /// Node subcommand
     Node(NodeCliOptions),
 
+    #[cfg(feature = "node")]
+    /// Run in-process nodes on a test chain and report block propagation stats
+    Simulate {
+        #[structopt(long, default_value = "4")]
+        nodes: usize,
+        #[structopt(long, default_value = "10")]
+        blocks: usize,
+        #[structopt(long, default_value = "5")]
+        slot_duration: u32,
+    },
+
     /// Wallet subcommand
     Wallet(WalletOptions),
 
     /// Chain subcommand
     Chain(ChainCliOptions),
+
+    /// Database subcommand
+    Db(DbCliOptions),
+
+    /// Genesis subcommand
+    Genesis(GenesisCliOptions),
+
+    /// Print shell completions (bash, zsh, fish, powershell or elvish)
+    Completions {
+        #[structopt(possible_values = &structopt::clap::Shell::variants())]
+        shell: structopt::clap::Shell,
+    },
+
+    /// Print a man page covering all subcommands
+    Man {},
 }
 
 #[cfg(feature = "node")]
+#[allow(clippy::too_many_arguments)]
 async fn run_node<K: KvStore, B: Blockchain<K>>(
     blockchain: B,
     bazuka_config: BazukaConfig,
@@ -251,6 +622,10 @@ async fn run_node<K: KvStore, B: Blockchain<K>>(
     social_profiles: SocialProfiles,
     client_only: bool,
     network: String,
+    opts: NodeOptions,
+    grpc: Option<SocketAddr>,
+    #[cfg_attr(not(feature = "upnp"), allow(unused_variables))] upnp: bool,
+    libp2p: Option<SocketAddr>,
 ) -> Result<(), NodeError> {
     let address = if client_only {
         None
@@ -269,49 +644,150 @@ async fn run_node<K: KvStore, B: Blockchain<K>>(
         println!("{} {}", "Internet endpoint:".bright_yellow(), addr);
     }
     println!("{} {}", "Network:".bright_yellow(), network);
+    if let Some(addr) = &grpc {
+        println!("{} {}", "gRPC:".bright_yellow(), addr);
+    }
+    if let Some(addr) = &libp2p {
+        println!("{} {}", "libp2p:".bright_yellow(), addr);
+    }
 
     let (inc_send, inc_recv) = mpsc::unbounded_channel::<NodeRequest>();
-    let (out_send, mut out_recv) = mpsc::unbounded_channel::<NodeRequest>();
+    let (out_send, out_recv) = mpsc::unbounded_channel::<NodeRequest>();
+
+    // Peers are talked to over libp2p when it is enabled, and over HTTP otherwise
+    #[cfg(feature = "libp2p")]
+    let (transport, p2p_service) = match libp2p {
+        Some(listen) => {
+            let (transport, service) = transport::Libp2pTransport::new(
+                bazuka::config::node::get_libp2p_options(listen),
+                inc_send.clone(),
+            )?;
+            (
+                Arc::new(transport) as Arc<dyn transport::Transport>,
+                Some(service),
+            )
+        }
+        None => (
+            Arc::new(transport::HttpTransport) as Arc<dyn transport::Transport>,
+            None,
+        ),
+    };
+    #[cfg(not(feature = "libp2p"))]
+    let transport: Arc<dyn transport::Transport> = Arc::new(transport::HttpTransport);
 
     let bootstrap_nodes = bazuka_config.bootstrap.clone();
 
+    // Flipped to true once the node has stopped, so that the HTTP server and client
+    // loops can finish too.
+    let (stopped_send, stopped_recv) = watch::channel(false);
+
     // 60 request per minute / 4GB per 15min
     let firewall = Firewall::new(360, 4 * GB);
+    let connection_limiter = ConnectionLimiter::new(
+        bazuka_config
+            .runtime
+            .max_connections_per_ip
+            .unwrap_or(DEFAULT_MAX_CONNECTIONS_PER_IP),
+    );
 
     // Async loop that is responsible for answering external requests and gathering
     // data from external world through a heartbeat loop.
-    let node = node_create(
-        config::node::get_node_options(),
+    let node_inc_send = inc_send.clone();
+    let node = NodeBuilder::new(
+        opts,
         &network,
-        address,
-        bootstrap_nodes,
         blockchain,
-        0,
         wallet.clone().validator().tx_builder(),
-        wallet.clone().user(0).tx_builder(),
-        social_profiles,
-        inc_recv,
-        out_send,
-        Some(firewall),
+    )
+    .address(address)
+    .bootstrap(bootstrap_nodes)
+    .user_wallet(wallet.clone().user(0).tx_builder())
+    .social_profiles(social_profiles)
+    .firewall(firewall)
+    .mpn_workers(
         bazuka_config
             .mpn_workers
             .iter()
             .map(|w| w.clone().try_into().unwrap())
             .collect(),
-    );
+    )
+    .run(inc_recv, out_send);
+    let node = async {
+        let result = node.await;
+        let _ = stopped_send.send(true);
+        result
+    };
+
+    // Async loop that waits for SIGINT/SIGTERM and asks the node to shut down through
+    // its local API, so that the block being applied at the moment is never cut in half.
+    let signal_loop = async {
+        let mut stopped = stopped_recv.clone();
+        tokio::select! {
+            _ = termination_signal() => {
+                tracing::info!("Termination signal received, shutting down...");
+                systemd::notify_stopping();
+                local_request(&node_inc_send, "/shutdown", b"{}".to_vec()).await?;
+            }
+            _ = stopped.changed() => {}
+        }
+        Ok::<(), NodeError>(())
+    };
+
+    // Async loop that applies the runtime section of the config file on startup, and
+    // again every time SIGHUP is received.
+    let reload_loop = async {
+        let mut stopped = stopped_recv.clone();
+        apply_runtime_config(&node_inc_send, &bazuka_config.runtime).await?;
+        loop {
+            tokio::select! {
+                _ = hangup_signal() => {
+                    match read_config(&config_path()) {
+                        Ok(Some(conf)) => {
+                            tracing::info!("Reloading runtime configuration...");
+                            apply_runtime_config(&node_inc_send, &conf.runtime).await?;
+                            connection_limiter.set_limit(
+                                conf.runtime
+                                    .max_connections_per_ip
+                                    .unwrap_or(DEFAULT_MAX_CONNECTIONS_PER_IP),
+                            );
+                        }
+                        Ok(None) => tracing::warn!("No config file to reload!"),
+                        Err(e) => tracing::error!("Cannot reload config file: {}", e),
+                    }
+                }
+                _ = stopped.changed() => break,
+            }
+        }
+        Ok::<(), NodeError>(())
+    };
 
     // Async loop that is responsible for getting incoming HTTP requests through a
     // socket and redirecting it to the node channels.
     let server_loop = async {
         let arc_inc_send = Arc::new(inc_send);
-        Server::bind(&bazuka_config.listen)
+        let mut stopped = stopped_recv.clone();
+        let server = Server::bind(&bazuka_config.listen);
+        // The database is already open at this point
+        systemd::notify_ready();
+        server
             .serve(make_service_fn(|conn: &AddrStream| {
                 let client = conn.remote_addr();
                 let arc_inc_send = Arc::clone(&arc_inc_send);
+                // Held until the connection is closed
+                let guard = connection_limiter.acquire(client);
+                if guard.is_none() {
+                    tracing::warn!("{} -> Too many connections!", client);
+                }
                 async move {
                     Ok::<_, NodeError>(service_fn(move |req: Request<Body>| {
                         let arc_inc_send = Arc::clone(&arc_inc_send);
+                        let permitted = guard.is_some();
                         async move {
+                            if !permitted {
+                                let mut resp = Response::new(Body::default());
+                                *resp.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+                                return Ok::<Response<Body>, NodeError>(resp);
+                            }
                             let (resp_snd, mut resp_rcv) =
                                 mpsc::unbounded_channel::<Result<Response<Body>, NodeError>>();
                             let req = NodeRequest {
@@ -338,59 +814,302 @@ async fn run_node<K: KvStore, B: Blockchain<K>>(
                     }))
                 }
             }))
+            .with_graceful_shutdown(async move {
+                let _ = stopped.changed().await;
+            })
+            .await?;
+        Ok::<(), NodeError>(())
+    };
+
+    // Async loop that serves the gRPC API, which is answered by the node through the
+    // same channel as the HTTP server.
+    let grpc_loop = async {
+        #[cfg(feature = "grpc")]
+        if let Some(addr) = grpc {
+            let mut stopped = stopped_recv.clone();
+            bazuka::node::grpc::serve(addr, network.clone(), node_inc_send.clone(), async move {
+                let _ = stopped.changed().await;
+            })
             .await?;
+        }
+        Ok::<(), NodeError>(())
+    };
+
+    // Async loop that keeps the port of the node forwarded on the router, renewing the
+    // mapping before its lease expires and removing it once the node has stopped.
+    let upnp_loop = async {
+        #[cfg(feature = "upnp")]
+        if upnp && !client_only {
+            let mut stopped = stopped_recv.clone();
+            bazuka::node::upnp::serve(
+                bazuka::config::node::get_upnp_options(
+                    bazuka_config.listen.port(),
+                    bazuka_config.external.0.port(),
+                ),
+                async move {
+                    let _ = stopped.changed().await;
+                },
+            )
+            .await;
+        }
         Ok::<(), NodeError>(())
     };
 
     // Async loop that is responsible for redirecting node requests from its outgoing
     // channel to the Internet and piping back the responses.
     let client_loop = async {
-        while let Some(req) = out_recv.recv().await {
-            tokio::spawn(async move {
-                let resp = async {
-                    let client = Client::new();
-                    let resp = if let Some(time_limit) = req.limit.time {
-                        tokio::time::timeout(time_limit, client.request(req.body)).await?
-                    } else {
-                        client.request(req.body).await
-                    }?;
-                    Ok::<_, NodeError>(resp)
-                }
+        let mut stopped = stopped_recv.clone();
+        transport::relay(transport, out_recv, async move {
+            let _ = stopped.changed().await;
+        })
+        .await;
+        Ok::<(), NodeError>(())
+    };
+
+    // Async loop that carries the requests between the node and its peers over libp2p,
+    // when enabled instead of HTTP.
+    let p2p_loop = async {
+        #[cfg(feature = "libp2p")]
+        if let Some(service) = p2p_service {
+            let mut stopped = stopped_recv.clone();
+            service
+                .serve(async move {
+                    let _ = stopped.changed().await;
+                })
                 .await;
-                if let Err(e) = req.resp.send(resp) {
-                    log::debug!("Node not listening to its HTTP request answer: {}", e);
-                }
-            });
         }
         Ok::<(), NodeError>(())
     };
 
-    try_join!(server_loop, client_loop, node).unwrap();
+    try_join!(
+        server_loop,
+        grpc_loop,
+        upnp_loop,
+        client_loop,
+        p2p_loop,
+        signal_loop,
+        reload_loop,
+        node
+    )
+    .unwrap();
+    tracing::info!("Node stopped gracefully");
+
+    Ok(())
+}
+
+#[cfg(feature = "client")]
+type LogFilterReloader = Box<dyn Fn(EnvFilter) + Send + Sync>;
+
+/// Replaces the log filter of the global subscriber, not set when the log level is
+/// fixed through `--log-level`
+#[cfg(feature = "client")]
+static LOG_FILTER_RELOADER: OnceLock<LogFilterReloader> = OnceLock::new();
+
+#[cfg(feature = "client")]
+fn init_logging(log_level: Option<String>, json: bool) {
+    let fixed = log_level.is_some();
+    let filter = match log_level {
+        Some(directives) => EnvFilter::new(directives),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    };
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    let reloader: LogFilterReloader = if json {
+        let builder = builder.json().with_filter_reloading();
+        let handle = builder.reload_handle();
+        builder.init();
+        Box::new(move |f| {
+            let _ = handle.reload(f);
+        })
+    } else {
+        let builder = builder.with_filter_reloading();
+        let handle = builder.reload_handle();
+        builder.init();
+        Box::new(move |f| {
+            let _ = handle.reload(f);
+        })
+    };
+    if !fixed {
+        let _ = LOG_FILTER_RELOADER.set(reloader);
+    }
+}
 
+#[cfg(feature = "node")]
+async fn local_request(
+    chan: &mpsc::UnboundedSender<NodeRequest>,
+    uri: &str,
+    body: Vec<u8>,
+) -> Result<(), NodeError> {
+    let (resp_snd, mut resp_rcv) = mpsc::unbounded_channel::<Result<Response<Body>, NodeError>>();
+    chan.send(NodeRequest {
+        limit: Limit::default(),
+        socket_addr: None,
+        body: Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .body(Body::from(body))?,
+        resp: resp_snd,
+    })
+    .map_err(|_| NodeError::NotListeningError)?;
+    resp_rcv.recv().await;
     Ok(())
 }
 
+#[cfg(feature = "node")]
+async fn apply_runtime_config(
+    chan: &mpsc::UnboundedSender<NodeRequest>,
+    runtime: &BazukaRuntimeConfig,
+) -> Result<(), NodeError> {
+    if let Some(log_level) = &runtime.log_level {
+        if let Some(reload) = LOG_FILTER_RELOADER.get() {
+            reload(EnvFilter::new(log_level));
+        }
+    }
+    let req = UpdateConfigRequest {
+        num_peers: runtime.num_peers,
+        request_limit_per_minute: runtime.request_limit_per_minute,
+        traffic_limit_per_15m: runtime.traffic_limit_per_15m,
+        max_body_size: runtime.max_body_size,
+        max_block_txs: runtime.max_block_txs,
+        min_fee: runtime.min_fee,
+        punishments: runtime.punishments.clone(),
+        max_punish: runtime.max_punish,
+    };
+    local_request(chan, "/config", serde_json::to_vec(&req)?).await
+}
+
+#[cfg(feature = "node")]
+async fn hangup_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        signal(SignalKind::hangup())
+            .expect("cannot listen to SIGHUP")
+            .recv()
+            .await;
+    }
+    #[cfg(not(unix))]
+    {
+        futures::future::pending::<()>().await;
+    }
+}
+
+#[cfg(feature = "node")]
+async fn termination_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = signal(SignalKind::terminate()).expect("cannot listen to SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = sigterm.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
 pub async fn initialize_cli() {
-    let opts = CliOptions::from_args();
+    let cli = Cli::from_args();
+    init_logging(cli.log_level, cli.json_logs);
+    let opts = cli.command;
 
-    let conf_path = home::home_dir().unwrap().join(Path::new(".bazuka.yaml"));
-    let conf: Option<BazukaConfig> = std::fs::File::open(conf_path.clone())
-        .ok()
-        .map(|f| serde_yaml::from_reader(f).unwrap());
-    let wallet_path = home::home_dir().unwrap().join(Path::new(".bazuka-wallet"));
-    let wallet = WalletCollection::open(wallet_path.clone()).unwrap();
+    let conf_path = config_path();
+    let conf: Option<BazukaConfig> = read_config(&conf_path).unwrap();
+    let wallet_path = env_var::<PathBuf>("BAZUKA_WALLET")
+        .unwrap_or_else(|| home::home_dir().unwrap().join(Path::new(".bazuka-wallet")));
+    let keystore_path = env_var::<PathBuf>("BAZUKA_KEYSTORE").unwrap_or_else(|| {
+        home::home_dir()
+            .unwrap()
+            .join(Path::new(".bazuka"))
+            .join("keystore")
+    });
+    let wallet_file = match cli.keystore {
+        Some(name) => crate::cli::wallet::WalletFile::keystore(
+            &keystore_path,
+            name,
+            env_var("BAZUKA_KEYSTORE_PASSPHRASE"),
+        )
+        .unwrap(),
+        None => crate::cli::wallet::WalletFile::Plain(wallet_path),
+    };
+    let wallet = wallet_file.open().unwrap();
 
     match opts {
         CliOptions::Chain(chain_opts) => match chain_opts {
+            ChainCliOptions::Info {} => {
+                crate::cli::chain::info(&conf.expect(BAZUKA_NOT_INITILIZED));
+            }
             ChainCliOptions::Rollback {} => {
                 crate::cli::chain::rollback(&conf.expect(BAZUKA_NOT_INITILIZED)).await;
             }
-            ChainCliOptions::DbQuery { prefix } => {
-                crate::cli::chain::db_query(prefix, &conf.expect(BAZUKA_NOT_INITILIZED));
-            }
             ChainCliOptions::HealthCheck {} => {
                 crate::cli::chain::health_check(&conf.expect(BAZUKA_NOT_INITILIZED));
             }
+            ChainCliOptions::Verify {} => {
+                crate::cli::chain::verify(&conf.expect(BAZUKA_NOT_INITILIZED));
+            }
+            ChainCliOptions::Replay { log, verbose } => {
+                crate::cli::chain::replay(log, verbose, &conf.expect(BAZUKA_NOT_INITILIZED));
+            }
+            ChainCliOptions::ExportAnalytics {
+                output,
+                format,
+                from,
+                to,
+            } => {
+                crate::cli::chain::export_analytics(
+                    output,
+                    format,
+                    from,
+                    to,
+                    &conf.expect(BAZUKA_NOT_INITILIZED),
+                );
+            }
+            ChainCliOptions::Inspect { json, target } => {
+                crate::cli::chain::inspect(
+                    match target {
+                        InspectCliOptions::Block { index } => chain::InspectTarget::Block(index),
+                        InspectCliOptions::Header { index } => chain::InspectTarget::Header(index),
+                        InspectCliOptions::Account { address } => {
+                            chain::InspectTarget::Account(address)
+                        }
+                        InspectCliOptions::Contract { contract_id } => {
+                            chain::InspectTarget::Contract(contract_id)
+                        }
+                    },
+                    json,
+                    &conf.expect(BAZUKA_NOT_INITILIZED),
+                );
+            }
+        },
+        #[cfg(feature = "node")]
+        CliOptions::Simulate {
+            nodes,
+            blocks,
+            slot_duration,
+        } => {
+            crate::cli::simulate::simulate(nodes, blocks, slot_duration).await;
+        }
+        CliOptions::Completions { shell } => {
+            crate::cli::completions::completions(shell);
+        }
+        CliOptions::Man {} => {
+            crate::cli::completions::man();
+        }
+        CliOptions::Genesis(genesis_opts) => match genesis_opts {
+            GenesisCliOptions::New { spec, output } => {
+                crate::cli::genesis::new(spec, &output);
+            }
+        },
+        CliOptions::Db(db_opts) => match db_opts {
+            DbCliOptions::Query { prefix } => {
+                crate::cli::db::query(prefix, &conf.expect(BAZUKA_NOT_INITILIZED));
+            }
+            DbCliOptions::Prune { keep_blocks } => {
+                crate::cli::db::prune(keep_blocks, &conf.expect(BAZUKA_NOT_INITILIZED));
+            }
         },
         #[cfg(feature = "node")]
         CliOptions::Node(node_opts) => match node_opts {
@@ -400,6 +1119,21 @@ pub async fn initialize_cli() {
                 dev,
                 ram,
                 small_mpn,
+                regtest,
+                genesis,
+                prune,
+                rollback_retention,
+                no_address_index,
+                replay_log,
+                snapshot_sync,
+                status_interval,
+                testnet,
+                faucet,
+                explorer,
+                grpc,
+                zmq,
+                upnp,
+                libp2p,
             } => {
                 crate::cli::node::start(
                     discord_handle,
@@ -409,6 +1143,21 @@ pub async fn initialize_cli() {
                     ram,
                     dev,
                     small_mpn,
+                    regtest,
+                    genesis,
+                    prune,
+                    rollback_retention,
+                    no_address_index,
+                    replay_log,
+                    snapshot_sync,
+                    status_interval,
+                    testnet,
+                    faucet,
+                    explorer,
+                    grpc,
+                    zmq,
+                    upnp,
+                    libp2p,
                 )
                 .await;
             }
@@ -435,6 +1184,7 @@ pub async fn initialize_cli() {
             external,
             listen,
             db,
+            testnet,
         } => {
             crate::cli::init(
                 bootstrap,
@@ -442,10 +1192,11 @@ pub async fn initialize_cli() {
                 external,
                 listen,
                 db,
+                testnet,
                 conf,
                 &conf_path,
                 wallet,
-                &wallet_path,
+                &wallet_file,
             )
             .await
         }
@@ -458,7 +1209,7 @@ pub async fn initialize_cli() {
                 crate::cli::wallet::add_token(
                     id,
                     wallet.expect(BAZUKA_NOT_INITILIZED),
-                    &wallet_path,
+                    &wallet_file,
                 );
             }
             WalletOptions::NewToken {
@@ -480,7 +1231,7 @@ pub async fn initialize_cli() {
                     fee,
                     conf.expect(BAZUKA_NOT_INITILIZED),
                     wallet.expect(BAZUKA_NOT_INITILIZED),
-                    &wallet_path,
+                    &wallet_file,
                 )
                 .await;
             }
@@ -491,6 +1242,7 @@ pub async fn initialize_cli() {
                 amount,
                 fee,
                 token_id,
+                label,
             } => {
                 crate::cli::wallet::send(
                     memo,
@@ -499,14 +1251,34 @@ pub async fn initialize_cli() {
                     amount,
                     fee,
                     token_id,
+                    label,
                     conf.expect(BAZUKA_NOT_INITILIZED),
                     wallet.expect(BAZUKA_NOT_INITILIZED),
-                    &wallet_path,
+                    &wallet_file,
                 )
                 .await;
             }
+            WalletOptions::Label { tx, label } => {
+                crate::cli::wallet::label(&wallet_file, tx, label);
+            }
+            WalletOptions::ExcludeInflow {
+                tx,
+                amount,
+                token_id,
+            } => {
+                crate::cli::wallet::exclude_inflow(&wallet_file, tx, amount, token_id);
+            }
+            WalletOptions::IncludeInflow { tx } => {
+                crate::cli::wallet::include_inflow(&wallet_file, tx);
+            }
+            WalletOptions::Init { mnemonic } => {
+                crate::cli::wallet::init(mnemonic, wallet, &wallet_file);
+            }
+            WalletOptions::Backup {} => {
+                crate::cli::wallet::backup(wallet.expect(BAZUKA_NOT_INITILIZED));
+            }
             WalletOptions::Reset {} => {
-                crate::cli::wallet::reset(wallet.expect(BAZUKA_NOT_INITILIZED), &wallet_path);
+                crate::cli::wallet::reset(wallet.expect(BAZUKA_NOT_INITILIZED), &wallet_file);
             }
             WalletOptions::RegisterValidator {
                 memo,
@@ -519,7 +1291,7 @@ pub async fn initialize_cli() {
                     fee,
                     conf.expect(BAZUKA_NOT_INITILIZED),
                     wallet.expect(BAZUKA_NOT_INITILIZED),
-                    &wallet_path,
+                    &wallet_file,
                 )
                 .await;
             }
@@ -532,7 +1304,7 @@ pub async fn initialize_cli() {
                 crate::cli::wallet::undelegate(
                     conf.expect(BAZUKA_NOT_INITILIZED),
                     wallet.expect(BAZUKA_NOT_INITILIZED),
-                    &wallet_path,
+                    &wallet_file,
                     memo,
                     amount,
                     from,
@@ -549,7 +1321,7 @@ pub async fn initialize_cli() {
                 crate::cli::wallet::auto_delegate(
                     conf.expect(BAZUKA_NOT_INITILIZED),
                     wallet.expect(BAZUKA_NOT_INITILIZED),
-                    &wallet_path,
+                    &wallet_file,
                     memo,
                     to,
                     ratio.try_into().unwrap(),
@@ -566,7 +1338,7 @@ pub async fn initialize_cli() {
                 crate::cli::wallet::delegate(
                     conf.expect(BAZUKA_NOT_INITILIZED),
                     wallet.expect(BAZUKA_NOT_INITILIZED),
-                    &wallet_path,
+                    &wallet_file,
                     memo,
                     amount,
                     to,
@@ -578,18 +1350,34 @@ pub async fn initialize_cli() {
                 crate::cli::wallet::resend_pending(
                     conf.expect(BAZUKA_NOT_INITILIZED),
                     wallet.expect(BAZUKA_NOT_INITILIZED),
-                    &wallet_path,
+                    &wallet_file,
                 )
                 .await;
             }
-            WalletOptions::Info { validator } => {
+            WalletOptions::Info {
+                validator,
+                accounts,
+            } => {
                 crate::cli::wallet::info(
                     conf.expect(BAZUKA_NOT_INITILIZED),
                     wallet.expect(BAZUKA_NOT_INITILIZED),
+                    &wallet_file,
                     validator,
+                    accounts,
                 )
                 .await;
             }
+            WalletOptions::KeystoreSave { name, overwrite } => {
+                crate::cli::wallet::keystore_save(
+                    name,
+                    wallet.expect(BAZUKA_NOT_INITILIZED),
+                    &keystore_path,
+                    overwrite,
+                );
+            }
+            WalletOptions::KeystoreList {} => {
+                crate::cli::wallet::keystore_list(&keystore_path);
+            }
         },
     }
 }
@@ -1,10 +1,23 @@
-use crate::cli::{run_node, BazukaConfig, CURRENT_NETWORK};
+use crate::cli::{datadir::DataDir, run_node, BazukaConfig, CURRENT_NETWORK};
 
 use bazuka::{
-    blockchain::KvStoreChain, client::messages::SocialProfiles, config, db::LevelDbKvStore,
-    db::RamKvStore, wallet::WalletCollection,
+    blockchain::{Blockchain, BlockchainError, KvStoreChain, ReplayLog},
+    client::messages::SocialProfiles,
+    config,
+    db::LevelDbKvStore,
+    db::RamKvStore,
+    node::NodeOptions,
+    utils::local_timestamp,
+    wallet::WalletCollection,
 };
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
+const PRIVATE_NETWORK: &str = "private";
+const TESTNET_NETWORK: &str = "testnet";
+
+#[allow(clippy::too_many_arguments)]
 pub async fn start(
     discord_handle: Option<String>,
     client_only: bool,
@@ -13,16 +26,113 @@ pub async fn start(
     ram: bool,
     dev: bool,
     small_mpn: bool,
+    regtest: bool,
+    genesis: Option<PathBuf>,
+    prune: Option<u64>,
+    rollback_retention: Option<u64>,
+    no_address_index: bool,
+    replay_log: bool,
+    snapshot_sync: bool,
+    status_interval: Option<u64>,
+    testnet: bool,
+    faucet: bool,
+    explorer: bool,
+    grpc: Option<SocketAddr>,
+    zmq: Option<String>,
+    upnp: bool,
+    libp2p: Option<SocketAddr>,
 ) {
-    let blockchain_conf = if dev {
+    let testnet = testnet || conf.testnet;
+    if faucet && !testnet {
+        panic!("Faucet can only be run on the testnet!");
+    }
+    if explorer && !cfg!(feature = "explorer") {
+        panic!("Bazuka was built without the `explorer` feature!");
+    }
+    if grpc.is_some() && !cfg!(feature = "grpc") {
+        panic!("Bazuka was built without the `grpc` feature!");
+    }
+    if zmq.is_some() && !cfg!(feature = "zmq") {
+        panic!("Bazuka was built without the `zmq` feature!");
+    }
+    if upnp && !cfg!(feature = "upnp") {
+        panic!("Bazuka was built without the `upnp` feature!");
+    }
+    if libp2p.is_some() && !cfg!(feature = "libp2p") {
+        panic!("Bazuka was built without the `libp2p` feature!");
+    }
+    // Faucet coins are sent from the first user account of the operator, which
+    // should be the one funded in the testnet genesis block
+    let faucet_wallet = wallet.user(0).tx_builder();
+    if faucet && faucet_wallet.get_address().to_string() != config::blockchain::TESTNET_FAUCET {
+        tracing::warn!(
+            "Faucet wallet {} is not the testnet faucet {}!",
+            faucet_wallet.get_address(),
+            config::blockchain::TESTNET_FAUCET
+        );
+    }
+    // Explorer index and metrics are kept in memory when the chain itself is
+    let node_options = |mut opts: NodeOptions, datadir: Option<&DataDir>| {
+        if let Some(secs) = status_interval {
+            opts.heartbeat_intervals.log_info = Duration::from_secs(secs);
+        }
+        if faucet {
+            opts.faucet = Some(config::node::get_testnet_faucet_options(
+                faucet_wallet.clone(),
+            ));
+        }
+        // Transactions are gossiped over libp2p, instead of being pulled by the peers
+        opts.relay_txs = libp2p.is_some();
+        opts.webhooks = conf.webhooks.clone();
+        opts.telemetry = conf
+            .telemetry
+            .clone()
+            .map(config::node::get_telemetry_options);
+        opts.cluster = conf.cluster.clone();
+        opts.access_list = conf.access_list.clone();
+        if let Some(policy) = &conf.contract_policy {
+            opts.mempool_contract_policy = policy.clone();
+        }
+        opts.metrics = Some(config::node::get_metrics_options(
+            datadir.map(DataDir::metrics_db),
+        ));
+        opts.mempool_db = datadir.map(DataDir::mempool_db);
+        opts.ban_db = datadir.map(DataDir::ban_db);
+        #[cfg(feature = "zmq")]
+        {
+            opts.zmq = zmq.clone();
+        }
+        #[cfg(feature = "explorer")]
+        if explorer {
+            opts.explorer = Some(config::node::get_explorer_options(
+                datadir.map(DataDir::explorer_db),
+            ));
+        }
+        opts
+    };
+
+    let blockchain_conf = if regtest {
+        let validator_wallet = wallet.validator().tx_builder();
+        let user_wallet = wallet.user(0).tx_builder();
+        config::blockchain::get_regtest_blockchain_config(
+            &validator_wallet,
+            &user_wallet,
+            Default::default(),
+        )
+    } else if dev {
         let validator_wallet = wallet.validator().tx_builder();
         let user_wallet = wallet.user(0).tx_builder();
         config::blockchain::get_dev_blockchain_config(&validator_wallet, &user_wallet, small_mpn)
+    } else if testnet {
+        config::blockchain::get_testnet_blockchain_config()
+    } else if let Some(genesis) = &genesis {
+        let genesis = bincode::deserialize(&std::fs::read(genesis).unwrap()).unwrap();
+        config::blockchain::get_custom_blockchain_config(genesis)
     } else {
         config::blockchain::get_blockchain_config()
     };
 
-    if ram {
+    if regtest {
         run_node(
             KvStoreChain::new(RamKvStore::new(), blockchain_conf).unwrap(),
             conf.clone(),
@@ -30,23 +140,106 @@ pub async fn start(
             SocialProfiles {
                 discord: discord_handle,
             },
+            true,
+            "regtest".into(),
+            node_options(config::node::get_regtest_options(), None),
+            grpc,
+            upnp,
+            libp2p,
+        )
+        .await
+        .unwrap();
+    } else if ram {
+        run_node(
+            KvStoreChain::new(RamKvStore::new(), blockchain_conf)
+                .unwrap()
+                .with_address_index(!no_address_index),
+            conf.clone(),
+            wallet.clone(),
+            SocialProfiles {
+                discord: discord_handle,
+            },
             client_only,
             "dev".into(),
+            node_options(config::node::get_node_options(), None),
+            grpc,
+            upnp,
+            libp2p,
         )
         .await
         .unwrap();
     } else {
+        let datadir = DataDir::lock(&conf.db).unwrap();
+        let dirty = datadir.mark_dirty().unwrap();
+        let restart = Some(datadir.record_start(local_timestamp(), dirty).unwrap());
+        let mut chain = LevelDbKvStore::new(&datadir.db(), 64)
+            .map_err(BlockchainError::from)
+            .and_then(|db| KvStoreChain::new(db, blockchain_conf))
+            .map(|chain| chain.with_address_index(!no_address_index))
+            .unwrap_or_else(|e| {
+                panic!(
+                    "Cannot open the blockchain database at {:?} ({}), remove it to resync!",
+                    datadir.db(),
+                    e
+                )
+            });
+        if replay_log {
+            let log = ReplayLog::open(datadir.replay_log()).unwrap_or_else(|e| {
+                panic!(
+                    "Cannot open the replay log at {:?} ({})!",
+                    datadir.replay_log(),
+                    e
+                )
+            });
+            chain = chain.with_replay_log(log);
+        }
+        if dirty {
+            tracing::warn!("Node was not shut down cleanly, checking the database...");
+            match chain.recover() {
+                Ok(0) => tracing::info!("Database is consistent"),
+                Ok(n) => tracing::warn!(
+                    "Recovered by rolling back {} block(s), height is now {}",
+                    n,
+                    chain.get_height().unwrap()
+                ),
+                Err(e) => panic!(
+                    "Cannot recover the blockchain database at {:?} ({}), remove it to resync!",
+                    datadir.db(),
+                    e
+                ),
+            }
+        }
         run_node(
-            KvStoreChain::new(LevelDbKvStore::new(&conf.db, 64).unwrap(), blockchain_conf).unwrap(),
+            chain,
             conf.clone(),
             wallet.clone(),
             SocialProfiles {
                 discord: discord_handle,
             },
             client_only,
-            CURRENT_NETWORK.into(),
+            if testnet {
+                TESTNET_NETWORK.into()
+            } else if genesis.is_some() {
+                PRIVATE_NETWORK.into()
+            } else {
+                CURRENT_NETWORK.into()
+            },
+            node_options(
+                NodeOptions {
+                    prune,
+                    rollback_retention,
+                    snapshot_sync,
+                    restart,
+                    ..config::node::get_node_options()
+                },
+                Some(&datadir),
+            ),
+            grpc,
+            upnp,
+            libp2p,
         )
         .await
         .unwrap();
+        datadir.mark_clean().unwrap();
     }
 }
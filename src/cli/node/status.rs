@@ -5,8 +5,10 @@ use crate::cli::{BazukaConfig, CURRENT_NETWORK};
 use bazuka::{
     client::{BazukaClient, Limit, NodeError},
     common::*,
+    core::TokenId,
     wallet::WalletCollection,
 };
+use colored::Colorize;
 
 pub async fn status(conf: BazukaConfig, mut wallet: WalletCollection) {
     let wallet = wallet.user(0).tx_builder();
@@ -18,7 +20,39 @@ pub async fn status(conf: BazukaConfig, mut wallet: WalletCollection) {
     );
     try_join!(
         async move {
-            println!("{:#?}", client.stats().await?);
+            let stats = client.stats().await?;
+            let balance = client
+                .get_balance(wallet.get_address(), TokenId::Ziesha)
+                .await?
+                .balance;
+
+            let target = std::cmp::max(stats.best_peer_height, stats.height);
+            let progress = stats.height as f64 / target.max(1) as f64 * 100.0;
+
+            println!("{}\t{}", "Network:".bright_yellow(), stats.network);
+            println!("{}\t{}", "Version:".bright_yellow(), stats.version);
+            println!("{}\t{}", "Height:".bright_yellow(), stats.height);
+            println!("{}\t{}", "Peers:".bright_yellow(), stats.nodes);
+            println!(
+                "{}\t{:.2}% ({}/{})",
+                "Sync progress:".bright_yellow(),
+                progress,
+                stats.height,
+                target
+            );
+            println!(
+                "{}\t{}",
+                "Mempool size:".bright_yellow(),
+                stats.mempool_size
+            );
+            println!(
+                "{}\t{}{}",
+                "Wallet balance:".bright_yellow(),
+                balance.display_by_decimals(bazuka::config::UNIT_ZEROS),
+                bazuka::config::SYMBOL
+            );
+            println!();
+            println!("{:#?}", stats);
             Ok::<(), NodeError>(())
         },
         req_loop
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bazuka::client::{BazukaClient, NodeError};
+use bazuka::config::blockchain::{get_regtest_blockchain_config, ConsensusParams};
+use bazuka::core::{Amount, Money, Ratio, Signature, Transaction, TransactionData};
+use bazuka::node::simulation::{test_network, NodeOpts};
+use bazuka::wallet::TxBuilder;
+use colored::Colorize;
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+
+const FIRST_ADDR: u16 = 100;
+const PROPAGATION_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+fn validator_seed(index: usize) -> Vec<u8> {
+    Vec::from(format!("VALIDATOR{}", index))
+}
+
+async fn heights(clients: &[BazukaClient]) -> Result<Vec<u64>, NodeError> {
+    let mut heights = Vec::new();
+    for client in clients.iter() {
+        heights.push(client.stats().await?.height);
+    }
+    Ok(heights)
+}
+
+/// Runs `nodes` in-process nodes connected in a chain, asks them to produce `blocks`
+/// blocks in turns and reports how fast each block reached all the other nodes.
+pub async fn simulate(nodes: usize, blocks: usize, slot_duration: u32) {
+    assert!(
+        nodes > 0 && nodes <= 100,
+        "Number of nodes should be in 1..=100!"
+    );
+    // Regtest chains do not check validator proofs, so any registered staker can
+    // produce blocks
+    let mut conf = get_regtest_blockchain_config(
+        &TxBuilder::new(&validator_seed(0)),
+        &TxBuilder::new(&Vec::from("USER")),
+        ConsensusParams {
+            slot_duration,
+            ..Default::default()
+        },
+    );
+    for i in 1..nodes {
+        let validator = TxBuilder::new(&validator_seed(i));
+        conf.genesis.body.push(Transaction {
+            memo: "Simulated staker".into(),
+            src: Some(validator.get_address()),
+            data: TransactionData::UpdateStaker {
+                vrf_pub_key: validator.get_vrf_public_key(),
+                commission: Ratio(12),
+            },
+            nonce: 0,
+            fee: Money::ziesha(0),
+            sig: Signature::Unsigned,
+        });
+        conf.genesis.body.push(Transaction {
+            memo: "Simulated delegation".into(),
+            src: None,
+            data: TransactionData::Delegate {
+                to: validator.get_address(),
+                amount: Amount(1000000000000),
+            },
+            nonce: 0,
+            fee: Money::ziesha(0),
+            sig: Signature::Unsigned,
+        });
+    }
+
+    let rules = Arc::new(RwLock::new(Vec::new()));
+    let (node_futs, route_futs, clients) = test_network(
+        rules,
+        (0..nodes)
+            .map(|i| NodeOpts {
+                config: conf.clone(),
+                wallet: TxBuilder::new(&validator_seed(i)),
+                addr: FIRST_ADDR + i as u16,
+                bootstrap: if i == 0 {
+                    vec![]
+                } else {
+                    vec![FIRST_ADDR + i as u16 - 1]
+                },
+                timestamp_offset: 0,
+                auto_gen_block: false,
+                mpn_workers: vec![],
+                contract_policy: Default::default(),
+            })
+            .collect(),
+    );
+
+    let logic = async {
+        // Give nodes some time to discover each other
+        sleep(Duration::from_secs(2)).await;
+
+        let mut produced = 0;
+        let mut propagation_times = Vec::new();
+        let mut timeouts = 0;
+        for i in 0..blocks {
+            let producer = &clients[i % nodes];
+            let generated = producer.generate(1).await?.generated;
+            let number = match generated.first() {
+                Some(number) => *number,
+                None => {
+                    println!("Node #{} could not produce a block!", i % nodes);
+                    continue;
+                }
+            };
+            produced += 1;
+            let start = Instant::now();
+            loop {
+                if heights(&clients).await?.iter().all(|h| *h > number) {
+                    let elapsed = start.elapsed();
+                    propagation_times.push(elapsed);
+                    println!(
+                        "Block #{} produced by node #{} reached all nodes in {:?}",
+                        number,
+                        i % nodes,
+                        elapsed
+                    );
+                    break;
+                }
+                if start.elapsed() > PROPAGATION_TIMEOUT {
+                    timeouts += 1;
+                    println!(
+                        "Block #{} produced by node #{} did not reach all nodes!",
+                        number,
+                        i % nodes
+                    );
+                    break;
+                }
+                sleep(POLL_INTERVAL).await;
+            }
+        }
+
+        let heights = heights(&clients).await?;
+        let mut tips = Vec::new();
+        for (client, height) in clients.iter().zip(heights.iter()) {
+            tips.push(client.get_headers(height - 1, 1).await?.headers[0].hash());
+        }
+        let consensus = tips.windows(2).all(|w| w[0] == w[1]);
+
+        println!();
+        println!("{}", "Simulation\n---------".bright_green());
+        println!("{}\t{}", "Nodes:".bright_yellow(), nodes);
+        println!("{}\t{}", "Blocks produced:".bright_yellow(), produced);
+        println!("{}\t{:?}", "Heights:".bright_yellow(), heights);
+        if !propagation_times.is_empty() {
+            let total: Duration = propagation_times.iter().sum();
+            println!(
+                "{}\t{:?}",
+                "Average propagation:".bright_yellow(),
+                total / propagation_times.len() as u32
+            );
+            println!(
+                "{}\t{:?}",
+                "Max propagation:".bright_yellow(),
+                propagation_times.iter().max().unwrap()
+            );
+        }
+        println!("{}\t{}", "Propagation timeouts:".bright_yellow(), timeouts);
+        println!(
+            "{}\t{}",
+            "Consensus:".bright_yellow(),
+            if consensus {
+                "all nodes agree on the tip".bright_green()
+            } else {
+                "nodes disagree on the tip!".bright_red()
+            }
+        );
+
+        for client in clients.iter() {
+            client.shutdown().await?;
+        }
+        Ok::<(), NodeError>(())
+    };
+
+    tokio::try_join!(node_futs, route_futs, logic).unwrap();
+}
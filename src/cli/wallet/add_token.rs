@@ -1,8 +1,8 @@
-use std::path::PathBuf;
+use super::WalletFile;
 
 use bazuka::{core::TokenId, wallet::WalletCollection};
 
-pub fn add_token(token_id: TokenId, mut wallet: WalletCollection, wallet_path: &PathBuf) -> () {
+pub fn add_token(token_id: TokenId, mut wallet: WalletCollection, wallet_file: &WalletFile) -> () {
     wallet.user(0).add_token(token_id);
-    wallet.save(wallet_path).unwrap();
+    wallet_file.save(&wallet).unwrap();
 }
@@ -0,0 +1,16 @@
+use bazuka::wallet::WalletCollection;
+use colored::Colorize;
+
+pub fn backup(wallet: WalletCollection) {
+    println!(
+        "{} {}",
+        "Mnemonic phrase:".bright_yellow(),
+        wallet.mnemonic()
+    );
+    println!(
+        "{}",
+        "Anyone with this phrase can spend your funds, keep it secret! \
+        Restore the wallet with `bazuka wallet init --mnemonic \"<phrase>\"`."
+            .italic()
+    );
+}
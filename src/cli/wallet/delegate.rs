@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use super::WalletFile;
 use tokio::try_join;
 
 use crate::cli::{BazukaConfig, CURRENT_NETWORK};
@@ -12,7 +12,7 @@ use colored::Colorize;
 pub async fn delegate(
     conf: BazukaConfig,
     mut wallet: WalletCollection,
-    wallet_path: &PathBuf,
+    wallet_file: &WalletFile,
     memo: Option<String>,
     amount: Decimal,
     to: Address,
@@ -57,7 +57,7 @@ pub async fn delegate(
                 println!("Error: {}", err);
             } else {
                 wallet.user(0).add_tx(tx.clone().into());
-                wallet.save(wallet_path).unwrap();
+                wallet_file.save(&wallet).unwrap();
                 println!("Sent");
             }
             Ok::<(), NodeError>(())
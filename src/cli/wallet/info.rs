@@ -1,17 +1,25 @@
+use super::WalletFile;
 use tokio::try_join;
 
 use crate::cli::{BazukaConfig, CURRENT_NETWORK};
 use bazuka::client::{Limit, NodeError};
 use bazuka::common::*;
 use bazuka::core::{MpnAddress, NonceGroup};
-use bazuka::wallet::WalletCollection;
+use bazuka::wallet::{WalletCollection, WalletMetadata};
 use bazuka::{client::BazukaClient, core::TokenId};
 use colored::Colorize;
 use std::collections::HashMap;
 
-pub async fn info(conf: BazukaConfig, mut wallet: WalletCollection, validator: bool) -> () {
+pub async fn info(
+    conf: BazukaConfig,
+    mut wallet: WalletCollection,
+    wallet_file: &WalletFile,
+    validator: bool,
+    accounts: usize,
+) -> () {
     let val_tx_builder = wallet.validator().tx_builder();
     let tx_builder = wallet.user(0).tx_builder();
+    let meta = WalletMetadata::open(wallet_file.metadata_path().unwrap()).unwrap();
 
     let (req_loop, client) = BazukaClient::connect(
         tx_builder.get_priv_key(),
@@ -182,6 +190,55 @@ pub async fn info(conf: BazukaConfig, mut wallet: WalletCollection, validator: b
                     }
                 }
 
+                if meta.labels().next().is_some() {
+                    println!();
+                    println!("{}", "Labels\n---------".bright_green());
+                    for (tx, label) in meta.labels() {
+                        println!("{}\t{}", format!("{}:", tx).bright_yellow(), label);
+                    }
+                }
+
+                if meta.excluded().next().is_some() {
+                    println!();
+                    println!("{}", "Excluded inflows\n---------".bright_green());
+                    for (tx, inflow) in meta.excluded() {
+                        println!(
+                            "{}\t{} (Token-Id: {})",
+                            format!("{}:", tx).bright_yellow(),
+                            tokens
+                                .get(&inflow.token_id)
+                                .map(|t| inflow
+                                    .amount
+                                    .to_amount(t.decimals)
+                                    .display_by_decimals(t.decimals))
+                                .unwrap_or("N/A".to_string()),
+                            inflow.token_id
+                        );
+                    }
+                }
+
+                if accounts > 1 {
+                    let addrs = wallet.derive_addresses(accounts);
+                    let resp = client.get_balances(&addrs, TokenId::Ziesha).await?;
+                    println!();
+                    println!("{}", "Derived accounts\n---------".bright_green());
+                    for (i, (addr, balance)) in addrs.iter().zip(resp.balances).enumerate() {
+                        println!(
+                            "{}\t{}\t{}{}",
+                            format!("#{}:", i).bright_yellow(),
+                            addr,
+                            balance.display_by_decimals(bazuka::config::UNIT_ZEROS),
+                            bazuka::config::SYMBOL
+                        );
+                    }
+                    println!(
+                        "{}\t{}{}",
+                        "Total:".bright_yellow(),
+                        resp.total.display_by_decimals(bazuka::config::UNIT_ZEROS),
+                        bazuka::config::SYMBOL
+                    );
+                }
+
                 println!();
 
                 let mpn_address = MpnAddress {
@@ -0,0 +1,35 @@
+use super::WalletFile;
+
+use bazuka::wallet::WalletCollection;
+use bip39::Mnemonic;
+use colored::Colorize;
+
+pub fn init(
+    mnemonic: Option<Mnemonic>,
+    wallet: Option<WalletCollection>,
+    wallet_file: &WalletFile,
+) {
+    if wallet.is_none() {
+        if let Some(mnemonic) = mnemonic {
+            let w = WalletCollection::from_mnemonic(&mnemonic.to_string()).unwrap();
+            wallet_file.save(&w).unwrap();
+            println!("Wallet restored from the mnemonic phrase!");
+            return;
+        }
+        // Seed is taken directly from the operating system's CSPRNG
+        let mut rng = rand_mnemonic::rngs::OsRng::new().expect("OS randomness unavailable!");
+        let w = WalletCollection::generate(&mut rng);
+        wallet_file.save(&w).unwrap();
+        println!("Wallet generated!");
+        println!("{} {}", "Mnemonic phrase:".bright_yellow(), w.mnemonic());
+        println!(
+            "{}",
+            "WRITE DOWN YOUR MNEMONIC PHRASE IN A SAFE PLACE!"
+                .italic()
+                .bold()
+                .bright_green()
+        );
+    } else {
+        println!("Wallet is already initialized!");
+    }
+}
@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use bazuka::wallet::{Keystore, WalletCollection, WalletError, WalletMetadata};
+
+fn read_passphrase(prompt: &str) -> String {
+    rpassword::prompt_password(prompt).expect("Cannot read the passphrase!")
+}
+
+/// Where the wallet of the CLI is read from and written to. Wallets of the keystore
+/// are only decrypted in memory, and are encrypted again whenever they change.
+pub enum WalletFile {
+    Plain(PathBuf),
+    Keystore {
+        keystore: Keystore,
+        name: String,
+        passphrase: String,
+    },
+}
+
+impl WalletFile {
+    /// The wallet `name` of the keystore, unlocked with `passphrase`, or with a
+    /// passphrase asked from the user if it is not given
+    pub fn keystore(
+        keystore_path: &PathBuf,
+        name: String,
+        passphrase: Option<String>,
+    ) -> Result<Self, WalletError> {
+        let keystore = Keystore::new(keystore_path);
+        if !keystore.exists(&name)? {
+            return Err(WalletError::WalletNotFound(name));
+        }
+        let passphrase = passphrase.unwrap_or_else(|| read_passphrase("Passphrase: "));
+        Ok(Self::Keystore {
+            keystore,
+            name,
+            passphrase,
+        })
+    }
+
+    pub fn open(&self) -> Result<Option<WalletCollection>, WalletError> {
+        match self {
+            Self::Plain(path) => WalletCollection::open(path),
+            Self::Keystore {
+                keystore,
+                name,
+                passphrase,
+            } => keystore.unlock(name, passphrase).map(Some),
+        }
+    }
+
+    pub fn save(&self, wallet: &WalletCollection) -> Result<(), WalletError> {
+        match self {
+            Self::Plain(path) => wallet.save(path),
+            Self::Keystore {
+                keystore,
+                name,
+                passphrase,
+            } => keystore.save(name, wallet, passphrase),
+        }
+    }
+
+    /// Labels and excluded inflows are kept next to the wallet, unencrypted
+    pub fn metadata_path(&self) -> Result<PathBuf, WalletError> {
+        Ok(WalletMetadata::path(match self {
+            Self::Plain(path) => path.clone(),
+            Self::Keystore { keystore, name, .. } => keystore.path(name)?,
+        }))
+    }
+}
+
+pub fn keystore_save(
+    name: String,
+    wallet: WalletCollection,
+    keystore_path: &PathBuf,
+    overwrite: bool,
+) {
+    let keystore = Keystore::new(keystore_path);
+    if !overwrite && keystore.exists(&name).unwrap() {
+        println!("Wallet {} already exists in the keystore!", name);
+        return;
+    }
+    let passphrase = read_passphrase("Passphrase: ");
+    if read_passphrase("Repeat passphrase: ") != passphrase {
+        println!("Passphrases do not match!");
+        return;
+    }
+    keystore.save(&name, &wallet, &passphrase).unwrap();
+    println!("Wallet saved as {}", name);
+    println!(
+        "Pass --keystore {} to use it instead of the wallet file",
+        name
+    );
+}
+
+pub fn keystore_list(keystore_path: &PathBuf) {
+    for name in Keystore::new(keystore_path).list().unwrap() {
+        println!("{}", name);
+    }
+}
@@ -0,0 +1,42 @@
+use super::WalletFile;
+
+use bazuka::core::{Decimal, TokenId};
+use bazuka::wallet::{ExcludedInflow, WalletMetadata};
+
+pub fn label(wallet_file: &WalletFile, tx: String, label: Option<String>) {
+    let meta_path = wallet_file.metadata_path().unwrap();
+    let mut meta = WalletMetadata::open(&meta_path).unwrap();
+    if let Some(label) = label {
+        meta.set_label(tx, label);
+    } else if meta.remove_label(&tx).is_none() {
+        panic!("Transaction is not labeled!");
+    }
+    meta.save(&meta_path).unwrap();
+}
+
+pub fn exclude_inflow(
+    wallet_file: &WalletFile,
+    tx: String,
+    amount: Decimal,
+    token_id: Option<TokenId>,
+) {
+    let meta_path = wallet_file.metadata_path().unwrap();
+    let mut meta = WalletMetadata::open(&meta_path).unwrap();
+    meta.exclude(
+        tx,
+        ExcludedInflow {
+            token_id: token_id.unwrap_or(TokenId::Ziesha),
+            amount,
+        },
+    );
+    meta.save(&meta_path).unwrap();
+}
+
+pub fn include_inflow(wallet_file: &WalletFile, tx: String) {
+    let meta_path = wallet_file.metadata_path().unwrap();
+    let mut meta = WalletMetadata::open(&meta_path).unwrap();
+    if meta.include(&tx).is_none() {
+        panic!("Inflow is not excluded!");
+    }
+    meta.save(&meta_path).unwrap();
+}
@@ -1,7 +1,11 @@
 pub mod add_token;
 pub mod auto_delegate;
+pub mod backup;
 pub mod delegate;
 pub mod info;
+pub mod init;
+pub mod keystore;
+pub mod metadata;
 pub mod new_token;
 pub mod register_validator;
 pub mod resend_pending;
@@ -11,8 +15,12 @@ pub mod undelegate;
 
 pub use add_token::*;
 pub use auto_delegate::*;
+pub use backup::*;
 pub use delegate::*;
 pub use info::*;
+pub use init::*;
+pub use keystore::*;
+pub use metadata::*;
 pub use new_token::*;
 pub use register_validator::*;
 pub use resend_pending::*;
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use super::WalletFile;
 
 use crate::cli::{BazukaConfig, CURRENT_NETWORK};
 use bazuka::client::{BazukaClient, Limit, NodeError};
@@ -17,7 +17,7 @@ pub async fn new_token(
     fee: Decimal,
     conf: BazukaConfig,
     mut wallet: WalletCollection,
-    wallet_path: &PathBuf,
+    wallet_file: &WalletFile,
 ) -> () {
     let tx_builder = wallet.user(0).tx_builder();
     let (req_loop, client) = BazukaClient::connect(
@@ -52,7 +52,7 @@ pub async fn new_token(
             } else {
                 wallet.user(0).add_token(token_id);
                 wallet.user(0).add_tx(pay.clone().into());
-                wallet.save(wallet_path).unwrap();
+                wallet_file.save(&wallet).unwrap();
                 println!("Sent");
                 println!("Token-Id: {}", token_id);
             }
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use super::WalletFile;
 
 use tokio::try_join;
 
@@ -14,7 +14,7 @@ pub async fn register_validator(
     fee: Decimal,
     conf: BazukaConfig,
     mut wallet: WalletCollection,
-    wallet_path: &PathBuf,
+    wallet_file: &WalletFile,
 ) -> () {
     let commission: Ratio = commission.try_into().unwrap();
     let tx_builder = wallet.validator().tx_builder();
@@ -45,7 +45,7 @@ pub async fn register_validator(
                 println!("Error: {}", err);
             } else {
                 wallet.validator().add_tx(tx.clone().into());
-                wallet.save(wallet_path).unwrap();
+                wallet_file.save(&wallet).unwrap();
                 println!("Sent");
             }
             Ok::<(), NodeError>(())
@@ -1,9 +1,9 @@
+use super::WalletFile;
 use crate::cli::{BazukaConfig, CURRENT_NETWORK};
 use bazuka::client::{BazukaClient, Limit, NodeError};
 use bazuka::common::*;
 
 use bazuka::wallet::WalletCollection;
-use std::path::PathBuf;
 use tokio::try_join;
 
 #[cfg(feature = "client")]
@@ -43,8 +43,8 @@ async fn resend_all_wallet_txs(
 pub async fn resend_pending(
     conf: BazukaConfig,
     mut wallet: WalletCollection,
-    wallet_path: &PathBuf,
+    wallet_file: &WalletFile,
 ) -> () {
     resend_all_wallet_txs(conf, &mut wallet).await.unwrap();
-    wallet.save(wallet_path).unwrap();
+    wallet_file.save(&wallet).unwrap();
 }
@@ -1,8 +1,8 @@
-use std::path::PathBuf;
+use super::WalletFile;
 
 use bazuka::wallet::WalletCollection;
 
-pub fn reset(mut wallet: WalletCollection, wallet_path: &PathBuf) -> () {
+pub fn reset(mut wallet: WalletCollection, wallet_file: &WalletFile) -> () {
     wallet.user(0).reset();
-    wallet.save(wallet_path).unwrap();
+    wallet_file.save(&wallet).unwrap();
 }
@@ -1,7 +1,7 @@
-use std::path::PathBuf;
+use super::WalletFile;
 
 use crate::cli::{BazukaConfig, CURRENT_NETWORK};
-use bazuka::wallet::WalletCollection;
+use bazuka::wallet::{WalletCollection, WalletMetadata};
 use bazuka::{
     client::{BazukaClient, Limit, NodeError},
     common::*,
@@ -10,6 +10,7 @@ use bazuka::{
 };
 use tokio::try_join;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn send(
     memo: Option<String>,
     from: GeneralAddress,
@@ -17,11 +18,24 @@ pub async fn send(
     amount: Decimal,
     fee: Decimal,
     token_id: Option<TokenId>,
+    label: Option<String>,
     conf: BazukaConfig,
     mut wallet: WalletCollection,
-    wallet_path: &PathBuf,
+    wallet_file: &WalletFile,
 ) {
     let tx_builder = wallet.user(0).tx_builder();
+    let meta_path = wallet_file.metadata_path().unwrap();
+    let mut meta = WalletMetadata::open(&meta_path).unwrap();
+    let chain_to_chain = matches!(
+        (&from, &to),
+        (
+            GeneralAddress::ChainAddress(_),
+            GeneralAddress::ChainAddress(_)
+        )
+    );
+    if label.is_some() && !chain_to_chain {
+        panic!("Only main-chain to main-chain transactions can be labeled!");
+    }
     let mpn_contract_id = config::blockchain::get_blockchain_config()
         .mpn_config
         .mpn_contract_id;
@@ -55,6 +69,14 @@ pub async fn send(
                     if tx_builder.get_address() != from {
                         panic!("Source address doesn't exist in your wallet!");
                     }
+                    let balance = client.get_balance(from, tkn).await?.balance;
+                    let mut needed = amount.to_amount(tkn_decimals);
+                    if tkn == TokenId::Ziesha {
+                        needed += fee.to_amount(bazuka::config::UNIT_ZEROS);
+                    }
+                    if needed > meta.spendable(balance, tkn, tkn_decimals) {
+                        panic!("Not enough balance without spending the excluded inflows!");
+                    }
                     match to {
                         GeneralAddress::ChainAddress(to) => {
                             let curr_nonce =
@@ -83,7 +105,11 @@ pub async fn send(
                                 println!("Error: {}", err);
                             } else {
                                 wallet.user(0).add_tx(tx.clone().into());
-                                wallet.save(wallet_path).unwrap();
+                                wallet_file.save(&wallet).unwrap();
+                                if let Some(label) = label {
+                                    meta.set_label(hex::encode(tx.tx.hash()), label);
+                                    meta.save(&meta_path).unwrap();
+                                }
                                 println!("Sent");
                             }
                         }
@@ -111,7 +137,7 @@ pub async fn send(
                                 },
                             );
                             wallet.user(0).add_tx(pay.clone().into());
-                            wallet.save(wallet_path).unwrap();
+                            wallet_file.save(&wallet).unwrap();
                             println!("{:#?}", client.transact(pay.clone().into()).await?);
                         }
                     }
@@ -143,7 +169,7 @@ pub async fn send(
                                 to.to_string().parse().unwrap(), // TODO: WTH :D
                             );
                             wallet.user(0).add_tx(pay.clone().into());
-                            wallet.save(wallet_path).unwrap();
+                            wallet_file.save(&wallet).unwrap();
                             println!("{:#?}", client.transact(pay.clone().into()).await?);
                         }
 
@@ -169,7 +195,7 @@ pub async fn send(
                                 new_nonce,
                             );
                             wallet.user(0).add_tx(tx.clone().into());
-                            wallet.save(wallet_path).unwrap();
+                            wallet_file.save(&wallet).unwrap();
                             println!("{:#?}", client.transact(tx.clone().into()).await?);
                         }
                     }
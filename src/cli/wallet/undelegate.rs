@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use super::WalletFile;
 use tokio::try_join;
 
 use crate::cli::{BazukaConfig, CURRENT_NETWORK};
@@ -10,7 +10,7 @@ use bazuka::wallet::WalletCollection;
 pub async fn undelegate(
     conf: BazukaConfig,
     mut wallet: WalletCollection,
-    wallet_path: &PathBuf,
+    wallet_file: &WalletFile,
     memo: Option<String>,
     amount: Decimal,
     from: Address,
@@ -46,7 +46,7 @@ pub async fn undelegate(
                 println!("Error: {}", err);
             } else {
                 wallet.user(0).add_tx(tx.clone().into());
-                wallet.save(wallet_path).unwrap();
+                wallet_file.save(&wallet).unwrap();
                 println!("Sent");
             }
             Ok::<(), NodeError>(())
@@ -44,6 +44,8 @@ pub enum NodeError {
     TimeoutError(#[from] tokio::time::error::Elapsed),
     #[error("http body size limit error")]
     SizeLimitError,
+    #[error("response stream ended in the middle of an item")]
+    TruncatedStream,
     #[error("bad input: {0}")]
     InputError(#[from] InputError),
     #[error("signature (authorization) header is invalid")]
@@ -58,10 +60,37 @@ pub enum NodeError {
     HandshakeClientMismatch,
     #[error("remote server error: {0}")]
     RemoteServerError(String),
+    #[error("endpoint is not served by the remote node")]
+    EndpointNotFound,
     #[error("block timestamp is way higher than current network timestamp")]
     BlockTimestampInFuture,
     #[error("your validator is not exposed on the internet")]
     ValidatorNotExposed,
     #[error("request sender's ip address is unknown")]
     SenderIpUnknown,
+    #[error("faucet is not enabled on this node")]
+    FaucetDisabled,
+    #[error("faucet has already been used recently, try again later")]
+    FaucetRateLimited,
+    #[error("explorer index is not enabled on this node")]
+    ExplorerDisabled,
+    #[error("metrics history is not enabled on this node")]
+    MetricsDisabled,
+    #[error("transaction not found")]
+    TransactionNotFound,
+    #[error("too many addresses requested at once, at most {0} allowed")]
+    TooManyAddresses(usize),
+    #[error("too many blocks requested at once, at most {0} allowed")]
+    TooManyBlocks(usize),
+    #[error("request cancelled since the node is shutting down")]
+    RequestCancelled,
+    #[cfg(feature = "grpc")]
+    #[error("grpc server error happened: {0}")]
+    GrpcError(#[from] tonic::transport::Error),
+    #[cfg(feature = "zmq")]
+    #[error("zeromq error happened: {0}")]
+    ZmqError(#[from] zeromq::ZmqError),
+    #[cfg(feature = "libp2p")]
+    #[error("libp2p error happened: {0}")]
+    Libp2pError(String),
 }
@@ -495,6 +495,35 @@ impl From<&Block> for ExplorerBlock {
     }
 }
 
+/// A transaction of a block, along with its hash
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ExplorerBlockTransaction {
+    pub hash: String,
+    pub fee: ExplorerMoney,
+    pub transaction: ExplorerTransaction,
+}
+
+impl From<&Transaction> for ExplorerBlockTransaction {
+    fn from(obj: &Transaction) -> Self {
+        Self {
+            hash: hex::encode(obj.hash()),
+            fee: obj.fee.into(),
+            transaction: obj.into(),
+        }
+    }
+}
+
+/// What the validator of a block and its delegators were paid. There is no reward
+/// transaction in the block body, rewards are paid from the treasury.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ExplorerBlockReward {
+    pub validator: String,
+    /// Newly issued Ziesha, `None` for the genesis block
+    pub reward: Option<u64>,
+    /// Sum of the fees of the block, `None` if its body has been pruned
+    pub fees: Option<u64>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ExplorerStaker {
     pub_key: String,
@@ -589,3 +618,36 @@ impl From<&GeneralTransaction> for ExplorerGeneralTransaction {
         }
     }
 }
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExplorerBlockStats {
+    pub number: u64,
+    pub hash: String,
+    pub timestamp: u32,
+    pub validator: String,
+    pub tx_count: usize,
+    pub size: usize,
+    pub fees: u64,
+}
+
+impl From<&Block> for ExplorerBlockStats {
+    fn from(obj: &Block) -> Self {
+        Self {
+            number: obj.header.number,
+            hash: hex::encode(obj.header.hash()),
+            timestamp: obj.header.proof_of_stake.timestamp,
+            validator: obj.header.proof_of_stake.validator.to_string(),
+            tx_count: obj.body.len(),
+            size: bincode::serialize(obj).unwrap().len(),
+            fees: obj.body.iter().map(|tx| u64::from(tx.fee.amount)).sum(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExplorerAddressActivity {
+    pub block: u64,
+    pub index: u32,
+    pub timestamp: u32,
+    pub hash: String,
+}
@@ -0,0 +1,45 @@
+use super::NodeError;
+use hyper::body::Bytes;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Serializes an item of a streamed response, prefixed by its size as a
+/// little-endian u32
+pub fn encode_frame<T: Serialize>(item: &T) -> Result<Bytes, NodeError> {
+    let data = bincode::serialize(item)?;
+    let mut frame = Vec::with_capacity(4 + data.len());
+    frame.extend((data.len() as u32).to_le_bytes());
+    frame.extend(data);
+    Ok(frame.into())
+}
+
+/// Collects the chunks of a streamed response and splits them into items, so
+/// that items can be handled before the whole response is received
+#[derive(Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Next complete item, `None` if more chunks are needed
+    pub fn next_item<T: DeserializeOwned>(&mut self) -> Result<Option<T>, NodeError> {
+        if self.buffer.len() < 4 {
+            return Ok(None);
+        }
+        let size = u32::from_le_bytes(self.buffer[..4].try_into().unwrap()) as usize;
+        if self.buffer.len() < 4 + size {
+            return Ok(None);
+        }
+        let item = bincode::deserialize(&self.buffer[4..4 + size])?;
+        self.buffer.drain(..4 + size);
+        Ok(Some(item))
+    }
+
+    /// Whether there are no leftover bytes of an incomplete item
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+}
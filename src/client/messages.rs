@@ -1,15 +1,21 @@
 use crate::blockchain::{TimestampCommit, TransactionStats};
 use crate::core::{
-    Address, Amount, Block, GeneralAddress, GeneralTransaction, Header, Money, MpnAddress,
-    Signature, Token, TransactionAndDelta, Undelegation, ValidatorProof,
+    Address, Amount, Block, BlockMeta, GeneralAddress, GeneralTransaction, Header, Money,
+    MpnAddress, Signature, Token, TransactionAndDelta, Undelegation, ValidatorProof,
 };
+use crate::db::{Blob, StringKey};
 use crate::mpn::MpnWork;
 use crate::zk;
 use std::collections::HashMap;
+use std::net::IpAddr;
 use thiserror::Error;
 
 use super::{
-    explorer::{ExplorerBlock, ExplorerGeneralTransaction, ExplorerMpnAccount, ExplorerStaker},
+    explorer::{
+        ExplorerAddressActivity, ExplorerBlock, ExplorerBlockReward, ExplorerBlockStats,
+        ExplorerBlockTransaction, ExplorerGeneralTransaction, ExplorerHeader, ExplorerMpnAccount,
+        ExplorerStaker, ExplorerTransaction,
+    },
     Peer, PeerAddress,
 };
 use serde::{Deserialize, Serialize};
@@ -22,12 +28,22 @@ pub struct SocialProfiles {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GetStatsRequest {}
 
+/// Fees of the mempool transactions paid in Ziesha
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FeePercentiles {
+    pub p10: Amount,
+    pub p50: Amount,
+    pub p90: Amount,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GetStatsResponse {
     pub social_profiles: SocialProfiles,
     pub address: String,
     pub height: u64,
+    pub best_peer_height: u64,
     pub nodes: usize,
+    pub mempool_size: usize,
     pub next_reward: Amount,
     pub timestamp: u32,
     pub timestamp_offset: i32,
@@ -36,6 +52,83 @@ pub struct GetStatsResponse {
     pub version: String,
     pub network: String,
     pub validator_claim: Option<ValidatorClaim>,
+    /// Whether the node is behind its peers
+    pub syncing: bool,
+    /// Seconds since the node was started
+    pub uptime: u32,
+    /// How the node came to be started, unknown if it has no data directory
+    pub restart: Option<RestartInfo>,
+    /// Average seconds between the recent blocks
+    pub avg_block_interval: Option<f64>,
+    pub mempool_fees: Option<FeePercentiles>,
+}
+
+/// Why a node was started
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartReason {
+    /// The node was never started on its data directory before
+    FirstStart,
+    /// The previous run was shut down cleanly
+    Restart,
+    /// The previous run stopped without shutting down, e.g it crashed or was killed
+    Crash,
+}
+
+/// Start history of a node, kept in its data directory
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RestartInfo {
+    pub reason: RestartReason,
+    /// Local timestamp of the current start
+    pub started_at: u32,
+    /// Local timestamp of the previous start
+    pub previous_start: Option<u32>,
+    /// Number of times the node was started, including the current one
+    pub starts: u32,
+    /// Number of starts which followed a crash
+    pub crashes: u32,
+}
+
+/// Status of the node at some point in time
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct MetricsSample {
+    pub timestamp: u32,
+    pub height: u64,
+    pub peers: usize,
+    pub mempool_size: usize,
+    /// Sync speed since the previous sample
+    pub blocks_per_sec: f64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetMetricsHistoryRequest {
+    pub since: u32,
+    pub until: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetMetricsHistoryResponse {
+    pub samples: Vec<MetricsSample>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetMinerStatsRequest {}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetMinerStatsResponse {
+    pub accepted_solutions: u64,
+    pub rejected_solutions: u64,
+    pub produced_blocks: u64,
+    pub stale_solutions: u64,
+    pub stale_blocks: u64,
+    pub height: u64,
+    pub average_block_time: Option<u32>,
+    pub time_since_last_block: u32,
+    /// Blocks are produced by stakers rather than miners, so there is no network
+    /// hashrate. The stake of all the validators is what secures the chain instead.
+    pub total_stake: Amount,
+    /// Number of distinct validators of the recent blocks
+    pub recent_validators: usize,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -49,6 +142,31 @@ pub struct GetAccountResponse {
     pub mpn_deposit_nonce: u32,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetAccountTransactionsRequest {
+    /// Given in the path
+    #[serde(default)]
+    pub address: String,
+    /// Only transactions included at or above this height
+    #[serde(default)]
+    pub since: u64,
+    #[serde(default)]
+    pub page: usize,
+    #[serde(alias = "count")]
+    pub page_size: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct AccountTransaction {
+    pub block: u64,
+    pub hash: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetAccountTransactionsResponse {
+    pub txs: Vec<AccountTransaction>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GetDelegationsRequest {
     pub address: String,
@@ -95,14 +213,149 @@ pub struct HandshakeResponse {
     pub timestamp: u32,
     pub timestamp_offset: i32,
     pub validator_claim: Option<ValidatorClaim>,
+    /// Seconds since the node was started, unknown for nodes answering legacy handshakes
+    pub uptime: Option<u32>,
+    pub restart: Option<RestartInfo>,
+}
+
+/// `Peer` as known by the nodes running protocol version 1
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LegacyPeer {
+    pub address: PeerAddress,
+    pub pub_key: Address,
+    pub height: u64,
+}
+
+/// Handshake of the nodes running protocol version 1, without the pruned height,
+/// uptime and restart of the node
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct LegacyHandshakeResponse {
+    pub peer: LegacyPeer,
+    pub timestamp: u32,
+    pub timestamp_offset: i32,
+    pub validator_claim: Option<ValidatorClaim>,
+}
+
+impl From<HandshakeResponse> for LegacyHandshakeResponse {
+    fn from(resp: HandshakeResponse) -> Self {
+        Self {
+            peer: LegacyPeer {
+                address: resp.peer.address,
+                pub_key: resp.peer.pub_key,
+                height: resp.peer.height,
+            },
+            timestamp: resp.timestamp,
+            timestamp_offset: resp.timestamp_offset,
+            validator_claim: resp.validator_claim,
+        }
+    }
+}
+
+impl From<LegacyHandshakeResponse> for HandshakeResponse {
+    fn from(resp: LegacyHandshakeResponse) -> Self {
+        Self {
+            peer: Peer {
+                address: resp.peer.address,
+                pub_key: resp.peer.pub_key,
+                height: resp.peer.height,
+                pruned_height: 0,
+            },
+            timestamp: resp.timestamp,
+            timestamp_offset: resp.timestamp_offset,
+            validator_claim: resp.validator_claim,
+            uptime: None,
+            restart: None,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GetPeersRequest {}
 
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerDirection {
+    /// The peer introduced itself to us
+    Inbound,
+    /// We found the peer through the bootstrap nodes or other peers
+    Outbound,
+}
+
+/// Kinds of misbehavior peers are punished for
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Offense {
+    /// Served blocks or headers that could not be applied
+    InvalidBlock,
+    /// Served blocks with invalid proofs
+    BadProof,
+    /// Served blocks or transactions with invalid signatures
+    BadSignature,
+    /// Sent requests that could not be served
+    Spam,
+    /// Did not respond in time
+    Timeout,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PeerInfo {
+    pub address: PeerAddress,
+    pub pub_key: String,
+    pub height: u64,
+    pub pruned_height: u64,
+    /// Reported by the peer in the headers of its requests, unknown until it
+    /// contacts us
+    pub protocol_version: Option<u32>,
+    pub user_agent: Option<String>,
+    pub direction: PeerDirection,
+    /// Last time we heard from the peer
+    pub last_seen: u32,
+    /// Round-trip time of the last handshake
+    pub latency_ms: u64,
+    /// Uptime reported by the peer in the last handshake
+    pub uptime: Option<u32>,
+    pub restart: Option<RestartInfo>,
+    /// Number of times the peer was punished recently
+    pub punishments: u32,
+    pub last_punished: Option<u32>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub offenses: HashMap<Offense, u32>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GetPeersResponse {
     pub peers: Vec<PeerAddress>,
+    /// Only given to local clients
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<PeerInfo>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetBannedPeersRequest {}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BannedPeer {
+    pub ip: IpAddr,
+    /// Local timestamp the ban ends at
+    pub banned_till: u32,
+    /// Offenses recently committed by the peer, by kind
+    pub offenses: HashMap<Offense, u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetBannedPeersResponse {
+    pub banned: Vec<BannedPeer>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct UnbanPeerRequest {
+    pub ip: IpAddr,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UnbanPeerResponse {
+    /// False if the peer was not banned
+    pub unbanned: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -135,6 +388,69 @@ pub struct GetExplorerBlocksResponse {
     pub blocks: Vec<ExplorerBlock>,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetBlockRequest {
+    /// Number or hex encoded hash of the block
+    pub id: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetBlockResponse {
+    pub hash: String,
+    pub header: ExplorerHeader,
+    pub reward: ExplorerBlockReward,
+    /// Hashes of the transactions, `None` if the body has been pruned
+    pub transactions: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetBlockTransactionsRequest {
+    /// Number or hex encoded hash of the block
+    pub id: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetBlockTransactionsResponse {
+    pub transactions: Vec<ExplorerBlockTransaction>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetExplorerBlockStatsRequest {
+    pub since: u64,
+    pub count: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetExplorerBlockStatsResponse {
+    pub indexed_height: u64,
+    pub stats: Vec<ExplorerBlockStats>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetExplorerTransactionRequest {
+    pub hash: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetExplorerTransactionResponse {
+    pub block: u64,
+    pub index: u32,
+    /// `None` if the block body has been pruned
+    pub transaction: Option<ExplorerTransaction>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetExplorerAddressActivityRequest {
+    pub address: String,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetExplorerAddressActivityResponse {
+    pub activity: Vec<ExplorerAddressActivity>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GetExplorerStakersRequest {}
 
@@ -154,6 +470,30 @@ pub struct GetHeadersResponse {
     pub headers: Vec<Header>,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetBlockMetasRequest {
+    pub since: u64,
+    pub count: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetBlockMetasResponse {
+    /// Metadata of the blocks starting from `since`, in order
+    pub metas: Vec<BlockMeta>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetSnapshotRequest {
+    pub after: Option<StringKey>,
+    pub count: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetSnapshotResponse {
+    pub tip: Header,
+    pub pairs: Vec<(StringKey, Blob)>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct TransactRequest {
     pub tx: GeneralTransaction,
@@ -171,6 +511,35 @@ pub struct ShutdownRequest {}
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ShutdownResponse {}
 
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct UpdateConfigRequest {
+    pub num_peers: Option<usize>,
+    pub request_limit_per_minute: Option<usize>,
+    pub traffic_limit_per_15m: Option<u64>,
+    /// Largest request body accepted from remote clients, in bytes
+    pub max_body_size: Option<u64>,
+    pub max_block_txs: Option<usize>,
+    pub min_fee: Option<Amount>,
+    /// Ban durations of the given offenses, the rest are kept
+    #[serde(default)]
+    pub punishments: HashMap<Offense, u32>,
+    pub max_punish: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdateConfigResponse {}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct FaucetRequest {
+    pub address: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct FaucetResponse {
+    pub amount: Amount,
+    pub nonce: u32,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GetMempoolRequest {
     pub filter: Option<GeneralAddress>,
@@ -203,6 +572,72 @@ pub struct GetBalanceResponse {
     pub symbol: String,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetBalancesRequest {
+    /// Comma separated list of addresses
+    pub addresses: String,
+    pub token_id: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct GetBalancesResponse {
+    /// In the same order as the requested addresses
+    pub balances: Vec<Amount>,
+    pub total: Amount,
+    pub name: String,
+    pub symbol: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GetTransactionStatusRequest {
+    /// Hex encoded hash of the transaction
+    pub hash: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    /// Neither on the chain, nor in the mempool, nor recently rejected
+    Unknown,
+    /// Waiting in the mempool
+    Pending,
+    /// Included in the chain
+    Confirmed {
+        block: u64,
+        index: u32,
+        /// Number of blocks on top of the including block, plus one
+        confirmations: u64,
+    },
+    /// Not accepted by this node
+    Rejected { reason: String },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GetTransactionStatusResponse {
+    pub status: TransactionStatus,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct GetFeeEstimateRequest {
+    /// Size of the transaction to be sent in bytes, a plain payment if not given
+    #[serde(default)]
+    pub size: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct GetFeeEstimateResponse {
+    /// Transactions paying less than this plus `min_fee_per_byte` for each of
+    /// their bytes are rejected by the node
+    pub min_fee: Amount,
+    pub min_fee_per_byte: Amount,
+    /// Fee likely to get a transaction into one of the next few blocks
+    pub suggested_fee: Amount,
+    /// Average share of the recent blocks filled with non-MPN transactions
+    pub block_fullness: f32,
+    /// Size of the pending chain transactions, in number of blocks
+    pub mempool_depth: f32,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct JsonMpnTransaction {
     pub nonce: u32,
@@ -363,6 +798,16 @@ pub struct GenerateBlockResponse {
     pub success: bool,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GenerateRequest {
+    pub count: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GenerateResponse {
+    pub generated: Vec<u64>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GetMpnWorkRequest {
     pub mpn_address: MpnAddress,
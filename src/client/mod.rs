@@ -1,5 +1,4 @@
-use crate::core::{Address, GeneralTransaction, MpnAddress, Signer, TokenId};
-use crate::crypto::ed25519;
+use crate::core::{Address, Block, GeneralTransaction, MpnAddress, Signer, TokenId};
 use crate::crypto::SignatureScheme;
 use crate::zk::ZkProof;
 use hyper::body::{Bytes, HttpBody};
@@ -11,10 +10,12 @@ use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch, Semaphore};
 use tokio::time::timeout;
 
 mod error;
+mod frames;
+pub use frames::*;
 pub mod explorer;
 pub mod messages;
 pub mod utils;
@@ -24,7 +25,41 @@ use messages::*;
 pub type Timestamp = u32;
 
 pub const SIGNATURE_HEADER: &str = "X-ZIESHA-SIGNATURE";
+pub const SIGNATURE_TIMESTAMP_HEADER: &str = "X-ZIESHA-SIGNATURE-TIMESTAMP";
 pub const NETWORK_HEADER: &str = "X-ZIESHA-NETWORK-NAME";
+pub const PROTOCOL_HEADER: &str = "X-ZIESHA-PROTOCOL-VERSION";
+
+/// Version of the messages exchanged between the nodes
+pub const PROTOCOL_VERSION: u32 = 2;
+/// Nodes at or below this version, or not reporting their version, are answered with
+/// and answer with `LegacyHandshakeResponse`
+pub const LEGACY_HANDSHAKE_VERSION: u32 = 1;
+
+/// Protocol version reported in the headers of a request or response
+pub fn protocol_version(headers: &hyper::HeaderMap) -> Option<u32> {
+    headers
+        .get(PROTOCOL_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+pub const USER_AGENT: &str = concat!("bazuka/", env!("CARGO_PKG_VERSION"));
+
+/// What the signature of a request covers, so that a signature can't be replayed on
+/// another endpoint or query, or long after it was made
+pub fn signed_message(
+    method: &Method,
+    uri: &hyper::Uri,
+    timestamp: Timestamp,
+    body: &[u8],
+) -> Result<Vec<u8>, NodeError> {
+    let path_and_query = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    Ok(bincode::serialize(&(
+        method.as_str(),
+        path_and_query,
+        timestamp,
+        body,
+    ))?)
+}
 
 #[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PeerAddress(pub SocketAddr); // ip, port
@@ -51,8 +86,36 @@ impl FromStr for PeerAddress {
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Peer {
     pub address: PeerAddress,
-    pub pub_key: ed25519::PublicKey,
+    pub pub_key: Address,
     pub height: u64,
+    /// Bodies of the blocks below this height are pruned and cannot be served
+    pub pruned_height: u64,
+}
+
+/// Reads the body of a successful response, failing if it may exceed `size_limit`
+async fn read_body(resp: Response<Body>, size_limit: Option<u64>) -> Result<Bytes, NodeError> {
+    let status = resp.status();
+    let body = resp.into_body();
+
+    if let Some(size_limit) = size_limit {
+        if body
+            .size_hint()
+            .upper()
+            .map(|u| u > size_limit)
+            .unwrap_or(true)
+        {
+            return Err(NodeError::SizeLimitError);
+        }
+    }
+    let body_bytes = hyper::body::to_bytes(body).await?;
+
+    if status != StatusCode::OK {
+        return Err(NodeError::RemoteServerError(
+            String::from_utf8_lossy(&body_bytes).to_string(),
+        ));
+    }
+
+    Ok(body_bytes)
 }
 
 pub struct NodeRequest {
@@ -62,10 +125,18 @@ pub struct NodeRequest {
     pub resp: mpsc::UnboundedSender<Result<Response<Body>, NodeError>>,
 }
 
+/// Requests without a time limit are given up after this long
+pub const DEFAULT_REQUEST_DEADLINE: Duration = Duration::from_secs(120);
+/// Maximum number of requests waiting for a response at the same time, unless
+/// configured otherwise
+pub const MAX_IN_FLIGHT_REQUESTS: usize = 256;
+
 pub struct OutgoingSender {
-    pub priv_key: ed25519::PrivateKey,
+    pub priv_key: <Signer as SignatureScheme>::Priv,
     pub network: String,
     pub chan: mpsc::UnboundedSender<NodeRequest>,
+    in_flight: Semaphore,
+    cancelled: watch::Sender<bool>,
 }
 
 #[derive(Default, Clone)]
@@ -86,55 +157,122 @@ impl Limit {
 }
 
 impl OutgoingSender {
-    pub async fn raw(&self, mut body: Request<Body>, limit: Limit) -> Result<Bytes, NodeError> {
+    pub fn new(
+        priv_key: <Signer as SignatureScheme>::Priv,
+        network: String,
+        chan: mpsc::UnboundedSender<NodeRequest>,
+    ) -> Self {
+        Self {
+            priv_key,
+            network,
+            chan,
+            in_flight: Semaphore::new(MAX_IN_FLIGHT_REQUESTS),
+            cancelled: watch::channel(false).0,
+        }
+    }
+
+    /// Requests beyond `max_in_flight` wait until one of the pending requests is done
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.in_flight = Semaphore::new(std::cmp::max(max_in_flight, 1));
+        self
+    }
+
+    /// Fails the pending requests and the ones sent afterwards
+    pub fn cancel(&self) {
+        self.cancelled.send_replace(true);
+    }
+
+    pub async fn raw(&self, body: Request<Body>, limit: Limit) -> Result<Bytes, NodeError> {
+        let size_limit = limit.size;
+        self.request(body, limit, |resp| read_body(resp, size_limit))
+            .await
+    }
+
+    /// Handshakes with a node, in the format of the protocol version it reports in
+    /// its response
+    pub async fn handshake(
+        &self,
+        peer: PeerAddress,
+        req: HandshakeRequest,
+        limit: Limit,
+    ) -> Result<HandshakeResponse, NodeError> {
+        let bytes = bincode::serialize(&req)?;
+        let req = self.sign(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("http://{}/bincode/peers", peer))
+                .header("content-type", "application/octet-stream"),
+            bytes,
+        )?;
+        let size_limit = limit.size;
+        self.request(req, limit, |resp| async move {
+            let version = protocol_version(resp.headers()).unwrap_or(LEGACY_HANDSHAKE_VERSION);
+            let body = read_body(resp, size_limit).await?;
+            Ok(if version <= LEGACY_HANDSHAKE_VERSION {
+                bincode::deserialize::<LegacyHandshakeResponse>(&body)?.into()
+            } else {
+                bincode::deserialize(&body)?
+            })
+        })
+        .await
+    }
+
+    /// Sends the request and hands the response to `read`, within the deadline of
+    /// the request
+    async fn request<R, Fut, F>(
+        &self,
+        mut body: Request<Body>,
+        limit: Limit,
+        read: F,
+    ) -> Result<R, NodeError>
+    where
+        F: FnOnce(Response<Body>) -> Fut,
+        Fut: futures::Future<Output = Result<R, NodeError>>,
+    {
+        let mut cancelled = self.cancelled.subscribe();
+        if *cancelled.borrow() {
+            return Err(NodeError::RequestCancelled);
+        }
         let (resp_snd, mut resp_rcv) =
             mpsc::unbounded_channel::<Result<Response<Body>, NodeError>>();
-        body.headers_mut()
-            .insert(NETWORK_HEADER, HeaderValue::from_str(&self.network)?);
+        let headers = body.headers_mut();
+        headers.insert(NETWORK_HEADER, HeaderValue::from_str(&self.network)?);
+        headers.insert(PROTOCOL_HEADER, HeaderValue::from(PROTOCOL_VERSION));
+        headers.insert(
+            hyper::header::USER_AGENT,
+            HeaderValue::from_static(USER_AGENT),
+        );
         let req = NodeRequest {
             limit: limit.clone(),
             socket_addr: None,
             body,
             resp: resp_snd,
         };
-        self.chan
-            .send(req)
-            .map_err(|_| NodeError::NotListeningError)?;
 
         let recver = async {
+            // The permit is held until the whole response is read
+            let _permit = self
+                .in_flight
+                .acquire()
+                .await
+                .map_err(|_| NodeError::NotListeningError)?;
+            self.chan
+                .send(req)
+                .map_err(|_| NodeError::NotListeningError)?;
             let resp = resp_rcv
                 .recv()
                 .await
                 .ok_or(NodeError::NotAnsweringError)??;
-            let status = resp.status();
-            let body = resp.into_body();
-
-            if let Some(size_limit) = limit.size {
-                if body
-                    .size_hint()
-                    .upper()
-                    .map(|u| u > size_limit)
-                    .unwrap_or(true)
-                {
-                    return Err(NodeError::SizeLimitError);
-                }
-            }
-            Ok::<_, NodeError>((status, hyper::body::to_bytes(body).await?))
+            read(resp).await
         };
 
-        let (status, body_bytes) = if let Some(time_limit) = limit.time {
-            timeout(time_limit, recver).await?
-        } else {
-            recver.await
-        }?;
-
-        if status != StatusCode::OK {
-            return Err(NodeError::RemoteServerError(
-                String::from_utf8_lossy(&body_bytes).to_string(),
-            ));
+        let deadline = limit.time.unwrap_or(DEFAULT_REQUEST_DEADLINE);
+        tokio::select! {
+            resp = timeout(deadline, recver) => resp?,
+            _ = cancelled.wait_for(|c| *c) => {
+                Err(NodeError::RequestCancelled)
+            }
         }
-
-        Ok(body_bytes)
     }
 
     fn sign(
@@ -142,15 +280,17 @@ impl OutgoingSender {
         req: hyper::http::request::Builder,
         body: Vec<u8>,
     ) -> Result<Request<Body>, NodeError> {
-        let pub_key = hex::encode(bincode::serialize(&ed25519::PublicKey::from(
-            self.priv_key.clone(),
-        ))?);
-        let sig = hex::encode(bincode::serialize(&Signer::sign(&self.priv_key, &body))?);
-        let mut req = req.body(Body::from(body))?;
+        let timestamp = crate::utils::local_timestamp();
+        let mut req = req.body(Body::from(body.clone()))?;
+        let msg = signed_message(req.method(), req.uri(), timestamp, &body)?;
+        let pub_key = hex::encode(bincode::serialize(&Address::from(self.priv_key.clone()))?);
+        let sig = hex::encode(bincode::serialize(&Signer::sign(&self.priv_key, &msg))?);
         req.headers_mut().insert(
             SIGNATURE_HEADER,
             HeaderValue::from_str(&format!("{}-{}", pub_key, sig))?,
         );
+        req.headers_mut()
+            .insert(SIGNATURE_TIMESTAMP_HEADER, HeaderValue::from(timestamp));
         Ok(req)
     }
 
@@ -167,6 +307,89 @@ impl OutgoingSender {
         Ok(resp)
     }
 
+    /// Like `bincode_get`, for endpoints answering with a stream of framed items.
+    /// Items are handed to `on_item` as they arrive, instead of buffering the whole
+    /// response.
+    pub async fn bincode_get_stream<
+        Req: serde::Serialize,
+        Item: serde::de::DeserializeOwned,
+        F: FnMut(Item) -> Result<(), NodeError>,
+    >(
+        &self,
+        addr: String,
+        req: Req,
+        limit: Limit,
+        mut on_item: F,
+    ) -> Result<(), NodeError> {
+        let bytes = bincode::serialize(&req)?;
+        let req = self.sign(Request::builder().method(Method::GET).uri(&addr), bytes)?;
+        let size_limit = limit.size;
+        self.request(req, limit, |resp| async move {
+            let status = resp.status();
+            let mut body = resp.into_body();
+            if status == StatusCode::NOT_FOUND {
+                return Err(NodeError::EndpointNotFound);
+            }
+            if status != StatusCode::OK {
+                return Err(NodeError::RemoteServerError(
+                    String::from_utf8_lossy(&hyper::body::to_bytes(body).await?).to_string(),
+                ));
+            }
+
+            let mut received = 0u64;
+            let mut decoder = FrameDecoder::default();
+            while let Some(chunk) = body.data().await {
+                let chunk = chunk?;
+                received += chunk.len() as u64;
+                if size_limit.map(|l| received > l).unwrap_or(false) {
+                    return Err(NodeError::SizeLimitError);
+                }
+                decoder.push(&chunk);
+                while let Some(item) = decoder.next_item()? {
+                    on_item(item)?;
+                }
+            }
+            if !decoder.is_empty() {
+                return Err(NodeError::TruncatedStream);
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Downloads blocks through the framed `/bincode/blocks/stream` endpoint, handing
+    /// them to `on_block` one at a time. Peers running older versions only serve
+    /// `/bincode/blocks`, which answers with all the blocks at once.
+    pub async fn get_blocks<F: FnMut(Block) -> Result<(), NodeError>>(
+        &self,
+        peer: PeerAddress,
+        req: GetBlocksRequest,
+        limit: Limit,
+        mut on_block: F,
+    ) -> Result<(), NodeError> {
+        match self
+            .bincode_get_stream(
+                format!("http://{}/bincode/blocks/stream", peer),
+                req.clone(),
+                limit.clone(),
+                &mut on_block,
+            )
+            .await
+        {
+            Err(NodeError::EndpointNotFound) => self
+                .bincode_get::<GetBlocksRequest, GetBlocksResponse>(
+                    format!("http://{}/bincode/blocks", peer),
+                    req,
+                    limit,
+                )
+                .await?
+                .blocks
+                .into_iter()
+                .try_for_each(on_block),
+            res => res,
+        }
+    }
+
     #[allow(dead_code)]
     pub async fn bincode_post<Req: serde::Serialize, Resp: serde::de::DeserializeOwned>(
         &self,
@@ -237,7 +460,7 @@ pub struct BazukaClient {
 
 impl BazukaClient {
     pub fn connect(
-        priv_key: ed25519::PrivateKey,
+        priv_key: <Signer as SignatureScheme>::Priv,
         peer: PeerAddress,
         network: String,
         limit: Option<Limit>,
@@ -253,7 +476,7 @@ impl BazukaClient {
                     }
                     .await;
                     if let Err(e) = req.resp.send(resp) {
-                        log::error!("Node not listening to its HTTP request answer: {}", e);
+                        tracing::error!("Node not listening to its HTTP request answer: {}", e);
                     }
                 });
             }
@@ -263,11 +486,7 @@ impl BazukaClient {
             client_loop,
             Self {
                 peer,
-                sender: Arc::new(OutgoingSender {
-                    priv_key,
-                    network,
-                    chan: sender_send,
-                }),
+                sender: Arc::new(OutgoingSender::new(priv_key, network, sender_send)),
                 limit,
             },
         )
@@ -282,6 +501,27 @@ impl BazukaClient {
             .await?;
         Ok(())
     }
+    pub async fn update_config(&self, req: UpdateConfigRequest) -> Result<(), NodeError> {
+        self.sender
+            .json_post::<UpdateConfigRequest, UpdateConfigResponse>(
+                format!("http://{}/config", self.peer),
+                req,
+                self.limit.clone().unwrap_or_default(),
+            )
+            .await?;
+        Ok(())
+    }
+    pub async fn faucet(&self, address: Address) -> Result<FaucetResponse, NodeError> {
+        self.sender
+            .json_post::<FaucetRequest, FaucetResponse>(
+                format!("http://{}/faucet", self.peer),
+                FaucetRequest {
+                    address: address.to_string(),
+                },
+                self.limit.clone().unwrap_or_default(),
+            )
+            .await
+    }
     pub async fn stats(&self) -> Result<GetStatsResponse, NodeError> {
         self.sender
             .json_get::<GetStatsRequest, GetStatsResponse>(
@@ -291,6 +531,15 @@ impl BazukaClient {
             )
             .await
     }
+    pub async fn miner_stats(&self) -> Result<GetMinerStatsResponse, NodeError> {
+        self.sender
+            .json_get::<GetMinerStatsRequest, GetMinerStatsResponse>(
+                format!("http://{}/miner/stats", self.peer),
+                GetMinerStatsRequest {},
+                self.limit.clone().unwrap_or_default(),
+            )
+            .await
+    }
     pub async fn peers(&self) -> Result<GetPeersResponse, NodeError> {
         self.sender
             .json_get::<GetPeersRequest, GetPeersResponse>(
@@ -300,6 +549,24 @@ impl BazukaClient {
             )
             .await
     }
+    pub async fn banned_peers(&self) -> Result<GetBannedPeersResponse, NodeError> {
+        self.sender
+            .json_get::<GetBannedPeersRequest, GetBannedPeersResponse>(
+                format!("http://{}/peers/banned", self.peer),
+                GetBannedPeersRequest {},
+                self.limit.clone().unwrap_or_default(),
+            )
+            .await
+    }
+    pub async fn unban_peer(&self, ip: IpAddr) -> Result<UnbanPeerResponse, NodeError> {
+        self.sender
+            .json_post::<UnbanPeerRequest, UnbanPeerResponse>(
+                format!("http://{}/peers/unban", self.peer),
+                UnbanPeerRequest { ip },
+                self.limit.clone().unwrap_or_default(),
+            )
+            .await
+    }
 
     pub async fn get_headers(
         &self,
@@ -315,14 +582,34 @@ impl BazukaClient {
             .await
     }
 
+    pub async fn get_block_metas(
+        &self,
+        since: u64,
+        count: u64,
+    ) -> Result<GetBlockMetasResponse, NodeError> {
+        self.sender
+            .bincode_get::<GetBlockMetasRequest, GetBlockMetasResponse>(
+                format!("http://{}/bincode/headers/meta", self.peer),
+                GetBlockMetasRequest { since, count },
+                self.limit.clone().unwrap_or_default(),
+            )
+            .await
+    }
+
     pub async fn get_blocks(&self, since: u64, count: u64) -> Result<GetBlocksResponse, NodeError> {
+        let mut blocks = Vec::new();
         self.sender
-            .bincode_get::<GetBlocksRequest, GetBlocksResponse>(
-                format!("http://{}/bincode/blocks", self.peer),
+            .get_blocks(
+                self.peer,
                 GetBlocksRequest { since, count },
                 self.limit.clone().unwrap_or_default(),
+                |block| {
+                    blocks.push(block);
+                    Ok(())
+                },
             )
-            .await
+            .await?;
+        Ok(GetBlocksResponse { blocks })
     }
 
     pub async fn get_account(&self, address: Address) -> Result<GetAccountResponse, NodeError> {
@@ -371,6 +658,27 @@ impl BazukaClient {
             .await
     }
 
+    pub async fn get_balances(
+        &self,
+        addresses: &[Address],
+        token_id: TokenId,
+    ) -> Result<GetBalancesResponse, NodeError> {
+        self.sender
+            .json_get::<GetBalancesRequest, GetBalancesResponse>(
+                format!("http://{}/balances", self.peer),
+                GetBalancesRequest {
+                    addresses: addresses
+                        .iter()
+                        .map(|a| a.to_string())
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    token_id: token_id.to_string(),
+                },
+                self.limit.clone().unwrap_or_default(),
+            )
+            .await
+    }
+
     pub async fn get_token(&self, token_id: TokenId) -> Result<GetTokenInfoResponse, NodeError> {
         self.sender
             .json_get::<GetTokenInfoRequest, GetTokenInfoResponse>(
@@ -421,6 +729,16 @@ impl BazukaClient {
             .await
     }
 
+    pub async fn generate(&self, count: usize) -> Result<GenerateResponse, NodeError> {
+        self.sender
+            .json_post::<GenerateRequest, GenerateResponse>(
+                format!("http://{}/generate?count={}", self.peer, count),
+                GenerateRequest { count },
+                self.limit.clone().unwrap_or_default(),
+            )
+            .await
+    }
+
     pub async fn get_mpn_works(
         &self,
         mpn_address: MpnAddress,
@@ -460,3 +778,269 @@ impl BazukaClient {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::TxBuilder;
+
+    fn unanswered_sender() -> (OutgoingSender, mpsc::UnboundedReceiver<NodeRequest>) {
+        let (chan, recv) = mpsc::unbounded_channel();
+        let wallet = TxBuilder::new(&Vec::from("ABC"));
+        (
+            OutgoingSender::new(wallet.get_priv_key(), "test".into(), chan),
+            recv,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_outgoing_deadline_and_cancellation() {
+        let (sender, _recv) = unanswered_sender();
+        let req = || {
+            Request::builder()
+                .uri("http://1.2.3.4:8765/stats")
+                .body(Body::empty())
+        };
+        assert!(matches!(
+            sender.raw(req().unwrap(), Limit::default().time(100)).await,
+            Err(NodeError::TimeoutError(_))
+        ));
+
+        let sender = Arc::new(sender);
+        let pending = tokio::spawn({
+            let sender = Arc::clone(&sender);
+            async move { sender.raw(req().unwrap(), Limit::default()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        sender.cancel();
+        assert!(matches!(
+            pending.await.unwrap(),
+            Err(NodeError::RequestCancelled)
+        ));
+        assert!(matches!(
+            sender.raw(req().unwrap(), Limit::default()).await,
+            Err(NodeError::RequestCancelled)
+        ));
+    }
+
+    #[test]
+    fn test_signature_covers_the_request() {
+        let (sender, _recv) = unanswered_sender();
+        let req = sender
+            .sign(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("http://1.2.3.4:8765/config?reload=true"),
+                vec![1, 2, 3],
+            )
+            .unwrap();
+        let header = req.headers()[SIGNATURE_HEADER].to_str().unwrap();
+        let sig_hex = header.split('-').nth(1).unwrap();
+        let sig = bincode::deserialize(&hex::decode(sig_hex).unwrap()).unwrap();
+        let timestamp: Timestamp = req.headers()[SIGNATURE_TIMESTAMP_HEADER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let pub_key = Address::from(sender.priv_key.clone());
+        let verify = |method: &Method, uri: &str, timestamp: Timestamp| {
+            let msg = signed_message(method, &uri.parse().unwrap(), timestamp, &[1, 2, 3]).unwrap();
+            Signer::verify(&pub_key, &msg, &sig)
+        };
+        assert!(verify(&Method::POST, "/config?reload=true", timestamp));
+        assert!(!verify(&Method::GET, "/config?reload=true", timestamp));
+        assert!(!verify(&Method::POST, "/shutdown?reload=true", timestamp));
+        assert!(!verify(&Method::POST, "/config?reload=false", timestamp));
+        assert!(!verify(&Method::POST, "/config?reload=true", timestamp + 1));
+    }
+
+    #[tokio::test]
+    async fn test_outgoing_max_in_flight() {
+        let (sender, mut recv) = unanswered_sender();
+        let sender = Arc::new(sender.with_max_in_flight(1));
+        for _ in 0..2 {
+            let sender = Arc::clone(&sender);
+            tokio::spawn(async move {
+                let req = Request::builder()
+                    .uri("http://1.2.3.4:8765/stats")
+                    .body(Body::empty())
+                    .unwrap();
+                sender.raw(req, Limit::default().time(300)).await
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(recv.try_recv().is_ok());
+        assert!(recv.try_recv().is_err());
+
+        // The second request is sent once the first one times out
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(recv.try_recv().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_bincode_get_stream() {
+        let (sender, mut recv) = unanswered_sender();
+        let mut stream = Vec::new();
+        for i in 0..5u64 {
+            stream.extend(encode_frame(&vec![i; i as usize]).unwrap());
+        }
+        tokio::spawn(async move {
+            while let Some(req) = recv.recv().await {
+                let truncated = req.body.uri().path() == "/truncated";
+                let mut data = stream.clone();
+                if truncated {
+                    data.pop();
+                }
+                // Frames are split across chunks at arbitrary positions
+                let chunks = data
+                    .chunks(7)
+                    .map(|c| Ok::<_, std::convert::Infallible>(c.to_vec()))
+                    .collect::<Vec<_>>();
+                let _ = req
+                    .resp
+                    .send(Ok(Response::new(Body::wrap_stream(futures::stream::iter(
+                        chunks,
+                    )))));
+            }
+        });
+
+        let mut items = Vec::new();
+        sender
+            .bincode_get_stream(
+                "http://1.2.3.4:8765/items".into(),
+                (),
+                Limit::default(),
+                |item: Vec<u64>| {
+                    items.push(item);
+                    Ok(())
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            items,
+            (0..5u64).map(|i| vec![i; i as usize]).collect::<Vec<_>>()
+        );
+
+        assert!(matches!(
+            sender
+                .bincode_get_stream(
+                    "http://1.2.3.4:8765/truncated".into(),
+                    (),
+                    Limit::default(),
+                    |_: Vec<u64>| Ok(()),
+                )
+                .await,
+            Err(NodeError::TruncatedStream)
+        ));
+        assert!(matches!(
+            sender
+                .bincode_get_stream(
+                    "http://1.2.3.4:8765/items".into(),
+                    (),
+                    Limit::default().size(20),
+                    |_: Vec<u64>| Ok(()),
+                )
+                .await,
+            Err(NodeError::SizeLimitError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_blocks_falls_back_for_old_peers() {
+        let (sender, mut recv) = unanswered_sender();
+        let genesis = crate::config::blockchain::get_test_blockchain_config().genesis;
+        let blocks = vec![genesis.clone(), genesis];
+        let resp = GetBlocksResponse {
+            blocks: blocks.clone(),
+        };
+        // Older nodes do not serve the streamed endpoint
+        tokio::spawn(async move {
+            while let Some(req) = recv.recv().await {
+                let resp = if req.body.uri().path() == "/bincode/blocks" {
+                    Response::new(Body::from(bincode::serialize(&resp).unwrap()))
+                } else {
+                    Response::builder()
+                        .status(StatusCode::NOT_FOUND)
+                        .body(Body::empty())
+                        .unwrap()
+                };
+                let _ = req.resp.send(Ok(resp));
+            }
+        });
+
+        let mut received = Vec::new();
+        sender
+            .get_blocks(
+                PeerAddress("1.2.3.4:8765".parse().unwrap()),
+                GetBlocksRequest { since: 0, count: 2 },
+                Limit::default(),
+                |block| {
+                    received.push(block);
+                    Ok(())
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(received, blocks);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_with_old_peers() {
+        let (sender, mut recv) = unanswered_sender();
+        let wallet = TxBuilder::new(&Vec::from("PEER"));
+        let resp = HandshakeResponse {
+            peer: Peer {
+                address: PeerAddress("1.2.3.4:8765".parse().unwrap()),
+                pub_key: wallet.get_address(),
+                height: 10,
+                pruned_height: 5,
+            },
+            timestamp: 1000,
+            timestamp_offset: 2,
+            validator_claim: None,
+            uptime: Some(60),
+            restart: None,
+        };
+        let legacy = LegacyHandshakeResponse::from(resp.clone());
+        // Older nodes do not report their version
+        tokio::spawn(async move {
+            while let Some(req) = recv.recv().await {
+                assert_eq!(protocol_version(req.body.headers()), Some(PROTOCOL_VERSION));
+                let resp = if req.body.uri().host() == Some("1.2.3.4") {
+                    Response::builder()
+                        .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
+                        .body(Body::from(bincode::serialize(&resp).unwrap()))
+                        .unwrap()
+                } else {
+                    Response::new(Body::from(bincode::serialize(&legacy).unwrap()))
+                };
+                let _ = req.resp.send(Ok(resp));
+            }
+        });
+
+        let current = sender
+            .handshake(
+                PeerAddress("1.2.3.4:8765".parse().unwrap()),
+                HandshakeRequest::Client,
+                Limit::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(current.peer.pruned_height, 5);
+        assert_eq!(current.uptime, Some(60));
+
+        let old = sender
+            .handshake(
+                PeerAddress("5.6.7.8:8765".parse().unwrap()),
+                HandshakeRequest::Client,
+                Limit::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(old.peer.height, 10);
+        assert_eq!(old.peer.pruned_height, 0);
+        assert_eq!(old.timestamp, 1000);
+        assert_eq!(old.uptime, None);
+    }
+}
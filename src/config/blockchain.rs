@@ -3,9 +3,11 @@ use super::{initials, UNIT, UNIT_ZEROS};
 use crate::blockchain::BlockchainConfig;
 use crate::common::*;
 use crate::core::{
-    Amount, Block, ContractId, Header, Money, ProofOfStake, Ratio, RegularSendEntry, Signature,
-    Token, TokenId, Transaction, TransactionAndDelta, TransactionData, ValidatorProof, ZkHasher,
+    Address, Amount, Block, ContractId, Header, Money, ProofOfStake, Ratio, RegularSendEntry,
+    Signature, Token, TokenId, Transaction, TransactionAndDelta, TransactionData, ValidatorProof,
+    Vrf, ZkHasher,
 };
+use crate::crypto::VerifiableRandomFunction;
 use crate::mpn::circuits::MpnCircuit;
 use crate::mpn::MpnConfig;
 use crate::wallet::TxBuilder;
@@ -25,6 +27,46 @@ const MPN_LOG4_UPDATE_BATCH_SIZE: u8 = 4;
 
 const TESTNET_HEIGHT_LIMIT: u64 = 10000;
 
+// Header hashes of main network blocks, as (number, hash)
+const CHECKPOINTS: &[(u64, &str)] = &[];
+
+// Only public keys of the testnet validator and faucet are known, their private
+// keys are kept by the operators of the testnet
+const TESTNET_VALIDATOR: &str =
+    "ed4dea4c0152f850f6a561b418cf05da9dac8ed841f821b4b3d5f6df4f8496c3db";
+const TESTNET_VALIDATOR_VRF: &str =
+    "vrf865ff7665b3259787ab83e78f669ceb6e21e4d679613ca9d9e32fa9732c5d00f";
+pub const TESTNET_FAUCET: &str =
+    "ed4570841acb5662d7084baafadca3bfd21695875cb10159812bdca5891eeb43b3";
+const TESTNET_CHAIN_START_TIMESTAMP: u32 = 1790000000;
+const TESTNET_FAUCET_SUPPLY: u64 = 1_000_000_000 * UNIT;
+
+/// Block timing and reward parameters, overridable on test and regtest chains
+#[derive(Debug, Clone, Copy)]
+pub struct ConsensusParams {
+    pub slot_duration: u32,
+    pub slot_per_epoch: u32,
+    pub reward_ratio: u64,
+}
+
+impl Default for ConsensusParams {
+    fn default() -> Self {
+        Self {
+            slot_duration: 60,
+            slot_per_epoch: 10,
+            reward_ratio: 100_000, // 1/100_000 -> 0.01% of Treasury Supply per block
+        }
+    }
+}
+
+impl ConsensusParams {
+    pub fn apply(&self, conf: &mut BlockchainConfig) {
+        conf.slot_duration = self.slot_duration;
+        conf.slot_per_epoch = self.slot_per_epoch;
+        conf.reward_ratio = self.reward_ratio;
+    }
+}
+
 lazy_static! {
     pub static ref MPN_UPDATE_VK: zk::groth16::Groth16VerifyingKey =
         bincode::deserialize(&hex::decode("b02701884fb4065e5dec5456f29cbbf7b093b5847c56b7f6c1fb103851b674f9122395c01b2ac3015bbffddd0ccce114a8c239c56aa3543ba593e69f94a411230b6138bbfade4ac527e990466b1b625617f415f58d572e2b0f559e590180ee17005001160b651af92d477bc900a6f468abe5a03d8d16667e104721d84053149b8c8e6dbaaa04f767fe3480adf9ec4e2501948c01cd4d17416f97407c9b1b69bd004dbeefb3ab8a56893eb0efd44d13f740d479eb3b43d4b11b0e23f9bed985ac0a0033316f8dbcea7ba33a2e6e3225c09f3db359b808dcd316f27ac309886060cda95c63b1f274d2f15731dd2e54027173182b5f79b1b1875c11669b2a89584308f461ce1becda321c0ede1c8e060e3dea7255d464c93ce846d65d200327888a320043ba1a5d14a41af8c158ed640c8d3ea06a21525671261fd03f8050c6e25c643a6dfb27418d1b36c14c3ce4a035b22a07a70b43b2f39e4cc54ff9bcc27f36508f0a408446d47a5e520c14a809605865a074631777ba098eb61145839216fa571000c0bd67354bcfaff0ac9be6d6e60dd27ba907b73e48cd29c9d04bfb1648047d00e6e8357101d30b79946c6072c6967909b9aae7f069033cafaea578a6b2e0e6b2bfabd528e90c2d3424af26a7d26bf95dd06296c89ddd8a662c52756656304118dce1cb5ca358fa9726344e8c37eede52e11786758be88dab87d896216dc0291c8f250322ca0aff90cea90f5ac30a250a65e187464f11b76f15fe8fd5ae1a71fd02131af2f1585807ba1729693d7481ec47d7731eefba89272466472f6482d109004188bba4fc60efa79ea39994af0bf56accd370b06fdce321aa7c0d00d4bf8cfac3ef3408822145f58963bcddd84f1711752f24db6810bcfc10b9f2d1ee7601703e2da6f8c42ce2e771e85dc81f0f71d3ec1537848e1d29220136e4193ae98a17005732f4779ecd296857e4217453314ebc5b733d289cae7d2b4109ac8df4d7cf4b368c6942006c79503155fde7a4dfbd1840f3f8f8599dcfded2050bfc1c1f41d9a0931b52bd5ea22053e7913104eba04a68e4aa9991c74949ca80871c14744f0c247b6df3c6bd961430f1aa53b855967a91432ad5645876e6b67ccf29f0cb6b2197bdc3fae24a8f5c5215aab931e62b193e64b49c48f4e916a73a2752542b78c53b7b96ab8a819fd45c37c6bc5ef76fe5b7a1d8f74df6a776b413bb7bd385280300050000000000000043a6f266a47b03bf88f2e8fb15fb3b62d20e12b405a07f68d84b69de8a9c52b77149e9856ace5913d91955911dad1e060fe5fba59c82c7629ac19db1b1760672a3d34215e22c77c7b481297d7efe062e487d8db8643909a2654c79874516261100c9b604e4c7f22c43dee2283240d0c2674fb85723a67db1d3d5c155377aa3292cee05b6c43b27a1fe04d8878e288a6e0a61949e5da8f5b7a99af76f5fa65241b915d72a16bbf0d955895a08928ef87e887edd499f717722976e8b480fa78a0604009ec0881aefe81b75e6929d68c56910dad75d478c31bbb967a6955b557035d78de677634e91302adf5ab14acab5f5830e879b2724d16efe5e3bb96dd4e142ff07307a8a0dd9bd773383ef040084214e1fce0e4392ae140897bb4ea42bcce0a10d00c3666e30e7404067260d54ecb1602bcb9430efc437b303ad37f07b23879c3034600674f6312d98b4b609ef49eecb19166dd619041ce11112f183d7e8489c384c2279bd3cd78ef06b34a47e27e7f2239460faae4add23039d5878a5e73834c809009aa92b0e69272ad538986fd0ae0888df53c71fb9eae573e0eccba522cc3f75e28a0bf56b875197857359966c032da811041ad13ca15a76d9eafe616b0e1b4581ec8bb2dd44f77f92140ea154f5dd3f0f6ad196e19b08bf67743751b7e9727b1100").unwrap()).unwrap();
@@ -158,6 +200,7 @@ fn get_ziesha_token_creation_tx() -> Transaction {
 }
 
 pub fn get_blockchain_config() -> BlockchainConfig {
+    let params = ConsensusParams::default();
     let mpn_tx_delta = get_mpn_contract(
         MPN_LOG4_TREE_SIZE,
         MPN_LOG4_TOKENS_TREE_SIZE,
@@ -257,25 +300,150 @@ pub fn get_blockchain_config() -> BlockchainConfig {
 
         ziesha_token_id,
         genesis: blk,
-        reward_ratio: 100_000, // 1/100_000 -> 0.01% of Treasury Supply per block
+        reward_ratio: params.reward_ratio,
         max_block_size: MB as usize,
+        max_tx_execution_cost: 20_000,
+        max_block_execution_cost: 100_000,
 
         testnet_height_limit: Some(TESTNET_HEIGHT_LIMIT),
+        checkpoints: CHECKPOINTS
+            .iter()
+            .map(|(number, hash)| (*number, hex::decode(hash).unwrap().try_into().unwrap()))
+            .collect(),
         max_memo_length: 64,
-        slot_duration: 60,
-        slot_per_epoch: 10,
+        slot_duration: params.slot_duration,
+        slot_per_epoch: params.slot_per_epoch,
         chain_start_timestamp: CHAIN_START_TIMESTAMP,
         check_validator: true,
         max_validator_commission: Ratio(26), // 26 / 255 ~= 10%
     }
 }
 
+/// Initial state of a custom network, used for building private network genesis blocks
+#[derive(Debug, Clone)]
+pub struct GenesisParams {
+    pub chain_start_timestamp: u32,
+    pub validator: Address,
+    pub validator_vrf: <Vrf as VerifiableRandomFunction>::Pub,
+    pub validator_commission: Ratio,
+    pub validator_stake: Amount,
+    pub allocations: Vec<(Address, Amount)>,
+    /// Contracts deployed after the allocations, along with their initial states
+    pub contracts: Vec<(zk::ZkContract, zk::ZkDataPairs)>,
+}
+
+/// Builds a genesis block with the same token and MPN contract as the main network,
+/// but with the given staker, initial balances and contracts
+pub fn get_custom_genesis_block(params: &GenesisParams) -> Block {
+    let mpn_tx_delta = get_mpn_contract(
+        MPN_LOG4_TREE_SIZE,
+        MPN_LOG4_TOKENS_TREE_SIZE,
+        MPN_LOG4_DEPOSIT_BATCH_SIZE,
+        MPN_LOG4_WITHDRAW_BATCH_SIZE,
+    );
+    let mut blk = Block {
+        header: Header {
+            parent_hash: Default::default(),
+            number: 0,
+            block_root: Default::default(),
+            proof_of_stake: ProofOfStake {
+                timestamp: params.chain_start_timestamp,
+                validator: Default::default(),
+                proof: ValidatorProof::Unproven,
+            },
+        },
+        body: vec![
+            get_ziesha_token_creation_tx(),
+            mpn_tx_delta.tx,
+            Transaction {
+                memo: "Very first staker created!".into(),
+                src: Some(params.validator.clone()),
+                data: TransactionData::UpdateStaker {
+                    vrf_pub_key: params.validator_vrf.clone(),
+                    commission: params.validator_commission,
+                },
+                nonce: 0,
+                fee: Money::ziesha(0),
+                sig: Signature::Unsigned,
+            },
+            Transaction {
+                memo: "Very first delegation!".into(),
+                src: None,
+                data: TransactionData::Delegate {
+                    to: params.validator.clone(),
+                    amount: params.validator_stake,
+                },
+                nonce: 0,
+                fee: Money::ziesha(0),
+                sig: Signature::Unsigned,
+            },
+        ],
+    };
+    for (dst, amount) in params.allocations.iter() {
+        blk.body.push(Transaction {
+            memo: "".into(),
+            src: None,
+            data: TransactionData::RegularSend {
+                entries: vec![RegularSendEntry {
+                    dst: dst.clone(),
+                    amount: Money::ziesha(amount.0),
+                }],
+            },
+            nonce: 0,
+            fee: Money::ziesha(0),
+            sig: Signature::Unsigned,
+        });
+    }
+    for (contract, state) in params.contracts.iter() {
+        blk.body.push(Transaction {
+            memo: "".into(),
+            src: None,
+            data: TransactionData::CreateContract {
+                contract: contract.clone(),
+                state: Some(state.clone()),
+            },
+            nonce: 0,
+            fee: Money::ziesha(0),
+            sig: Signature::Unsigned,
+        });
+    }
+    blk
+}
+
+/// Main network rules running on top of a custom genesis block
+pub fn get_custom_blockchain_config(genesis: Block) -> BlockchainConfig {
+    let mut conf = get_blockchain_config();
+    conf.chain_start_timestamp = genesis.header.proof_of_stake.timestamp;
+    conf.genesis = genesis;
+    conf.testnet_height_limit = None;
+    conf.checkpoints.clear();
+    conf
+}
+
+/// Public test network with the main network rules, where most of the coins belong to
+/// the faucet
+pub fn get_testnet_blockchain_config() -> BlockchainConfig {
+    get_custom_blockchain_config(get_custom_genesis_block(&GenesisParams {
+        chain_start_timestamp: TESTNET_CHAIN_START_TIMESTAMP,
+        validator: TESTNET_VALIDATOR.parse().unwrap(),
+        validator_vrf: TESTNET_VALIDATOR_VRF.parse().unwrap(),
+        validator_commission: Ratio(12),
+        validator_stake: Amount(1_000_000 * UNIT),
+        allocations: vec![(
+            TESTNET_FAUCET.parse().unwrap(),
+            Amount(TESTNET_FAUCET_SUPPLY),
+        )],
+        contracts: vec![],
+    }))
+}
+
 pub fn get_dev_blockchain_config(
     validator: &TxBuilder,
     user: &TxBuilder,
     small_mpn: bool,
 ) -> BlockchainConfig {
     let mut conf = get_blockchain_config();
+    conf.checkpoints.clear();
 
     if small_mpn {
         let log4_tree_size = 10;
@@ -286,7 +454,7 @@ pub fn get_dev_blockchain_config(
 
         let mut rng = ChaChaRng::from_seed([0u8; 32]);
 
-        log::info!("Generating MPN params...");
+        tracing::info!("Generating MPN params...");
         let deposit_params =
             bellman::groth16::generate_random_parameters::<bls12_381::Bls12, _, _>(
                 crate::mpn::circuits::DepositCircuit::empty(
@@ -316,7 +484,7 @@ pub fn get_dev_blockchain_config(
             &mut rng,
         )
         .unwrap();
-        log::info!("Done generating MPN params!");
+        tracing::info!("Done generating MPN params!");
 
         conf.mpn_config = MpnConfig {
             mpn_contract_id: conf.mpn_config.mpn_contract_id,
@@ -372,8 +540,31 @@ pub fn get_dev_blockchain_config(
     conf
 }
 
+pub fn get_regtest_blockchain_config(
+    validator: &TxBuilder,
+    user: &TxBuilder,
+    params: ConsensusParams,
+) -> BlockchainConfig {
+    let mut conf = get_dev_blockchain_config(validator, user, false);
+    params.apply(&mut conf);
+    conf.check_validator = false;
+    conf.testnet_height_limit = None;
+    conf.mpn_config.mpn_num_update_batches = 0;
+    conf.mpn_config.mpn_num_deposit_batches = 0;
+    conf.mpn_config.mpn_num_withdraw_batches = 0;
+    conf
+}
+
 #[cfg(test)]
 pub fn get_test_blockchain_config() -> BlockchainConfig {
+    get_test_blockchain_config_with(ConsensusParams {
+        slot_duration: 5,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+pub fn get_test_blockchain_config_with(params: ConsensusParams) -> BlockchainConfig {
     let mpn_tx_delta = get_test_mpn_contract();
     let mpn_contract_id = ContractId::new(&mpn_tx_delta.tx);
 
@@ -394,9 +585,10 @@ pub fn get_test_blockchain_config() -> BlockchainConfig {
         update_vk: zk::ZkVerifierKey::Dummy,
     };
     conf.testnet_height_limit = None;
+    conf.checkpoints.clear();
     conf.chain_start_timestamp = 0;
     conf.check_validator = false;
-    conf.slot_duration = 5;
+    params.apply(&mut conf);
 
     conf.genesis.body[1] = get_test_mpn_contract().tx;
     conf.genesis.body.drain(2..);
@@ -1,4 +1,11 @@
-use crate::node::{HeartbeatIntervals, NodeOptions};
+use super::UNIT;
+use crate::client::{messages::Offense, MAX_IN_FLIGHT_REQUESTS};
+use crate::core::Amount;
+use crate::node::{
+    FaucetOptions, HeartbeatIntervals, MetricsOptions, NodeOptions, PunishmentPolicy,
+    TelemetryOptions,
+};
+use crate::wallet::TxBuilder;
 use std::time::Duration;
 
 pub fn get_node_options() -> NodeOptions {
@@ -15,19 +22,105 @@ pub fn get_node_options() -> NodeOptions {
             generate_block: Duration::from_secs(3),
         },
         num_peers: 8,
+        max_outgoing_requests: MAX_IN_FLIGHT_REQUESTS,
         max_blocks_fetch: 16,
-        default_punish: 60,
-        no_response_punish: 600,
-        invalid_data_punish: 3600,
-        max_punish: 7200,
-        incorrect_chain_punish: 3600,
+        max_pending_headers: 4096,
+        block_download_peers: 4,
+        punishments: PunishmentPolicy {
+            durations: [
+                (Offense::InvalidBlock, 3600),
+                (Offense::BadProof, 3600),
+                (Offense::BadSignature, 3600),
+                (Offense::Spam, 60),
+                (Offense::Timeout, 0),
+            ]
+            .into_iter()
+            .collect(),
+            max: 7200,
+        },
         candidate_remove_threshold: 3600,
         mempool_max_fetch: 1000,
+        mempool_max_size: 10000,
+        mempool_min_fee: Amount(0),
+        mempool_min_fee_per_byte: Amount(0),
+        mempool_contract_policy: Default::default(),
         max_block_time_difference: 120,
         automatic_block_generation: true,
+        regtest: false,
+        prune: None,
+        rollback_retention: None,
+        snapshot_sync: false,
+        relay_txs: false,
+        faucet: None,
+        #[cfg(feature = "explorer")]
+        explorer: None,
+        webhooks: Vec::new(),
+        #[cfg(feature = "zmq")]
+        zmq: None,
+        telemetry: None,
+        metrics: None,
+        cluster: None,
+        access_list: None,
+        mempool_db: None,
+        ban_db: None,
+        restart: None,
+        clock: Default::default(),
+    }
+}
+
+pub fn get_regtest_options() -> NodeOptions {
+    NodeOptions {
+        automatic_block_generation: false,
+        regtest: true,
+        ..get_node_options()
+    }
+}
+
+pub fn get_testnet_faucet_options(wallet: TxBuilder) -> FaucetOptions {
+    FaucetOptions {
+        wallet,
+        amount: Amount(1000 * UNIT),
+        interval: 3600,
+    }
+}
+
+pub fn get_telemetry_options(url: String) -> TelemetryOptions {
+    TelemetryOptions {
+        url,
+        interval: Duration::from_secs(3600),
     }
 }
 
+pub fn get_metrics_options(db: Option<std::path::PathBuf>) -> MetricsOptions {
+    MetricsOptions {
+        db,
+        interval: Duration::from_secs(60),
+        retention: 7 * 24 * 3600,
+    }
+}
+
+#[cfg(feature = "explorer")]
+pub fn get_explorer_options(db: Option<std::path::PathBuf>) -> crate::node::ExplorerOptions {
+    crate::node::ExplorerOptions {
+        db,
+        max_blocks_per_round: 100,
+    }
+}
+
+#[cfg(feature = "upnp")]
+pub fn get_upnp_options(local_port: u16, external_port: u16) -> crate::node::upnp::UpnpOptions {
+    crate::node::upnp::UpnpOptions {
+        local_port,
+        external_port,
+        lease_duration: 3600,
+    }
+}
+
+#[cfg(feature = "libp2p")]
+pub fn get_libp2p_options(listen: std::net::SocketAddr) -> crate::node::transport::Libp2pOptions {
+    crate::node::transport::Libp2pOptions { listen }
+}
+
 pub fn get_simulator_options() -> NodeOptions {
     NodeOptions {
         tx_max_time_alive: None,
@@ -42,15 +135,40 @@ pub fn get_simulator_options() -> NodeOptions {
             generate_block: Duration::from_millis(300),
         },
         num_peers: 8,
+        max_outgoing_requests: MAX_IN_FLIGHT_REQUESTS,
         max_blocks_fetch: 16,
-        default_punish: 0,
-        no_response_punish: 0,
-        invalid_data_punish: 0,
-        max_punish: 0,
-        incorrect_chain_punish: 0,
+        max_pending_headers: 4096,
+        block_download_peers: 4,
+        punishments: PunishmentPolicy {
+            durations: Default::default(),
+            max: 0,
+        },
         candidate_remove_threshold: 600,
         mempool_max_fetch: 1000,
+        mempool_max_size: 10000,
+        mempool_min_fee: Amount(0),
+        mempool_min_fee_per_byte: Amount(0),
+        mempool_contract_policy: Default::default(),
         max_block_time_difference: 120,
         automatic_block_generation: false,
+        regtest: false,
+        prune: None,
+        rollback_retention: None,
+        snapshot_sync: false,
+        relay_txs: false,
+        faucet: None,
+        #[cfg(feature = "explorer")]
+        explorer: None,
+        webhooks: Vec::new(),
+        #[cfg(feature = "zmq")]
+        zmq: None,
+        telemetry: None,
+        metrics: None,
+        cluster: None,
+        access_list: None,
+        mempool_db: None,
+        ban_db: None,
+        restart: None,
+        clock: Default::default(),
     }
 }
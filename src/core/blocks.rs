@@ -13,8 +13,24 @@ pub struct Block<H: Hash, S: SignatureScheme, V: VerifiableRandomFunction> {
     pub body: Vec<Transaction<H, S, V>>,
 }
 
+/// Size figures of a block, kept next to its header so that throughput of the chain
+/// can be shown without downloading the bodies. They are not committed in the header,
+/// peers serving them are trusted.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlockMeta {
+    pub tx_count: u64,
+    /// Size of the bincode encoded transactions
+    pub body_size: u64,
+}
+
 impl<H: Hash, S: SignatureScheme, V: VerifiableRandomFunction> Block<H, S, V> {
     pub fn merkle_tree(&self) -> MerkleTree<H> {
         MerkleTree::<H>::new(self.body.iter().map(|tx| tx.hash()).collect())
     }
+    pub fn meta(&self) -> BlockMeta {
+        BlockMeta {
+            tx_count: self.body.len() as u64,
+            body_size: bincode::serialized_size(&self.body).unwrap(),
+        }
+    }
 }
@@ -11,12 +11,13 @@ use crate::zk;
 use std::str::FromStr;
 use thiserror::Error;
 
+pub use blocks::BlockMeta;
 pub use money::Amount;
 pub use money::Decimal;
 pub use transaction::{Money, Ratio};
 
 pub type Hasher = hash::Sha3Hasher;
-pub type Signer = crypto::ed25519::Ed25519<Hasher>;
+pub type Signer = crypto::tagged::TaggedScheme<Hasher>;
 pub type Vrf = crypto::vrf::VRF;
 
 pub type ZkHasher = crate::zk::PoseidonHasher;
@@ -134,6 +135,20 @@ impl GeneralTransaction {
             }
         }
     }
+    /// Whether all of the addresses receiving funds or rights are valid
+    pub fn has_valid_destinations(&self) -> bool {
+        match self {
+            GeneralTransaction::TransactionAndDelta(tx_delta) => tx_delta
+                .tx
+                .destinations()
+                .iter()
+                .all(|addr| addr.validate().is_ok()),
+            GeneralTransaction::MpnWithdraw(mpn_withdraw) => {
+                mpn_withdraw.payment.dst.validate().is_ok()
+            }
+            _ => true,
+        }
+    }
     pub fn nonce(&self) -> u32 {
         match self {
             GeneralTransaction::TransactionAndDelta(tx_delta) => tx_delta.tx.nonce,
@@ -142,6 +157,17 @@ impl GeneralTransaction {
             GeneralTransaction::MpnWithdraw(mpn_withdraw) => mpn_withdraw.zk_nonce,
         }
     }
+    pub fn fee(&self) -> Money {
+        match self {
+            GeneralTransaction::TransactionAndDelta(tx_delta) => tx_delta.tx.fee,
+            GeneralTransaction::MpnDeposit(mpn_deposit) => mpn_deposit.payment.fee,
+            GeneralTransaction::MpnTransaction(mpn_tx) => mpn_tx.fee,
+            GeneralTransaction::MpnWithdraw(mpn_withdraw) => mpn_withdraw.payment.fee,
+        }
+    }
+    pub fn size(&self) -> usize {
+        bincode::serialized_size(self).unwrap() as usize
+    }
     pub fn sender(&self) -> GeneralAddress {
         match self {
             GeneralTransaction::TransactionAndDelta(tx_delta) => {
@@ -21,6 +21,8 @@ pub enum ParseDecimalError {
     #[error("amount invalid")]
     Invalid,
 }
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Decimal {
     pub value: u64,
     pub num_decimals: u8,
@@ -456,9 +456,51 @@ impl<H: Hash, S: SignatureScheme, V: VerifiableRandomFunction> Transaction<H, S,
                 Signature::Unsigned => false,
                 Signature::Signed(sig) => {
                     let bytes = bincode::serialize(&self.sig_state_excluded()).unwrap();
+                    // Checked with the scheme the source address is tagged with
                     S::verify(pk, &bytes, sig)
                 }
             },
         }
     }
+    /// Addresses whose activity history this transaction belongs to
+    pub fn involved_addresses(&self) -> Vec<S::Pub> {
+        let mut addresses = Vec::new();
+        if let Some(src) = &self.src {
+            addresses.push(src.clone());
+        }
+        match &self.data {
+            TransactionData::RegularSend { entries } => {
+                addresses.extend(entries.iter().map(|e| e.dst.clone()));
+            }
+            TransactionData::Delegate { to, .. } | TransactionData::AutoDelegate { to, .. } => {
+                addresses.push(to.clone());
+            }
+            TransactionData::Undelegate { from, .. } => {
+                addresses.push(from.clone());
+            }
+            _ => {}
+        }
+        let mut unique = Vec::new();
+        for addr in addresses {
+            if !unique.contains(&addr) {
+                unique.push(addr);
+            }
+        }
+        unique
+    }
+    /// Addresses this transaction sends funds or rights to
+    pub fn destinations(&self) -> Vec<&S::Pub> {
+        match &self.data {
+            TransactionData::RegularSend { entries } => entries.iter().map(|e| &e.dst).collect(),
+            TransactionData::Delegate { to, .. } | TransactionData::AutoDelegate { to, .. } => {
+                vec![to]
+            }
+            TransactionData::CreateToken { token } => token.minter.iter().collect(),
+            TransactionData::UpdateToken {
+                update: TokenUpdate::ChangeMinter { minter },
+                ..
+            } => vec![minter],
+            _ => vec![],
+        }
+    }
 }
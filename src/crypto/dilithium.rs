@@ -0,0 +1,136 @@
+use super::SignatureScheme;
+
+use crate::core::hash::Hash;
+use fips204::ml_dsa_44;
+use fips204::traits::{KeyGen, SerDes, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use thiserror::Error;
+
+const SIGNING_CONTEXT: &[u8] = b"ZieshaSignature";
+
+pub const PUBLIC_KEY_LEN: usize = ml_dsa_44::PK_LEN;
+pub const SIGNATURE_LEN: usize = ml_dsa_44::SIG_LEN;
+
+/// Post-quantum signatures (ML-DSA-44, the standardized Dilithium2). Keys and
+/// signatures are a few kilobytes, much larger than Ed25519 ones, this scheme exists
+/// to measure what that costs in block space and verification time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq, Hash, Default)]
+pub struct Dilithium<H: Hash>(std::marker::PhantomData<H>);
+
+#[derive(Clone)]
+pub struct PrivateKey(pub ml_dsa_44::PrivateKey);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PublicKey(#[serde(with = "serde_bytes")] pub Vec<u8>);
+
+impl From<PrivateKey> for PublicKey {
+    fn from(priv_key: PrivateKey) -> Self {
+        Self(priv_key.0.get_public_key().into_bytes().to_vec())
+    }
+}
+
+impl Default for PublicKey {
+    fn default() -> Self {
+        Self(vec![0u8; PUBLIC_KEY_LEN])
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature(#[serde(with = "serde_bytes")] pub Vec<u8>);
+
+impl Default for Signature {
+    fn default() -> Self {
+        Self(vec![0u8; SIGNATURE_LEN])
+    }
+}
+
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(&self.0))
+    }
+}
+
+impl<H: Hash> SignatureScheme for Dilithium<H> {
+    type PubParseError = ParsePublicKeyError;
+    type Pub = PublicKey;
+    type Priv = PrivateKey;
+    type Sig = Signature;
+    fn generate_keys(seed: &[u8]) -> (PublicKey, PrivateKey) {
+        let mut xi = [0u8; 32];
+        xi.copy_from_slice(&H::hash(seed).as_ref()[..32]);
+        let (pk, sk) = ml_dsa_44::KG::keygen_from_seed(&xi);
+        (PublicKey(pk.into_bytes().to_vec()), PrivateKey(sk))
+    }
+    fn sign(sk: &PrivateKey, message: &[u8]) -> Signature {
+        // Zero randomness gives the deterministic variant of ML-DSA
+        Signature(
+            sk.0.try_sign_with_seed(&[0u8; 32], message, SIGNING_CONTEXT)
+                .unwrap()
+                .to_vec(),
+        )
+    }
+    fn verify(pk: &PublicKey, message: &[u8], sig: &Signature) -> bool {
+        match (
+            <[u8; PUBLIC_KEY_LEN]>::try_from(pk.0.as_slice()),
+            <[u8; SIGNATURE_LEN]>::try_from(sig.0.as_slice()),
+        ) {
+            (Ok(pk), Ok(sig)) => ml_dsa_44::PublicKey::try_from_bytes(pk)
+                .map(|pk| pk.verify(message, &sig, SIGNING_CONTEXT))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "dl{}", hex::encode(&self.0))
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ParsePublicKeyError {
+    #[error("public key invalid")]
+    Invalid,
+}
+
+impl FromStr for PublicKey {
+    type Err = ParsePublicKeyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex_key = s.strip_prefix("dl").ok_or(ParsePublicKeyError::Invalid)?;
+        let bytes = hex::decode(hex_key).map_err(|_| ParsePublicKeyError::Invalid)?;
+        if bytes.len() != PUBLIC_KEY_LEN {
+            return Err(ParsePublicKeyError::Invalid);
+        }
+        Ok(PublicKey(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dilithium_signature_verification() {
+        let (pk, sk) = Dilithium::<crate::core::Hasher>::generate_keys(b"ABC");
+        let msg = b"salam1";
+        let fake_msg = b"salam2";
+        let sig = Dilithium::<crate::core::Hasher>::sign(&sk, msg);
+
+        assert_eq!(sig.0.len(), SIGNATURE_LEN);
+        assert_eq!(sig, Dilithium::<crate::core::Hasher>::sign(&sk, msg));
+        assert!(Dilithium::<crate::core::Hasher>::verify(&pk, msg, &sig));
+        assert!(!Dilithium::<crate::core::Hasher>::verify(
+            &pk, fake_msg, &sig
+        ));
+        assert!(!Dilithium::<crate::core::Hasher>::verify(
+            &PublicKey::default(),
+            msg,
+            &sig
+        ));
+
+        assert_eq!(pk.to_string().parse::<PublicKey>().unwrap(), pk);
+        assert_eq!(PublicKey::from(sk), pk);
+    }
+}
@@ -92,6 +92,49 @@ impl std::fmt::Display for PublicKey {
 pub enum ParsePublicKeyError {
     #[error("public key invalid")]
     Invalid,
+    #[error("public key checksum mismatch")]
+    ChecksumMismatch,
+    #[error("public key has a small order and cannot be owned by anyone")]
+    SmallOrder,
+}
+
+/// Hex digits of the key, uppercased where the corresponding nibble of the hash of the
+/// lowercase digits is at least 8 (Like EIP-55)
+fn checksummed(lower_hex: &str) -> String {
+    let hash = crate::core::Hasher::hash(lower_hex.as_bytes());
+    lower_hex
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let byte = hash.as_ref()[i / 2];
+            let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+impl PublicKey {
+    /// Checks that the key is a point someone can actually hold the private key of.
+    /// Small-order points (Including the all-zero key) can receive funds, but nobody is
+    /// able to spend them.
+    pub fn validate(&self) -> Result<(), ParsePublicKeyError> {
+        let point = curve25519_dalek::edwards::CompressedEdwardsY(self.0.to_bytes())
+            .decompress()
+            .ok_or(ParsePublicKeyError::Invalid)?;
+        if point.is_small_order() {
+            return Err(ParsePublicKeyError::SmallOrder);
+        }
+        Ok(())
+    }
+    /// Mixed-case representation carrying a checksum, which is verified when parsed
+    pub fn to_checksummed_string(&self) -> String {
+        let s = self.to_string();
+        format!("ed{}", checksummed(&s[2..]))
+    }
 }
 
 impl FromStr for PublicKey {
@@ -101,6 +144,12 @@ impl FromStr for PublicKey {
             return Err(ParsePublicKeyError::Invalid);
         }
         s = &s[2..];
+        // Single-case keys carry no checksum
+        let has_lower = s.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = s.chars().any(|c| c.is_ascii_uppercase());
+        if has_lower && has_upper && checksummed(&s.to_ascii_lowercase()) != s {
+            return Err(ParsePublicKeyError::ChecksumMismatch);
+        }
         let bytes = hex::decode(s)
             .map_err(|_| ParsePublicKeyError::Invalid)?
             .into_iter()
@@ -133,4 +182,35 @@ mod tests {
         assert!(Ed25519::<crate::core::Hasher>::verify(&pk, msg, &sig));
         assert!(!Ed25519::<crate::core::Hasher>::verify(&pk, fake_msg, &sig));
     }
+
+    #[test]
+    fn test_public_key_validation() {
+        let (pk, _) = Ed25519::<crate::core::Hasher>::generate_keys(b"ABC");
+        assert!(pk.validate().is_ok());
+        assert!(matches!(
+            PublicKey::default().validate(),
+            Err(ParsePublicKeyError::SmallOrder)
+        ));
+
+        let checksummed = pk.to_checksummed_string();
+        assert_ne!(checksummed, pk.to_string());
+        assert_eq!(checksummed.parse::<PublicKey>().unwrap(), pk);
+        assert_eq!(pk.to_string().parse::<PublicKey>().unwrap(), pk);
+        assert_eq!(
+            pk.to_string().to_uppercase().parse::<PublicKey>().unwrap(),
+            pk
+        );
+
+        // Flip the case of a single letter
+        let pos = checksummed[2..]
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap()
+            + 2;
+        let mut typo = checksummed.clone().into_bytes();
+        typo[pos] ^= 0x20;
+        assert!(matches!(
+            String::from_utf8(typo).unwrap().parse::<PublicKey>(),
+            Err(ParsePublicKeyError::ChecksumMismatch)
+        ));
+    }
 }
@@ -90,6 +90,15 @@ impl<H: Hash> MerkleTree<H> {
         proof
     }
 
+    /// Checks a proof generated by `prove` without having the tree
+    pub fn verify(root: &H::Output, leaf: &H::Output, proof: &[H::Output]) -> bool {
+        let mut curr = *leaf;
+        for entry in proof {
+            curr = merge_hash::<H>(&curr, entry);
+        }
+        curr == *root
+    }
+
     pub fn new(leaves: Vec<H::Output>) -> MerkleTree<H> {
         if leaves.is_empty() {
             return MerkleTree::<H> {
@@ -127,6 +136,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_merkle_verify() {
+        let tree = MerkleTree::<Sha3Hasher>::new((0..7).map(|i| Sha3Hasher::hash(&[i])).collect());
+        let proof = tree.prove(3);
+        assert!(MerkleTree::<Sha3Hasher>::verify(
+            &tree.root(),
+            &Sha3Hasher::hash(&[3]),
+            &proof
+        ));
+        assert!(!MerkleTree::<Sha3Hasher>::verify(
+            &tree.root(),
+            &Sha3Hasher::hash(&[4]),
+            &proof
+        ));
+    }
+
     #[test]
     fn test_calculation() {
         assert_eq!(MerkleTree::<Sha3Hasher>::new(Vec::new()).root(), [0u8; 32]);
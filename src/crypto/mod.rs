@@ -8,8 +8,11 @@ use serde::Serialize;
 
 pub mod merkle;
 
+#[cfg(feature = "dilithium")]
+pub mod dilithium;
 pub mod ed25519;
 pub mod jubjub;
+pub mod tagged;
 pub mod vrf;
 
 pub trait SignatureScheme: Clone + Serialize + Default {
@@ -0,0 +1,343 @@
+use super::ed25519::{self, Ed25519};
+use super::SignatureScheme;
+
+use crate::core::hash::Hash;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+use thiserror::Error;
+
+const SCHNORR_SIGNING_CONTEXT: &[u8] = b"ZieshaSignature";
+
+/// Signature schemes an address may belong to. Addresses are tagged with their
+/// scheme, so that keys of different schemes can coexist on the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SchemeTag {
+    Ed25519,
+    /// Schnorr signatures over Ristretto25519 (sr25519)
+    Schnorr,
+    /// Reserved, no BLS keys are accepted yet
+    Bls,
+}
+
+impl SchemeTag {
+    fn prefix(&self) -> &'static str {
+        match self {
+            Self::Ed25519 => "ed",
+            Self::Schnorr => "sr",
+            Self::Bls => "bl",
+        }
+    }
+    fn byte(&self) -> u8 {
+        match self {
+            Self::Ed25519 => 0,
+            Self::Schnorr => 1,
+            Self::Bls => 2,
+        }
+    }
+}
+
+/// Signature scheme dispatching on the tag of the public key. Keys are generated for
+/// Ed25519 unless another scheme is asked for.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Eq, Hash, Default)]
+pub struct TaggedScheme<H: Hash>(std::marker::PhantomData<H>);
+
+#[derive(Clone)]
+pub enum PrivateKey {
+    Ed25519(ed25519::PrivateKey),
+    Schnorr(schnorrkel::Keypair),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PublicKey {
+    Ed25519(ed25519::PublicKey),
+    Schnorr(schnorrkel::PublicKey),
+}
+
+/// Signature bytes, interpreted according to the scheme of the signer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature(pub [u8; 64]);
+
+impl Default for Signature {
+    fn default() -> Self {
+        Self([0u8; 64])
+    }
+}
+
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+// Encoded like an Ed25519 signature
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+        let mut seq = serializer.serialize_tuple(64)?;
+        for byte in self.0.iter() {
+            seq.serialize_element(byte)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SignatureVisitor;
+        impl<'de> de::Visitor<'de> for SignatureVisitor {
+            type Value = Signature;
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "64 bytes of signature")
+            }
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Signature, A::Error> {
+                let mut bytes = [0u8; 64];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                Ok(Signature(bytes))
+            }
+        }
+        deserializer.deserialize_tuple(64, SignatureVisitor)
+    }
+}
+
+impl PublicKey {
+    pub fn scheme(&self) -> SchemeTag {
+        match self {
+            Self::Ed25519(_) => SchemeTag::Ed25519,
+            Self::Schnorr(_) => SchemeTag::Schnorr,
+        }
+    }
+    /// Checks that the key is a point someone can actually hold the private key of
+    pub fn validate(&self) -> Result<(), ParsePublicKeyError> {
+        match self {
+            Self::Ed25519(pk) => Ok(pk.validate()?),
+            Self::Schnorr(_) => Ok(()),
+        }
+    }
+    /// Ed25519 keys are encoded as their 32 bytes, as they were before addresses were
+    /// tagged. Keys of other schemes are prefixed with the byte of their tag.
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Ed25519(pk) => pk.0.to_bytes().to_vec(),
+            Self::Schnorr(pk) => {
+                let mut bytes = vec![SchemeTag::Schnorr.byte()];
+                bytes.extend(pk.to_bytes());
+                bytes
+            }
+        }
+    }
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParsePublicKeyError> {
+        if bytes.len() == 32 {
+            return Ok(Self::Ed25519(ed25519::PublicKey(
+                ed25519_dalek::PublicKey::from_bytes(bytes)
+                    .map_err(|_| ParsePublicKeyError::Invalid)?,
+            )));
+        }
+        match bytes.split_first() {
+            Some((tag, key)) if *tag == SchemeTag::Schnorr.byte() => Ok(Self::Schnorr(
+                schnorrkel::PublicKey::from_bytes(key).map_err(|_| ParsePublicKeyError::Invalid)?,
+            )),
+            Some((tag, _)) if *tag == SchemeTag::Bls.byte() => {
+                Err(ParsePublicKeyError::UnsupportedScheme)
+            }
+            _ => Err(ParsePublicKeyError::Invalid),
+        }
+    }
+}
+
+impl Default for PublicKey {
+    fn default() -> Self {
+        Self::Ed25519(Default::default())
+    }
+}
+
+impl From<PrivateKey> for PublicKey {
+    fn from(priv_key: PrivateKey) -> Self {
+        match priv_key {
+            PrivateKey::Ed25519(sk) => Self::Ed25519(sk.into()),
+            PrivateKey::Schnorr(keypair) => Self::Schnorr(keypair.public),
+        }
+    }
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_bytes::Bytes::new(&self.to_bytes()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = serde_bytes::ByteBuf::deserialize(deserializer)?;
+        Self::from_bytes(&bytes).map_err(de::Error::custom)
+    }
+}
+
+impl std::fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Ed25519(pk) => write!(f, "{}", pk),
+            Self::Schnorr(pk) => write!(
+                f,
+                "{}{}",
+                SchemeTag::Schnorr.prefix(),
+                hex::encode(pk.to_bytes())
+            ),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ParsePublicKeyError {
+    #[error("public key invalid")]
+    Invalid,
+    #[error("signature scheme of the public key is not supported")]
+    UnsupportedScheme,
+    #[error("{0}")]
+    Ed25519(#[from] ed25519::ParsePublicKeyError),
+}
+
+impl FromStr for PublicKey {
+    type Err = ParsePublicKeyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let prefix = s.get(..2).ok_or(ParsePublicKeyError::Invalid)?;
+        match prefix.to_lowercase() {
+            p if p == SchemeTag::Ed25519.prefix() => Ok(Self::Ed25519(s.parse()?)),
+            p if p == SchemeTag::Schnorr.prefix() => {
+                let bytes = hex::decode(&s[2..]).map_err(|_| ParsePublicKeyError::Invalid)?;
+                Ok(Self::Schnorr(
+                    schnorrkel::PublicKey::from_bytes(&bytes)
+                        .map_err(|_| ParsePublicKeyError::Invalid)?,
+                ))
+            }
+            p if p == SchemeTag::Bls.prefix() => Err(ParsePublicKeyError::UnsupportedScheme),
+            _ => Err(ParsePublicKeyError::Invalid),
+        }
+    }
+}
+
+impl<H: Hash> TaggedScheme<H> {
+    /// Derives the keys of the given scheme from the seed, `None` if the scheme is not
+    /// supported yet
+    pub fn generate_keys_for(scheme: SchemeTag, seed: &[u8]) -> Option<(PublicKey, PrivateKey)> {
+        match scheme {
+            SchemeTag::Ed25519 => {
+                let (pk, sk) = Ed25519::<H>::generate_keys(seed);
+                Some((PublicKey::Ed25519(pk), PrivateKey::Ed25519(sk)))
+            }
+            SchemeTag::Schnorr => {
+                let keypair = schnorrkel::MiniSecretKey::from_bytes(H::hash(seed).as_ref())
+                    .ok()?
+                    .expand_to_keypair(schnorrkel::ExpansionMode::Ed25519);
+                Some((
+                    PublicKey::Schnorr(keypair.public),
+                    PrivateKey::Schnorr(keypair),
+                ))
+            }
+            SchemeTag::Bls => None,
+        }
+    }
+}
+
+impl<H: Hash> SignatureScheme for TaggedScheme<H> {
+    type PubParseError = ParsePublicKeyError;
+    type Pub = PublicKey;
+    type Priv = PrivateKey;
+    type Sig = Signature;
+    fn generate_keys(seed: &[u8]) -> (PublicKey, PrivateKey) {
+        Self::generate_keys_for(SchemeTag::Ed25519, seed).unwrap()
+    }
+    fn sign(sk: &PrivateKey, message: &[u8]) -> Signature {
+        match sk {
+            PrivateKey::Ed25519(sk) => Signature(Ed25519::<H>::sign(sk, message).0.to_bytes()),
+            PrivateKey::Schnorr(keypair) => {
+                let ctx = schnorrkel::signing_context(SCHNORR_SIGNING_CONTEXT);
+                Signature(keypair.sign(ctx.bytes(message)).to_bytes())
+            }
+        }
+    }
+    fn verify(pk: &PublicKey, message: &[u8], sig: &Signature) -> bool {
+        match pk {
+            PublicKey::Ed25519(pk) => ed25519_dalek::Signature::from_bytes(&sig.0)
+                .map(|sig| Ed25519::<H>::verify(pk, message, &ed25519::Signature(sig)))
+                .unwrap_or(false),
+            PublicKey::Schnorr(pk) => schnorrkel::Signature::from_bytes(&sig.0)
+                .map(|sig| {
+                    let ctx = schnorrkel::signing_context(SCHNORR_SIGNING_CONTEXT);
+                    pk.verify(ctx.bytes(message), &sig).is_ok()
+                })
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Hasher;
+
+    type Scheme = TaggedScheme<Hasher>;
+
+    #[test]
+    fn test_tagged_signature_verification() {
+        let (ed_pk, ed_sk) = Scheme::generate_keys_for(SchemeTag::Ed25519, b"ABC").unwrap();
+        let (sr_pk, sr_sk) = Scheme::generate_keys_for(SchemeTag::Schnorr, b"ABC").unwrap();
+        assert!(Scheme::generate_keys_for(SchemeTag::Bls, b"ABC").is_none());
+        assert_eq!(ed_pk.scheme(), SchemeTag::Ed25519);
+        assert_eq!(sr_pk.scheme(), SchemeTag::Schnorr);
+        assert_eq!(Scheme::generate_keys(b"ABC").0, ed_pk);
+
+        let msg = b"salam1";
+        let ed_sig = Scheme::sign(&ed_sk, msg);
+        let sr_sig = Scheme::sign(&sr_sk, msg);
+        assert!(Scheme::verify(&ed_pk, msg, &ed_sig));
+        assert!(Scheme::verify(&sr_pk, msg, &sr_sig));
+        assert!(!Scheme::verify(&ed_pk, b"salam2", &ed_sig));
+        assert!(!Scheme::verify(&sr_pk, b"salam2", &sr_sig));
+
+        // Signatures are checked with the scheme of the key
+        assert!(!Scheme::verify(&ed_pk, msg, &sr_sig));
+        assert!(!Scheme::verify(&sr_pk, msg, &ed_sig));
+    }
+
+    #[test]
+    fn test_tagged_public_key_encoding() {
+        let (ed_pk, ed_sk) = Scheme::generate_keys_for(SchemeTag::Ed25519, b"ABC").unwrap();
+        let (sr_pk, _) = Scheme::generate_keys_for(SchemeTag::Schnorr, b"ABC").unwrap();
+
+        // Ed25519 keys and signatures are encoded as before they were tagged
+        let (plain_pk, plain_sk) = Ed25519::<Hasher>::generate_keys(b"ABC");
+        assert_eq!(
+            bincode::serialize(&ed_pk).unwrap(),
+            bincode::serialize(&plain_pk).unwrap()
+        );
+        assert_eq!(
+            bincode::serialize(&Scheme::sign(&ed_sk, b"msg")).unwrap(),
+            bincode::serialize(&Ed25519::<Hasher>::sign(&plain_sk, b"msg")).unwrap()
+        );
+        assert_eq!(ed_pk.to_string(), plain_pk.to_string());
+
+        for pk in [ed_pk, sr_pk.clone(), PublicKey::default()] {
+            let bin = bincode::serialize(&pk).unwrap();
+            assert_eq!(bincode::deserialize::<PublicKey>(&bin).unwrap(), pk);
+            let json = serde_json::to_string(&pk).unwrap();
+            assert_eq!(serde_json::from_str::<PublicKey>(&json).unwrap(), pk);
+            assert_eq!(pk.to_string().parse::<PublicKey>().unwrap(), pk);
+        }
+        assert!(sr_pk.to_string().starts_with("sr"));
+        assert!(sr_pk.validate().is_ok());
+
+        let bls = format!("bl{}", "00".repeat(48));
+        assert!(matches!(
+            bls.parse::<PublicKey>(),
+            Err(ParsePublicKeyError::UnsupportedScheme)
+        ));
+        assert!(
+            bincode::deserialize::<PublicKey>(&bincode::serialize(&vec![2u8; 49]).unwrap())
+                .is_err()
+        );
+    }
+}
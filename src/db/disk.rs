@@ -50,14 +50,17 @@ impl Drop for ReadOnlyLevelDbKvStore {
     }
 }
 
-pub struct LevelDbKvStore(Database<StringKey>);
+pub struct LevelDbKvStore(Database<StringKey>, PathBuf);
 impl LevelDbKvStore {
     pub fn new(path: &Path, cache_size: usize) -> Result<LevelDbKvStore, KvStoreError> {
         fs::create_dir_all(&path)?;
         let mut options = Options::new();
         options.create_if_missing = true;
         options.cache = Some(Cache::new(cache_size));
-        Ok(LevelDbKvStore(Database::open(path, options)?))
+        Ok(LevelDbKvStore(
+            Database::open(path, options)?,
+            path.to_path_buf(),
+        ))
     }
 }
 
@@ -65,7 +68,7 @@ impl KvStore for LevelDbKvStore {
     fn get(&self, k: StringKey) -> Result<Option<Blob>, KvStoreError> {
         let read_opts = ReadOptions::new();
         match self.0.get(read_opts, k) {
-            Ok(v) => Ok(v.map(Blob)),
+            Ok(v) => Ok(v.map(Blob::from)),
             Err(_) => Err(KvStoreError::Failure),
         }
     }
@@ -88,13 +91,28 @@ impl KvStore for LevelDbKvStore {
         it.seek(&prefix);
         Ok(QueryResult::LevelDb { db: it, prefix })
     }
+    fn pairs_from(&self, from: StringKey) -> Result<QueryResult<'_>, KvStoreError> {
+        let it = self.0.iter(ReadOptions::new());
+        it.seek(&from);
+        Ok(QueryResult::LevelDb {
+            db: it,
+            prefix: "".into(),
+        })
+    }
+    fn disk_usage(&self) -> Option<u64> {
+        let mut size = 0;
+        for entry in fs::read_dir(&self.1).ok()? {
+            size += entry.ok()?.metadata().ok()?.len();
+        }
+        Some(size)
+    }
 }
 
 impl<'a> KvStore for LevelDbSnapshot<'a> {
     fn get(&self, k: StringKey) -> Result<Option<Blob>, KvStoreError> {
         let read_opts = ReadOptions::new();
         match self.0.get(read_opts, k) {
-            Ok(v) => Ok(v.map(Blob)),
+            Ok(v) => Ok(v.map(Blob::from)),
             Err(_) => Err(KvStoreError::Failure),
         }
     }
@@ -106,4 +124,12 @@ impl<'a> KvStore for LevelDbSnapshot<'a> {
         it.seek(&prefix);
         Ok(QueryResult::LevelDb { db: it, prefix })
     }
+    fn pairs_from(&self, from: StringKey) -> Result<QueryResult<'_>, KvStoreError> {
+        let it = self.0.iter(ReadOptions::new());
+        it.seek(&from);
+        Ok(QueryResult::LevelDb {
+            db: it,
+            prefix: "".into(),
+        })
+    }
 }
@@ -11,6 +11,25 @@ pub fn randomness() -> StringKey {
     "RND".into()
 }
 
+pub fn pruned_height() -> StringKey {
+    "PRN".into()
+}
+
+pub fn rollback_pruned_height() -> StringKey {
+    "RPR".into()
+}
+
+/// Whether the key is part of the chain state. Block bodies, rollback data,
+/// prune markers and lookup indexes are left out, since they depend on how the
+/// node is configured rather than on the blocks applied.
+pub fn is_state(key: &StringKey) -> bool {
+    *key != pruned_height()
+        && *key != rollback_pruned_height()
+        && !["BLK-", "MRK-", "RLK-", "BMT-", "HNM-", "TXL-", "ATX-"]
+            .iter()
+            .any(|prefix| key.0.starts_with(prefix))
+}
+
 pub fn block(index: u64) -> StringKey {
     format!("BLK-{:010}", index).into()
 }
@@ -19,6 +38,22 @@ pub fn header(index: u64) -> StringKey {
     format!("HDR-{:010}", index).into()
 }
 
+pub fn block_meta(index: u64) -> StringKey {
+    format!("BMT-{:010}", index).into()
+}
+
+pub fn header_number(hash: &[u8]) -> StringKey {
+    format!("HNM-{}", hex::encode(hash)).into()
+}
+
+pub fn block_reward(index: u64) -> StringKey {
+    format!("RWD-{:010}", index).into()
+}
+
+pub fn transaction_location(hash: &[u8]) -> StringKey {
+    format!("TXL-{}", hex::encode(hash)).into()
+}
+
 pub fn rollback(index: u64) -> StringKey {
     format!("RLK-{:010}", index).into()
 }
@@ -247,6 +282,48 @@ impl DelegateeRankDbKey {
     }
 }
 
+/// Transactions of an account, most recent first
+pub struct AccountTxDbKey {
+    pub address: Address,
+    pub block: u64,
+    pub index: u32,
+}
+impl From<AccountTxDbKey> for StringKey {
+    fn from(key: AccountTxDbKey) -> StringKey {
+        format!(
+            "{}-{:016x}-{:08x}",
+            AccountTxDbKey::prefix(&key.address),
+            u64::MAX - key.block,
+            u32::MAX - key.index
+        )
+        .into()
+    }
+}
+impl TryFrom<StringKey> for AccountTxDbKey {
+    type Error = ParseDbKeyError;
+    fn try_from(key: StringKey) -> Result<Self, ParseDbKeyError> {
+        let splitted = key.0.split("-").collect::<Vec<_>>();
+        if splitted.len() != 4 {
+            return Err(ParseDbKeyError::Invalid);
+        }
+        let address: Address = splitted[1].parse().map_err(|_| ParseDbKeyError::Invalid)?;
+        let block = u64::MAX
+            - u64::from_str_radix(splitted[2], 16).map_err(|_| ParseDbKeyError::Invalid)?;
+        let index = u32::MAX
+            - u32::from_str_radix(splitted[3], 16).map_err(|_| ParseDbKeyError::Invalid)?;
+        Ok(AccountTxDbKey {
+            address,
+            block,
+            index,
+        })
+    }
+}
+impl AccountTxDbKey {
+    pub fn prefix(address: &Address) -> String {
+        format!("ATX-{}", address)
+    }
+}
+
 pub fn delegate(delegator: &Address, delegatee: &Address) -> StringKey {
     format!("DEL-{}-{}", delegator, delegatee).into()
 }
@@ -1,14 +1,15 @@
 pub mod keys;
 
-use crate::blockchain::ZkCompressedStateChange;
+use crate::blockchain::{TransactionLocation, ZkCompressedStateChange};
 use crate::core::{
-    hash::Hash, Amount, Block, ContractAccount, ContractId, Delegate, Hasher, Header, Ratio,
-    Staker, Token, Undelegation,
+    hash::Hash, Amount, Block, BlockMeta, ContractAccount, ContractId, Delegate, Hasher, Header,
+    Ratio, Staker, Token, Undelegation,
 };
 use crate::crypto::merkle::MerkleTree;
 use crate::zk::{
     ZkCompressedState, ZkContract, ZkDataPairs, ZkDeltaPairs, ZkScalar, ZkState, ZkStateModel,
 };
+use bytes::Bytes;
 use db_key::Key;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
@@ -26,6 +27,9 @@ pub enum KvStoreError {
     #[cfg(feature = "db")]
     #[error("leveldb error: {0}")]
     LevelDb(#[from] leveldb::error::Error),
+    #[cfg(feature = "rocksdb")]
+    #[error("rocksdb error: {0}")]
+    RocksDb(#[from] rocksdb::Error),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, std::hash::Hash)]
@@ -55,8 +59,16 @@ impl StringKey {
     }
 }
 
+/// Values are reference-counted, so that cloning them while passing through stores,
+/// mirrors and responses does not copy the data. Encoded the same way as a `Vec<u8>`.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub struct Blob(pub Vec<u8>);
+pub struct Blob(pub Bytes);
+
+impl From<Vec<u8>> for Blob {
+    fn from(v: Vec<u8>) -> Self {
+        Self(v.into())
+    }
+}
 
 impl std::fmt::Display for Blob {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -82,7 +94,7 @@ macro_rules! gen_from {
         $(
             impl From<$x> for Blob {
                 fn from(n: $x) -> Self {
-                    Self(bincode::serialize(&n).unwrap())
+                    Self(bincode::serialize(&n).unwrap().into())
                 }
             }
         )*
@@ -102,6 +114,7 @@ gen_try_into!(
     ContractAccount,
     Header,
     Block,
+    BlockMeta,
     Vec<WriteOp>,
     MerkleTree<Hasher>,
     ZkContract,
@@ -116,6 +129,7 @@ gen_try_into!(
     ZkDeltaPairs,
     Token,
     Amount,
+    TransactionLocation,
     ()
 );
 gen_from!(
@@ -131,6 +145,7 @@ gen_from!(
     ContractAccount,
     Header,
     &Block,
+    BlockMeta,
     Vec<WriteOp>,
     MerkleTree<Hasher>,
     ZkContract,
@@ -145,6 +160,7 @@ gen_from!(
     &ZkDeltaPairs,
     &Token,
     Amount,
+    TransactionLocation,
     ()
 );
 
@@ -182,6 +198,11 @@ pub enum QueryResult<'a> {
         prefix: StringKey,
         db: leveldb::iterator::Iterator<'a, StringKey>,
     },
+    #[cfg(feature = "rocksdb")]
+    RocksDb {
+        prefix: StringKey,
+        db: rocksdb::DBIterator<'a>,
+    },
     Ram {
         range: std::collections::btree_map::Range<'a, StringKey, Blob>,
         prefix: StringKey,
@@ -280,7 +301,18 @@ impl<'a> QueryResult<'a> {
             QueryResult::Precalculated(v) => Box::new(v.into_iter()),
             #[cfg(feature = "db")]
             QueryResult::LevelDb { prefix, db } => Box::new(
-                db.map(|(k, v)| (k, Blob(v)))
+                db.map(|(k, v)| (k, Blob::from(v)))
+                    .take_while(move |(k, _)| k.0.starts_with(&prefix.0)),
+            ),
+            #[cfg(feature = "rocksdb")]
+            QueryResult::RocksDb { prefix, db } => Box::new(
+                db.map_while(Result::ok)
+                    .map(|(k, v)| {
+                        (
+                            StringKey::new(&String::from_utf8_lossy(&k)),
+                            Blob(v.into_vec().into()),
+                        )
+                    })
                     .take_while(move |(k, _)| k.0.starts_with(&prefix.0)),
             ),
             QueryResult::Ram { range, prefix } => Box::new(
@@ -318,6 +350,12 @@ pub trait KvStore {
     fn get(&self, k: StringKey) -> Result<Option<Blob>, KvStoreError>;
     fn update(&mut self, ops: &[WriteOp]) -> Result<(), KvStoreError>;
     fn pairs(&self, prefix: StringKey) -> Result<QueryResult, KvStoreError>;
+    /// All pairs in key order, starting at the key `from`
+    fn pairs_from(&self, from: StringKey) -> Result<QueryResult<'_>, KvStoreError>;
+    /// Size of the store on disk in bytes, `None` if it is not persisted
+    fn disk_usage(&self) -> Option<u64> {
+        None
+    }
     fn mirror(&self) -> RamMirrorKvStore<'_, Self>
     where
         Self: Sized,
@@ -385,6 +423,15 @@ impl<'a, K: KvStore> KvStore for RamMirrorKvStore<'a, K> {
             prefix,
         })
     }
+    fn pairs_from(&self, from: StringKey) -> Result<QueryResult<'_>, KvStoreError> {
+        Ok(QueryResult::Mirror {
+            actual: Box::new(self.store.pairs_from(from.clone())?),
+            overwrite: self
+                .overwrite
+                .range((Bound::Included(from), Bound::Unbounded)),
+            prefix: "".into(),
+        })
+    }
 }
 
 mod ram;
@@ -395,5 +442,10 @@ mod disk;
 #[cfg(feature = "db")]
 pub use disk::*;
 
+#[cfg(feature = "rocksdb")]
+mod rocks;
+#[cfg(feature = "rocksdb")]
+pub use rocks::*;
+
 #[cfg(test)]
 mod test;
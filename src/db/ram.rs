@@ -35,4 +35,10 @@ impl KvStore for RamKvStore {
             prefix,
         })
     }
+    fn pairs_from(&self, from: StringKey) -> Result<QueryResult<'_>, KvStoreError> {
+        Ok(QueryResult::Ram {
+            range: self.0.range((Bound::Included(from), Bound::Unbounded)),
+            prefix: "".into(),
+        })
+    }
 }
@@ -0,0 +1,59 @@
+use super::*;
+use rocksdb::{Direction, IteratorMode, Options, WriteBatch, DB};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// RocksDB alternative to `LevelDbKvStore`, writes of a single update are applied
+/// atomically through a write batch.
+pub struct RocksDbKvStore(DB, PathBuf);
+
+impl RocksDbKvStore {
+    pub fn new(path: &Path) -> Result<RocksDbKvStore, KvStoreError> {
+        fs::create_dir_all(path)?;
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        Ok(RocksDbKvStore(
+            DB::open(&options, path)?,
+            path.to_path_buf(),
+        ))
+    }
+}
+
+impl KvStore for RocksDbKvStore {
+    fn get(&self, k: StringKey) -> Result<Option<Blob>, KvStoreError> {
+        Ok(self.0.get(k.0.as_bytes())?.map(Blob::from))
+    }
+    fn update(&mut self, ops: &[WriteOp]) -> Result<(), KvStoreError> {
+        let mut batch = WriteBatch::default();
+        for op in ops.iter() {
+            match op {
+                WriteOp::Remove(k) => batch.delete(k.0.as_bytes()),
+                WriteOp::Put(k, v) => batch.put(k.0.as_bytes(), &v.0),
+            }
+        }
+        self.0.write(batch)?;
+        Ok(())
+    }
+    fn pairs(&self, prefix: StringKey) -> Result<QueryResult, KvStoreError> {
+        let it = self
+            .0
+            .iterator(IteratorMode::From(prefix.0.as_bytes(), Direction::Forward));
+        Ok(QueryResult::RocksDb { db: it, prefix })
+    }
+    fn pairs_from(&self, from: StringKey) -> Result<QueryResult<'_>, KvStoreError> {
+        let it = self
+            .0
+            .iterator(IteratorMode::From(from.0.as_bytes(), Direction::Forward));
+        Ok(QueryResult::RocksDb {
+            db: it,
+            prefix: "".into(),
+        })
+    }
+    fn disk_usage(&self) -> Option<u64> {
+        let mut size = 0;
+        for entry in fs::read_dir(&self.1).ok()? {
+            size += entry.ok()?.metadata().ok()?.len();
+        }
+        Some(size)
+    }
+}
@@ -20,11 +20,11 @@ fn test_ram_and_disk_pair_prefix() -> Result<(), KvStoreError> {
     );
 
     let ops = &[
-        WriteOp::Put("bc".into(), Blob(vec![0, 1, 2, 3])),
-        WriteOp::Put("aa".into(), Blob(vec![3, 2, 1, 0])),
-        WriteOp::Put("a0a".into(), Blob(vec![])),
-        WriteOp::Put("bge".into(), Blob(vec![])),
-        WriteOp::Put("def".into(), Blob(vec![])),
+        WriteOp::Put("bc".into(), Blob::from(vec![0, 1, 2, 3])),
+        WriteOp::Put("aa".into(), Blob::from(vec![3, 2, 1, 0])),
+        WriteOp::Put("a0a".into(), Blob(Default::default())),
+        WriteOp::Put("bge".into(), Blob(Default::default())),
+        WriteOp::Put("def".into(), Blob(Default::default())),
     ];
 
     ram.update(ops)?;
@@ -115,9 +115,9 @@ fn test_ram_and_disk_db_consistency() -> Result<(), KvStoreError> {
     );
 
     let ops = &[
-        WriteOp::Put("bc".into(), Blob(vec![0, 1, 2, 3])),
-        WriteOp::Put("aa".into(), Blob(vec![3, 2, 1, 0])),
-        WriteOp::Put("def".into(), Blob(vec![])),
+        WriteOp::Put("bc".into(), Blob::from(vec![0, 1, 2, 3])),
+        WriteOp::Put("aa".into(), Blob::from(vec![3, 2, 1, 0])),
+        WriteOp::Put("def".into(), Blob(Default::default())),
     ];
 
     ram.update(ops)?;
@@ -130,8 +130,8 @@ fn test_ram_and_disk_db_consistency() -> Result<(), KvStoreError> {
 
     let new_ops = &[
         WriteOp::Remove("aa".into()),
-        WriteOp::Put("def".into(), Blob(vec![1, 1, 1, 2])),
-        WriteOp::Put("ghi".into(), Blob(vec![3, 3, 3, 3])),
+        WriteOp::Put("def".into(), Blob::from(vec![1, 1, 1, 2])),
+        WriteOp::Put("ghi".into(), Blob::from(vec![3, 3, 3, 3])),
     ];
 
     ram.update(new_ops)?;
@@ -150,9 +150,9 @@ fn test_mirror_kv_store() -> Result<(), KvStoreError> {
     let mut ram = RamKvStore::default();
 
     let ops = &[
-        WriteOp::Put("bc".into(), Blob(vec![0, 1, 2, 3])),
-        WriteOp::Put("aa".into(), Blob(vec![3, 2, 1, 0])),
-        WriteOp::Put("def".into(), Blob(vec![])),
+        WriteOp::Put("bc".into(), Blob::from(vec![0, 1, 2, 3])),
+        WriteOp::Put("aa".into(), Blob::from(vec![3, 2, 1, 0])),
+        WriteOp::Put("def".into(), Blob(Default::default())),
     ];
 
     ram.update(ops)?;
@@ -162,9 +162,9 @@ fn test_mirror_kv_store() -> Result<(), KvStoreError> {
     let mut mirror = RamMirrorKvStore::new(&ram);
 
     let ops_on_mirror = &[
-        WriteOp::Put("bc".into(), Blob(vec![0, 1, 2, 4])),
-        WriteOp::Put("dd".into(), Blob(vec![1, 1, 1])),
-        WriteOp::Put("ghi".into(), Blob(vec![2, 3])),
+        WriteOp::Put("bc".into(), Blob::from(vec![0, 1, 2, 4])),
+        WriteOp::Put("dd".into(), Blob::from(vec![1, 1, 1])),
+        WriteOp::Put("ghi".into(), Blob::from(vec![2, 3])),
     ];
 
     mirror.update(ops_on_mirror)?;
@@ -185,14 +185,37 @@ fn test_mirror_kv_store() -> Result<(), KvStoreError> {
     Ok(())
 }
 
+#[test]
+fn test_pairs_from() -> Result<(), KvStoreError> {
+    let mut ram = RamKvStore::default();
+    ram.update(&[
+        WriteOp::Put("aa".into(), Blob::from(vec![1])),
+        WriteOp::Put("bc".into(), Blob::from(vec![2])),
+        WriteOp::Put("def".into(), Blob::from(vec![3])),
+    ])?;
+    let mut mirror = RamMirrorKvStore::new(&ram);
+    mirror.update(&[
+        WriteOp::Put("bd".into(), Blob::from(vec![4])),
+        WriteOp::Remove("def".into()),
+    ])?;
+
+    let keys = |res: QueryResult| res.into_iter().map(|(k, _)| k.0).collect::<Vec<_>>();
+    assert_eq!(keys(ram.pairs_from("b".into())?), vec!["bc", "def"]);
+    assert_eq!(keys(ram.pairs_from("bc".into())?), vec!["bc", "def"]);
+    assert_eq!(keys(mirror.pairs_from("b".into())?), vec!["bc", "bd"]);
+    assert!(keys(ram.pairs_from("e".into())?).is_empty());
+
+    Ok(())
+}
+
 #[test]
 fn test_mirror_rollback() -> Result<(), KvStoreError> {
     let mut ram = RamKvStore::default();
 
     let ops = &[
-        WriteOp::Put("bc".into(), Blob(vec![0, 1, 2, 3])),
-        WriteOp::Put("aa".into(), Blob(vec![3, 2, 1, 0])),
-        WriteOp::Put("def".into(), Blob(vec![])),
+        WriteOp::Put("bc".into(), Blob::from(vec![0, 1, 2, 3])),
+        WriteOp::Put("aa".into(), Blob::from(vec![3, 2, 1, 0])),
+        WriteOp::Put("def".into(), Blob(Default::default())),
     ];
 
     ram.update(ops)?;
@@ -206,9 +229,9 @@ fn test_mirror_rollback() -> Result<(), KvStoreError> {
 
     let mut mirror3 = ram.mirror();
     mirror3.update(&[
-        WriteOp::Put("bc".into(), Blob(vec![3, 2, 1])),
-        WriteOp::Put("gg".into(), Blob(vec![2, 2, 2, 2])),
-        WriteOp::Put("fre".into(), Blob(vec![1, 1])),
+        WriteOp::Put("bc".into(), Blob::from(vec![3, 2, 1])),
+        WriteOp::Put("gg".into(), Blob::from(vec![2, 2, 2, 2])),
+        WriteOp::Put("fre".into(), Blob::from(vec![1, 1])),
         WriteOp::Remove("aa".into()),
     ])?;
     let mut mirror3_rollback = mirror3.rollback()?;
@@ -219,8 +242,8 @@ fn test_mirror_rollback() -> Result<(), KvStoreError> {
     assert_eq!(
         mirror3_rollback,
         vec![
-            WriteOp::Put("aa".into(), Blob(vec![3, 2, 1, 0])),
-            WriteOp::Put("bc".into(), Blob(vec![0, 1, 2, 3])),
+            WriteOp::Put("aa".into(), Blob::from(vec![3, 2, 1, 0])),
+            WriteOp::Put("bc".into(), Blob::from(vec![0, 1, 2, 3])),
             WriteOp::Remove("fre".into()),
             WriteOp::Remove("gg".into()),
         ]
@@ -228,3 +251,46 @@ fn test_mirror_rollback() -> Result<(), KvStoreError> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(all(feature = "db", feature = "rocksdb"))]
+fn test_ram_and_rocksdb_consistency() -> Result<(), KvStoreError> {
+    let dir = TempDir::new("bazuka_test").unwrap();
+    let mut ram = RamKvStore::default();
+    let mut rocks = RocksDbKvStore::new(dir.path())?;
+
+    let ops = &[
+        WriteOp::Put("bc".into(), Blob::from(vec![0, 1, 2, 3])),
+        WriteOp::Put("aa".into(), Blob::from(vec![3, 2, 1, 0])),
+        WriteOp::Put("a0a".into(), Blob(Default::default())),
+        WriteOp::Remove("bc".into()),
+    ];
+    ram.update(ops)?;
+    rocks.update(ops)?;
+
+    for prefix in ["", "a", "b", "a0"] {
+        assert_eq!(
+            ram.pairs(prefix.into())?.checksum::<Hasher>()?,
+            rocks.pairs(prefix.into())?.checksum::<Hasher>()?
+        );
+    }
+    assert_eq!(rocks.get("aa".into())?, Some(Blob::from(vec![3, 2, 1, 0])));
+    assert_eq!(rocks.get("bc".into())?, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_blob_encoding_matches_vec() {
+    // Stored rollback data and snapshot responses rely on the encoding of `Blob`
+    let data = vec![3u8, 2, 1, 0];
+    let blob = Blob::from(data.clone());
+    assert_eq!(
+        bincode::serialize(&blob).unwrap(),
+        bincode::serialize(&data).unwrap()
+    );
+    assert_eq!(
+        bincode::deserialize::<Blob>(&bincode::serialize(&data).unwrap()).unwrap(),
+        blob
+    );
+}
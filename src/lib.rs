@@ -8,11 +8,15 @@ pub mod config;
 pub mod core;
 pub mod crypto;
 pub mod db;
+pub mod light;
 pub mod mpn;
 pub mod utils;
 pub mod wallet;
 pub mod zk;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
 #[cfg(feature = "node")]
 pub mod node;
 
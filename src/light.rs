@@ -0,0 +1,208 @@
+//! Proof verification for light clients
+//!
+//! Nothing in here touches a database or the network, so wallets (including
+//! the wasm build) can check what a full node claims against the headers they
+//! already trust.
+
+use crate::core::hash::Hash;
+use crate::core::{Hasher, Header, Transaction};
+use crate::crypto::merkle::MerkleTree;
+use crate::zk::{ZkHasher, ZkScalar};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum LightError {
+    #[error("header chain is empty")]
+    EmptyHeaderChain,
+    #[error("header {0} is not connected to its parent")]
+    HeaderNotConnected(u64),
+    #[error("header {0} is not in the header chain")]
+    HeaderNotFound(u64),
+    #[error("merkle branch does not match the block root")]
+    InvalidMerkleBranch,
+    #[error("state proof does not match the state root")]
+    InvalidStateProof,
+}
+
+/// Proof that a transaction is included in the body of a block
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleBranch {
+    pub block_number: u64,
+    pub proof: Vec<<Hasher as Hash>::Output>,
+}
+
+/// Makes sure every header is the child of the one before it
+pub fn verify_header_chain(header_chain: &[Header]) -> Result<(), LightError> {
+    if header_chain.is_empty() {
+        return Err(LightError::EmptyHeaderChain);
+    }
+    for pair in header_chain.windows(2) {
+        if pair[1].number != pair[0].number + 1 || pair[1].parent_hash != pair[0].hash() {
+            return Err(LightError::HeaderNotConnected(pair[1].number));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `tx` is part of the block `merkle_branch` points to. The first
+/// header of `header_chain` is the trusted one, the rest should link to it.
+pub fn verify_tx_inclusion(
+    header_chain: &[Header],
+    merkle_branch: &MerkleBranch,
+    tx: &Transaction,
+) -> Result<(), LightError> {
+    verify_header_chain(header_chain)?;
+    let header = header_chain
+        .iter()
+        .find(|h| h.number == merkle_branch.block_number)
+        .ok_or(LightError::HeaderNotFound(merkle_branch.block_number))?;
+    if !MerkleTree::<Hasher>::verify(&header.block_root, &tx.hash(), &merkle_branch.proof) {
+        return Err(LightError::InvalidMerkleBranch);
+    }
+    Ok(())
+}
+
+/// Checks a membership proof of a zk-state list (As returned by
+/// `KvStoreStateManager::prove`) against the root of that list.
+pub fn verify_state_proof<H: ZkHasher>(
+    root: ZkScalar,
+    mut index: u64,
+    value: ZkScalar,
+    proof: &[[ZkScalar; 3]],
+) -> Result<(), LightError> {
+    if proof.len() < 32 && index >> (2 * proof.len()) != 0 {
+        return Err(LightError::InvalidStateProof);
+    }
+    let mut curr = value;
+    for part in proof {
+        let pos = (index % 4) as usize;
+        let mut vals = Vec::with_capacity(4);
+        vals.extend(&part[..pos]);
+        vals.push(curr);
+        vals.extend(&part[pos..]);
+        curr = H::hash(&vals);
+        index /= 4;
+    }
+    if curr != root {
+        return Err(LightError::InvalidStateProof);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Block, Money, ProofOfStake, Signature, TransactionData, ValidatorProof};
+    use crate::zk::{PoseidonHasher, ZkDataLocator, ZkDeltaPairs, ZkStateBuilder, ZkStateModel};
+
+    fn make_chain(len: u64) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for number in 0..len {
+            let body = (0..5)
+                .map(|i| Transaction {
+                    memo: format!("{}-{}", number, i),
+                    src: None,
+                    data: TransactionData::RegularSend { entries: vec![] },
+                    nonce: i,
+                    fee: Money::ziesha(0),
+                    sig: Signature::Unsigned,
+                })
+                .collect::<Vec<_>>();
+            let mut block = Block {
+                header: Header {
+                    parent_hash: blocks.last().map(|b| b.header.hash()).unwrap_or_default(),
+                    number,
+                    block_root: Default::default(),
+                    proof_of_stake: ProofOfStake {
+                        timestamp: number as u32,
+                        validator: Default::default(),
+                        proof: ValidatorProof::Unproven,
+                    },
+                },
+                body,
+            };
+            block.header.block_root = block.merkle_tree().root();
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    #[test]
+    fn test_verify_tx_inclusion() {
+        let blocks = make_chain(4);
+        let headers = blocks.iter().map(|b| b.header.clone()).collect::<Vec<_>>();
+        for block in blocks.iter() {
+            let tree = block.merkle_tree();
+            for (i, tx) in block.body.iter().enumerate() {
+                let branch = MerkleBranch {
+                    block_number: block.header.number,
+                    proof: tree.prove(i),
+                };
+                assert_eq!(verify_tx_inclusion(&headers, &branch, tx), Ok(()));
+            }
+        }
+
+        let branch = MerkleBranch {
+            block_number: 2,
+            proof: blocks[2].merkle_tree().prove(0),
+        };
+        assert_eq!(
+            verify_tx_inclusion(&headers, &branch, &blocks[1].body[0]),
+            Err(LightError::InvalidMerkleBranch)
+        );
+        assert_eq!(
+            verify_tx_inclusion(&headers[..2], &branch, &blocks[2].body[0]),
+            Err(LightError::HeaderNotFound(2))
+        );
+
+        let mut broken = headers.clone();
+        broken[1].block_root = Default::default();
+        assert_eq!(
+            verify_tx_inclusion(&broken, &branch, &blocks[2].body[0]),
+            Err(LightError::HeaderNotConnected(2))
+        );
+        assert_eq!(
+            verify_tx_inclusion(&[], &branch, &blocks[2].body[0]),
+            Err(LightError::EmptyHeaderChain)
+        );
+    }
+
+    #[test]
+    fn test_verify_state_proof() {
+        let mut builder = ZkStateBuilder::<PoseidonHasher>::new(ZkStateModel::List {
+            log4_size: 3,
+            item_type: Box::new(ZkStateModel::Scalar),
+        });
+        for i in 0..40 {
+            builder
+                .batch_set(&ZkDeltaPairs(
+                    [(ZkDataLocator(vec![i]), Some(ZkScalar::from(i + 100)))].into(),
+                ))
+                .unwrap();
+        }
+        let root = builder.get(ZkDataLocator(vec![])).unwrap();
+        for i in 0..64 {
+            let proof = builder.prove(ZkDataLocator(vec![]), i).unwrap();
+            let value = if i < 40 {
+                ZkScalar::from(i + 100)
+            } else {
+                ZkScalar::default()
+            };
+            assert_eq!(
+                verify_state_proof::<PoseidonHasher>(root, i, value, &proof),
+                Ok(())
+            );
+            assert_eq!(
+                verify_state_proof::<PoseidonHasher>(root, i, ZkScalar::from(1), &proof),
+                Err(LightError::InvalidStateProof)
+            );
+        }
+        let proof = builder.prove(ZkDataLocator(vec![]), 3).unwrap();
+        assert_eq!(
+            verify_state_proof::<PoseidonHasher>(root, 64 + 3, ZkScalar::from(103), &proof),
+            Err(LightError::InvalidStateProof)
+        );
+        assert_eq!(builder.compress().unwrap().state_hash, root);
+    }
+}
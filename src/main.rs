@@ -10,7 +10,6 @@ use bazuka::client::NodeError;
 #[cfg(feature = "client")]
 #[tokio::main]
 async fn main() -> Result<(), NodeError> {
-    env_logger::init();
     initialize_cli().await;
     Ok(())
 }
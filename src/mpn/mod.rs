@@ -79,6 +79,9 @@ impl MpnWorkPool {
 
         result
     }
+    pub fn is_solved(&self, id: usize) -> bool {
+        self.solutions.contains_key(&id)
+    }
     pub fn prove(&mut self, id: usize, proof: &ZkProof) -> bool {
         if !self.solutions.contains_key(&id) {
             if let Some(work) = self.works.get(&id) {
@@ -265,7 +268,9 @@ pub fn prepare_works<K: KvStore>(
     let mut works = Vec::new();
     let mut workers = workers.values().cloned().collect::<Vec<_>>();
     if workers.len() == 0 {
-        log::warn!("No MPN-workers defined! All proving rewards will go into validator's wallet!");
+        tracing::warn!(
+            "No MPN-workers defined! All proving rewards will go into validator's wallet!"
+        );
         workers = vec![MpnWorker {
             mpn_address: user_tx_builder.get_mpn_address(),
         }];
@@ -297,9 +302,9 @@ pub fn prepare_works<K: KvStore>(
             &mut mirror,
             &deposits,
         )?;
-        log::info!("Made MPN-Deposit block of {} txs.", transitions.len());
+        tracing::info!("Made MPN-Deposit block of {} txs.", transitions.len());
         for (i, tx) in transitions.iter().enumerate() {
-            log::info!("MPN-Deposit tx {}: {:?}", i, tx.tx);
+            tracing::info!("MPN-Deposit tx {}: {:?}", i, tx.tx);
         }
         works.push(MpnWork {
             config: config.clone(),
@@ -323,9 +328,9 @@ pub fn prepare_works<K: KvStore>(
             &mut mirror,
             &withdraws,
         )?;
-        log::info!("Made MPN-Withdraw block of {} txs.", transitions.len());
+        tracing::info!("Made MPN-Withdraw block of {} txs.", transitions.len());
         for (i, tx) in transitions.iter().enumerate() {
-            log::info!("MPN-Withdraw tx {}: {:?}", i, tx.tx);
+            tracing::info!("MPN-Withdraw tx {}: {:?}", i, tx.tx);
         }
         works.push(MpnWork {
             config: config.clone(),
@@ -381,9 +386,9 @@ pub fn prepare_works<K: KvStore>(
             &mut mirror,
             &updates,
         )?;
-        log::info!("Made MPN-Update block of {} txs.", transitions.len());
+        tracing::info!("Made MPN-Update block of {} txs.", transitions.len());
         for (i, tx) in transitions.iter().enumerate() {
-            log::info!("MPN-Update tx {}: {:?}", i, tx.tx);
+            tracing::info!("MPN-Update tx {}: {:?}", i, tx.tx);
         }
         rewards.clear();
         works.push(MpnWork {
@@ -62,7 +62,11 @@ pub fn withdraw<K: KvStore>(
             || tx.payment.amount.amount > acc_token.amount
             || tx.zk_address_index(mpn_log4_account_capacity) > 0x3fffffff
         {
-            println!("{} {}", tx.zk_nonce, acc.withdraw_nonce);
+            tracing::debug!(
+                "Withdraw rejected, nonce {} after {}",
+                tx.zk_nonce,
+                acc.withdraw_nonce
+            );
             rejected.push(tx.clone());
             continue;
         } else {
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ParseIpNetworkError {
+    #[error("invalid ip network: {0}")]
+    Invalid(String),
+}
+
+/// A range of IP addresses in CIDR notation, e.g `10.0.0.0/8`. A single address
+/// is a network of that address alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, canonical(ip)) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+// IPv4 clients of a dual-stack listener appear as IPv4-mapped IPv6 addresses
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        _ => ip,
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = ParseIpNetworkError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseIpNetworkError::Invalid(s.into());
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr = canonical(addr.parse::<IpAddr>().map_err(|_| invalid())?);
+        let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse::<u8>().map_err(|_| invalid())?,
+            None => max_prefix,
+        };
+        if prefix > max_prefix {
+            return Err(invalid());
+        }
+        Ok(Self { addr, prefix })
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+impl TryFrom<String> for IpNetwork {
+    type Error = ParseIpNetworkError;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<IpNetwork> for String {
+    fn from(net: IpNetwork) -> String {
+        net.to_string()
+    }
+}
+
+/// Networks the node accepts requests from. Requests from loopback are always
+/// accepted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessListOptions {
+    /// Only requests from these networks are accepted, unless empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<IpNetwork>,
+    /// Requests from these networks are rejected, even if allowed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<IpNetwork>,
+}
+
+impl AccessListOptions {
+    pub fn permits(&self, ip: IpAddr) -> bool {
+        if ip.is_loopback() {
+            return true;
+        }
+        (self.allow.is_empty() || self.allow.iter().any(|net| net.contains(ip)))
+            && !self.deny.iter().any(|net| net.contains(ip))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_list() {
+        let net = |s: &str| s.parse::<IpNetwork>().unwrap();
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+
+        assert!(net("10.0.0.0/8").contains(ip("10.1.2.3")));
+        assert!(!net("10.0.0.0/8").contains(ip("11.0.0.1")));
+        assert!(net("0.0.0.0/0").contains(ip("1.2.3.4")));
+        assert!(net("1.2.3.4").contains(ip("1.2.3.4")));
+        assert!(!net("1.2.3.4").contains(ip("1.2.3.5")));
+        assert!(net("10.0.0.0/8").contains(ip("::ffff:10.0.0.1")));
+        assert!(net("fd00::/8").contains(ip("fd12::1")));
+        assert!(!net("fd00::/8").contains(ip("10.0.0.1")));
+        assert!("10.0.0.0/33".parse::<IpNetwork>().is_err());
+        assert!("10.0.0/8".parse::<IpNetwork>().is_err());
+        assert_eq!(net("192.168.0.0/16").to_string(), "192.168.0.0/16");
+
+        let acl = AccessListOptions {
+            allow: vec![net("10.0.0.0/8")],
+            deny: vec![net("10.0.0.0/16")],
+        };
+        assert!(acl.permits(ip("10.1.0.1")));
+        assert!(!acl.permits(ip("10.0.0.1")));
+        assert!(!acl.permits(ip("8.8.8.8")));
+        assert!(acl.permits(ip("127.0.0.1")));
+        assert!(AccessListOptions::default().permits(ip("8.8.8.8")));
+
+        let acl: AccessListOptions = serde_yaml::from_str("deny: [\"8.8.0.0/16\"]").unwrap();
+        assert!(!acl.permits(ip("8.8.8.8")));
+        assert!(acl.permits(ip("1.1.1.1")));
+    }
+}
@@ -0,0 +1,121 @@
+use super::messages::{FaucetRequest, FaucetResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::core::{Address, Amount, Money};
+use crate::db::KvStore;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub async fn faucet<K: KvStore, B: Blockchain<K>>(
+    client: Option<SocketAddr>,
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: FaucetRequest,
+) -> Result<FaucetResponse, NodeError> {
+    let mut ctx = context.write().await;
+    let now = ctx.local_timestamp();
+    let dst: Address = req.address.parse()?;
+    let faucet = ctx.faucet.as_mut().ok_or(NodeError::FaucetDisabled)?;
+    faucet.drip(client.map(|c| c.ip()), &dst, now)?;
+    let opts = faucet.opts.clone();
+
+    // Chain transactions of the faucet wallet that are still in the mempool
+    let src = opts.wallet.get_address();
+    let mut nonce = ctx.blockchain.get_nonce(src.clone())?;
+    for (tx, _) in ctx.mempool.tx_deltas() {
+        if tx.tx.src.as_ref() == Some(&src) {
+            nonce = std::cmp::max(nonce, tx.tx.nonce);
+        }
+    }
+    let create_tx = |fee: Amount| {
+        opts.wallet.create_transaction(
+            "Faucet".into(),
+            dst.clone(),
+            Money::ziesha(opts.amount.into()),
+            Money::ziesha(fee.into()),
+            nonce + 1,
+        )
+    };
+    // Size of a transaction does not depend on its fee
+    let fee = ctx.mempool.min_fee_for(&create_tx(Amount(0)).tx);
+    ctx.mempool_add_tx(true, create_tx(fee).into())?;
+
+    Ok(FaucetResponse {
+        amount: opts.amount,
+        nonce: nonce + 1,
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::FaucetOptions;
+    use crate::wallet::TxBuilder;
+
+    #[tokio::test]
+    async fn test_faucet() {
+        let ctx = test_context();
+        let req = FaucetRequest {
+            address: TxBuilder::new(&Vec::from("DST")).get_address().to_string(),
+        };
+        assert!(matches!(
+            faucet(None, ctx.clone(), req.clone()).await,
+            Err(NodeError::FaucetDisabled)
+        ));
+
+        ctx.write().await.faucet = Some(crate::node::Faucet::new(FaucetOptions {
+            wallet: TxBuilder::new(&Vec::from("ABC")),
+            amount: Amount(10),
+            interval: 60,
+        }));
+        let resp = faucet(None, ctx.clone(), req.clone()).await.unwrap();
+        assert_eq!(resp.nonce, 1);
+        assert_eq!(ctx.read().await.mempool.len(), 1);
+        assert!(matches!(
+            faucet(None, ctx.clone(), req).await,
+            Err(NodeError::FaucetRateLimited)
+        ));
+
+        // Another address gets the next nonce of the faucet wallet
+        let resp = faucet(
+            None,
+            ctx.clone(),
+            FaucetRequest {
+                address: TxBuilder::new(&Vec::from("DST2")).get_address().to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.nonce, 2);
+        assert_eq!(ctx.read().await.mempool.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_faucet_pays_min_fee() {
+        let ctx = test_context();
+        {
+            let mut ctx = ctx.write().await;
+            ctx.mempool = ctx
+                .mempool
+                .clone()
+                .with_min_fee(Amount(5))
+                .with_min_fee_per_byte(Amount(1));
+            ctx.faucet = Some(crate::node::Faucet::new(FaucetOptions {
+                wallet: TxBuilder::new(&Vec::from("ABC")),
+                amount: Amount(10),
+                interval: 60,
+            }));
+        }
+        let req = FaucetRequest {
+            address: TxBuilder::new(&Vec::from("DST")).get_address().to_string(),
+        };
+        faucet(None, ctx.clone(), req).await.unwrap();
+        let ctx = ctx.read().await;
+        let (tx, _) = ctx.mempool.tx_deltas().next().unwrap();
+        assert_eq!(tx.tx.fee.amount, ctx.mempool.min_fee_for(&tx.tx));
+        assert!(tx.tx.fee.amount > Amount(5));
+    }
+}
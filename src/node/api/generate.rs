@@ -0,0 +1,68 @@
+use super::messages::{GenerateRequest, GenerateResponse};
+use super::{promote_block, NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub const MAX_GENERATE_COUNT: usize = 1000;
+
+// Produces `count` blocks right away, each one in the slot right after the
+// previous block. Only available to trusted clients of regtest nodes, where
+// validator checks are disabled.
+pub async fn generate<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GenerateRequest,
+) -> Result<GenerateResponse, NodeError> {
+    if req.count > MAX_GENERATE_COUNT {
+        return Err(NodeError::TooManyBlocks(MAX_GENERATE_COUNT));
+    }
+    let mut generated = Vec::new();
+    for _ in 0..req.count {
+        let mut ctx = context.write().await;
+        let wallet = ctx.validator_wallet.clone();
+        let tip_ts = ctx.blockchain.get_tip()?.proof_of_stake.timestamp;
+        let ts = std::cmp::max(
+            ctx.network_timestamp(),
+            tip_ts + ctx.blockchain.config().slot_duration,
+        );
+        if let Some(draft) = ctx.try_produce_at(ts, wallet)? {
+            drop(ctx);
+            generated.push(draft.header.number);
+            promote_block(context.clone(), draft).await;
+        } else {
+            break;
+        }
+    }
+    Ok(GenerateResponse { generated })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_generate() {
+        let ctx = test_context();
+        let resp = generate(ctx.clone(), GenerateRequest { count: 3 })
+            .await
+            .unwrap();
+        assert_eq!(resp.generated, vec![101, 102, 103]);
+        assert_eq!(ctx.read().await.blockchain.get_height().unwrap(), 104);
+
+        assert!(matches!(
+            generate(
+                ctx.clone(),
+                GenerateRequest {
+                    count: MAX_GENERATE_COUNT + 1
+                }
+            )
+            .await,
+            Err(NodeError::TooManyBlocks(_))
+        ));
+        assert_eq!(ctx.read().await.blockchain.get_height().unwrap(), 104);
+    }
+}
@@ -26,7 +26,7 @@ use super::tests::*;
 mod tests {
     use super::*;
     use crate::core::Address;
-    use crate::node::TxBuilder;
+    use crate::wallet::TxBuilder;
 
     #[tokio::test]
     async fn test_get_account() {
@@ -0,0 +1,92 @@
+use super::messages::{
+    AccountTransaction, GetAccountTransactionsRequest, GetAccountTransactionsResponse,
+};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_PAGE_SIZE: usize = 100;
+
+pub async fn get_account_transactions<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetAccountTransactionsRequest,
+) -> Result<GetAccountTransactionsResponse, NodeError> {
+    let context = context.read().await;
+    let page_size = req
+        .page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .min(MAX_PAGE_SIZE);
+    Ok(GetAccountTransactionsResponse {
+        txs: context
+            .blockchain
+            .get_account_transactions(req.address.parse()?, req.since, req.page, page_size)?
+            .into_iter()
+            .map(|(block, hash)| AccountTransaction {
+                block,
+                hash: hex::encode(hash),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::TxBuilder;
+
+    #[tokio::test]
+    async fn test_get_account_transactions() {
+        let delegator = TxBuilder::new(&Vec::from("DELEGATOR"));
+        let ctx = test_context();
+        let all = get_account_transactions(
+            ctx.clone(),
+            GetAccountTransactionsRequest {
+                address: delegator.get_address().to_string(),
+                since: 0,
+                page: 0,
+                page_size: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(all.txs.len() >= 3);
+        assert!(all.txs.iter().all(|tx| tx.block == 0));
+
+        let second_page = get_account_transactions(
+            ctx.clone(),
+            GetAccountTransactionsRequest {
+                address: delegator.get_address().to_string(),
+                since: 0,
+                page: 1,
+                page_size: Some(2),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(second_page.txs[..], all.txs[2..all.txs.len().min(4)]);
+    }
+
+    #[tokio::test]
+    async fn test_get_account_transactions_unknown_address() {
+        let stranger = TxBuilder::new(&Vec::from("STRANGER"));
+        let ctx = test_context();
+        let resp = get_account_transactions(
+            ctx,
+            GetAccountTransactionsRequest {
+                address: stranger.get_address().to_string(),
+                since: 0,
+                page: 0,
+                page_size: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(resp.txs.is_empty());
+    }
+}
@@ -32,7 +32,7 @@ use super::tests::*;
 mod tests {
     use super::*;
     use crate::core::Amount;
-    use crate::node::TxBuilder;
+    use crate::wallet::TxBuilder;
 
     #[tokio::test]
     async fn test_get_balance() {
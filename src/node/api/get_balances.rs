@@ -0,0 +1,88 @@
+use super::messages::{GetBalancesRequest, GetBalancesResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::core::{Address, Amount, TokenId};
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub const MAX_BALANCE_ADDRESSES: usize = 256;
+
+/// Balances of many addresses at once, e.g. all of the addresses derived from a wallet
+pub async fn get_balances<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetBalancesRequest,
+) -> Result<GetBalancesResponse, NodeError> {
+    let addresses = req
+        .addresses
+        .split(',')
+        .filter(|a| !a.is_empty())
+        .map(|a| a.parse())
+        .collect::<Result<Vec<Address>, _>>()?;
+    if addresses.len() > MAX_BALANCE_ADDRESSES {
+        return Err(NodeError::TooManyAddresses(MAX_BALANCE_ADDRESSES));
+    }
+    let context = context.read().await;
+    let token_id: TokenId = req.token_id.parse()?;
+    let tkn = context
+        .blockchain
+        .get_token(token_id)?
+        .ok_or(crate::blockchain::BlockchainError::TokenNotFound)?;
+    let balances = addresses
+        .into_iter()
+        .map(|addr| context.blockchain.get_balance(addr, token_id))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(GetBalancesResponse {
+        total: balances.iter().fold(Amount(0), |sum, b| sum + *b),
+        balances,
+        name: tkn.name,
+        symbol: tkn.symbol,
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::TxBuilder;
+
+    #[tokio::test]
+    async fn test_get_balances() {
+        let ctx = test_context();
+        let abc = TxBuilder::new(&Vec::from("ABC")).get_address();
+        let other = TxBuilder::new(&Vec::from("NOBODY")).get_address();
+        let resp = get_balances(
+            ctx.clone(),
+            GetBalancesRequest {
+                token_id: "Ziesha".into(),
+                addresses: format!("{},{},{}", abc, other, abc),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            resp,
+            GetBalancesResponse {
+                balances: vec![Amount(10000), Amount(0), Amount(10000)],
+                total: Amount(20000),
+                name: "Ziesha".into(),
+                symbol: "ZSH".into(),
+            }
+        );
+
+        let too_many = vec![abc.to_string(); MAX_BALANCE_ADDRESSES + 1].join(",");
+        assert!(matches!(
+            get_balances(
+                ctx.clone(),
+                GetBalancesRequest {
+                    token_id: "Ziesha".into(),
+                    addresses: too_many,
+                },
+            )
+            .await,
+            Err(NodeError::TooManyAddresses(_))
+        ));
+    }
+}
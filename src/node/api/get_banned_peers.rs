@@ -0,0 +1,17 @@
+use super::messages::{GetBannedPeersRequest, GetBannedPeersResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub async fn get_banned_peers<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    _req: GetBannedPeersRequest,
+) -> Result<GetBannedPeersResponse, NodeError> {
+    let context = context.read().await;
+    let now = context.local_timestamp();
+    Ok(GetBannedPeersResponse {
+        banned: context.peer_manager.get_punished(now),
+    })
+}
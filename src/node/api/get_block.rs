@@ -0,0 +1,131 @@
+use super::messages::{
+    GetBlockRequest, GetBlockResponse, GetBlockTransactionsRequest, GetBlockTransactionsResponse,
+    InputError,
+};
+use super::{NodeContext, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::client::explorer::ExplorerBlockReward;
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Number of the block identified by either its number or its hex encoded hash
+fn block_number<K: KvStore, B: Blockchain<K>>(blockchain: &B, id: &str) -> Result<u64, NodeError> {
+    if id.len() == 64 {
+        let hash: [u8; 32] = hex::decode(id)
+            .ok()
+            .and_then(|h| h.try_into().ok())
+            .ok_or(InputError::Invalid)?;
+        Ok(blockchain
+            .get_header_number(&hash)?
+            .ok_or(BlockchainError::BlockNotFound)?)
+    } else {
+        Ok(id.parse().map_err(|_| InputError::Invalid)?)
+    }
+}
+
+pub async fn get_block<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetBlockRequest,
+) -> Result<GetBlockResponse, NodeError> {
+    let context = context.read().await;
+    let number = block_number(&context.blockchain, &req.id)?;
+    let header = context.blockchain.get_header(number)?;
+    let body = match context.blockchain.get_block(number) {
+        Ok(block) => Some(block.body),
+        Err(BlockchainError::BlockPruned) => None,
+        Err(e) => return Err(e.into()),
+    };
+    Ok(GetBlockResponse {
+        hash: hex::encode(header.hash()),
+        reward: ExplorerBlockReward {
+            validator: header.proof_of_stake.validator.to_string(),
+            reward: context.blockchain.get_block_reward(number)?.map(u64::from),
+            fees: body
+                .as_ref()
+                .map(|txs| txs.iter().map(|tx| u64::from(tx.fee.amount)).sum()),
+        },
+        header: (&header).into(),
+        transactions: body.map(|txs| txs.iter().map(|tx| hex::encode(tx.hash())).collect()),
+    })
+}
+
+pub async fn get_block_transactions<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetBlockTransactionsRequest,
+) -> Result<GetBlockTransactionsResponse, NodeError> {
+    let context = context.read().await;
+    let number = block_number(&context.blockchain, &req.id)?;
+    let block = context.blockchain.get_block(number)?;
+    Ok(GetBlockTransactionsResponse {
+        transactions: block.body.iter().map(|tx| tx.into()).collect(),
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_block() {
+        let ctx = test_context();
+        let (genesis, second) = {
+            let ctx = ctx.read().await;
+            (
+                ctx.blockchain.get_block(0).unwrap(),
+                ctx.blockchain.get_block(1).unwrap(),
+            )
+        };
+
+        let by_number = get_block(ctx.clone(), GetBlockRequest { id: "0".into() })
+            .await
+            .unwrap();
+        assert_eq!(by_number.hash, hex::encode(genesis.header.hash()));
+        assert_eq!(by_number.reward.reward, None);
+        assert_eq!(by_number.reward.fees, Some(0));
+        assert_eq!(
+            by_number.transactions.unwrap(),
+            genesis
+                .body
+                .iter()
+                .map(|tx| hex::encode(tx.hash()))
+                .collect::<Vec<_>>()
+        );
+
+        let by_hash = get_block(
+            ctx.clone(),
+            GetBlockRequest {
+                id: hex::encode(second.header.hash()),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(by_hash.header.number, 1);
+        assert_eq!(
+            by_hash.reward.validator,
+            second.header.proof_of_stake.validator.to_string()
+        );
+        assert!(by_hash.reward.reward.unwrap() > 0);
+
+        let txs =
+            get_block_transactions(ctx.clone(), GetBlockTransactionsRequest { id: "0".into() })
+                .await
+                .unwrap()
+                .transactions;
+        assert_eq!(txs.len(), genesis.body.len());
+        assert_eq!(txs[2].hash, hex::encode(genesis.body[2].hash()));
+        assert_eq!(txs[2].transaction.memo, genesis.body[2].memo);
+
+        assert!(matches!(
+            get_block(ctx.clone(), GetBlockRequest { id: "1000".into() }).await,
+            Err(NodeError::BlockchainError(BlockchainError::BlockNotFound))
+        ));
+        assert!(matches!(
+            get_block(ctx.clone(), GetBlockRequest { id: "abc".into() }).await,
+            Err(NodeError::InputError(_))
+        ));
+    }
+}
@@ -0,0 +1,50 @@
+use super::messages::{GetBlockMetasRequest, GetBlockMetasResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub async fn get_block_metas<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetBlockMetasRequest,
+) -> Result<GetBlockMetasResponse, NodeError> {
+    let context = context.read().await;
+    let count = std::cmp::min(context.opts.max_blocks_fetch, req.count);
+    let until = std::cmp::min(
+        context.blockchain.get_height()?,
+        req.since.saturating_add(count),
+    );
+    let metas = (req.since..until)
+        .map(|i| context.blockchain.get_block_meta(i))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(GetBlockMetasResponse { metas })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_block_metas() {
+        let ctx = test_context();
+        let resp = get_block_metas(
+            ctx.clone(),
+            GetBlockMetasRequest {
+                since: 99,
+                count: 10,
+            },
+        )
+        .await
+        .unwrap();
+        let blocks = ctx.read().await.blockchain.get_blocks(99, 10).unwrap();
+        assert_eq!(
+            resp.metas,
+            blocks.iter().map(|b| b.meta()).collect::<Vec<_>>()
+        );
+        assert_eq!(resp.metas.len(), 2);
+    }
+}
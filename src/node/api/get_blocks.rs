@@ -105,4 +105,19 @@ mod tests {
             .collect::<Vec<_>>();
         assert!(block_indices.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_get_blocks_huge_since() {
+        let ctx = test_context();
+        let resp = get_blocks(
+            ctx.clone(),
+            GetBlocksRequest {
+                since: u64::MAX,
+                count: 10,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(resp.blocks.is_empty());
+    }
 }
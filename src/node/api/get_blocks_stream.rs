@@ -0,0 +1,197 @@
+use super::messages::GetBlocksRequest;
+use super::{NodeContext, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::client::encode_frame;
+use crate::db::KvStore;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use hyper::body::Sender;
+use hyper::Body;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+
+/// A streamed response of blocks `since..until`, waiting to be written
+pub struct BlockStream {
+    since: u64,
+    until: u64,
+    sender: Sender,
+}
+
+/// Like `get_blocks`, but the blocks are sent as a stream of frames. Blocks are read
+/// and serialized one at a time by `serve_block_streams` as the receiver consumes
+/// them, so that neither side needs a single buffer holding all of them.
+pub async fn get_blocks_stream<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetBlocksRequest,
+) -> Result<Body, NodeError> {
+    let context = context.read().await;
+    // Failing before the response starts tells the receiver why it got no blocks
+    if req.since > 0 && req.since < context.blockchain.get_pruned_height()? {
+        return Err(BlockchainError::BlockPruned.into());
+    }
+    let count = std::cmp::min(context.opts.max_blocks_fetch, req.count);
+    let until = std::cmp::min(
+        context.blockchain.get_height()?,
+        req.since.saturating_add(count),
+    );
+    let (sender, body) = Body::channel();
+    context
+        .block_streams
+        .as_ref()
+        .ok_or(NodeError::NotListeningError)?
+        .send(BlockStream {
+            since: req.since,
+            until,
+            sender,
+        })
+        .map_err(|_| NodeError::NotListeningError)?;
+    Ok(body)
+}
+
+/// Writes the blocks of the streamed responses until `shutdown` resolves
+pub async fn serve_block_streams<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    mut streams: mpsc::UnboundedReceiver<BlockStream>,
+    shutdown: impl futures::Future<Output = ()>,
+) {
+    tokio::pin!(shutdown);
+    let mut writers = FuturesUnordered::new();
+    loop {
+        tokio::select! {
+            stream = streams.recv() => match stream {
+                Some(stream) => writers.push(write_blocks(&context, stream)),
+                None => break,
+            },
+            Some(_) = writers.next(), if !writers.is_empty() => {}
+            _ = &mut shutdown => return,
+        }
+    }
+    while writers.next().await.is_some() {}
+}
+
+async fn write_blocks<K: KvStore, B: Blockchain<K>>(
+    context: &RwLock<NodeContext<K, B>>,
+    mut stream: BlockStream,
+) {
+    for index in stream.since..stream.until {
+        let frame = context
+            .read()
+            .await
+            .blockchain
+            .get_block(index)
+            .map_err(NodeError::from)
+            .and_then(|block| encode_frame(&block));
+        match frame {
+            Ok(frame) => {
+                // The receiver is gone
+                if stream.sender.send_data(frame).await.is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Could not stream block {}: {}", index, e);
+                stream.sender.abort();
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::FrameDecoder;
+    use crate::core::Block;
+
+    async fn read_blocks(body: Body) -> Vec<Block> {
+        let mut decoder = FrameDecoder::default();
+        decoder.push(&hyper::body::to_bytes(body).await.unwrap());
+        let mut blocks = Vec::new();
+        while let Some(block) = decoder.next_item().unwrap() {
+            blocks.push(block);
+        }
+        assert!(decoder.is_empty());
+        blocks
+    }
+
+    async fn stream_blocks(since: u64, count: u64) -> Vec<Block> {
+        let ctx = test_context();
+        let (streams_send, streams_recv) = mpsc::unbounded_channel();
+        ctx.write().await.block_streams = Some(streams_send);
+        let body = get_blocks_stream(ctx.clone(), GetBlocksRequest { since, count })
+            .await
+            .unwrap();
+        // Lets the writer stop once the stream is written
+        ctx.write().await.block_streams = None;
+        let (blocks, _) = tokio::join!(
+            read_blocks(body),
+            serve_block_streams(ctx, streams_recv, futures::future::pending())
+        );
+        blocks
+    }
+
+    fn numbers(blocks: Vec<Block>) -> Vec<u64> {
+        blocks.iter().map(|b| b.header.number).collect()
+    }
+
+    #[tokio::test]
+    async fn test_get_blocks_stream() {
+        assert_eq!(
+            numbers(stream_blocks(10, 10).await),
+            vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
+        );
+        assert_eq!(
+            numbers(stream_blocks(10, 10000).await),
+            vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25]
+        );
+        assert_eq!(numbers(stream_blocks(99, 10000).await), vec![99, 100]);
+        assert!(stream_blocks(200, 10).await.is_empty());
+        assert!(stream_blocks(u64::MAX, 10).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_blocks_stream_not_served() {
+        assert!(matches!(
+            get_blocks_stream(
+                test_context(),
+                GetBlocksRequest {
+                    since: 10,
+                    count: 10
+                }
+            )
+            .await,
+            Err(NodeError::NotListeningError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_blocks_stream_pruned() {
+        let ctx = test_context();
+        let (streams_send, _streams_recv) = mpsc::unbounded_channel();
+        ctx.write().await.block_streams = Some(streams_send);
+        ctx.write().await.blockchain.prune(10).unwrap();
+        assert!(matches!(
+            get_blocks_stream(
+                ctx.clone(),
+                GetBlocksRequest {
+                    since: 10,
+                    count: 10
+                }
+            )
+            .await,
+            Err(NodeError::BlockchainError(BlockchainError::BlockPruned))
+        ));
+        assert!(get_blocks_stream(
+            ctx,
+            GetBlocksRequest {
+                since: 91,
+                count: 10
+            }
+        )
+        .await
+        .is_ok());
+    }
+}
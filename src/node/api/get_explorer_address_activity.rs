@@ -0,0 +1,56 @@
+use super::messages::{GetExplorerAddressActivityRequest, GetExplorerAddressActivityResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+const MAX_PAGE_SIZE: usize = 100;
+
+pub async fn get_explorer_address_activity<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetExplorerAddressActivityRequest,
+) -> Result<GetExplorerAddressActivityResponse, NodeError> {
+    let context = context.read().await;
+    let explorer = context
+        .explorer
+        .as_ref()
+        .ok_or(NodeError::ExplorerDisabled)?;
+    Ok(GetExplorerAddressActivityResponse {
+        activity: explorer
+            .address_activity(
+                &req.address.parse()?,
+                req.page,
+                std::cmp::min(req.page_size, MAX_PAGE_SIZE),
+            )
+            .map_err(BlockchainError::from)?,
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::TxBuilder;
+
+    #[tokio::test]
+    async fn test_get_explorer_address_activity() {
+        let ctx = test_context_with_explorer().await;
+        let validator = TxBuilder::new(&Vec::from("VALIDATOR")).get_address();
+        let resp = get_explorer_address_activity(
+            ctx.clone(),
+            GetExplorerAddressActivityRequest {
+                address: validator.to_string(),
+                page: 0,
+                page_size: 1000,
+            },
+        )
+        .await
+        .unwrap();
+        // Registered and got delegations in the genesis block
+        assert_eq!(resp.activity.len(), 2);
+        assert!(resp.activity.iter().all(|a| a.block == 0));
+    }
+}
@@ -0,0 +1,61 @@
+use super::messages::{GetExplorerBlockStatsRequest, GetExplorerBlockStatsResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub async fn get_explorer_block_stats<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetExplorerBlockStatsRequest,
+) -> Result<GetExplorerBlockStatsResponse, NodeError> {
+    let context = context.read().await;
+    let explorer = context
+        .explorer
+        .as_ref()
+        .ok_or(NodeError::ExplorerDisabled)?;
+    let count = std::cmp::min(context.opts.max_blocks_fetch, req.count);
+    Ok(GetExplorerBlockStatsResponse {
+        indexed_height: explorer.height().map_err(BlockchainError::from)?,
+        stats: explorer
+            .block_stats(req.since, count)
+            .map_err(BlockchainError::from)?,
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_explorer_block_stats() {
+        let ctx = test_context_with_explorer().await;
+        let resp = get_explorer_block_stats(
+            ctx.clone(),
+            GetExplorerBlockStatsRequest {
+                since: 95,
+                count: 10,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.indexed_height, 101);
+        assert_eq!(
+            resp.stats.iter().map(|s| s.number).collect::<Vec<_>>(),
+            vec![95, 96, 97, 98, 99, 100]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_explorer_block_stats_disabled() {
+        let ctx = test_context();
+        assert!(matches!(
+            get_explorer_block_stats(ctx, GetExplorerBlockStatsRequest { since: 0, count: 1 })
+                .await,
+            Err(NodeError::ExplorerDisabled)
+        ));
+    }
+}
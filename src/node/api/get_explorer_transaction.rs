@@ -0,0 +1,67 @@
+use super::messages::{GetExplorerTransactionRequest, GetExplorerTransactionResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub async fn get_explorer_transaction<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetExplorerTransactionRequest,
+) -> Result<GetExplorerTransactionResponse, NodeError> {
+    let context = context.read().await;
+    let explorer = context
+        .explorer
+        .as_ref()
+        .ok_or(NodeError::ExplorerDisabled)?;
+    let location = explorer
+        .find_transaction(&req.hash)
+        .map_err(BlockchainError::from)?
+        .ok_or(NodeError::TransactionNotFound)?;
+    let transaction = match context.blockchain.get_block(location.block) {
+        Ok(block) => block.body.get(location.index as usize).map(|tx| tx.into()),
+        Err(BlockchainError::BlockPruned) => None,
+        Err(e) => return Err(e.into()),
+    };
+    Ok(GetExplorerTransactionResponse {
+        block: location.block,
+        index: location.index,
+        transaction,
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_explorer_transaction() {
+        let ctx = test_context_with_explorer().await;
+        let genesis = ctx.read().await.blockchain.get_block(0).unwrap();
+        let resp = get_explorer_transaction(
+            ctx.clone(),
+            GetExplorerTransactionRequest {
+                hash: hex::encode(genesis.body[2].hash()),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.block, 0);
+        assert_eq!(resp.index, 2);
+        assert_eq!(resp.transaction.unwrap().memo, genesis.body[2].memo);
+
+        assert!(matches!(
+            get_explorer_transaction(
+                ctx.clone(),
+                GetExplorerTransactionRequest {
+                    hash: "00".repeat(32)
+                },
+            )
+            .await,
+            Err(NodeError::TransactionNotFound)
+        ));
+    }
+}
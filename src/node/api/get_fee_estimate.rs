@@ -0,0 +1,245 @@
+use super::messages::{GetFeeEstimateRequest, GetFeeEstimateResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::{updates_contract, Blockchain};
+use crate::core::{Amount, Money, TokenId};
+use crate::db::KvStore;
+use std::cmp::Ordering;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Number of latest blocks the fees and fullness are taken from
+const RECENT_BLOCKS: u64 = 10;
+/// Blocks fuller than this compete on fees, below it the minimum fee is enough
+const CONGESTED_FULLNESS: f32 = 0.5;
+
+/// Compares the fees paid per byte by two transactions, given their fees and sizes
+fn cmp_fee_rate((a_fee, a_size): &(Amount, usize), (b_fee, b_size): &(Amount, usize)) -> Ordering {
+    (u64::from(*a_fee) as u128 * *b_size as u128)
+        .cmp(&(u64::from(*b_fee) as u128 * *a_size as u128))
+}
+
+/// Fee a transaction of `size` bytes pays at the per byte rate of the given transaction
+fn fee_at_rate((fee, tx_size): &(Amount, usize), size: usize) -> Amount {
+    let fee = u64::from(*fee) as u128 * size as u128 / std::cmp::max(*tx_size, 1) as u128;
+    Amount(std::cmp::min(fee, u64::MAX as u128) as u64)
+}
+
+pub async fn get_fee_estimate<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetFeeEstimateRequest,
+) -> Result<GetFeeEstimateResponse, NodeError> {
+    let context = context.read().await;
+    let config = context.blockchain.config();
+    let mpn_contract_id = config.mpn_config.mpn_contract_id;
+    let max_block_size = config.max_block_size;
+    // Fees are suggested for a plain payment, unless the size is given
+    let (size, min_fee) = match req.size {
+        Some(size) => (size, context.mempool.min_fee_for_size(size)),
+        None => {
+            let payment = context.user_wallet.create_transaction(
+                "".into(),
+                context.user_wallet.get_address(),
+                Money::ziesha(0),
+                Money::ziesha(0),
+                0,
+            );
+            (payment.tx.size(), context.mempool.min_fee_for(&payment.tx))
+        }
+    };
+
+    // Genesis and pruned blocks are skipped
+    let since = std::cmp::max(
+        context
+            .blockchain
+            .get_height()?
+            .saturating_sub(RECENT_BLOCKS),
+        std::cmp::max(context.blockchain.get_pruned_height()?, 1),
+    );
+    let blocks = context.blockchain.get_blocks(since, RECENT_BLOCKS)?;
+    let mut fees = Vec::new();
+    let mut used_size = 0;
+    for tx in blocks
+        .iter()
+        .flat_map(|b| b.body.iter())
+        .filter(|tx| tx.src.is_some() && !updates_contract(tx, &mpn_contract_id))
+    {
+        used_size += tx.size();
+        if tx.fee.token_id == TokenId::Ziesha {
+            fees.push((tx.fee.amount, tx.size()));
+        }
+    }
+    fees.sort_by(cmp_fee_rate);
+    let block_fullness = if blocks.is_empty() {
+        0.0
+    } else {
+        used_size as f32 / (blocks.len() * max_block_size) as f32
+    };
+
+    // Pending transactions paying the most per byte are included first, a transaction
+    // has to pay more per byte than the ones beyond the next block to be included in it
+    let mut pending = context
+        .mempool
+        .tx_deltas()
+        .filter(|(tx_delta, _)| {
+            tx_delta.tx.fee.token_id == TokenId::Ziesha
+                && !updates_contract(&tx_delta.tx, &mpn_contract_id)
+        })
+        .map(|(tx_delta, _)| (tx_delta.tx.fee.amount, tx_delta.tx.size()))
+        .collect::<Vec<_>>();
+    pending.sort_by(|a, b| cmp_fee_rate(b, a));
+    let mut pending_size = 0;
+    let mut overflow = None;
+    for tx in pending {
+        pending_size += tx.1;
+        if pending_size > max_block_size && overflow.is_none() {
+            overflow = Some(tx);
+        }
+    }
+
+    let mut suggested_fee = min_fee;
+    if block_fullness >= CONGESTED_FULLNESS && !fees.is_empty() {
+        suggested_fee = std::cmp::max(suggested_fee, fee_at_rate(&fees[fees.len() / 2], size));
+    }
+    if let Some(tx) = overflow {
+        let fee = fee_at_rate(&tx, size);
+        suggested_fee = std::cmp::max(suggested_fee, Amount(fee.0.saturating_add(1)));
+    }
+
+    Ok(GetFeeEstimateResponse {
+        min_fee: context.mempool.min_fee(),
+        min_fee_per_byte: context.mempool.min_fee_per_byte(),
+        suggested_fee,
+        block_fullness,
+        mempool_depth: pending_size as f32 / max_block_size as f32,
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::Mempool;
+    use crate::core::GeneralTransaction;
+    use crate::wallet::TxBuilder;
+
+    #[tokio::test]
+    async fn test_get_fee_estimate() {
+        let ctx = test_context();
+        let resp = get_fee_estimate(ctx.clone(), GetFeeEstimateRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(resp.min_fee, Amount(0));
+        assert_eq!(resp.suggested_fee, Amount(0));
+        assert_eq!(resp.block_fullness, 0.0);
+        assert_eq!(resp.mempool_depth, 0.0);
+
+        ctx.write().await.mempool = Mempool::new(Amount(1), 100).with_min_fee(Amount(5));
+        let resp = get_fee_estimate(ctx.clone(), GetFeeEstimateRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(resp.min_fee, Amount(5));
+        assert_eq!(resp.suggested_fee, Amount(5));
+    }
+
+    #[tokio::test]
+    async fn test_get_fee_estimate_full_mempool() {
+        let ctx = test_context();
+        let max_block_size = ctx.read().await.blockchain.config().max_block_size;
+        // Only four of these transactions fit in a block
+        let memo = "a".repeat(max_block_size * 2 / 9);
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let tx = |fee, nonce| {
+            abc.create_transaction(
+                memo.clone(),
+                abc.get_address(),
+                Money::ziesha(200),
+                Money::ziesha(fee),
+                nonce,
+            )
+        };
+        let tx_size = tx(10, 1).tx.size();
+        {
+            let mut ctx = ctx.write().await;
+            let ctx = &mut *ctx;
+            ctx.mempool = Mempool::new(Amount(1), 100).with_min_fee(Amount(10));
+            for i in 0..10u32 {
+                ctx.mempool
+                    .add_tx(
+                        &ctx.blockchain,
+                        GeneralTransaction::TransactionAndDelta(tx(10 + i as u64, i + 1)),
+                        true,
+                        0,
+                    )
+                    .unwrap();
+            }
+            assert_eq!(ctx.mempool.len(), 10);
+        }
+
+        // The transactions paying 16 to 19 fill the next block
+        let resp = get_fee_estimate(
+            ctx.clone(),
+            GetFeeEstimateRequest {
+                size: Some(tx_size),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.min_fee, Amount(10));
+        assert_eq!(resp.suggested_fee, Amount(16));
+        assert_eq!(
+            resp.mempool_depth,
+            (10 * tx_size) as f32 / max_block_size as f32
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_fee_estimate_large_tx() {
+        let ctx = test_context();
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let large_tx = |fee| {
+            abc.create_transaction(
+                "a".repeat(1000),
+                abc.get_address(),
+                Money::ziesha(200),
+                Money::ziesha(fee),
+                1,
+            )
+        };
+        ctx.write().await.mempool = Mempool::new(Amount(1), 100)
+            .with_min_fee(Amount(5))
+            .with_min_fee_per_byte(Amount(2));
+
+        let payment = get_fee_estimate(ctx.clone(), GetFeeEstimateRequest::default())
+            .await
+            .unwrap();
+        let resp = get_fee_estimate(
+            ctx.clone(),
+            GetFeeEstimateRequest {
+                size: Some(large_tx(0).tx.size()),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.min_fee, Amount(5));
+        assert_eq!(resp.min_fee_per_byte, Amount(2));
+        assert_eq!(
+            resp.suggested_fee,
+            ctx.read().await.mempool.min_fee_for(&large_tx(0).tx)
+        );
+        assert!(payment.suggested_fee < resp.suggested_fee);
+
+        let mut ctx = ctx.write().await;
+        let ctx = &mut *ctx;
+        ctx.mempool
+            .add_tx(
+                &ctx.blockchain,
+                GeneralTransaction::TransactionAndDelta(large_tx(resp.suggested_fee.into())),
+                true,
+                0,
+            )
+            .unwrap();
+        assert_eq!(ctx.mempool.len(), 1);
+    }
+}
@@ -88,4 +88,19 @@ mod tests {
         let block_indices = resp.headers.iter().map(|b| b.number).collect::<Vec<_>>();
         assert!(block_indices.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_get_headers_huge_since() {
+        let ctx = test_context();
+        let resp = get_headers(
+            ctx.clone(),
+            GetHeadersRequest {
+                since: u64::MAX,
+                count: 10,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(resp.headers.is_empty());
+    }
 }
@@ -0,0 +1,77 @@
+use super::messages::{GetMetricsHistoryRequest, GetMetricsHistoryResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Maximum number of samples returned by a single query
+const MAX_SAMPLES: usize = 1440;
+
+pub async fn get_metrics_history<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetMetricsHistoryRequest,
+) -> Result<GetMetricsHistoryResponse, NodeError> {
+    let context = context.read().await;
+    let metrics = context.metrics.as_ref().ok_or(NodeError::MetricsDisabled)?;
+    Ok(GetMetricsHistoryResponse {
+        samples: metrics
+            .samples(req.since, req.until.unwrap_or(u32::MAX), MAX_SAMPLES)
+            .map_err(BlockchainError::from)?,
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{Metrics, MetricsOptions};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_get_metrics_history() {
+        let ctx = test_context();
+        let mut metrics = Metrics::new(MetricsOptions {
+            db: None,
+            interval: Duration::from_secs(60),
+            retention: 3600,
+        })
+        .unwrap();
+        for i in 0..5 {
+            metrics.record(i * 60, 100 + i as u64, 3, 0).unwrap();
+        }
+        ctx.write().await.metrics = Some(metrics);
+
+        let resp = get_metrics_history(
+            ctx.clone(),
+            GetMetricsHistoryRequest {
+                since: 60,
+                until: Some(180),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            resp.samples.iter().map(|s| s.height).collect::<Vec<_>>(),
+            vec![101, 102, 103]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_metrics_history_disabled() {
+        let ctx = test_context();
+        assert!(matches!(
+            get_metrics_history(
+                ctx,
+                GetMetricsHistoryRequest {
+                    since: 0,
+                    until: None
+                }
+            )
+            .await,
+            Err(NodeError::MetricsDisabled)
+        ));
+    }
+}
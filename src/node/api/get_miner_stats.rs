@@ -0,0 +1,85 @@
+use super::messages::{GetMinerStatsRequest, GetMinerStatsResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::core::Amount;
+use crate::db::KvStore;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Number of recent blocks the average block time and the validators are taken from
+const BLOCK_TIME_WINDOW: u64 = 10;
+
+pub async fn get_miner_stats<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    _req: GetMinerStatsRequest,
+) -> Result<GetMinerStatsResponse, NodeError> {
+    let context = context.read().await;
+    let ts = context.network_timestamp();
+    let height = context.blockchain.get_height()?;
+    let tip = context.blockchain.get_tip()?;
+    // Genesis timestamp is not related to the actual block times
+    let since = std::cmp::max(height.saturating_sub(BLOCK_TIME_WINDOW + 1), 1);
+    let headers = context.blockchain.get_headers(since, height - since)?;
+    let total_stake = Amount(
+        context
+            .blockchain
+            .get_stakers()?
+            .into_iter()
+            .fold(0u64, |sum, (_, stake)| sum.saturating_add(stake.into())),
+    );
+    let recent_validators = headers
+        .iter()
+        .map(|h| &h.proof_of_stake.validator)
+        .collect::<HashSet<_>>()
+        .len();
+    let average_block_time = if headers.len() > 1 {
+        let first = headers.first().unwrap().proof_of_stake.timestamp;
+        let last = headers.last().unwrap().proof_of_stake.timestamp;
+        Some(last.saturating_sub(first) / (headers.len() as u32 - 1))
+    } else {
+        None
+    };
+    Ok(GetMinerStatsResponse {
+        accepted_solutions: context.miner_stats.accepted_solutions,
+        rejected_solutions: context.miner_stats.rejected_solutions,
+        produced_blocks: context.miner_stats.produced_blocks,
+        stale_solutions: context.miner_stats.stale_solutions,
+        stale_blocks: context.miner_stats.stale_blocks,
+        height,
+        average_block_time,
+        time_since_last_block: ts.saturating_sub(tip.proof_of_stake.timestamp),
+        total_stake,
+        recent_validators,
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_miner_stats() {
+        let ctx = test_context();
+        let resp = get_miner_stats(ctx.clone(), GetMinerStatsRequest {})
+            .await
+            .unwrap();
+        assert_eq!(resp.height, 101);
+        assert_eq!(resp.average_block_time, Some(60));
+        assert_eq!(resp.accepted_solutions, 0);
+        assert_eq!(resp.rejected_solutions, 0);
+        assert_eq!(resp.recent_validators, 1);
+        let stakers = ctx.read().await.blockchain.get_stakers().unwrap();
+        assert!(!stakers.is_empty());
+        assert_eq!(
+            u64::from(resp.total_stake),
+            stakers
+                .into_iter()
+                .map(|(_, stake)| u64::from(stake))
+                .sum::<u64>()
+        );
+    }
+}
@@ -16,7 +16,7 @@ pub async fn get_mpn_work<K: KvStore, B: Blockchain<K>>(
         .map(|p| p.get_works(req.mpn_address.clone()))
         .unwrap_or_default();
     if !works.is_empty() {
-        println!("Sending {} works to {}", works.len(), req.mpn_address);
+        tracing::info!("Sending {} works to {}", works.len(), req.mpn_address);
     }
     Ok(GetMpnWorkResponse { works })
 }
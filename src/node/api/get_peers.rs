@@ -21,6 +21,7 @@ pub async fn get_peers<K: KvStore, B: Blockchain<K>>(
                     .get_nodes()
                     .map(|p| p.address)
                     .collect(),
+                details: context.peer_manager.get_node_details(),
             });
         }
     }
@@ -33,5 +34,6 @@ pub async fn get_peers<K: KvStore, B: Blockchain<K>>(
             .into_iter()
             .map(|p| p.address)
             .collect(),
+        details: Vec::new(),
     })
 }
@@ -0,0 +1,118 @@
+use super::messages::{GetSnapshotRequest, GetSnapshotResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Maximum number of key-value pairs returned in a single snapshot chunk
+pub const MAX_SNAPSHOT_CHUNK: usize = 1024;
+
+pub async fn get_snapshot<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetSnapshotRequest,
+) -> Result<GetSnapshotResponse, NodeError> {
+    let context = context.read().await;
+    let count = std::cmp::min(MAX_SNAPSHOT_CHUNK, req.count);
+    Ok(GetSnapshotResponse {
+        tip: context.blockchain.get_tip()?,
+        pairs: context.blockchain.get_snapshot(req.after, count)?,
+    })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::KvStoreChain;
+    use crate::db::{RamKvStore, StringKey};
+
+    #[tokio::test]
+    async fn test_get_snapshot() {
+        let ctx = test_context();
+        let mut pairs = Vec::new();
+        let tip = loop {
+            let resp = get_snapshot(
+                ctx.clone(),
+                GetSnapshotRequest {
+                    after: pairs.last().map(|(k, _): &(StringKey, _)| k.clone()),
+                    count: 100,
+                },
+            )
+            .await
+            .unwrap();
+            let done = resp.pairs.len() < 100;
+            pairs.extend(resp.pairs);
+            if done {
+                break resp.tip;
+            }
+        };
+
+        let mut chain = KvStoreChain::new(
+            RamKvStore::new(),
+            crate::config::blockchain::get_test_blockchain_config(),
+        )
+        .unwrap();
+        let ctx = ctx.read().await;
+
+        // The snapshot should end at the announced tip
+        assert!(matches!(
+            chain.import_snapshot(&ctx.blockchain.get_header(99).unwrap(), &pairs),
+            Err(crate::blockchain::BlockchainError::InvalidSnapshot)
+        ));
+
+        // Headers of the snapshot should extend our genesis block
+        let mut tampered = pairs.clone();
+        for (k, v) in tampered.iter_mut() {
+            if *k == crate::db::keys::header(50) {
+                let mut header: crate::core::Header = v.clone().try_into().unwrap();
+                header.proof_of_stake.timestamp = 0;
+                *v = header.into();
+            }
+        }
+        assert!(matches!(
+            chain.import_snapshot(&tip, &tampered),
+            Err(crate::blockchain::BlockchainError::InvalidSnapshot)
+        ));
+        assert_eq!(chain.get_height().unwrap(), 1);
+
+        chain.import_snapshot(&tip, &pairs).unwrap();
+        assert_eq!(chain.get_height().unwrap(), 101);
+        assert_eq!(chain.get_tip().unwrap(), tip);
+        assert_eq!(chain.get_pruned_height().unwrap(), 100);
+        assert_eq!(
+            chain.get_block(100).unwrap(),
+            ctx.blockchain.get_block(100).unwrap()
+        );
+        for (k, v) in ctx
+            .blockchain
+            .database()
+            .pairs("".into())
+            .unwrap()
+            .into_iter()
+        {
+            let is_block_data = ["BLK-", "MRK-", "RLK-"]
+                .iter()
+                .any(|prefix| k.0.starts_with(prefix));
+            if !is_block_data {
+                assert_eq!(chain.db().get(k).unwrap(), Some(v));
+            }
+        }
+
+        // A snapshot can only be imported into a fresh chain
+        assert!(matches!(
+            chain.import_snapshot(&tip, &pairs),
+            Err(crate::blockchain::BlockchainError::SnapshotOnNonEmptyChain)
+        ));
+
+        // The tip can still be rolled back, older blocks are pruned
+        chain.rollback().unwrap();
+        assert_eq!(chain.get_height().unwrap(), 100);
+        assert!(matches!(
+            chain.rollback(),
+            Err(crate::blockchain::BlockchainError::BlockPruned)
+        ));
+    }
+}
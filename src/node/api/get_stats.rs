@@ -1,10 +1,50 @@
-use super::messages::{GetStatsRequest, GetStatsResponse};
+use super::messages::{FeePercentiles, GetStatsRequest, GetStatsResponse};
 use super::{NodeContext, NodeError};
-use crate::blockchain::Blockchain;
+use crate::blockchain::{Blockchain, BlockchainError, Mempool};
+use crate::core::TokenId;
 use crate::db::KvStore;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Number of recent blocks the average block interval is measured on
+const BLOCK_INTERVAL_WINDOW: u64 = 100;
+
+fn avg_block_interval<K: KvStore, B: Blockchain<K>>(
+    chain: &B,
+    height: u64,
+) -> Result<Option<f64>, BlockchainError> {
+    // Genesis timestamp is not the time it was produced
+    let since = std::cmp::max(1, height.saturating_sub(BLOCK_INTERVAL_WINDOW + 1));
+    let headers = chain.get_headers(since, BLOCK_INTERVAL_WINDOW + 1)?;
+    Ok(match (headers.first(), headers.last()) {
+        (Some(first), Some(last)) if headers.len() > 1 => Some(
+            last.proof_of_stake
+                .timestamp
+                .saturating_sub(first.proof_of_stake.timestamp) as f64
+                / (headers.len() - 1) as f64,
+        ),
+        _ => None,
+    })
+}
+
+fn fee_percentiles(mempool: &Mempool) -> Option<FeePercentiles> {
+    let mut fees = mempool
+        .tx_deltas()
+        .filter(|(tx, _)| tx.tx.fee.token_id == TokenId::Ziesha)
+        .map(|(tx, _)| tx.tx.fee.amount)
+        .collect::<Vec<_>>();
+    if fees.is_empty() {
+        return None;
+    }
+    fees.sort();
+    let percentile = |p: usize| fees[(fees.len() - 1) * p / 100];
+    Some(FeePercentiles {
+        p10: percentile(10),
+        p50: percentile(50),
+        p90: percentile(90),
+    })
+}
+
 pub async fn get_stats<K: KvStore, B: Blockchain<K>>(
     context: Arc<RwLock<NodeContext<K, B>>>,
     _req: GetStatsRequest,
@@ -12,11 +52,20 @@ pub async fn get_stats<K: KvStore, B: Blockchain<K>>(
     let context = context.read().await;
     let ts = context.network_timestamp();
     let (epoch, slot) = context.blockchain.epoch_slot(ts);
+    let height = context.blockchain.get_height()?;
+    let best_peer_height = context
+        .peer_manager
+        .get_nodes()
+        .map(|p| p.height)
+        .max()
+        .unwrap_or(height);
     Ok(GetStatsResponse {
         social_profiles: context.social_profiles.clone(),
         address: context.validator_wallet.get_address().to_string(),
-        height: context.blockchain.get_height()?,
+        height,
+        best_peer_height,
         nodes: context.peer_manager.node_count(),
+        mempool_size: context.mempool.len(),
         next_reward: context.blockchain.next_reward()?,
         timestamp: ts,
         timestamp_offset: context.timestamp_offset,
@@ -25,5 +74,53 @@ pub async fn get_stats<K: KvStore, B: Blockchain<K>>(
         version: env!("CARGO_PKG_VERSION").into(),
         network: context.network.clone(),
         validator_claim: context.validator_claim.clone(),
+        syncing: best_peer_height > height,
+        uptime: context.local_timestamp().saturating_sub(context.started_at),
+        restart: context.opts.restart.clone(),
+        avg_block_interval: avg_block_interval(&context.blockchain, height)?,
+        mempool_fees: fee_percentiles(&context.mempool),
     })
 }
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{BlockchainError, MockBlockchain};
+    use crate::config::blockchain::get_test_blockchain_config;
+    use crate::core::Amount;
+    use crate::wallet::TxBuilder;
+
+    #[tokio::test]
+    async fn test_get_stats() {
+        let validator = TxBuilder::new(&Vec::from("VALIDATOR"));
+        let mut chain = MockBlockchain::new(get_test_blockchain_config());
+        for i in 1..=5 {
+            let block = chain.next_block(i * 60, &validator, vec![]);
+            chain.push_block(block);
+        }
+        chain.set_next_reward(Amount(1234));
+        let ctx = test_context_with(chain);
+        let resp = get_stats(ctx.clone(), GetStatsRequest {}).await.unwrap();
+        assert_eq!(resp.height, 6);
+        assert_eq!(resp.best_peer_height, 6);
+        assert_eq!(resp.next_reward, Amount(1234));
+        assert_eq!(resp.address, validator.get_address().to_string());
+        assert!(!resp.syncing);
+        assert_eq!(resp.avg_block_interval, Some(60.0));
+        assert_eq!(resp.mempool_fees, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_stats_chain_error() {
+        let mut chain = MockBlockchain::new(get_test_blockchain_config());
+        chain.fail("next_reward", || BlockchainError::Inconsistency);
+        let ctx = test_context_with(chain);
+        assert!(matches!(
+            get_stats(ctx.clone(), GetStatsRequest {}).await,
+            Err(NodeError::BlockchainError(BlockchainError::Inconsistency))
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+use super::messages::{
+    GetTransactionStatusRequest, GetTransactionStatusResponse, TransactionStatus,
+};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::client::messages::InputError;
+use crate::db::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub async fn get_transaction_status<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: GetTransactionStatusRequest,
+) -> Result<GetTransactionStatusResponse, NodeError> {
+    let context = context.read().await;
+    let hash: [u8; 32] = hex::decode(&req.hash)
+        .ok()
+        .and_then(|h| h.try_into().ok())
+        .ok_or(InputError::Invalid)?;
+
+    let status = if let Some(loc) = context.blockchain.get_transaction_location(&hash)? {
+        TransactionStatus::Confirmed {
+            block: loc.block,
+            index: loc.index,
+            confirmations: context.blockchain.get_height()? - loc.block,
+        }
+    } else if context
+        .mempool
+        .tx_deltas()
+        .any(|(tx_delta, _)| tx_delta.tx.hash() == hash)
+    {
+        TransactionStatus::Pending
+    } else if let Some(reason) = context.mempool.rejection(&hash) {
+        TransactionStatus::Rejected {
+            reason: reason.into(),
+        }
+    } else {
+        TransactionStatus::Unknown
+    };
+    Ok(GetTransactionStatusResponse { status })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::super::{messages::TransactRequest, transact};
+    use super::*;
+    use crate::core::{GeneralTransaction, Money};
+    use crate::wallet::TxBuilder;
+
+    async fn status<K: KvStore, B: Blockchain<K>>(
+        ctx: Arc<RwLock<NodeContext<K, B>>>,
+        hash: [u8; 32],
+    ) -> TransactionStatus {
+        get_transaction_status(
+            ctx,
+            GetTransactionStatusRequest {
+                hash: hex::encode(hash),
+            },
+        )
+        .await
+        .unwrap()
+        .status
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction_status() {
+        let ctx = test_context();
+        let genesis = ctx.read().await.blockchain.get_block(0).unwrap();
+        assert_eq!(
+            status(ctx.clone(), genesis.body[2].hash()).await,
+            TransactionStatus::Confirmed {
+                block: 0,
+                index: 2,
+                confirmations: 101
+            }
+        );
+
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let send = |nonce| {
+            abc.create_transaction(
+                "".into(),
+                TxBuilder::new(&Vec::from("CBA")).get_address(),
+                Money::ziesha(10),
+                Money::ziesha(1),
+                nonce,
+            )
+        };
+        let pending = send(1);
+        assert_eq!(
+            status(ctx.clone(), pending.tx.hash()).await,
+            TransactionStatus::Unknown
+        );
+        ctx.write()
+            .await
+            .mempool_add_tx(
+                true,
+                GeneralTransaction::TransactionAndDelta(pending.clone()),
+            )
+            .unwrap();
+        assert_eq!(
+            status(ctx.clone(), pending.tx.hash()).await,
+            TransactionStatus::Pending
+        );
+
+        let used_nonce = send(0);
+        let resp = transact(
+            None,
+            ctx.clone(),
+            TransactRequest {
+                tx: GeneralTransaction::TransactionAndDelta(used_nonce.clone()),
+                timestamp_commit: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            status(ctx.clone(), used_nonce.tx.hash()).await,
+            TransactionStatus::Rejected {
+                reason: resp.error.unwrap()
+            }
+        );
+
+        assert!(matches!(
+            get_transaction_status(
+                ctx.clone(),
+                GetTransactionStatusRequest { hash: "zz".into() },
+            )
+            .await,
+            Err(NodeError::InputError(_))
+        ));
+    }
+}
@@ -1,27 +1,47 @@
-use super::{promote_block, promote_validator_claim, NodeContext, NodeError};
+use super::{promote_block, promote_tx, promote_validator_claim, NodeContext, NodeError};
 
 use crate::client::messages;
 
 mod get_stats;
 pub use get_stats::*;
+mod get_metrics_history;
+pub use get_metrics_history::*;
+mod get_miner_stats;
+pub use get_miner_stats::*;
 mod get_peers;
 pub use get_peers::*;
+mod get_banned_peers;
+pub use get_banned_peers::*;
+mod unban_peer;
+pub use unban_peer::*;
 mod post_peer;
 pub use post_peer::*;
 mod post_block;
 pub use post_block::*;
 mod get_blocks;
 pub use get_blocks::*;
+mod get_blocks_stream;
+pub use get_blocks_stream::*;
 mod get_explorer_blocks;
 pub use get_explorer_blocks::*;
 mod get_headers;
 pub use get_headers::*;
+mod get_block_metas;
+pub use get_block_metas::*;
+mod get_snapshot;
+pub use get_snapshot::*;
 mod transact;
 pub use transact::*;
 mod shutdown;
 pub use shutdown::*;
+mod update_config;
+pub use update_config::*;
+mod faucet;
+pub use faucet::*;
 mod get_account;
 pub use get_account::*;
+mod get_account_transactions;
+pub use get_account_transactions::*;
 mod get_mpn_account;
 pub use get_mpn_account::*;
 mod get_explorer_mpn_accounts;
@@ -32,6 +52,14 @@ mod get_debug_data;
 pub use get_debug_data::*;
 mod get_balance;
 pub use get_balance::*;
+mod get_balances;
+pub use get_balances::*;
+mod get_transaction_status;
+pub use get_transaction_status::*;
+mod get_fee_estimate;
+pub use get_fee_estimate::*;
+mod get_block;
+pub use get_block::*;
 mod get_token;
 pub use get_token::*;
 mod post_validator_claim;
@@ -50,13 +78,29 @@ mod get_explorer_mempool;
 pub use get_explorer_mempool::*;
 mod get_check_tx;
 pub use get_check_tx::*;
+mod generate;
+pub use generate::*;
+mod rpc;
+pub use rpc::*;
+#[cfg(feature = "explorer")]
+mod get_explorer_address_activity;
+#[cfg(feature = "explorer")]
+pub use get_explorer_address_activity::*;
+#[cfg(feature = "explorer")]
+mod get_explorer_block_stats;
+#[cfg(feature = "explorer")]
+pub use get_explorer_block_stats::*;
+#[cfg(feature = "explorer")]
+mod get_explorer_transaction;
+#[cfg(feature = "explorer")]
+pub use get_explorer_transaction::*;
 #[cfg(test)]
 mod generate_block;
 #[cfg(test)]
 pub use generate_block::*;
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use super::*;
     use crate::blockchain::Blockchain;
     use crate::blockchain::KvStoreChain;
@@ -64,22 +108,18 @@ mod tests {
     use crate::client::NodeRequest;
     use crate::client::OutgoingSender;
     use crate::core::Amount;
-    use crate::db::RamKvStore;
-    use crate::node::local_timestamp;
+    use crate::db::{KvStore, RamKvStore};
     use crate::node::Mempool;
     use crate::node::PeerManager;
-    use crate::node::TxBuilder;
+    use crate::utils::local_timestamp;
+    use crate::wallet::TxBuilder;
     use std::sync::Arc;
     use tokio::sync::mpsc;
     use tokio::sync::RwLock;
 
     pub fn test_context() -> Arc<RwLock<NodeContext<RamKvStore, KvStoreChain<RamKvStore>>>> {
-        let network: String = "test".into();
         const NUM_BLOCKS: usize = 100;
-        let opts = crate::config::node::get_simulator_options();
-        let (out_send, _) = mpsc::unbounded_channel::<NodeRequest>();
         let validator_wallet = TxBuilder::new(&Vec::from("VALIDATOR"));
-        let user_wallet = TxBuilder::new(&Vec::from("ABC"));
         let mut blockchain = KvStoreChain::new(
             RamKvStore::new(),
             crate::config::blockchain::get_test_blockchain_config(),
@@ -92,22 +132,48 @@ mod tests {
                 .unwrap();
             blockchain.extend((i + 1) as u64, &[block]).unwrap();
         }
+        test_context_with(blockchain)
+    }
+
+    /// Context of a node on top of the given chain, with no peers and an empty mempool
+    pub fn test_context_with<K: KvStore, B: Blockchain<K>>(
+        blockchain: B,
+    ) -> Arc<RwLock<NodeContext<K, B>>> {
+        let network: String = "test".into();
+        let opts = crate::config::node::get_simulator_options();
+        let (out_send, _) = mpsc::unbounded_channel::<NodeRequest>();
+        let validator_wallet = TxBuilder::new(&Vec::from("VALIDATOR"));
+        let user_wallet = TxBuilder::new(&Vec::from("ABC"));
         Arc::new(RwLock::new(NodeContext {
             _phantom: std::marker::PhantomData,
             firewall: None,
             opts: opts.clone(),
+            default_punishments: opts.punishments.clone(),
             network: network.clone(),
             social_profiles: SocialProfiles { discord: None },
             address: None,
             shutdown: false,
-            outgoing: Arc::new(OutgoingSender {
-                network: network.clone(),
-                chan: out_send,
-                priv_key: user_wallet.get_priv_key(),
-            }),
+            started_at: opts.clock.now(),
+            outgoing: Arc::new(OutgoingSender::new(
+                user_wallet.get_priv_key(),
+                network.clone(),
+                out_send,
+            )),
             mpn_workers: Default::default(),
             mpn_work_pool: None,
-            mempool: Mempool::new(Amount(1_000_000_000)),
+            miner_stats: Default::default(),
+            last_status: None,
+            faucet: None,
+            #[cfg(feature = "explorer")]
+            explorer: None,
+            webhooks: None,
+            metrics: None,
+            #[cfg(feature = "zmq")]
+            publisher: None,
+            block_streams: None,
+            mempool: Mempool::new(Amount(1_000_000_000), opts.mempool_max_size),
+            mempool_store: None,
+            ban_store: None,
             blockchain,
             validator_wallet: validator_wallet.clone(),
             user_wallet: user_wallet.clone(),
@@ -121,4 +187,21 @@ mod tests {
             validator_claim: None,
         }))
     }
+
+    #[cfg(feature = "explorer")]
+    pub async fn test_context_with_explorer(
+    ) -> Arc<RwLock<NodeContext<RamKvStore, KvStoreChain<RamKvStore>>>> {
+        let ctx = test_context();
+        {
+            let mut ctx = ctx.write().await;
+            let mut explorer = crate::node::Explorer::new(crate::node::ExplorerOptions {
+                db: None,
+                max_blocks_per_round: 1000,
+            })
+            .unwrap();
+            explorer.update(&ctx.blockchain).unwrap();
+            ctx.explorer = Some(explorer);
+        }
+        ctx
+    }
 }
@@ -1,16 +1,38 @@
-use super::messages::{PostBlockRequest, PostBlockResponse};
+use super::messages::{Offense, PostBlockRequest, PostBlockResponse};
 use super::{promote_block, NodeContext, NodeError};
 use crate::blockchain::Blockchain;
+use crate::client::PeerAddress;
 use crate::db::KvStore;
+use crate::node::heartbeat::generate_block;
+use crate::node::offense_of;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Competing blocks are only counted as stale when they are at most this many
+/// blocks behind the tip, older ones are just leftovers of a finished race
+pub const STALE_BLOCK_WINDOW: u64 = 2;
+
 pub async fn post_block<K: KvStore, B: Blockchain<K>>(
+    client: Option<SocketAddr>,
     context: Arc<RwLock<NodeContext<K, B>>>,
     req: PostBlockRequest,
 ) -> Result<PostBlockResponse, NodeError> {
     let mut ctx = context.write().await;
-    if req.block.header.number == ctx.blockchain.get_height()? {
+    let height = ctx.blockchain.get_height()?;
+    if req.block.header.number < height {
+        // Another block has already been accepted for this height, keep the
+        // first one and only keep track of the loser.
+        if req.block.header.number + STALE_BLOCK_WINDOW >= height
+            && ctx.blockchain.get_header(req.block.header.number)?.hash() != req.block.header.hash()
+        {
+            tracing::info!(
+                "Stale block received for height {}",
+                req.block.header.number
+            );
+            ctx.miner_stats.block_stale();
+        }
+    } else if req.block.header.number == height {
         if req
             .block
             .header
@@ -21,11 +43,144 @@ pub async fn post_block<K: KvStore, B: Blockchain<K>>(
         {
             return Err(NodeError::BlockTimestampInFuture);
         }
-        ctx.blockchain
-            .extend(req.block.header.number, &[req.block.clone()])?;
+        if let Err(e) = ctx
+            .blockchain
+            .extend(req.block.header.number, &[req.block.clone()])
+        {
+            // Honest peers may relay blocks of another fork, but never blocks with
+            // broken proofs or signatures
+            let offense = offense_of(&e);
+            if let Some(client) = client {
+                if matches!(offense, Offense::BadProof | Offense::BadSignature) {
+                    ctx.punish_bad_behavior(
+                        PeerAddress(client),
+                        offense,
+                        "Posted an invalid block!",
+                    );
+                }
+            }
+            return Err(e.into());
+        }
         ctx.on_update()?;
+        // Our own claim and MPN works were built on top of the previous tip and
+        // lost the race, prepare fresh ones on top of the new block right away
+        let our_claim = ctx
+            .validator_claim
+            .as_ref()
+            .map(|c| c.address == ctx.validator_wallet.get_address())
+            .unwrap_or(false);
+        if our_claim {
+            tracing::info!("Block {} won the race!", req.block.header.number);
+            ctx.mpn_work_pool = None;
+            ctx.validator_claim = None;
+        }
         drop(ctx);
-        promote_block(context, req.block).await;
+        promote_block(context.clone(), req.block).await;
+        if our_claim {
+            if let Err(e) = generate_block(context).await {
+                tracing::warn!("Cannot prepare new works: {}", e);
+            }
+        }
     }
     Ok(PostBlockResponse {})
 }
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Money, Signature, Transaction, TransactionData};
+    use crate::wallet::TxBuilder;
+
+    #[tokio::test]
+    async fn test_post_stale_block() {
+        let ctx = test_context();
+        let mut block = ctx.read().await.blockchain.get_block(100).unwrap();
+        post_block(
+            None,
+            ctx.clone(),
+            PostBlockRequest {
+                block: block.clone(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(ctx.read().await.miner_stats.stale_blocks, 0);
+
+        block.header.proof_of_stake.timestamp += 1;
+        post_block(None, ctx.clone(), PostBlockRequest { block })
+            .await
+            .unwrap();
+        assert_eq!(ctx.read().await.miner_stats.stale_blocks, 1);
+        assert_eq!(ctx.read().await.blockchain.get_height().unwrap(), 101);
+
+        // Competitors of old blocks are not part of a race anymore
+        let mut block = ctx.read().await.blockchain.get_block(10).unwrap();
+        block.header.proof_of_stake.timestamp += 1;
+        post_block(None, ctx.clone(), PostBlockRequest { block })
+            .await
+            .unwrap();
+        assert_eq!(ctx.read().await.miner_stats.stale_blocks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_post_competing_block_drops_stale_work() {
+        let ctx = test_context();
+        let block = {
+            let mut ctx = ctx.write().await;
+            let ts = 100 * 60 + 30;
+            let proof = ctx
+                .blockchain
+                .validator_status(ts, &ctx.validator_wallet)
+                .unwrap();
+            let node = PeerAddress("127.0.0.1:8765".parse().unwrap());
+            let claim = ctx.validator_wallet.claim_validator(ts, proof, node);
+            ctx.validator_claim = Some(claim);
+            ctx.blockchain
+                .draft_block(ts, &[], &ctx.validator_wallet, true)
+                .unwrap()
+                .unwrap()
+        };
+        post_block(None, ctx.clone(), PostBlockRequest { block })
+            .await
+            .unwrap();
+        let ctx = ctx.read().await;
+        assert_eq!(ctx.blockchain.get_height().unwrap(), 102);
+        assert!(ctx.validator_claim.is_none());
+        assert!(ctx.mpn_work_pool.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_post_invalid_block_punishes_client() {
+        let ctx = test_context();
+        ctx.write().await.opts.punishments = crate::config::node::get_node_options().punishments;
+        let client: SocketAddr = "1.2.3.4:5678".parse().unwrap();
+        let mut block = {
+            let ctx = ctx.read().await;
+            ctx.blockchain
+                .draft_block(100 * 60 + 30, &[], &ctx.validator_wallet, true)
+                .unwrap()
+                .unwrap()
+        };
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        block.body.push(Transaction {
+            memo: "".into(),
+            src: Some(abc.get_address()),
+            data: TransactionData::RegularSend { entries: vec![] },
+            nonce: 1,
+            fee: Money::ziesha(0),
+            sig: Signature::Unsigned,
+        });
+        block.header.block_root = block.merkle_tree().root();
+        assert!(
+            post_block(Some(client), ctx.clone(), PostBlockRequest { block })
+                .await
+                .is_err()
+        );
+        let ctx = ctx.read().await;
+        let now = ctx.local_timestamp();
+        assert!(ctx.peer_manager.is_ip_punished(now, client.ip()));
+    }
+}
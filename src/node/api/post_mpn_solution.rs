@@ -10,14 +10,20 @@ pub async fn post_mpn_solution<K: KvStore, B: Blockchain<K>>(
     req: PostMpnSolutionRequest,
 ) -> Result<PostMpnSolutionResponse, NodeError> {
     let mut ctx = context.write().await;
+    let ctx = &mut *ctx;
     if let Some(mpn_work_pool) = &mut ctx.mpn_work_pool {
         let mut accepted = 0;
         for (id, proof) in req.proofs.iter() {
-            if mpn_work_pool.prove(*id, proof) {
+            if mpn_work_pool.is_solved(*id) {
+                ctx.miner_stats.solution_stale();
+            } else if mpn_work_pool.prove(*id, proof) {
+                ctx.miner_stats.solution_accepted();
                 accepted += 1;
+            } else {
+                ctx.miner_stats.solution_rejected();
             }
         }
-        println!("Got {} accepted SNARK proofs!", accepted);
+        tracing::info!("Got {} accepted SNARK proofs!", accepted);
         Ok(PostMpnSolutionResponse { accepted })
     } else {
         Ok(PostMpnSolutionResponse { accepted: 0 })
@@ -1,8 +1,7 @@
-use super::messages::{HandshakeRequest, HandshakeResponse};
+use super::messages::{HandshakeRequest, HandshakeResponse, PeerDirection};
 use super::{NodeContext, NodeError};
 use crate::blockchain::Blockchain;
 use crate::db::KvStore;
-use crate::utils;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -21,9 +20,10 @@ pub async fn post_peer<K: KvStore, B: Blockchain<K>>(
                 return Err(NodeError::HandshakeClientMismatch);
             }
         }
+        let now = context.local_timestamp();
         context
             .peer_manager
-            .add_candidate(utils::local_timestamp(), peer);
+            .add_candidate(now, peer, PeerDirection::Inbound);
     }
 
     Ok(HandshakeResponse {
@@ -31,5 +31,7 @@ pub async fn post_peer<K: KvStore, B: Blockchain<K>>(
         timestamp: context.network_timestamp(),
         timestamp_offset: context.timestamp_offset,
         validator_claim: context.validator_claim.clone(),
+        uptime: Some(context.local_timestamp().saturating_sub(context.started_at)),
+        restart: context.opts.restart.clone(),
     })
 }
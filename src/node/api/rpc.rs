@@ -0,0 +1,385 @@
+use super::messages::{
+    GetBalanceRequest, GetFeeEstimateRequest, GetTransactionStatusRequest, TransactRequest,
+};
+use super::{NodeContext, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::core::GeneralTransaction;
+use crate::db::KvStore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const SERVER_ERROR: i64 = -32000;
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    /// Requests without an id are notifications, which are not answered
+    id: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+    pub id: Value,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<NodeError> for RpcError {
+    fn from(e: NodeError) -> Self {
+        match e {
+            NodeError::InputError(_)
+            | NodeError::AccountParseAddressError(_)
+            | NodeError::MpnAccountParseAddressError(_)
+            | NodeError::GeneralParseAddressError(_)
+            | NodeError::TokenIdParseError(_)
+            | NodeError::TooManyAddresses(_)
+            | NodeError::TooManyBlocks(_) => Self::new(INVALID_PARAMS, e),
+            _ => Self::new(SERVER_ERROR, e),
+        }
+    }
+}
+
+impl From<BlockchainError> for RpcError {
+    fn from(e: BlockchainError) -> Self {
+        Self::new(SERVER_ERROR, e)
+    }
+}
+
+impl RpcResponse {
+    fn new(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(v) => (Some(v), None),
+            Err(e) => (None, Some(e)),
+        };
+        Self {
+            jsonrpc: "2.0".into(),
+            result,
+            error,
+            id,
+        }
+    }
+}
+
+/// Params may be given either by name or by position
+#[derive(Deserialize)]
+struct NumberParams {
+    number: u64,
+}
+
+#[derive(Deserialize)]
+struct BalanceParams {
+    address: String,
+    #[serde(default = "ziesha")]
+    token_id: String,
+}
+
+fn ziesha() -> String {
+    "Ziesha".into()
+}
+
+#[derive(Deserialize)]
+struct SubmitParams {
+    tx: GeneralTransaction,
+}
+
+fn params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e))
+}
+
+fn to_result<T: Serialize>(v: T) -> Result<Value, RpcError> {
+    serde_json::to_value(v).map_err(|e| RpcError::new(SERVER_ERROR, e))
+}
+
+async fn call<K: KvStore, B: Blockchain<K>>(
+    client: Option<SocketAddr>,
+    context: &Arc<RwLock<NodeContext<K, B>>>,
+    method: &str,
+    params_value: Value,
+) -> Result<Value, RpcError> {
+    match method {
+        "chain_getHeight" => to_result(context.read().await.blockchain.get_height()?),
+        "chain_getHeader" => {
+            let p: NumberParams = params(params_value)?;
+            to_result(context.read().await.blockchain.get_header(p.number)?)
+        }
+        "chain_getBlock" => {
+            let p: NumberParams = params(params_value)?;
+            to_result(context.read().await.blockchain.get_block(p.number)?)
+        }
+        "account_getBalance" => {
+            let p: BalanceParams = params(params_value)?;
+            to_result(
+                super::get_balance(
+                    Arc::clone(context),
+                    GetBalanceRequest {
+                        address: p.address,
+                        token_id: p.token_id,
+                    },
+                )
+                .await?,
+            )
+        }
+        "tx_submit" => {
+            let p: SubmitParams = params(params_value)?;
+            let resp = super::transact(
+                client,
+                Arc::clone(context),
+                TransactRequest {
+                    tx: p.tx,
+                    timestamp_commit: None,
+                },
+            )
+            .await?;
+            match resp.error {
+                Some(err) => Err(RpcError::new(SERVER_ERROR, err)),
+                None => Ok(Value::Null),
+            }
+        }
+        "tx_getStatus" => {
+            let p: GetTransactionStatusRequest = params(params_value)?;
+            to_result(super::get_transaction_status(Arc::clone(context), p).await?)
+        }
+        "tx_estimateFee" => {
+            let p: GetFeeEstimateRequest = if params_value.is_null() {
+                Default::default()
+            } else {
+                params(params_value)?
+            };
+            to_result(super::get_fee_estimate(Arc::clone(context), p).await?)
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("method {} not found", method),
+        )),
+    }
+}
+
+async fn handle_single<K: KvStore, B: Blockchain<K>>(
+    client: Option<SocketAddr>,
+    context: &Arc<RwLock<NodeContext<K, B>>>,
+    req: Value,
+) -> Option<RpcResponse> {
+    let id = req.get("id").cloned().unwrap_or(Value::Null);
+    let req: RpcRequest = match serde_json::from_value(req) {
+        Ok(req) => req,
+        Err(e) => return Some(RpcResponse::new(id, Err(RpcError::new(INVALID_REQUEST, e)))),
+    };
+    if req.jsonrpc != "2.0" {
+        return Some(RpcResponse::new(
+            id,
+            Err(RpcError::new(INVALID_REQUEST, "jsonrpc should be 2.0")),
+        ));
+    }
+    let result = call(client, context, &req.method, req.params).await;
+    req.id.map(|id| RpcResponse::new(id, result))
+}
+
+/// JSON-RPC 2.0 endpoint, answering single and batch requests. Returns `None` when
+/// there is nothing to answer, i.e. all of the requests were notifications.
+pub async fn rpc<K: KvStore, B: Blockchain<K>>(
+    client: Option<SocketAddr>,
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    body: &[u8],
+) -> Result<Option<Value>, NodeError> {
+    let req: Value = match serde_json::from_slice(body) {
+        Ok(req) => req,
+        Err(e) => {
+            return Ok(Some(serde_json::to_value(RpcResponse::new(
+                Value::Null,
+                Err(RpcError::new(PARSE_ERROR, e)),
+            ))?));
+        }
+    };
+    match req {
+        Value::Array(reqs) if !reqs.is_empty() => {
+            let mut resps = Vec::new();
+            for req in reqs {
+                resps.extend(handle_single(client, &context, req).await);
+            }
+            Ok(if resps.is_empty() {
+                None
+            } else {
+                Some(serde_json::to_value(resps)?)
+            })
+        }
+        req => Ok(handle_single(client, &context, req)
+            .await
+            .map(serde_json::to_value)
+            .transpose()?),
+    }
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::KvStoreChain;
+    use crate::core::{Amount, Header};
+    use crate::db::RamKvStore;
+    use serde_json::json;
+
+    async fn request(
+        ctx: &Arc<RwLock<NodeContext<RamKvStore, KvStoreChain<RamKvStore>>>>,
+        req: Value,
+    ) -> Option<Value> {
+        rpc(None, Arc::clone(ctx), &serde_json::to_vec(&req).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rpc_methods() {
+        let ctx = test_context();
+
+        let resp = request(
+            &ctx,
+            json!({"jsonrpc": "2.0", "method": "chain_getHeight", "id": 1}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp, json!({"jsonrpc": "2.0", "result": 101, "id": 1}));
+
+        // Named and positional params
+        for params in [json!({"number": 5}), json!([5])] {
+            let resp: RpcResponse = serde_json::from_value(
+                request(
+                    &ctx,
+                    json!({"jsonrpc": "2.0", "method": "chain_getHeader", "params": params, "id": "a"}),
+                )
+                .await
+                .unwrap(),
+            )
+            .unwrap();
+            let header: Header = serde_json::from_value(resp.result.unwrap()).unwrap();
+            assert_eq!(header.number, 5);
+            assert_eq!(resp.id, json!("a"));
+        }
+
+        let abc = crate::wallet::TxBuilder::new(&Vec::from("ABC")).get_address();
+        let resp: RpcResponse = serde_json::from_value(
+            request(
+                &ctx,
+                json!({"jsonrpc": "2.0", "method": "account_getBalance", "params": {"address": abc.to_string()}, "id": 2}),
+            )
+            .await
+            .unwrap(),
+        )
+        .unwrap();
+        let balance: Amount =
+            serde_json::from_value(resp.result.unwrap()["balance"].clone()).unwrap();
+        assert_eq!(
+            balance,
+            ctx.read()
+                .await
+                .blockchain
+                .get_balance(abc, crate::core::TokenId::Ziesha)
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rpc_errors() {
+        let ctx = test_context();
+        let code = |resp: Option<Value>| resp.unwrap()["error"]["code"].as_i64().unwrap();
+
+        assert_eq!(
+            code(rpc(None, Arc::clone(&ctx), b"{").await.unwrap()),
+            PARSE_ERROR
+        );
+        assert_eq!(
+            code(request(&ctx, json!({"method": "chain_getHeight", "id": 1})).await),
+            INVALID_REQUEST
+        );
+        assert_eq!(
+            code(request(&ctx, json!({"jsonrpc": "2.0", "method": "foo", "id": 1})).await),
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            code(
+                request(
+                    &ctx,
+                    json!({"jsonrpc": "2.0", "method": "chain_getBlock", "params": {}, "id": 1})
+                )
+                .await
+            ),
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            code(
+                request(
+                    &ctx,
+                    json!({"jsonrpc": "2.0", "method": "account_getBalance", "params": ["xyz"], "id": 1})
+                )
+                .await
+            ),
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            code(
+                request(
+                    &ctx,
+                    json!({"jsonrpc": "2.0", "method": "chain_getBlock", "params": [1000], "id": 1})
+                )
+                .await
+            ),
+            SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rpc_batch_and_notifications() {
+        let ctx = test_context();
+        assert_eq!(
+            request(&ctx, json!({"jsonrpc": "2.0", "method": "chain_getHeight"})).await,
+            None
+        );
+        let resp = request(
+            &ctx,
+            json!([
+                {"jsonrpc": "2.0", "method": "chain_getHeight", "id": 1},
+                {"jsonrpc": "2.0", "method": "chain_getHeight"},
+                {"jsonrpc": "2.0", "method": "foo", "id": 2},
+            ]),
+        )
+        .await
+        .unwrap();
+        let resps: Vec<RpcResponse> = serde_json::from_value(resp).unwrap();
+        assert_eq!(resps.len(), 2);
+        assert_eq!(resps[0].result, Some(json!(101)));
+        assert_eq!(resps[1].error.as_ref().unwrap().code, METHOD_NOT_FOUND);
+        assert_eq!(
+            request(&ctx, json!([])).await.unwrap()["error"]["code"],
+            json!(INVALID_REQUEST)
+        );
+    }
+}
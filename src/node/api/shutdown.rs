@@ -9,6 +9,9 @@ pub async fn shutdown<K: KvStore, B: Blockchain<K>>(
     context: Arc<RwLock<NodeContext<K, B>>>,
     _req: ShutdownRequest,
 ) -> Result<ShutdownResponse, NodeError> {
-    context.write().await.shutdown = true;
+    let mut ctx = context.write().await;
+    ctx.shutdown = true;
+    // Heartbeats waiting for peers should not keep the node alive
+    ctx.outgoing.cancel();
     Ok(ShutdownResponse {})
 }
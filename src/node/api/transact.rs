@@ -1,6 +1,6 @@
 use super::messages::{TransactRequest, TransactResponse};
-use super::{NodeContext, NodeError};
-use crate::blockchain::{Blockchain, BlockchainError};
+use super::{promote_tx, NodeContext, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError, Mempool};
 use crate::core::GeneralTransaction;
 use crate::db::KvStore;
 use std::net::SocketAddr;
@@ -14,17 +14,47 @@ pub async fn transact<K: KvStore, B: Blockchain<K>>(
 ) -> Result<TransactResponse, NodeError> {
     let mut ctx = context.write().await;
 
+    // Chain transactions can later be looked up by hash, so remember why they failed
+    let tx_hash = match &req.tx {
+        GeneralTransaction::TransactionAndDelta(tx_delta) => Some(tx_delta.tx.hash()),
+        _ => None,
+    };
+    let reject = |mempool: &mut Mempool, err: BlockchainError| {
+        if let Some(hash) = tx_hash {
+            mempool.reject(hash, err.to_string());
+        }
+        err
+    };
+
     if let GeneralTransaction::TransactionAndDelta(tx_delta) = &req.tx {
         if let Some(err) = ctx.blockchain.check_tx(&tx_delta.tx).err() {
             if !matches!(err, BlockchainError::InvalidTransactionNonce) {
                 return Ok(TransactResponse {
-                    error: Some(err.to_string()),
+                    error: Some(reject(&mut ctx.mempool, err).to_string()),
                 });
             }
         }
     }
 
+    if let Err(err) = ctx.mempool.check_fee(&req.tx) {
+        return Ok(TransactResponse {
+            error: Some(reject(&mut ctx.mempool, err).to_string()),
+        });
+    }
+
     let is_local = client.map(|c| c.ip().is_loopback()).unwrap_or(false);
-    ctx.mempool_add_tx(is_local, req.tx)?;
+    if let Err(err) = ctx.mempool.check_nonce(&ctx.blockchain, &req.tx, is_local) {
+        return Ok(TransactResponse {
+            error: Some(reject(&mut ctx.mempool, err).to_string()),
+        });
+    }
+    let relayed = ctx.opts.relay_txs.then(|| req.tx.clone());
+    if let Err(err) = ctx.mempool_add_tx(is_local, req.tx) {
+        return Err(reject(&mut ctx.mempool, err).into());
+    }
+    drop(ctx);
+    if let Some(tx) = relayed {
+        promote_tx(context, tx).await;
+    }
     Ok(TransactResponse { error: None })
 }
@@ -0,0 +1,59 @@
+use super::messages::{UnbanPeerRequest, UnbanPeerResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::node::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub async fn unban_peer<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: UnbanPeerRequest,
+) -> Result<UnbanPeerResponse, NodeError> {
+    let mut context = context.write().await;
+    let unbanned = context.unpunish_ip(req.ip)?;
+    if unbanned {
+        tracing::info!("Peer {} is unbanned", req.ip);
+    }
+    Ok(UnbanPeerResponse { unbanned })
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::RamKvStore;
+    use crate::node::BanStore;
+
+    #[tokio::test]
+    async fn test_unban_peer() {
+        let ctx = test_context();
+        let ip = "1.2.3.4".parse().unwrap();
+        {
+            let mut ctx = ctx.write().await;
+            ctx.ban_store = Some(BanStore::new(Box::new(RamKvStore::new())));
+            ctx.punish_ip_for(ip, 3600);
+            assert_eq!(ctx.ban_store.as_ref().unwrap().all().unwrap().len(), 1);
+            assert_eq!(
+                ctx.peer_manager.get_punished(ctx.local_timestamp()).len(),
+                1
+            );
+        }
+        assert!(
+            unban_peer(ctx.clone(), UnbanPeerRequest { ip })
+                .await
+                .unwrap()
+                .unbanned
+        );
+        assert!(
+            !unban_peer(ctx.clone(), UnbanPeerRequest { ip })
+                .await
+                .unwrap()
+                .unbanned
+        );
+        let ctx = ctx.read().await;
+        assert!(ctx.ban_store.as_ref().unwrap().all().unwrap().is_empty());
+        assert!(!ctx.peer_manager.is_ip_punished(ctx.local_timestamp(), ip));
+    }
+}
@@ -0,0 +1,140 @@
+use super::messages::{UpdateConfigRequest, UpdateConfigResponse};
+use super::{NodeContext, NodeError};
+use crate::blockchain::Blockchain;
+use crate::node::KvStore;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Applies settings that can change without restarting the node. Absent limits
+// keep the current ones, while punishments are rebuilt from the ones the node was
+// started with, so that removed offenses get back to their defaults.
+pub async fn update_config<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    req: UpdateConfigRequest,
+) -> Result<UpdateConfigResponse, NodeError> {
+    let mut context = context.write().await;
+    if let Some(num_peers) = req.num_peers {
+        context.opts.num_peers = num_peers;
+    }
+    let mut punishments = context.default_punishments.clone();
+    punishments.durations.extend(req.punishments);
+    if let Some(max_punish) = req.max_punish {
+        punishments.max = max_punish;
+    }
+    context.opts.punishments = punishments;
+    if let Some(firewall) = context.firewall.as_mut() {
+        if let Some(limit) = req.request_limit_per_minute {
+            firewall.set_request_count_limit_per_minute(limit);
+        }
+        if let Some(limit) = req.traffic_limit_per_15m {
+            firewall.set_traffic_limit_per_15m(limit);
+        }
+        if let Some(limit) = req.max_body_size {
+            firewall.set_max_body_size(limit);
+        }
+    }
+    context
+        .blockchain
+        .update_block_template_limits(req.max_block_txs, req.min_fee);
+    tracing::info!(
+        num_peers = context.opts.num_peers,
+        max_block_txs = req.max_block_txs,
+        min_fee = req.min_fee.map(u64::from),
+        "Runtime configuration updated"
+    );
+    Ok(UpdateConfigResponse {})
+}
+
+#[cfg(test)]
+use super::tests::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::DefaultBlockTemplatePolicy;
+    use crate::client::messages::Offense;
+    use crate::core::Money;
+    use crate::wallet::TxBuilder;
+
+    #[tokio::test]
+    async fn test_update_config() {
+        let ctx = test_context();
+        let prev_num_peers = ctx.read().await.opts.num_peers;
+        update_config(ctx.clone(), UpdateConfigRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(ctx.read().await.opts.num_peers, prev_num_peers);
+        update_config(
+            ctx.clone(),
+            UpdateConfigRequest {
+                num_peers: Some(3),
+                max_block_txs: Some(10),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(ctx.read().await.opts.num_peers, 3);
+
+        update_config(
+            ctx.clone(),
+            UpdateConfigRequest {
+                punishments: [(Offense::Spam, 120)].into_iter().collect(),
+                max_punish: Some(600),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let punishments = ctx.read().await.opts.punishments.clone();
+        assert_eq!(punishments.max, 600);
+        assert_eq!(punishments.duration(Offense::Spam, 0), 120);
+
+        // Offenses removed from the config get back to their defaults
+        update_config(ctx.clone(), UpdateConfigRequest::default())
+            .await
+            .unwrap();
+        let ctx = ctx.read().await;
+        assert_eq!(ctx.opts.punishments, ctx.default_punishments);
+    }
+
+    #[tokio::test]
+    async fn test_update_config_keeps_block_template_policy() {
+        let ctx = test_context();
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        ctx.write()
+            .await
+            .blockchain
+            .set_block_template_policy(Arc::new(DefaultBlockTemplatePolicy {
+                blacklist: [abc.get_address()].into_iter().collect(),
+                ..Default::default()
+            }));
+        update_config(
+            ctx.clone(),
+            UpdateConfigRequest {
+                max_block_txs: Some(10),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let ctx = ctx.read().await;
+        let tx = abc.create_transaction(
+            "".into(),
+            abc.get_address(),
+            Money::ziesha(1),
+            Money::ziesha(10),
+            1,
+        );
+        let draft = ctx
+            .blockchain
+            .draft_block(100 * 60 + 30, &[tx], &ctx.validator_wallet, true)
+            .unwrap()
+            .unwrap();
+        assert!(draft
+            .body
+            .iter()
+            .all(|tx| tx.src != Some(abc.get_address())));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::db::{Blob, KvStore, KvStoreError, StringKey, WriteOp};
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+mod keys {
+    use super::*;
+
+    pub fn prefix() -> StringKey {
+        "BAN-".into()
+    }
+
+    pub fn ban(ip: &IpAddr) -> StringKey {
+        format!("BAN-{}", ip).into()
+    }
+}
+
+/// Bans of misbehaving peers, kept in a separate database so that a restart does not
+/// let them back in.
+pub struct BanStore {
+    database: Box<dyn KvStore + Send + Sync>,
+}
+
+impl BanStore {
+    pub fn new(database: Box<dyn KvStore + Send + Sync>) -> Self {
+        Self { database }
+    }
+
+    pub fn put(&mut self, ip: IpAddr, punished_till: u32) -> Result<(), KvStoreError> {
+        self.database.update(&[WriteOp::Put(
+            keys::ban(&ip),
+            Blob::from(bincode::serialize(&(ip, punished_till))?),
+        )])
+    }
+
+    pub fn remove(&mut self, ip: IpAddr) -> Result<(), KvStoreError> {
+        self.database.update(&[WriteOp::Remove(keys::ban(&ip))])
+    }
+
+    /// Removes the bans which are not active anymore
+    pub fn retain(&mut self, banned: impl Iterator<Item = IpAddr>) -> Result<(), KvStoreError> {
+        let banned = banned.map(|ip| keys::ban(&ip)).collect::<HashSet<_>>();
+        let removed = self
+            .database
+            .pairs(keys::prefix())?
+            .into_iter()
+            .filter(|(k, _)| !banned.contains(k))
+            .map(|(k, _)| WriteOp::Remove(k))
+            .collect::<Vec<_>>();
+        self.database.update(&removed)
+    }
+
+    pub fn all(&self) -> Result<Vec<(IpAddr, u32)>, KvStoreError> {
+        self.database
+            .pairs(keys::prefix())?
+            .into_iter()
+            .map(|(_, v)| Ok(bincode::deserialize(&v.0)?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::RamKvStore;
+
+    #[test]
+    fn test_ban_store() {
+        let a: IpAddr = "1.1.1.1".parse().unwrap();
+        let b: IpAddr = "::2".parse().unwrap();
+        let mut store = BanStore::new(Box::new(RamKvStore::new()));
+        store.put(a, 100).unwrap();
+        store.put(b, 200).unwrap();
+        store.put(a, 300).unwrap();
+        let mut all = store.all().unwrap();
+        all.sort();
+        assert_eq!(all, vec![(a, 300), (b, 200)]);
+
+        store.retain([b].into_iter()).unwrap();
+        assert_eq!(store.all().unwrap(), vec![(b, 200)]);
+        store.remove(b).unwrap();
+        assert!(store.all().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+use super::{node_create, Firewall, NodeError, NodeOptions, NodeRequest, PeerAddress};
+use crate::blockchain::Blockchain;
+use crate::client::messages::SocialProfiles;
+use crate::db::KvStore;
+use crate::mpn::MpnWorker;
+use crate::wallet::TxBuilder;
+use tokio::sync::mpsc;
+
+/// Configures a node before running it. Nodes do not share any global state, so a
+/// single process may run as many of them as needed.
+pub struct NodeBuilder<K: KvStore, B: Blockchain<K>> {
+    pub(super) opts: NodeOptions,
+    pub(super) network: String,
+    pub(super) address: Option<PeerAddress>,
+    pub(super) bootstrap: Vec<PeerAddress>,
+    pub(super) blockchain: B,
+    pub(super) timestamp_offset: i32,
+    pub(super) validator_wallet: TxBuilder,
+    pub(super) user_wallet: TxBuilder,
+    pub(super) social_profiles: SocialProfiles,
+    pub(super) firewall: Option<Firewall>,
+    pub(super) mpn_workers: Vec<MpnWorker>,
+    pub(super) _phantom: std::marker::PhantomData<K>,
+}
+
+impl<K: KvStore, B: Blockchain<K>> NodeBuilder<K, B> {
+    /// A client-only node (Not exposed on the internet) with no peers, which uses
+    /// `wallet` both as its validator and user wallet
+    pub fn new(opts: NodeOptions, network: &str, blockchain: B, wallet: TxBuilder) -> Self {
+        Self {
+            opts,
+            network: network.into(),
+            address: None,
+            bootstrap: Vec::new(),
+            blockchain,
+            timestamp_offset: 0,
+            validator_wallet: wallet.clone(),
+            user_wallet: wallet,
+            social_profiles: SocialProfiles::default(),
+            firewall: None,
+            mpn_workers: Vec::new(),
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Public address of the node, `None` if it is not exposed on the internet
+    pub fn address(mut self, address: Option<PeerAddress>) -> Self {
+        self.address = address;
+        self
+    }
+
+    pub fn bootstrap(mut self, bootstrap: Vec<PeerAddress>) -> Self {
+        self.bootstrap = bootstrap;
+        self
+    }
+
+    pub fn timestamp_offset(mut self, timestamp_offset: i32) -> Self {
+        self.timestamp_offset = timestamp_offset;
+        self
+    }
+
+    pub fn user_wallet(mut self, user_wallet: TxBuilder) -> Self {
+        self.user_wallet = user_wallet;
+        self
+    }
+
+    pub fn social_profiles(mut self, social_profiles: SocialProfiles) -> Self {
+        self.social_profiles = social_profiles;
+        self
+    }
+
+    pub fn firewall(mut self, firewall: Firewall) -> Self {
+        self.firewall = Some(firewall);
+        self
+    }
+
+    pub fn mpn_workers(mut self, mpn_workers: Vec<MpnWorker>) -> Self {
+        self.mpn_workers = mpn_workers;
+        self
+    }
+
+    /// Runs the node until it is shut down. Requests sent to the node are received
+    /// from `incoming`, and the requests of the node itself are sent to `outgoing`.
+    pub async fn run(
+        self,
+        incoming: mpsc::UnboundedReceiver<NodeRequest>,
+        outgoing: mpsc::UnboundedSender<NodeRequest>,
+    ) -> Result<(), NodeError> {
+        node_create(self, incoming, outgoing).await
+    }
+}
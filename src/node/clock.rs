@@ -0,0 +1,27 @@
+use crate::utils;
+use tokio::time::Instant;
+
+/// Source of the local time of a node
+#[derive(Debug, Clone, Default)]
+pub enum Clock {
+    #[default]
+    System,
+    /// Starts at the given timestamp and advances with the tokio clock, which can be
+    /// paused and fast-forwarded in tests for deterministic timing
+    Simulated { start: u32, instant: Instant },
+}
+
+impl Clock {
+    pub fn simulated(start: u32) -> Self {
+        Clock::Simulated {
+            start,
+            instant: Instant::now(),
+        }
+    }
+    pub fn now(&self) -> u32 {
+        match self {
+            Clock::System => utils::local_timestamp(),
+            Clock::Simulated { start, instant } => start + instant.elapsed().as_secs() as u32,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::client::Timestamp;
+use crate::core::Address;
+use hyper::Method;
+use serde::{Deserialize, Serialize};
+
+/// Endpoints nodes use to download the chain and the mempool from each other
+const SYNC_ENDPOINTS: [&str; 4] = [
+    "/bincode/headers",
+    "/bincode/blocks",
+    "/bincode/blocks/stream",
+    "/bincode/mempool",
+];
+
+/// Signed requests older (Or further in the future) than this many seconds are
+/// treated as unsigned, so that captured requests can't be replayed later
+pub const MAX_SIGNATURE_AGE: u32 = 60;
+
+/// Whether a request signed at `timestamp` is recent enough to be trusted at `now`
+pub fn is_fresh(now: Timestamp, timestamp: Timestamp) -> bool {
+    now.abs_diff(timestamp) <= MAX_SIGNATURE_AGE
+}
+
+/// Nodes of a single operator, trusted with each others admin endpoints. Members are
+/// identified by the validator address of the node, requests are signed with its key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClusterOptions {
+    /// Validator addresses of the member nodes
+    pub members: Vec<String>,
+    /// Only members are allowed to download blocks, headers and mempool from the node
+    #[serde(default)]
+    pub restrict_sync: bool,
+}
+
+impl ClusterOptions {
+    pub fn is_member(&self, pub_key: &Address) -> bool {
+        self.members.contains(&pub_key.to_string())
+    }
+
+    /// Whether the endpoint is only served to the members
+    pub fn restricts(&self, method: &Method, path: &str) -> bool {
+        self.restrict_sync && method == Method::GET && SYNC_ENDPOINTS.contains(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::TxBuilder;
+
+    #[test]
+    fn test_cluster_membership() {
+        let member = TxBuilder::new(&Vec::from("MEMBER")).get_address();
+        let stranger = TxBuilder::new(&Vec::from("STRANGER")).get_address();
+        let cluster = ClusterOptions {
+            members: vec![member.to_string()],
+            restrict_sync: true,
+        };
+        assert!(cluster.is_member(&member));
+        assert!(!cluster.is_member(&stranger));
+
+        assert!(cluster.restricts(&Method::GET, "/bincode/blocks"));
+        assert!(!cluster.restricts(&Method::POST, "/bincode/blocks"));
+        assert!(!cluster.restricts(&Method::GET, "/stats"));
+        assert!(!ClusterOptions {
+            restrict_sync: false,
+            ..cluster
+        }
+        .restricts(&Method::GET, "/bincode/blocks"));
+    }
+
+    #[test]
+    fn test_signature_freshness() {
+        assert!(is_fresh(1000, 1000));
+        assert!(is_fresh(1000, 1000 - MAX_SIGNATURE_AGE));
+        assert!(is_fresh(1000, 1000 + MAX_SIGNATURE_AGE));
+        assert!(!is_fresh(1000, 1000 - MAX_SIGNATURE_AGE - 1));
+        assert!(!is_fresh(1000, 1000 + MAX_SIGNATURE_AGE + 1));
+    }
+}
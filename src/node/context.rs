@@ -1,21 +1,28 @@
-use super::{Firewall, NodeError, NodeOptions, OutgoingSender, Peer, PeerAddress, PeerManager};
+use super::{
+    Faucet, Firewall, MinerStats, NodeError, NodeOptions, OutgoingSender, Peer, PeerAddress,
+    PeerManager, PunishmentPolicy, WebhookEvent, Webhooks,
+};
 use crate::blockchain::{Blockchain, BlockchainError, Mempool};
-use crate::client::messages::{SocialProfiles, ValidatorClaim};
+use crate::client::messages::{Offense, SocialProfiles, ValidatorClaim};
 use crate::core::{Block, GeneralTransaction, MpnAddress, TransactionAndDelta};
 use crate::mpn::{MpnWorkPool, MpnWorker};
 use crate::node::KvStore;
-use crate::utils;
 use crate::wallet::TxBuilder;
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 pub struct NodeContext<K: KvStore, B: Blockchain<K>> {
     pub firewall: Option<Firewall>,
     pub social_profiles: SocialProfiles,
     pub opts: NodeOptions,
+    /// Punishments the node was started with, the runtime ones are applied on top
+    pub default_punishments: PunishmentPolicy,
     pub network: String,
     pub address: Option<PeerAddress>, // None means node is not exposed on the Internet
     pub shutdown: bool,
+    /// Local timestamp of when the node was started
+    pub started_at: u32,
     pub outgoing: Arc<OutgoingSender>,
     pub blockchain: B,
     pub validator_wallet: TxBuilder,
@@ -26,35 +33,100 @@ pub struct NodeContext<K: KvStore, B: Blockchain<K>> {
 
     pub mpn_workers: HashMap<MpnAddress, MpnWorker>,
     pub mpn_work_pool: Option<MpnWorkPool>,
+    pub miner_stats: MinerStats,
+    /// Local timestamp and height at the time of the last status log
+    pub last_status: Option<(u32, u64)>,
+    pub faucet: Option<Faucet>,
+    #[cfg(feature = "explorer")]
+    pub explorer: Option<super::Explorer>,
+    pub webhooks: Option<Webhooks>,
+    pub metrics: Option<super::Metrics>,
+    #[cfg(feature = "zmq")]
+    pub publisher: Option<super::zmq::Publisher>,
+    /// Where streamed responses are handed to be written, `None` if they are not served
+    pub block_streams: Option<tokio::sync::mpsc::UnboundedSender<super::api::BlockStream>>,
 
     pub mempool: Mempool,
+    pub mempool_store: Option<super::MempoolStore>,
+    pub ban_store: Option<super::BanStore>,
     pub _phantom: std::marker::PhantomData<K>,
 }
 
 impl<K: KvStore, B: Blockchain<K>> NodeContext<K, B> {
     pub fn local_timestamp(&self) -> u32 {
-        utils::local_timestamp()
+        self.opts.clock.now()
     }
     pub fn network_timestamp(&self) -> u32 {
         (self.local_timestamp() as i32 + self.timestamp_offset) as u32
     }
-    pub fn punish_bad_behavior(&mut self, bad_peer: PeerAddress, secs: u32, reason: &str) {
-        log::warn!("Peer {} is behaving bad! Reason: {}", bad_peer, reason);
-        log::warn!("Punishing {} for {} seconds...", bad_peer, secs);
-        self.peer_manager
-            .punish_ip_for(self.local_timestamp(), bad_peer.ip(), secs);
+    pub fn punish_bad_behavior(&mut self, bad_peer: PeerAddress, offense: Offense, reason: &str) {
+        tracing::warn!("Peer {} is behaving bad! Reason: {}", bad_peer, reason);
+        let now = self.local_timestamp();
+        let previous = self
+            .peer_manager
+            .record_offense(now, bad_peer.ip(), offense);
+        let secs = self.opts.punishments.duration(offense, previous);
+        if secs == 0 {
+            tracing::warn!("Moving peer {} to the candidate list!", bad_peer);
+            self.peer_manager.mark_as_candidate(now, &bad_peer);
+            return;
+        }
+        tracing::warn!("Punishing {} for {} seconds...", bad_peer, secs);
+        self.punish_ip_for(bad_peer.ip(), secs);
+        if let Some(webhooks) = &mut self.webhooks {
+            webhooks.emit(
+                now,
+                WebhookEvent::PeerBanned {
+                    peer: bad_peer.to_string(),
+                    seconds: secs,
+                    reason: reason.into(),
+                },
+            );
+        }
+    }
+    /// Bans the peer, persisting the ban so that it survives restarts
+    pub fn punish_ip_for(&mut self, ip: IpAddr, secs: u32) {
+        let now = self.local_timestamp();
+        self.peer_manager.punish_ip_for(now, ip, secs);
+        if let (Some(store), true) = (&mut self.ban_store, secs > 0) {
+            if let Err(e) = store.put(ip, now + secs) {
+                tracing::error!("Cannot persist the ban of {}: {}", ip, e);
+            }
+        }
+    }
+    pub fn unpunish_ip(&mut self, ip: IpAddr) -> Result<bool, BlockchainError> {
+        if let Some(store) = &mut self.ban_store {
+            store.remove(ip)?;
+        }
+        Ok(self.peer_manager.unpunish_ip(ip))
+    }
+    /// Brings back the bans which were active before the node was restarted
+    pub fn restore_bans(&mut self) -> Result<(), BlockchainError> {
+        let now = self.local_timestamp();
+        if let Some(store) = &mut self.ban_store {
+            for (ip, punished_till) in store.all()? {
+                if now < punished_till {
+                    self.peer_manager.restore_punishment(ip, punished_till);
+                }
+            }
+            store.retain(self.peer_manager.punished_ips())?;
+            tracing::info!(
+                "{} peer bans restored",
+                self.peer_manager.get_punished(now).len()
+            );
+        }
+        Ok(())
     }
     pub fn punish_unresponsive(&mut self, bad_peer: PeerAddress) {
-        log::warn!("Peer {} is unresponsive!", bad_peer);
-        log::warn!("Moving peer {} to the candidate list!", bad_peer);
-        self.peer_manager
-            .mark_as_candidate(self.local_timestamp(), &bad_peer);
+        self.punish_bad_behavior(bad_peer, Offense::Timeout, "Peer is unresponsive!");
     }
     pub fn get_info(&self) -> Result<Option<Peer>, NodeError> {
         let height = self.blockchain.get_height()?;
+        let pruned_height = self.blockchain.get_pruned_height()?;
         Ok(self.address.map(|address| Peer {
             address,
             height,
+            pruned_height,
             pub_key: self.validator_wallet.get_address(),
         }))
     }
@@ -62,6 +134,9 @@ impl<K: KvStore, B: Blockchain<K>> NodeContext<K, B> {
     pub fn refresh(&mut self) -> Result<(), BlockchainError> {
         let local_ts = self.local_timestamp();
         self.peer_manager.refresh(local_ts);
+        if let Some(store) = &mut self.ban_store {
+            store.retain(self.peer_manager.punished_ips())?;
+        }
 
         if let Some(firewall) = &mut self.firewall {
             firewall.refresh(local_ts);
@@ -76,8 +151,74 @@ impl<K: KvStore, B: Blockchain<K>> NodeContext<K, B> {
         tx: GeneralTransaction,
     ) -> Result<(), BlockchainError> {
         let local_ts = self.local_timestamp();
+        let replaced = self
+            .mempool
+            .pending(&tx)
+            .filter(|pending| *pending != &tx)
+            .cloned();
+        #[cfg(feature = "zmq")]
+        if let Some(publisher) = &mut self.publisher {
+            let in_mempool = |mempool: &Mempool| mempool.all().any(|(t, _)| t == &tx);
+            let known = in_mempool(&self.mempool);
+            self.mempool
+                .add_tx(&self.blockchain, tx.clone(), is_local, local_ts)?;
+            if !known && in_mempool(&self.mempool) {
+                publisher.on_tx(&tx);
+            }
+            return self.persist_tx(&tx, replaced);
+        }
         self.mempool
-            .add_tx(&self.blockchain, tx, is_local, local_ts)?;
+            .add_tx(&self.blockchain, tx.clone(), is_local, local_ts)?;
+        self.persist_tx(&tx, replaced)?;
+        Ok(())
+    }
+
+    /// Stores the transaction if the mempool has accepted it, and forgets the one it
+    /// has replaced
+    fn persist_tx(
+        &mut self,
+        tx: &GeneralTransaction,
+        replaced: Option<GeneralTransaction>,
+    ) -> Result<(), BlockchainError> {
+        if let Some(store) = &mut self.mempool_store {
+            if let Some((tx, stats)) = self.mempool.all().find(|(t, _)| t == tx) {
+                store.put(tx, stats)?;
+                if let Some(replaced) = replaced {
+                    if self.mempool.pending(&replaced) != Some(&replaced) {
+                        store.remove(&replaced)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the transactions which were pending before the node was restarted. Those
+    /// which are not valid anymore, or can't be restored, are dropped.
+    pub fn restore_mempool(&mut self) -> Result<(), BlockchainError> {
+        let txs = match &self.mempool_store {
+            Some(store) => store.all()?,
+            None => return Ok(()),
+        };
+        for (tx, stats) in txs {
+            if let GeneralTransaction::TransactionAndDelta(tx_delta) = &tx {
+                if let Err(e) = self.blockchain.check_tx(&tx_delta.tx) {
+                    if !matches!(e, BlockchainError::InvalidTransactionNonce) {
+                        continue;
+                    }
+                }
+            }
+            if let Err(e) =
+                self.mempool
+                    .add_tx(&self.blockchain, tx, stats.is_local, stats.first_seen)
+            {
+                tracing::warn!("Dropped a pending transaction: {}", e);
+            }
+        }
+        if let Some(store) = &mut self.mempool_store {
+            store.retain(self.mempool.all().map(|(tx, _)| tx))?;
+        }
+        tracing::info!("{} pending transactions restored", self.mempool.len());
         Ok(())
     }
 
@@ -90,6 +231,13 @@ impl<K: KvStore, B: Blockchain<K>> NodeContext<K, B> {
             self.opts.tx_max_time_alive,
             self.opts.tx_max_time_alive,
         )?;
+        if let Some(store) = &mut self.mempool_store {
+            store.retain(self.mempool.all().map(|(tx, _)| tx))?;
+        }
+        #[cfg(feature = "zmq")]
+        if let Some(publisher) = &mut self.publisher {
+            publisher.on_chain(&self.blockchain)?;
+        }
         Ok(())
     }
 
@@ -113,7 +261,7 @@ impl<K: KvStore, B: Blockchain<K>> NodeContext<K, B> {
                 && claim.verify_signature()
             {
                 self.validator_claim = Some(claim.clone());
-                log::info!("Address {} is the validator!", claim.address);
+                tracing::info!("Address {} is the validator!", claim.address);
                 return Ok(true);
             }
         }
@@ -122,6 +270,14 @@ impl<K: KvStore, B: Blockchain<K>> NodeContext<K, B> {
 
     pub fn try_produce(&mut self, wallet: TxBuilder) -> Result<Option<Block>, BlockchainError> {
         let ts = self.network_timestamp();
+        self.try_produce_at(ts, wallet)
+    }
+
+    pub fn try_produce_at(
+        &mut self,
+        ts: u32,
+        wallet: TxBuilder,
+    ) -> Result<Option<Block>, BlockchainError> {
         let raw_txs: Vec<TransactionAndDelta> =
             self.mempool.tx_deltas().map(|(tx, _)| tx.clone()).collect();
         match self.blockchain.draft_block(ts, &raw_txs, &wallet, true) {
@@ -130,13 +286,14 @@ impl<K: KvStore, B: Blockchain<K>> NodeContext<K, B> {
                     self.blockchain
                         .extend(draft.header.number, &[draft.clone()])?;
                     self.on_update()?;
+                    self.miner_stats.block_produced();
                     Ok(Some(draft))
                 } else {
                     Ok(None)
                 }
             }
             Err(e) => {
-                log::warn!("Cannot draft a block! Error: {}", e);
+                tracing::warn!("Cannot draft a block! Error: {}", e);
                 Ok(None)
             }
         }
@@ -0,0 +1,350 @@
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::client::explorer::{ExplorerAddressActivity, ExplorerBlockStats};
+use crate::core::{Address, Block};
+use crate::db::{Blob, KvStore, KvStoreError, LevelDbKvStore, RamKvStore, StringKey, WriteOp};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Static page querying the `/explorer/index/*` endpoints
+pub const EXPLORER_PAGE: &str = include_str!("explorer.html");
+
+#[derive(Debug, Clone)]
+pub struct ExplorerOptions {
+    /// Where the index is stored, kept in memory if `None`
+    pub db: Option<PathBuf>,
+    /// Maximum number of blocks indexed in a single heartbeat round
+    pub max_blocks_per_round: u64,
+}
+
+/// Location of a transaction on the chain
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxLocation {
+    pub block: u64,
+    pub index: u32,
+}
+
+/// Everything needed to remove a block from the index once it is rolled back
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct IndexedBlock {
+    stats: ExplorerBlockStats,
+    txs: Vec<String>,
+    addresses: Vec<Address>,
+}
+
+mod keys {
+    use super::*;
+
+    pub fn height() -> StringKey {
+        "HGT".into()
+    }
+
+    pub fn block(index: u64) -> StringKey {
+        format!("BLK-{:010}", index).into()
+    }
+
+    pub fn tx(hash: &str) -> StringKey {
+        format!("TX-{}", hash).into()
+    }
+
+    pub fn activity_prefix(address: &Address) -> StringKey {
+        format!("ACT-{}-", address).into()
+    }
+
+    pub fn block_activity_prefix(address: &Address, block: u64) -> StringKey {
+        format!("ACT-{}-{:010}-", address, block).into()
+    }
+
+    pub fn activity(address: &Address, block: u64, index: u32) -> StringKey {
+        format!("ACT-{}-{:010}-{:05}", address, block, index).into()
+    }
+}
+
+fn put<T: Serialize>(key: StringKey, value: &T) -> WriteOp {
+    WriteOp::Put(key, Blob::from(bincode::serialize(value).unwrap()))
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(blob: Blob) -> Result<T, KvStoreError> {
+    Ok(bincode::deserialize(&blob.0)?)
+}
+
+/// Indexes of address activity, transaction locations and block statistics, kept in
+/// a database separate from the chain so that it never affects the chain state.
+pub struct Explorer {
+    opts: ExplorerOptions,
+    database: Box<dyn KvStore + Send + Sync>,
+}
+
+impl Explorer {
+    pub fn new(opts: ExplorerOptions) -> Result<Self, KvStoreError> {
+        let database: Box<dyn KvStore + Send + Sync> = match &opts.db {
+            Some(path) => Box::new(LevelDbKvStore::new(path, 64)?),
+            None => Box::new(RamKvStore::new()),
+        };
+        Ok(Self { opts, database })
+    }
+
+    /// Number of blocks covered by the index
+    pub fn height(&self) -> Result<u64, KvStoreError> {
+        Ok(match self.database.get(keys::height())? {
+            Some(b) => b.try_into()?,
+            None => 0,
+        })
+    }
+
+    fn get_block(&self, index: u64) -> Result<Option<IndexedBlock>, KvStoreError> {
+        self.database
+            .get(keys::block(index))?
+            .map(decode)
+            .transpose()
+    }
+
+    fn index_block(&mut self, block: &Block) -> Result<(), KvStoreError> {
+        let stats = ExplorerBlockStats::from(block);
+        let mut ops = Vec::new();
+        let mut txs = Vec::new();
+        let mut addresses = Vec::new();
+        for (index, tx) in block.body.iter().enumerate() {
+            let hash = hex::encode(tx.hash());
+            let index = index as u32;
+            ops.push(put(
+                keys::tx(&hash),
+                &TxLocation {
+                    block: block.header.number,
+                    index,
+                },
+            ));
+            for addr in tx.involved_addresses() {
+                ops.push(put(
+                    keys::activity(&addr, block.header.number, index),
+                    &ExplorerAddressActivity {
+                        block: block.header.number,
+                        index,
+                        timestamp: stats.timestamp,
+                        hash: hash.clone(),
+                    },
+                ));
+                if !addresses.contains(&addr) {
+                    addresses.push(addr);
+                }
+            }
+            txs.push(hash);
+        }
+        ops.push(put(
+            keys::block(block.header.number),
+            &IndexedBlock {
+                stats,
+                txs,
+                addresses,
+            },
+        ));
+        ops.push(WriteOp::Put(
+            keys::height(),
+            (block.header.number + 1).into(),
+        ));
+        self.database.update(&ops)
+    }
+
+    fn unindex_block(&mut self, number: u64, block: &IndexedBlock) -> Result<(), KvStoreError> {
+        let mut ops = Vec::new();
+        for hash in block.txs.iter() {
+            ops.push(WriteOp::Remove(keys::tx(hash)));
+        }
+        for addr in block.addresses.iter() {
+            for (k, _) in self
+                .database
+                .pairs(keys::block_activity_prefix(addr, number))?
+                .into_iter()
+            {
+                ops.push(WriteOp::Remove(k));
+            }
+        }
+        ops.push(WriteOp::Remove(keys::block(number)));
+        ops.push(WriteOp::Put(keys::height(), number.into()));
+        self.database.update(&ops)
+    }
+
+    /// Brings the index up to date with `chain`, first removing the blocks that are
+    /// not on the chain anymore. Returns the number of blocks indexed.
+    pub fn update<K: KvStore, B: Blockchain<K>>(
+        &mut self,
+        chain: &B,
+    ) -> Result<u64, BlockchainError> {
+        let chain_height = chain.get_height()?;
+
+        // Undo blocks that have been rolled back (or replaced by a fork) since the
+        // last round
+        loop {
+            let height = self.height()?;
+            if height == 0 {
+                break;
+            }
+            match self.get_block(height - 1)? {
+                Some(indexed) => {
+                    if height <= chain_height
+                        && hex::encode(chain.get_header(height - 1)?.hash()) == indexed.stats.hash
+                    {
+                        break;
+                    }
+                    self.unindex_block(height - 1, &indexed)?;
+                }
+                None => {
+                    // Blocks before the pruning point were never indexed
+                    if height > chain_height {
+                        self.database
+                            .update(&[WriteOp::Put(keys::height(), chain_height.into())])?;
+                    }
+                    break;
+                }
+            }
+        }
+
+        let since = std::cmp::max(self.height()?, chain.get_pruned_height()?);
+        let until = std::cmp::min(
+            chain_height,
+            since.saturating_add(self.opts.max_blocks_per_round),
+        );
+        for i in since..until {
+            self.index_block(&chain.get_block(i)?)?;
+        }
+        Ok(until.saturating_sub(since))
+    }
+
+    pub fn block_stats(
+        &self,
+        since: u64,
+        count: u64,
+    ) -> Result<Vec<ExplorerBlockStats>, KvStoreError> {
+        let until = std::cmp::min(self.height()?, since.saturating_add(count));
+        let mut stats = Vec::new();
+        for i in since..until {
+            if let Some(indexed) = self.get_block(i)? {
+                stats.push(indexed.stats);
+            }
+        }
+        Ok(stats)
+    }
+
+    pub fn find_transaction(&self, hash: &str) -> Result<Option<TxLocation>, KvStoreError> {
+        self.database
+            .get(keys::tx(&hash.to_lowercase()))?
+            .map(decode)
+            .transpose()
+    }
+
+    /// Transactions involving `address`, most recent first
+    pub fn address_activity(
+        &self,
+        address: &Address,
+        page: usize,
+        page_size: usize,
+    ) -> Result<Vec<ExplorerAddressActivity>, KvStoreError> {
+        let mut activity = self
+            .database
+            .pairs(keys::activity_prefix(address))?
+            .into_iter()
+            .map(|(_, v)| v)
+            .collect::<Vec<_>>();
+        activity.reverse();
+        activity
+            .into_iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .map(decode)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Amount;
+    use crate::test_utils::*;
+
+    fn explorer() -> Explorer {
+        Explorer::new(ExplorerOptions {
+            db: None,
+            max_blocks_per_round: 2,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_explorer_indexes_blocks() {
+        let validator = validator_wallet();
+        let alice = wallet("ALICE");
+        let mut chain = funded_chain(&validator, &[(&alice, Amount(1_000_000))]);
+        let txs = random_transactions(&mut rand::thread_rng(), &chain, &alice, 3).unwrap();
+        let block = mine_block(&mut chain, &validator, &txs).unwrap();
+
+        let mut explorer = explorer();
+        assert_eq!(explorer.update(&chain).unwrap(), 2);
+        assert_eq!(explorer.update(&chain).unwrap(), 0);
+        assert_eq!(explorer.height().unwrap(), 2);
+
+        let stats = explorer.block_stats(1, 10).unwrap();
+        assert_eq!(stats, vec![ExplorerBlockStats::from(&block)]);
+        assert_eq!(stats[0].tx_count, block.body.len());
+
+        let tx_index = block.body.iter().position(|tx| *tx == txs[1].tx).unwrap();
+        assert_eq!(
+            explorer
+                .find_transaction(&hex::encode(txs[1].tx.hash()))
+                .unwrap(),
+            Some(TxLocation {
+                block: 1,
+                index: tx_index as u32
+            })
+        );
+
+        let activity = explorer
+            .address_activity(&alice.get_address(), 0, 100)
+            .unwrap();
+        // Genesis funding and the three payments, most recent first
+        assert_eq!(activity.len(), 4);
+        assert_eq!(activity[0].block, 1);
+        assert_eq!(activity[3].block, 0);
+        assert_eq!(
+            explorer
+                .address_activity(&alice.get_address(), 1, 3)
+                .unwrap(),
+            vec![activity[3].clone()]
+        );
+    }
+
+    #[test]
+    fn test_explorer_follows_rollbacks() {
+        let validator = validator_wallet();
+        let alice = wallet("ALICE");
+        let mut chain = funded_chain(&validator, &[(&alice, Amount(1_000_000))]);
+        let txs = random_transactions(&mut rand::thread_rng(), &chain, &alice, 2).unwrap();
+        mine_block(&mut chain, &validator, &txs).unwrap();
+        mine_blocks(&mut chain, &validator, 2).unwrap();
+
+        let mut explorer = explorer();
+        while explorer.update(&chain).unwrap() > 0 {}
+        assert_eq!(explorer.height().unwrap(), 4);
+
+        chain.rollback().unwrap();
+        chain.rollback().unwrap();
+        chain.rollback().unwrap();
+        let fork_block = mine_block(&mut chain, &validator, &[]).unwrap();
+
+        explorer.update(&chain).unwrap();
+        assert_eq!(explorer.height().unwrap(), 2);
+        assert_eq!(
+            explorer.block_stats(1, 10).unwrap(),
+            vec![ExplorerBlockStats::from(&fork_block)]
+        );
+        assert!(explorer
+            .find_transaction(&hex::encode(txs[0].tx.hash()))
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            explorer
+                .address_activity(&alice.get_address(), 0, 100)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
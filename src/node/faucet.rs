@@ -0,0 +1,60 @@
+use super::NodeError;
+use crate::core::{Address, Amount};
+use crate::wallet::TxBuilder;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+#[derive(Clone)]
+pub struct FaucetOptions {
+    /// Wallet the coins are sent from
+    pub wallet: TxBuilder,
+    pub amount: Amount,
+    /// Seconds an address or IP should wait between two requests
+    pub interval: u32,
+}
+
+impl std::fmt::Debug for FaucetOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("FaucetOptions")
+            .field("wallet", &self.wallet.get_address())
+            .field("amount", &self.amount)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+/// Sends test coins to whoever asks, at most once per interval for each address
+/// and requester IP
+pub struct Faucet {
+    pub opts: FaucetOptions,
+    last_ip_drips: HashMap<IpAddr, u32>,
+    last_address_drips: HashMap<Address, u32>,
+}
+
+impl Faucet {
+    pub fn new(opts: FaucetOptions) -> Self {
+        Self {
+            opts,
+            last_ip_drips: HashMap::new(),
+            last_address_drips: HashMap::new(),
+        }
+    }
+
+    /// Records a drip, fails if the address or IP has been served recently
+    pub fn drip(&mut self, ip: Option<IpAddr>, addr: &Address, now: u32) -> Result<(), NodeError> {
+        let interval = self.opts.interval;
+        let recent = |last: Option<&u32>| last.map(|t| now < t + interval).unwrap_or(false);
+        if recent(self.last_address_drips.get(addr))
+            || recent(ip.as_ref().and_then(|ip| self.last_ip_drips.get(ip)))
+        {
+            return Err(NodeError::FaucetRateLimited);
+        }
+        self.last_address_drips.retain(|_, t| now < *t + interval);
+        self.last_ip_drips.retain(|_, t| now < *t + interval);
+        self.last_address_drips.insert(addr.clone(), now);
+        if let Some(ip) = ip {
+            self.last_ip_drips.insert(ip, now);
+        }
+        Ok(())
+    }
+}
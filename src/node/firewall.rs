@@ -1,4 +1,8 @@
 use super::*;
+use std::sync::Mutex;
+
+/// Bodies of blocks are the largest legitimate requests
+const DEFAULT_MAX_BODY_SIZE: u64 = 4 * MB;
 
 pub struct Firewall {
     request_count_limit_per_minute: usize,
@@ -8,6 +12,8 @@ pub struct Firewall {
     traffic_limit_per_15m: u64,
     traffic_last_reset: Timestamp,
     traffic: HashMap<IpAddr, u64>,
+
+    max_body_size: u64,
 }
 
 impl Firewall {
@@ -19,8 +25,18 @@ impl Firewall {
             request_count: HashMap::new(),
             traffic_last_reset: 0,
             traffic: HashMap::new(),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
         }
     }
+    pub fn set_max_body_size(&mut self, limit: u64) {
+        self.max_body_size = limit;
+    }
+    pub fn set_request_count_limit_per_minute(&mut self, limit: usize) {
+        self.request_count_limit_per_minute = limit;
+    }
+    pub fn set_traffic_limit_per_15m(&mut self, limit: u64) {
+        self.traffic_limit_per_15m = limit;
+    }
     pub fn refresh(&mut self, now: u32) {
         if now.saturating_sub(self.request_count_last_reset) > 60 {
             self.request_count.clear();
@@ -35,6 +51,9 @@ impl Firewall {
     pub fn add_traffic(&mut self, ip: IpAddr, amount: u64) {
         *self.traffic.entry(ip).or_insert(0) += amount;
     }
+    pub fn body_permitted(&self, client: SocketAddr, size: u64) -> bool {
+        client.ip().is_loopback() || size <= self.max_body_size
+    }
     pub fn incoming_permitted(&mut self, client: SocketAddr) -> bool {
         // Incoming from loopback is always permitted
         if client.ip().is_loopback() {
@@ -55,6 +74,61 @@ impl Firewall {
     }
 }
 
+/// Caps the number of connections each client may keep open at the same time. It is
+/// shared by the connections of the HTTP server, which hold a guard until closed.
+#[derive(Clone)]
+pub struct ConnectionLimiter {
+    limit: Arc<Mutex<usize>>,
+    connections: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+pub struct ConnectionGuard {
+    ip: IpAddr,
+    connections: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut connections = self.connections.lock().unwrap();
+        if let Some(cnt) = connections.get_mut(&self.ip) {
+            *cnt -= 1;
+            if *cnt == 0 {
+                connections.remove(&self.ip);
+            }
+        }
+    }
+}
+
+impl ConnectionLimiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: Arc::new(Mutex::new(limit)),
+            connections: Default::default(),
+        }
+    }
+    pub fn set_limit(&self, limit: usize) {
+        *self.limit.lock().unwrap() = limit;
+    }
+    /// Registers a new connection, `None` if the client has too many of them open.
+    /// Connections from loopback are always permitted.
+    pub fn acquire(&self, client: SocketAddr) -> Option<ConnectionGuard> {
+        let ip = client.ip();
+        let mut connections = self.connections.lock().unwrap();
+        let cnt = connections.entry(ip).or_insert(0);
+        if !ip.is_loopback() && *cnt >= *self.limit.lock().unwrap() {
+            if *cnt == 0 {
+                connections.remove(&ip);
+            }
+            return None;
+        }
+        *cnt += 1;
+        Some(ConnectionGuard {
+            ip,
+            connections: Arc::clone(&self.connections),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,4 +163,35 @@ mod tests {
         firewall.refresh(1295);
         assert!(firewall.incoming_permitted(client));
     }
+
+    #[test]
+    fn test_body_limit() {
+        let mut firewall = Firewall::new(10, 1000);
+        firewall.set_max_body_size(100);
+        let client: SocketAddr = "123.234.56.78:12345".parse().unwrap();
+        let local: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+        assert!(firewall.body_permitted(client, 100));
+        assert!(!firewall.body_permitted(client, 101));
+        assert!(firewall.body_permitted(local, 101));
+    }
+
+    #[test]
+    fn test_connection_limit() {
+        let limiter = ConnectionLimiter::new(2);
+        let client: SocketAddr = "123.234.56.78:12345".parse().unwrap();
+        let other: SocketAddr = "123.234.56.79:12345".parse().unwrap();
+        let first = limiter.acquire(client).unwrap();
+        let _second = limiter.acquire(client).unwrap();
+        assert!(limiter.acquire(client).is_none());
+        assert!(limiter.acquire(other).is_some());
+
+        drop(first);
+        let _third = limiter.acquire(client).unwrap();
+        assert!(limiter.acquire(client).is_none());
+
+        limiter.set_limit(3);
+        assert!(limiter.acquire(client).is_some());
+        limiter.set_limit(0);
+        assert!(limiter.acquire("127.0.0.1:1234".parse().unwrap()).is_some());
+    }
 }
@@ -0,0 +1,85 @@
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::core::hash::Hash;
+use crate::core::{Block, Hasher};
+use crate::db::KvStore;
+use std::collections::VecDeque;
+
+/// Number of recent blocks remembered for detecting reorgs
+const KNOWN_BLOCKS: usize = 64;
+/// Maximum number of new blocks returned by a single update
+const MAX_BLOCKS_PER_ROUND: u64 = 100;
+
+/// Blocks that were replaced by a fork
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorg {
+    /// First block that was replaced
+    pub fork_height: u64,
+    pub old_tip: (u64, <Hasher as Hash>::Output),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChainUpdate {
+    pub reorg: Option<Reorg>,
+    pub blocks: Vec<Block>,
+}
+
+/// Keeps track of the blocks already seen by a consumer of chain events, so that it
+/// only gets the new blocks (and the reorgs) on each update.
+#[derive(Debug, Clone, Default)]
+pub struct ChainFollower {
+    known: VecDeque<(u64, <Hasher as Hash>::Output)>,
+}
+
+impl ChainFollower {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blocks added (or replaced) since the last call. Nothing is returned on the
+    /// first call, history is not replayed.
+    pub fn update<K: KvStore, B: Blockchain<K>>(
+        &mut self,
+        chain: &B,
+    ) -> Result<ChainUpdate, BlockchainError> {
+        let height = chain.get_height()?;
+        if self.known.is_empty() {
+            let tip = chain.get_tip()?;
+            self.known.push_back((tip.number, tip.hash()));
+            return Ok(ChainUpdate::default());
+        }
+
+        let old_tip = *self.known.back().unwrap();
+        let mut fork_height = None;
+        while let Some((number, hash)) = self.known.back().copied() {
+            if number < height && chain.get_header(number)?.hash() == hash {
+                break;
+            }
+            fork_height = Some(number);
+            self.known.pop_back();
+        }
+
+        let since = self
+            .known
+            .back()
+            .map(|(number, _)| number + 1)
+            .or(fork_height)
+            .unwrap_or(height);
+        let until = std::cmp::min(height, since.saturating_add(MAX_BLOCKS_PER_ROUND));
+        let mut blocks = Vec::new();
+        for number in since..until {
+            let block = chain.get_block(number)?;
+            self.known.push_back((number, block.header.hash()));
+            if self.known.len() > KNOWN_BLOCKS {
+                self.known.pop_front();
+            }
+            blocks.push(block);
+        }
+        Ok(ChainUpdate {
+            reorg: fork_height.map(|fork_height| Reorg {
+                fork_height,
+                old_tip,
+            }),
+            blocks,
+        })
+    }
+}
@@ -0,0 +1,399 @@
+pub mod proto;
+
+use crate::client::messages::*;
+use crate::client::{NodeError, NodeRequest, NETWORK_HEADER};
+use crate::core::hash::Hash;
+use crate::core::{GeneralTransaction, Hasher};
+use hyper::header::HeaderValue;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use proto::node_server::{Node, NodeServer};
+use std::collections::{HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// How often streams check the node for new blocks and transactions
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const STREAM_BUFFER: usize = 16;
+const STREAM_BLOCKS_BATCH: u64 = 16;
+/// Number of streamed blocks remembered for detecting reorgs
+const STREAM_REORG_DEPTH: usize = 64;
+
+impl From<NodeError> for tonic::Status {
+    fn from(e: NodeError) -> Self {
+        let msg = e.to_string();
+        match e {
+            NodeError::InputError(_)
+            | NodeError::AccountParseAddressError(_)
+            | NodeError::MpnAccountParseAddressError(_)
+            | NodeError::GeneralParseAddressError(_)
+            | NodeError::TokenIdParseError(_)
+            | NodeError::BincodeError(_)
+            | NodeError::QueryStringError(_) => tonic::Status::invalid_argument(msg),
+            NodeError::NotListeningError | NodeError::NotAnsweringError => {
+                tonic::Status::unavailable(msg)
+            }
+            NodeError::RemoteServerError(_) => tonic::Status::unknown(msg),
+            _ => tonic::Status::internal(msg),
+        }
+    }
+}
+
+/// Answers gRPC calls by passing them to the node as if they were received by its
+/// HTTP server, so that both APIs share the same handlers, firewall and punishments.
+#[derive(Clone)]
+struct Gateway {
+    network: String,
+    chan: mpsc::UnboundedSender<NodeRequest>,
+}
+
+impl Gateway {
+    async fn call(
+        &self,
+        client: Option<SocketAddr>,
+        mut req: Request<Body>,
+    ) -> Result<hyper::body::Bytes, NodeError> {
+        req.headers_mut()
+            .insert(NETWORK_HEADER, HeaderValue::from_str(&self.network)?);
+        let (resp_snd, mut resp_rcv) =
+            mpsc::unbounded_channel::<Result<Response<Body>, NodeError>>();
+        self.chan
+            .send(NodeRequest {
+                limit: Default::default(),
+                socket_addr: client,
+                body: req,
+                resp: resp_snd,
+            })
+            .map_err(|_| NodeError::NotListeningError)?;
+        let resp = resp_rcv
+            .recv()
+            .await
+            .ok_or(NodeError::NotAnsweringError)??;
+        let status = resp.status();
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        if status != StatusCode::OK {
+            return Err(NodeError::RemoteServerError(format!(
+                "{} {}",
+                status,
+                String::from_utf8_lossy(&body)
+            )));
+        }
+        Ok(body)
+    }
+
+    async fn json_get<Req: serde::Serialize, Resp: serde::de::DeserializeOwned>(
+        &self,
+        client: Option<SocketAddr>,
+        path: &str,
+        req: Req,
+    ) -> Result<Resp, NodeError> {
+        let uri = format!("{}?{}", path, serde_qs::to_string(&req)?);
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri(uri)
+            .body(Body::empty())?;
+        Ok(serde_json::from_slice(&self.call(client, req).await?)?)
+    }
+
+    async fn bincode_call<Req: serde::Serialize, Resp: serde::de::DeserializeOwned>(
+        &self,
+        client: Option<SocketAddr>,
+        method: Method,
+        path: &str,
+        req: Req,
+    ) -> Result<Resp, NodeError> {
+        let req = Request::builder()
+            .method(method)
+            .uri(path)
+            .body(Body::from(bincode::serialize(&req)?))?;
+        Ok(bincode::deserialize(&self.call(client, req).await?)?)
+    }
+
+    async fn get_headers(
+        &self,
+        client: Option<SocketAddr>,
+        since: u64,
+        count: u64,
+    ) -> Result<GetHeadersResponse, NodeError> {
+        self.bincode_call(
+            client,
+            Method::GET,
+            "/bincode/headers",
+            GetHeadersRequest { since, count },
+        )
+        .await
+    }
+
+    async fn get_blocks(
+        &self,
+        client: Option<SocketAddr>,
+        since: u64,
+        count: u64,
+    ) -> Result<GetBlocksResponse, NodeError> {
+        self.bincode_call(
+            client,
+            Method::GET,
+            "/bincode/blocks",
+            GetBlocksRequest { since, count },
+        )
+        .await
+    }
+
+    async fn get_mempool(
+        &self,
+        client: Option<SocketAddr>,
+        filter: Option<String>,
+    ) -> Result<GetMempoolResponse, NodeError> {
+        self.bincode_call(
+            client,
+            Method::GET,
+            "/bincode/mempool",
+            GetMempoolRequest {
+                filter: filter.map(|f| f.parse()).transpose()?,
+            },
+        )
+        .await
+    }
+
+    /// Sends the blocks of the chain from `since` on, forever, until the receiver is
+    /// dropped
+    async fn stream_blocks(
+        self,
+        client: Option<SocketAddr>,
+        mut next: u64,
+        out: mpsc::Sender<Result<proto::Block, tonic::Status>>,
+    ) -> Result<(), NodeError> {
+        let mut sent = VecDeque::<(u64, <Hasher as Hash>::Output)>::new();
+        loop {
+            // Go back to the fork point if the streamed blocks are not on the chain
+            // anymore
+            while let Some((number, hash)) = sent.back().cloned() {
+                let headers = self.get_headers(client, number, 1).await?.headers;
+                if headers.first().map(|h| h.hash()) == Some(hash) {
+                    break;
+                }
+                sent.pop_back();
+                next = number;
+            }
+
+            let blocks = self
+                .get_blocks(client, next, STREAM_BLOCKS_BATCH)
+                .await?
+                .blocks;
+            if blocks.is_empty() {
+                tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+            }
+            for block in blocks {
+                if out.send(Ok((&block).into())).await.is_err() {
+                    return Ok(());
+                }
+                next = block.header.number + 1;
+                sent.push_back((block.header.number, block.header.hash()));
+                if sent.len() > STREAM_REORG_DEPTH {
+                    sent.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Sends transactions as they enter the mempool, until the receiver is dropped
+    async fn stream_transactions(
+        self,
+        client: Option<SocketAddr>,
+        out: mpsc::Sender<Result<proto::MempoolTransaction, tonic::Status>>,
+    ) -> Result<(), NodeError> {
+        let mut seen = HashSet::<String>::new();
+        loop {
+            let mempool = self.get_mempool(client, None).await?.mempool;
+            let mut current = HashSet::new();
+            for tx in mempool.iter() {
+                let tx: proto::MempoolTransaction = tx.into();
+                current.insert(tx.hash.clone());
+                if !seen.contains(&tx.hash) && out.send(Ok(tx)).await.is_err() {
+                    return Ok(());
+                }
+            }
+            seen = current;
+            tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Node for Gateway {
+    async fn get_stats(
+        &self,
+        request: tonic::Request<proto::GetStatsRequest>,
+    ) -> Result<tonic::Response<proto::GetStatsResponse>, tonic::Status> {
+        let resp: GetStatsResponse = self
+            .json_get(request.remote_addr(), "/stats", GetStatsRequest {})
+            .await?;
+        Ok(tonic::Response::new(proto::GetStatsResponse {
+            address: resp.address,
+            height: resp.height,
+            best_peer_height: resp.best_peer_height,
+            nodes: resp.nodes as u64,
+            mempool_size: resp.mempool_size as u64,
+            next_reward: resp.next_reward.into(),
+            timestamp: resp.timestamp,
+            timestamp_offset: resp.timestamp_offset,
+            epoch: resp.epoch,
+            slot: resp.slot,
+            version: resp.version,
+            network: resp.network,
+        }))
+    }
+
+    async fn get_account(
+        &self,
+        request: tonic::Request<proto::GetAccountRequest>,
+    ) -> Result<tonic::Response<proto::GetAccountResponse>, tonic::Status> {
+        let client = request.remote_addr();
+        let resp: GetAccountResponse = self
+            .json_get(
+                client,
+                "/account",
+                GetAccountRequest {
+                    address: request.into_inner().address,
+                },
+            )
+            .await?;
+        Ok(tonic::Response::new(proto::GetAccountResponse {
+            nonce: resp.nonce,
+            mpn_deposit_nonce: resp.mpn_deposit_nonce,
+        }))
+    }
+
+    async fn get_balance(
+        &self,
+        request: tonic::Request<proto::GetBalanceRequest>,
+    ) -> Result<tonic::Response<proto::GetBalanceResponse>, tonic::Status> {
+        let client = request.remote_addr();
+        let req = request.into_inner();
+        let resp: GetBalanceResponse = self
+            .json_get(
+                client,
+                "/balance",
+                GetBalanceRequest {
+                    address: req.address,
+                    token_id: req.token_id,
+                },
+            )
+            .await?;
+        Ok(tonic::Response::new(proto::GetBalanceResponse {
+            balance: resp.balance.into(),
+            name: resp.name,
+            symbol: resp.symbol,
+        }))
+    }
+
+    async fn get_headers(
+        &self,
+        request: tonic::Request<proto::GetHeadersRequest>,
+    ) -> Result<tonic::Response<proto::GetHeadersResponse>, tonic::Status> {
+        let client = request.remote_addr();
+        let req = request.into_inner();
+        let resp = Gateway::get_headers(self, client, req.since, req.count).await?;
+        Ok(tonic::Response::new(proto::GetHeadersResponse {
+            headers: resp.headers.iter().map(|h| h.into()).collect(),
+        }))
+    }
+
+    async fn get_blocks(
+        &self,
+        request: tonic::Request<proto::GetBlocksRequest>,
+    ) -> Result<tonic::Response<proto::GetBlocksResponse>, tonic::Status> {
+        let client = request.remote_addr();
+        let req = request.into_inner();
+        let resp = Gateway::get_blocks(self, client, req.since, req.count).await?;
+        Ok(tonic::Response::new(proto::GetBlocksResponse {
+            blocks: resp.blocks.iter().map(|b| b.into()).collect(),
+        }))
+    }
+
+    async fn get_mempool(
+        &self,
+        request: tonic::Request<proto::GetMempoolRequest>,
+    ) -> Result<tonic::Response<proto::GetMempoolResponse>, tonic::Status> {
+        let client = request.remote_addr();
+        let resp = Gateway::get_mempool(self, client, request.into_inner().filter).await?;
+        Ok(tonic::Response::new(proto::GetMempoolResponse {
+            mempool: resp.mempool.iter().map(|tx| tx.into()).collect(),
+        }))
+    }
+
+    async fn transact(
+        &self,
+        request: tonic::Request<proto::TransactRequest>,
+    ) -> Result<tonic::Response<proto::TransactResponse>, tonic::Status> {
+        let client = request.remote_addr();
+        let tx: GeneralTransaction = bincode::deserialize(&request.into_inner().tx)
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        let resp: TransactResponse = self
+            .bincode_call(
+                client,
+                Method::POST,
+                "/bincode/transact",
+                TransactRequest {
+                    tx,
+                    timestamp_commit: None,
+                },
+            )
+            .await?;
+        Ok(tonic::Response::new(proto::TransactResponse {
+            error: resp.error,
+        }))
+    }
+
+    type StreamBlocksStream = ReceiverStream<Result<proto::Block, tonic::Status>>;
+
+    async fn stream_blocks(
+        &self,
+        request: tonic::Request<proto::StreamBlocksRequest>,
+    ) -> Result<tonic::Response<Self::StreamBlocksStream>, tonic::Status> {
+        let client = request.remote_addr();
+        let since = request.into_inner().since;
+        let (out, rx) = mpsc::channel(STREAM_BUFFER);
+        let gateway = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = gateway.stream_blocks(client, since, out.clone()).await {
+                let _ = out.send(Err(e.into())).await;
+            }
+        });
+        Ok(tonic::Response::new(ReceiverStream::new(rx)))
+    }
+
+    type StreamTransactionsStream =
+        ReceiverStream<Result<proto::MempoolTransaction, tonic::Status>>;
+
+    async fn stream_transactions(
+        &self,
+        request: tonic::Request<proto::StreamTransactionsRequest>,
+    ) -> Result<tonic::Response<Self::StreamTransactionsStream>, tonic::Status> {
+        let client = request.remote_addr();
+        let (out, rx) = mpsc::channel(STREAM_BUFFER);
+        let gateway = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = gateway.stream_transactions(client, out.clone()).await {
+                let _ = out.send(Err(e.into())).await;
+            }
+        });
+        Ok(tonic::Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Serves the gRPC API on `addr` until `shutdown` resolves. Calls are answered by
+/// the node listening on `chan`.
+pub async fn serve<F: std::future::Future<Output = ()>>(
+    addr: SocketAddr,
+    network: String,
+    chan: mpsc::UnboundedSender<NodeRequest>,
+    shutdown: F,
+) -> Result<(), NodeError> {
+    tonic::transport::Server::builder()
+        .add_service(NodeServer::new(Gateway { network, chan }))
+        .serve_with_shutdown(addr, shutdown)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,241 @@
+//! Messages of `proto/bazuka.proto`, keep both in sync!
+
+use crate::core::hash::Hash;
+use crate::core::{Block as CoreBlock, GeneralTransaction, Hasher, Header as CoreHeader};
+
+include!(concat!(env!("OUT_DIR"), "/bazuka.v1.Node.rs"));
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Money {
+    #[prost(string, tag = "1")]
+    pub token_id: String,
+    #[prost(uint64, tag = "2")]
+    pub amount: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Header {
+    #[prost(uint64, tag = "1")]
+    pub number: u64,
+    #[prost(string, tag = "2")]
+    pub hash: String,
+    #[prost(string, tag = "3")]
+    pub parent_hash: String,
+    #[prost(uint32, tag = "4")]
+    pub timestamp: u32,
+    #[prost(string, tag = "5")]
+    pub validator: String,
+    #[prost(bytes = "vec", tag = "6")]
+    pub raw: Vec<u8>,
+}
+
+impl From<&CoreHeader> for Header {
+    fn from(obj: &CoreHeader) -> Self {
+        Self {
+            number: obj.number,
+            hash: hex::encode(obj.hash()),
+            parent_hash: hex::encode(obj.parent_hash),
+            timestamp: obj.proof_of_stake.timestamp,
+            validator: obj.proof_of_stake.validator.to_string(),
+            raw: bincode::serialize(obj).unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Transaction {
+    #[prost(string, tag = "1")]
+    pub hash: String,
+    #[prost(string, optional, tag = "2")]
+    pub src: Option<String>,
+    #[prost(uint32, tag = "3")]
+    pub nonce: u32,
+    #[prost(message, optional, tag = "4")]
+    pub fee: Option<Money>,
+    #[prost(string, tag = "5")]
+    pub memo: String,
+    #[prost(bytes = "vec", tag = "6")]
+    pub raw: Vec<u8>,
+}
+
+impl From<&crate::core::Transaction> for Transaction {
+    fn from(obj: &crate::core::Transaction) -> Self {
+        Self {
+            hash: hex::encode(obj.hash()),
+            src: obj.src.as_ref().map(|src| src.to_string()),
+            nonce: obj.nonce,
+            fee: Some(Money {
+                token_id: obj.fee.token_id.to_string(),
+                amount: obj.fee.amount.into(),
+            }),
+            memo: obj.memo.clone(),
+            raw: bincode::serialize(obj).unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Block {
+    #[prost(message, optional, tag = "1")]
+    pub header: Option<Header>,
+    #[prost(message, repeated, tag = "2")]
+    pub body: Vec<Transaction>,
+}
+
+impl From<&CoreBlock> for Block {
+    fn from(obj: &CoreBlock) -> Self {
+        Self {
+            header: Some((&obj.header).into()),
+            body: obj.body.iter().map(|tx| tx.into()).collect(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MempoolTransaction {
+    #[prost(string, tag = "1")]
+    pub hash: String,
+    #[prost(string, tag = "2")]
+    pub sender: String,
+    #[prost(uint32, tag = "3")]
+    pub nonce: u32,
+    #[prost(bytes = "vec", tag = "4")]
+    pub raw: Vec<u8>,
+}
+
+impl From<&GeneralTransaction> for MempoolTransaction {
+    fn from(obj: &GeneralTransaction) -> Self {
+        let raw = bincode::serialize(obj).unwrap();
+        Self {
+            hash: hex::encode(Hasher::hash(&raw)),
+            sender: obj.sender().to_string(),
+            nonce: obj.nonce(),
+            raw,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetStatsRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetStatsResponse {
+    #[prost(string, tag = "1")]
+    pub address: String,
+    #[prost(uint64, tag = "2")]
+    pub height: u64,
+    #[prost(uint64, tag = "3")]
+    pub best_peer_height: u64,
+    #[prost(uint64, tag = "4")]
+    pub nodes: u64,
+    #[prost(uint64, tag = "5")]
+    pub mempool_size: u64,
+    #[prost(uint64, tag = "6")]
+    pub next_reward: u64,
+    #[prost(uint32, tag = "7")]
+    pub timestamp: u32,
+    #[prost(int32, tag = "8")]
+    pub timestamp_offset: i32,
+    #[prost(uint32, tag = "9")]
+    pub epoch: u32,
+    #[prost(uint32, tag = "10")]
+    pub slot: u32,
+    #[prost(string, tag = "11")]
+    pub version: String,
+    #[prost(string, tag = "12")]
+    pub network: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetAccountRequest {
+    #[prost(string, tag = "1")]
+    pub address: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetAccountResponse {
+    #[prost(uint32, tag = "1")]
+    pub nonce: u32,
+    #[prost(uint32, tag = "2")]
+    pub mpn_deposit_nonce: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetBalanceRequest {
+    #[prost(string, tag = "1")]
+    pub address: String,
+    #[prost(string, tag = "2")]
+    pub token_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetBalanceResponse {
+    #[prost(uint64, tag = "1")]
+    pub balance: u64,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub symbol: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetHeadersRequest {
+    #[prost(uint64, tag = "1")]
+    pub since: u64,
+    #[prost(uint64, tag = "2")]
+    pub count: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetHeadersResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub headers: Vec<Header>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetBlocksRequest {
+    #[prost(uint64, tag = "1")]
+    pub since: u64,
+    #[prost(uint64, tag = "2")]
+    pub count: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetBlocksResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetMempoolRequest {
+    #[prost(string, optional, tag = "1")]
+    pub filter: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetMempoolResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub mempool: Vec<MempoolTransaction>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransactRequest {
+    /// bincode encoded `GeneralTransaction`
+    #[prost(bytes = "vec", tag = "1")]
+    pub tx: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransactResponse {
+    #[prost(string, optional, tag = "1")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StreamBlocksRequest {
+    #[prost(uint64, tag = "1")]
+    pub since: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StreamTransactionsRequest {}
@@ -0,0 +1,124 @@
+use crate::core::hash::Hash;
+use crate::core::{Hasher, Header};
+use std::collections::VecDeque;
+
+/// A header along with its hash, which is needed over and over while validating a
+/// chain of headers
+#[derive(Debug, Clone)]
+pub struct HeaderMeta {
+    pub header: Header,
+    pub hash: <Hasher as Hash>::Output,
+}
+
+impl From<Header> for HeaderMeta {
+    fn from(header: Header) -> Self {
+        let hash = header.hash();
+        Self { header, hash }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderLinkError {
+    BadNumber,
+    NotLinked,
+}
+
+/// Pending chain of consecutive headers of a peer, validated before any of their
+/// bodies are downloaded
+#[derive(Debug, Clone, Default)]
+pub struct HeaderMetaCache {
+    headers: VecDeque<HeaderMeta>,
+}
+
+impl HeaderMetaCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+    pub fn first(&self) -> Option<&HeaderMeta> {
+        self.headers.front()
+    }
+    pub fn tip(&self) -> Option<&HeaderMeta> {
+        self.headers.back()
+    }
+    /// Appends the child of the current tip
+    pub fn push(&mut self, header: Header) -> Result<(), HeaderLinkError> {
+        let meta = HeaderMeta::from(header);
+        if let Some(tip) = self.tip() {
+            if meta.header.number != tip.header.number + 1 {
+                return Err(HeaderLinkError::BadNumber);
+            }
+            if meta.header.parent_hash != tip.hash {
+                return Err(HeaderLinkError::NotLinked);
+            }
+        }
+        self.headers.push_back(meta);
+        Ok(())
+    }
+    pub fn headers(&self) -> Vec<Header> {
+        self.headers.iter().map(|m| m.header.clone()).collect()
+    }
+    /// Removes the first `count` headers, once their bodies are in
+    pub fn take(&mut self, count: usize) -> Vec<Header> {
+        let count = std::cmp::min(count, self.headers.len());
+        self.headers
+            .drain(..count)
+            .map(|meta| meta.header)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ProofOfStake, ValidatorProof};
+
+    fn chain(len: u64) -> Vec<Header> {
+        let mut headers = Vec::new();
+        let mut parent_hash = Default::default();
+        for number in 0..len {
+            let header = Header {
+                parent_hash,
+                number,
+                block_root: Default::default(),
+                proof_of_stake: ProofOfStake {
+                    validator: Default::default(),
+                    timestamp: number as u32,
+                    proof: ValidatorProof::Unproven,
+                },
+            };
+            parent_hash = header.hash();
+            headers.push(header);
+        }
+        headers
+    }
+
+    #[test]
+    fn test_header_meta_cache() {
+        let headers = chain(5);
+        let mut cache = HeaderMetaCache::new();
+        for h in headers[2..4].iter() {
+            cache.push(h.clone()).unwrap();
+        }
+        assert_eq!(
+            cache.push(headers[2].clone()),
+            Err(HeaderLinkError::BadNumber)
+        );
+        let mut forged = headers[4].clone();
+        forged.parent_hash = Default::default();
+        assert_eq!(cache.push(forged), Err(HeaderLinkError::NotLinked));
+        cache.push(headers[4].clone()).unwrap();
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.headers(), headers[2..].to_vec());
+
+        assert_eq!(cache.take(2), headers[2..4].to_vec());
+        assert_eq!(cache.first().unwrap().header, headers[4]);
+        assert_eq!(cache.take(10), headers[4..].to_vec());
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+/// Milliseconds a webhook endpoint has to answer
+const WEBHOOK_TIMEOUT: u32 = 5000;
+
+pub async fn deliver_webhooks<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+) -> Result<(), NodeError> {
+    let (outgoing, deliveries) = {
+        let mut ctx = context.write().await;
+        let ctx = &mut *ctx;
+        let now = ctx.local_timestamp();
+        match &mut ctx.webhooks {
+            Some(webhooks) => {
+                webhooks.update(&ctx.blockchain, now)?;
+                (ctx.outgoing.clone(), webhooks.due(now))
+            }
+            None => return Ok(()),
+        }
+    };
+    if deliveries.is_empty() {
+        return Ok(());
+    }
+
+    let resps = http::group_request(&deliveries, |delivery| {
+        let outgoing = outgoing.clone();
+        let req = delivery.request();
+        async move {
+            outgoing
+                .raw(req?, Limit::default().time(WEBHOOK_TIMEOUT))
+                .await
+        }
+    })
+    .await;
+
+    let mut ctx = context.write().await;
+    let now = ctx.local_timestamp();
+    if let Some(webhooks) = &mut ctx.webhooks {
+        for (delivery, resp) in resps {
+            if let Err(e) = resp {
+                tracing::debug!("Webhook delivery to {} failed: {}", delivery.url, e);
+                webhooks.retry(delivery, now);
+            }
+        }
+    }
+    Ok(())
+}
@@ -29,8 +29,8 @@ pub async fn discover_peers<K: KvStore, B: Blockchain<K>>(
         async move {
             let timer = Instant::now();
             let result = net
-                .bincode_post::<HandshakeRequest, HandshakeResponse>(
-                    format!("http://{}/bincode/peers", peer),
+                .handshake(
+                    peer,
                     handshake_req,
                     Limit::default().size(5 * KB).time(SECOND),
                 )
@@ -42,12 +42,18 @@ pub async fn discover_peers<K: KvStore, B: Blockchain<K>>(
 
     {
         let mut ctx = context.write().await;
+        let now = ctx.local_timestamp();
         for (p, resp) in peer_responses {
-            if let Ok((resp, ping_time)) = resp {
-                if p == resp.peer.address {
-                    ctx.peer_manager.add_node(resp.peer, ping_time);
-                } else {
-                    // ?!
+            match resp {
+                Ok((resp, ping_time)) => {
+                    if p == resp.peer.address {
+                        ctx.peer_manager.add_node(now, resp.peer, ping_time);
+                    } else {
+                        // ?!
+                    }
+                }
+                Err(_) => {
+                    ctx.peer_manager.candidate_failed(p);
                 }
             }
         }
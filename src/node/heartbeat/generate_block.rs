@@ -24,7 +24,7 @@ pub async fn generate_block<K: KvStore, B: Blockchain<K>>(
                 let wallet = ctx.validator_wallet.clone();
                 let nonce = ctx.blockchain.get_nonce(wallet.get_address())?;
                 if let Some(tx_delta) = work_pool.ready(&wallet, nonce + 1) {
-                    log::info!("All MPN-proofs ready!");
+                    tracing::info!("All MPN-proofs ready!");
                     ctx.mempool_add_tx(true, tx_delta.into())?;
                     if let Some(draft) = ctx.try_produce(wallet)? {
                         ctx.mpn_work_pool = None;
@@ -86,7 +86,7 @@ pub async fn generate_block<K: KvStore, B: Blockchain<K>>(
             )?);
         }
         if let Some(claim) = ctx.validator_claim.clone() {
-            println!("You are the validator! Promoting...");
+            tracing::info!("You are the validator! Promoting...");
             if claim.address == ctx.validator_wallet.get_address() {
                 drop(ctx);
                 promote_validator_claim(context, claim).await;
@@ -97,7 +97,7 @@ pub async fn generate_block<K: KvStore, B: Blockchain<K>>(
             if claim.address == ctx.validator_wallet.get_address() {
                 if let Some(work_pool) = &ctx.mpn_work_pool {
                     for work in work_pool.remaining_works().values() {
-                        log::error!("Prover {} is late!", work.worker.mpn_address);
+                        tracing::error!("Prover {} is late!", work.worker.mpn_address);
                     }
                 }
             }
@@ -109,7 +109,7 @@ pub async fn generate_block<K: KvStore, B: Blockchain<K>>(
                 claim.address.clone(),
                 claim.proof.clone(),
             )? {
-                println!("{} is not the validator anymore!", claim.address);
+                tracing::info!("{} is not the validator anymore!", claim.address);
                 ctx.validator_claim = None;
             }
         }
@@ -0,0 +1,15 @@
+use super::*;
+
+pub async fn index_explorer<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+) -> Result<(), NodeError> {
+    let mut ctx = context.write().await;
+    let ctx = &mut *ctx;
+    if let Some(explorer) = &mut ctx.explorer {
+        let indexed = explorer.update(&ctx.blockchain)?;
+        if indexed > 0 {
+            tracing::debug!(indexed, "Indexed blocks for the explorer");
+        }
+    }
+    Ok(())
+}
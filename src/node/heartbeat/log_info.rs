@@ -1,21 +1,20 @@
 use super::*;
 
-use colored::Colorize;
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", size, UNITS[unit])
+}
 
 pub async fn log_info<K: KvStore, B: Blockchain<K>>(
     context: Arc<RwLock<NodeContext<K, B>>>,
 ) -> Result<(), NodeError> {
-    let ctx = context.read().await;
-    let mut inf = Vec::new();
-    inf.extend([
-        ("Height", ctx.blockchain.get_height()?.to_string()),
-        ("Timestamp", ctx.network_timestamp().to_string()),
-        ("Node count", ctx.peer_manager.node_count().to_string()),
-        ("Peer count", ctx.peer_manager.get_peers().len().to_string()),
-    ]);
-
-    inf.push(("Mempool", ctx.mempool.len().to_string()));
-
+    let mut ctx = context.write().await;
     let wallet_addr = ctx.validator_wallet.get_address();
     let tkn = ctx
         .blockchain
@@ -24,14 +23,47 @@ pub async fn log_info<K: KvStore, B: Blockchain<K>>(
     let balance = ctx
         .blockchain
         .get_balance(wallet_addr, crate::core::TokenId::Ziesha)?;
-    inf.push(("Balance", balance.display_by_decimals(tkn.decimals)));
+    let height = ctx.blockchain.get_height()?;
+    let best_peer_height = ctx
+        .peer_manager
+        .get_nodes()
+        .map(|p| p.height)
+        .max()
+        .unwrap_or(height);
+
+    // Blocks per second since the last summary, only meaningful while syncing
+    let now = ctx.local_timestamp();
+    let sync = if best_peer_height > height {
+        match ctx.last_status {
+            Some((last_ts, last_height)) if now > last_ts => format!(
+                "{:.2} blocks/s",
+                height.saturating_sub(last_height) as f64 / (now - last_ts) as f64
+            ),
+            _ => "started".into(),
+        }
+    } else {
+        "done".into()
+    };
+    ctx.last_status = Some((now, height));
+
+    let db_size = ctx
+        .blockchain
+        .database()
+        .disk_usage()
+        .map(human_size)
+        .unwrap_or_else(|| "-".into());
 
-    println!(
-        "{}",
-        inf.into_iter()
-            .map(|(k, v)| format!("{}: {}", k.bright_blue(), v))
-            .collect::<Vec<String>>()
-            .join(" ")
+    tracing::info!(
+        height,
+        best_peer_height,
+        timestamp = ctx.network_timestamp(),
+        nodes = ctx.peer_manager.node_count(),
+        peers = ctx.peer_manager.get_peers().len(),
+        mempool = ctx.mempool.len(),
+        sync = %sync,
+        db_size = %db_size,
+        balance = %balance.display_by_decimals(tkn.decimals),
+        "Node status"
     );
 
     Ok(())
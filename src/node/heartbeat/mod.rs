@@ -1,23 +1,38 @@
 mod log_info;
 
+mod deliver_webhooks;
 mod discover_peers;
 mod generate_block;
+#[cfg(feature = "explorer")]
+mod index_explorer;
+mod record_metrics;
 mod refresh;
+mod report_telemetry;
 mod sync_blocks;
 mod sync_clock;
 mod sync_mempool;
 mod sync_peers;
+mod sync_snapshot;
+mod watchdog;
+
+pub use generate_block::generate_block;
 
 use super::{
-    http, promote_block, promote_validator_claim, Limit, NodeContext, NodeError, Peer, PeerAddress,
+    http, offense_of, promote_block, promote_validator_claim, systemd, Limit, NodeContext,
+    NodeError, Peer, PeerAddress,
 };
 use crate::blockchain::Blockchain;
 use crate::client::messages::*;
+use crate::core::{Block, Header};
+use crate::db::{Blob, StringKey};
 use crate::node::KvStore;
 use crate::utils;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{RwLock, RwLockWriteGuard};
+use tracing::Instrument;
+
+const MAX_SLEEP_STEP: Duration = Duration::from_secs(1);
 
 pub async fn make_loop<
     K: KvStore,
@@ -34,9 +49,15 @@ pub async fn make_loop<
             break;
         }
         if let Err(e) = func(context).await {
-            log::error!("Heartbeat error: {}", e);
+            tracing::error!("Heartbeat error: {}", e);
+        }
+        // Sleep in small steps so that shutdowns are not delayed by long intervals
+        let mut remaining = interval;
+        while !remaining.is_zero() && !context.read().await.shutdown {
+            let step = std::cmp::min(remaining, MAX_SLEEP_STEP);
+            tokio::time::sleep(step).await;
+            remaining -= step;
         }
-        tokio::time::sleep(interval).await;
     }
 }
 
@@ -44,6 +65,7 @@ pub async fn heartbeater<K: KvStore, B: Blockchain<K>>(
     ctx: Arc<RwLock<NodeContext<K, B>>>,
 ) -> Result<(), NodeError> {
     let ints = ctx.read().await.opts.heartbeat_intervals.clone();
+    let watchdog_interval = systemd::watchdog_interval();
     tokio::join!(
         make_loop(&ctx, |ctx| log_info::log_info(ctx.clone()), ints.log_info),
         make_loop(&ctx, |ctx| refresh::refresh(ctx.clone()), ints.refresh),
@@ -64,7 +86,8 @@ pub async fn heartbeater<K: KvStore, B: Blockchain<K>>(
         ),
         make_loop(
             &ctx,
-            |ctx| sync_blocks::sync_blocks(ctx.clone()),
+            |ctx| sync_blocks::sync_blocks(ctx.clone())
+                .instrument(tracing::info_span!("sync_blocks")),
             ints.sync_blocks
         ),
         make_loop(
@@ -77,6 +100,47 @@ pub async fn heartbeater<K: KvStore, B: Blockchain<K>>(
             |ctx| generate_block::generate_block(ctx.clone()),
             ints.generate_block
         ),
+        make_loop(
+            &ctx,
+            |ctx| deliver_webhooks::deliver_webhooks(ctx.clone()),
+            ints.sync_blocks
+        ),
+        async {
+            #[cfg(feature = "explorer")]
+            make_loop(
+                &ctx,
+                |ctx| index_explorer::index_explorer(ctx.clone()),
+                ints.sync_blocks,
+            )
+            .await;
+        },
+        async {
+            let metrics = ctx.read().await.opts.metrics.clone();
+            if let Some(metrics) = metrics {
+                make_loop(
+                    &ctx,
+                    |ctx| record_metrics::record_metrics(ctx.clone()),
+                    metrics.interval,
+                )
+                .await;
+            }
+        },
+        async {
+            let telemetry = ctx.read().await.opts.telemetry.clone();
+            if let Some(telemetry) = telemetry {
+                make_loop(
+                    &ctx,
+                    |ctx| report_telemetry::report_telemetry(ctx.clone()),
+                    telemetry.interval,
+                )
+                .await;
+            }
+        },
+        async {
+            if let Some(interval) = watchdog_interval {
+                make_loop(&ctx, |ctx| watchdog::watchdog(ctx.clone()), interval).await;
+            }
+        },
     );
 
     Ok(())
@@ -0,0 +1,19 @@
+use super::*;
+use crate::blockchain::BlockchainError;
+
+pub async fn record_metrics<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+) -> Result<(), NodeError> {
+    let mut ctx = context.write().await;
+    let ctx = &mut *ctx;
+    let now = ctx.local_timestamp();
+    let height = ctx.blockchain.get_height()?;
+    let peers = ctx.peer_manager.node_count();
+    let mempool_size = ctx.mempool.len();
+    if let Some(metrics) = &mut ctx.metrics {
+        metrics
+            .record(now, height, peers, mempool_size)
+            .map_err(BlockchainError::from)?;
+    }
+    Ok(())
+}
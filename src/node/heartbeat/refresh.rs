@@ -6,6 +6,18 @@ pub async fn refresh<K: KvStore, B: Blockchain<K>>(
     let mut ctx = context.write().await;
     let opts = ctx.opts.clone();
     ctx.refresh()?;
+    if let Some(keep_blocks) = opts.prune {
+        let pruned = ctx.blockchain.prune(keep_blocks)?;
+        if pruned > 0 {
+            tracing::debug!(pruned, "Pruned old blocks");
+        }
+    }
+    if let Some(keep_blocks) = opts.rollback_retention {
+        let pruned = ctx.blockchain.prune_rollbacks(keep_blocks)?;
+        if pruned > 0 {
+            tracing::debug!(pruned, "Pruned old rollback data");
+        }
+    }
     if ctx.peer_manager.get_peers().len() < opts.num_peers {
         ctx.peer_manager.select_peers(opts.num_peers);
     }
@@ -0,0 +1,30 @@
+use super::*;
+use crate::node::TelemetryReport;
+
+/// Milliseconds the telemetry endpoint has to answer
+const TELEMETRY_TIMEOUT: u32 = 5000;
+
+pub async fn report_telemetry<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+) -> Result<(), NodeError> {
+    let (outgoing, req) = {
+        let ctx = context.read().await;
+        let url = match &ctx.opts.telemetry {
+            Some(opts) => opts.url.clone(),
+            None => return Ok(()),
+        };
+        let report = TelemetryReport::new(
+            ctx.network.clone(),
+            ctx.blockchain.get_height()?,
+            ctx.peer_manager.node_count(),
+        );
+        (ctx.outgoing.clone(), report.request(&url)?)
+    };
+    if let Err(e) = outgoing
+        .raw(req, Limit::default().time(TELEMETRY_TIMEOUT))
+        .await
+    {
+        tracing::debug!("Telemetry report failed: {}", e);
+    }
+    Ok(())
+}
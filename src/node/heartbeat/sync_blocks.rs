@@ -1,5 +1,9 @@
 use super::*;
+use crate::blockchain::BlockchainError;
+use crate::client::OutgoingSender;
 use crate::common::*;
+use crate::core::TransactionAndDelta;
+use crate::node::header_cache::HeaderMetaCache;
 
 pub async fn sync_blocks<K: KvStore, B: Blockchain<K>>(
     context: Arc<RwLock<NodeContext<K, B>>>,
@@ -8,103 +12,107 @@ pub async fn sync_blocks<K: KvStore, B: Blockchain<K>>(
     let net = ctx.outgoing.clone();
     let opts = ctx.opts.clone();
     let max_block_size = ctx.blockchain.config().max_block_size;
+    let checkpoints = ctx.blockchain.config().checkpoints.clone();
     let mut sorted_peers = ctx.peer_manager.get_peers();
     drop(ctx);
 
     sorted_peers.sort_by_key(|p| p.height);
 
+    if opts.snapshot_sync && context.read().await.blockchain.get_height()? <= 1 {
+        if let Some(peer) = sorted_peers.last() {
+            if peer.height > 1 {
+                super::sync_snapshot::sync_snapshot(&context, peer).await?;
+            }
+        }
+    }
+
     for peer in sorted_peers.iter().rev() {
         let mut net_fail = false;
         let mut chain_fail = false;
+        // The peer refused serving the blocks, which is not an offense
+        let mut refused = false;
+        let mut offense = Offense::InvalidBlock;
         loop {
             let ctx = context.read().await;
             if peer.height <= ctx.blockchain.get_height()? {
                 return Ok(());
             }
 
-            println!(
-                "Syncing blocks with: {} (Peer height: {})",
-                peer.address, peer.height
+            tracing::info!(
+                peer = %peer.address,
+                peer_height = peer.height,
+                "Syncing blocks"
             );
 
             let local_height = ctx.blockchain.get_height()?;
             let start_height = std::cmp::min(local_height, peer.height);
+            let net_ts = ctx.network_timestamp();
+            let max_ts_diff = ctx.opts.max_block_time_difference;
             drop(ctx);
 
             // WARN: Chain might change when getting responses from users, maybe get all data needed before dropping ctx
 
-            // Get all headers starting from the indices that we don't have.
-            let resp = if let Ok(resp) = net
-                .bincode_get::<GetHeadersRequest, GetHeadersResponse>(
-                    format!("http://{}/bincode/headers", peer.address),
-                    GetHeadersRequest {
-                        since: start_height,
-                        count: opts.max_blocks_fetch,
-                    },
-                    Limit::default()
-                        .size(opts.max_blocks_fetch * KB)
-                        .time(5 * SECOND),
-                )
+            // The local blockchain and the peer blockchain both have all blocks
+            // from 0 to height-1, though, the blocks might not be equal. Find
+            // the header from which the fork has happened, which cannot be below the
+            // latest checkpoint.
+            let lowest = checkpoints
+                .range(..start_height)
+                .next_back()
+                .map(|(number, _)| *number)
+                .unwrap_or(0);
+            let fork_point =
+                match find_fork_point(&context, &net, peer.address, start_height, lowest).await {
+                    Ok(number) => number,
+                    Err(PeerFault::Unresponsive) => {
+                        net_fail = true;
+                        break;
+                    }
+                    Err(PeerFault::Misbehaving(reason)) => {
+                        tracing::warn!("{}", reason);
+                        chain_fail = true;
+                        break;
+                    }
+                    Err(PeerFault::Local(e)) => {
+                        return Err(e);
+                    }
+                };
+            let fork_hash = context
+                .read()
                 .await
-            {
-                resp
-            } else {
-                net_fail = true;
-                break;
-            };
-
-            let mut headers = resp.headers;
-
-            if headers.is_empty() {
-                log::warn!("Peer returned no headers!");
-                chain_fail = true;
-                break;
-            }
-            // TODO: Check parent hashes
-            let ctx = context.read().await;
-            let net_ts = ctx.network_timestamp();
-            let max_ts_diff = ctx.opts.max_block_time_difference;
-            for (i, head) in headers.iter().enumerate() {
-                if head.number != start_height + i as u64 {
-                    log::warn!("Bad header number returned!");
-                    chain_fail = true;
-                    break;
-                }
-                if head.proof_of_stake.timestamp.saturating_sub(net_ts) > max_ts_diff {
-                    log::warn!("Block timestamp is way ahead of future!");
-                    chain_fail = true;
-                    break;
-                }
-                if head.number < local_height && head == &ctx.blockchain.get_header(head.number)? {
-                    log::warn!("Duplicate header given!");
-                    chain_fail = true;
-                    break;
-                }
+                .blockchain
+                .get_header(fork_point)?
+                .hash();
+            if fork_point + 1 < start_height {
+                tracing::info!("Peer is forked after block {}...", fork_point);
             }
-            drop(ctx);
-
-            if chain_fail || net_fail {
+            if peer.pruned_height > fork_point + 1 {
+                tracing::info!(
+                    peer = %peer.address,
+                    pruned_height = peer.pruned_height,
+                    "Peer has pruned the blocks we need"
+                );
                 break;
             }
 
-            log::info!(
-                "Got headers {}-{}...",
-                start_height,
-                start_height + headers.len() as u64
-            );
-
-            // The local blockchain and the peer blockchain both have all blocks
-            // from 0 to height-1, though, the blocks might not be equal. Find
-            // the header from which the fork has happened.
-            for index in (0..start_height).rev() {
-                let peer_resp = if let Ok(resp) = net
+            // Headers of the whole missing range are fetched and validated before any
+            // of the bodies, so that no bodies of a rejected branch are downloaded.
+            let mut pending = HeaderMetaCache::new();
+            while pending.len() < opts.max_pending_headers {
+                let since = fork_point + 1 + pending.len() as u64;
+                if since >= peer.height {
+                    break;
+                }
+                let resp = if let Ok(resp) = net
                     .bincode_get::<GetHeadersRequest, GetHeadersResponse>(
                         format!("http://{}/bincode/headers", peer.address),
                         GetHeadersRequest {
-                            since: index,
-                            count: 1,
+                            since,
+                            count: opts.max_blocks_fetch,
                         },
-                        Limit::default().size(KB).time(3 * SECOND),
+                        Limit::default()
+                            .size(opts.max_blocks_fetch * KB)
+                            .time(5 * SECOND),
                     )
                     .await
                 {
@@ -113,44 +121,57 @@ pub async fn sync_blocks<K: KvStore, B: Blockchain<K>>(
                     net_fail = true;
                     break;
                 };
-                if peer_resp.headers.is_empty() {
-                    log::warn!("Peer is not providing claimed headers!");
-                    chain_fail = true;
-                    break;
-                }
 
-                let peer_header = peer_resp.headers[0].clone();
-
-                if peer_header.number != index as u64 {
-                    log::warn!("Bad header number!");
-                    chain_fail = true;
-                    break;
-                }
-                if peer_header.proof_of_stake.timestamp.saturating_sub(net_ts) > max_ts_diff {
-                    log::warn!("Block timestamp is way ahead of future!");
-                    chain_fail = true;
-                    break;
-                }
-                if peer_header.hash() != headers[0].parent_hash {
-                    log::warn!("Bad header hash!");
-                    chain_fail = true;
+                if resp.headers.is_empty() {
+                    // Whatever the peer has served so far is still worth syncing to
+                    if pending.is_empty() {
+                        tracing::warn!("Peer returned no headers!");
+                        chain_fail = true;
+                    }
                     break;
                 }
-
-                log::info!("Got header {}...", index);
-
                 let ctx = context.read().await;
-                let local_header = ctx.blockchain.get_headers(index, 1)?.first().cloned();
-                drop(ctx);
-
-                if let Some(local_header) = local_header {
-                    if local_header.hash() != peer_header.hash() {
-                        headers.insert(0, peer_header);
-                    } else {
+                for (i, head) in resp.headers.into_iter().enumerate() {
+                    if head.number != since + i as u64 {
+                        tracing::warn!("Bad header number returned!");
+                        chain_fail = true;
                         break;
                     }
-                } else {
-                    chain_fail = true;
+                    if head.proof_of_stake.timestamp.saturating_sub(net_ts) > max_ts_diff {
+                        tracing::warn!("Block timestamp is way ahead of future!");
+                        chain_fail = true;
+                        break;
+                    }
+                    if head.number < local_height
+                        && head == ctx.blockchain.get_header(head.number)?
+                    {
+                        tracing::warn!("Duplicate header given!");
+                        chain_fail = true;
+                        break;
+                    }
+                    if checkpoints
+                        .get(&head.number)
+                        .map(|hash| hash != &head.hash())
+                        .unwrap_or(false)
+                    {
+                        tracing::warn!("Header does not match the checkpoint!");
+                        chain_fail = true;
+                        break;
+                    }
+                    if pending.is_empty() && head.parent_hash != fork_hash {
+                        tracing::warn!("Headers are not linked to the fork point!");
+                        chain_fail = true;
+                        break;
+                    }
+                    if pending.push(head).is_err() {
+                        tracing::warn!("Headers are not linked together!");
+                        chain_fail = true;
+                        break;
+                    }
+                }
+                drop(ctx);
+
+                if chain_fail {
                     break;
                 }
             }
@@ -159,17 +180,25 @@ pub async fn sync_blocks<K: KvStore, B: Blockchain<K>>(
                 break;
             }
 
+            tracing::info!(
+                "Got headers {}-{}...",
+                fork_point + 1,
+                fork_point + 1 + pending.len() as u64
+            );
+
             let ctx = context.read().await;
 
-            let will_extend = match ctx.blockchain.will_extend(headers[0].number, &headers) {
+            let mut from = pending.first().map(|h| h.header.number).unwrap_or_default();
+            let will_extend = match ctx.blockchain.will_extend(from, &pending.headers()) {
                 Ok(result) => {
                     if !result {
-                        log::warn!("Chain is not powerful enough!");
+                        tracing::warn!("Chain is not powerful enough!");
                     }
                     result
                 }
                 Err(e) => {
-                    log::warn!("Chain is invalid! Error: {}", e);
+                    tracing::warn!("Chain is invalid! Error: {}", e);
+                    offense = offense_of(&e);
                     false
                 }
             };
@@ -179,46 +208,113 @@ pub async fn sync_blocks<K: KvStore, B: Blockchain<K>>(
                 break;
             }
 
+            // The first batch takes the chain past its current height, so that the local
+            // chain never gets shorter while switching to the fork
+            let mut batch_size = std::cmp::max(
+                opts.max_blocks_fetch,
+                ctx.blockchain.get_height()?.saturating_sub(from) + 1,
+            );
+
             drop(ctx);
 
-            if let Ok(resp) = net
-                .bincode_get::<GetBlocksRequest, GetBlocksResponse>(
-                    format!("http://{}/bincode/blocks", peer.address).to_string(),
-                    GetBlocksRequest {
-                        since: headers[0].number,
-                        count: opts.max_blocks_fetch,
-                    },
-                    Limit::default()
-                        .size(opts.max_blocks_fetch as u64 * max_block_size as u64 * 2)
-                        .time(opts.max_blocks_fetch as u32 * 30 * SECOND),
-                )
-                .await
-            {
-                let mut ctx = context.write().await;
+            while !pending.is_empty() {
+                let headers = pending.take(batch_size as usize);
+                batch_size = opts.max_blocks_fetch;
+
+                // Other peers claiming to have all the blocks help downloading them
+                let (first, tip) = (headers[0].number, headers[headers.len() - 1].number);
+                let sources = std::iter::once(peer.address)
+                    .chain(
+                        sorted_peers
+                            .iter()
+                            .rev()
+                            .filter(|p| {
+                                p.address != peer.address
+                                    && p.height > tip
+                                    && p.pruned_height <= first
+                            })
+                            .map(|p| p.address),
+                    )
+                    .take(std::cmp::max(opts.block_download_peers, 1))
+                    .collect::<Vec<_>>();
+
+                match download_blocks(&context, &net, &sources, &headers, max_block_size).await {
+                    Ok(blocks) => {
+                        let mut ctx = context.write().await;
+
+                        if from + blocks.len() as u64 <= ctx.blockchain.get_height()? {
+                            tracing::warn!("Peer is not providing claimed blocks!");
+                            chain_fail = true;
+                            break;
+                        }
 
-                match ctx.blockchain.extend(headers[0].number, &resp.blocks) {
-                    Ok(_) => {
-                        println!("Height advanced to {}!", ctx.blockchain.get_height()?);
-                        ctx.on_update()?;
+                        match ctx.blockchain.reorg(from, &blocks) {
+                            Ok(evicted) => {
+                                tracing::info!(
+                                    height = ctx.blockchain.get_height()?,
+                                    "Height advanced"
+                                );
+                                ctx.on_update()?;
+                                if !evicted.is_empty() {
+                                    tracing::info!(
+                                        count = evicted.len(),
+                                        "Returning transactions of abandoned blocks to the mempool"
+                                    );
+                                }
+                                for tx in evicted {
+                                    let tx = TransactionAndDelta {
+                                        tx,
+                                        state_delta: None,
+                                    };
+                                    if let Err(e) = ctx.mempool_add_tx(false, tx.into()) {
+                                        tracing::debug!(
+                                            "Evicted transaction dropped. Error: {}",
+                                            e
+                                        );
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                chain_fail = true;
+                                offense = offense_of(&e);
+                                tracing::warn!("Cannot extend the blockchain. Error: {}", e);
+                                break;
+                            }
+                        }
+
+                        // Headers of the missing blocks are fetched again in the next round
+                        if blocks.len() < headers.len() {
+                            break;
+                        }
+                        from += blocks.len() as u64;
                     }
-                    Err(e) => {
+                    Err(ChunkError::Network) => {
+                        net_fail = true;
+                        tracing::warn!("Network error! Cannot fetch blocks...");
+                        break;
+                    }
+                    Err(ChunkError::Unavailable(e)) => {
+                        tracing::warn!("Peer cannot serve the blocks! Error: {}", e);
+                        refused = true;
+                        break;
+                    }
+                    Err(_) => {
+                        tracing::warn!("Peer served blocks not matching its headers!");
                         chain_fail = true;
-                        log::warn!("Cannot extend the blockchain. Error: {}", e);
                         break;
                     }
                 }
-            } else {
-                net_fail = true;
-                log::warn!("Network error! Cannot fetch blocks...");
+            }
+
+            if chain_fail || net_fail || refused {
                 break;
             }
         }
         if chain_fail {
-            context.write().await.punish_bad_behavior(
-                peer.address,
-                opts.incorrect_chain_punish,
-                "Cannot sync blocks!",
-            );
+            context
+                .write()
+                .await
+                .punish_bad_behavior(peer.address, offense, "Cannot sync blocks!");
         } else if net_fail {
             context.write().await.punish_unresponsive(peer.address);
         }
@@ -226,3 +322,305 @@ pub async fn sync_blocks<K: KvStore, B: Blockchain<K>>(
 
     Ok(())
 }
+
+/// Why a chunk of blocks could not be taken from a peer
+enum ChunkError {
+    Network,
+    /// The peer answered with an error, e.g. the blocks are pruned
+    Unavailable(String),
+    /// Blocks of another fork, the peer is not necessarily misbehaving
+    OtherFork,
+    /// Blocks whose bodies do not match their headers
+    BadData,
+}
+
+async fn fetch_chunk(
+    net: &OutgoingSender,
+    peer: PeerAddress,
+    headers: &[Header],
+    max_block_size: usize,
+) -> Result<Vec<Block>, ChunkError> {
+    let count = headers.len() as u64;
+    let mut blocks = Vec::new();
+    net.get_blocks(
+        peer,
+        GetBlocksRequest {
+            since: headers[0].number,
+            count,
+        },
+        Limit::default()
+            .size(count * max_block_size as u64 * 2)
+            .time(count as u32 * 30 * SECOND),
+        |block: Block| {
+            blocks.push(block);
+            Ok(())
+        },
+    )
+    .await
+    .map_err(|e| match e {
+        NodeError::RemoteServerError(e) => ChunkError::Unavailable(e),
+        _ => ChunkError::Network,
+    })?;
+
+    if blocks.is_empty() {
+        return Err(ChunkError::Network);
+    }
+    if blocks.len() > headers.len() {
+        return Err(ChunkError::BadData);
+    }
+    for (block, header) in blocks.iter().zip(headers.iter()) {
+        if &block.header != header {
+            return Err(ChunkError::OtherFork);
+        }
+        if block.merkle_tree().root() != header.block_root {
+            return Err(ChunkError::BadData);
+        }
+    }
+    Ok(blocks)
+}
+
+/// Downloads the blocks of the validated `headers`, split into chunks which are
+/// requested concurrently from `sources`. The first source is the peer the headers
+/// were taken from, chunks the others fail to serve are retried from it. Peers serving
+/// bodies not matching the headers are punished. Stops at the first chunk the source
+/// serves partially.
+async fn download_blocks<K: KvStore, B: Blockchain<K>>(
+    context: &Arc<RwLock<NodeContext<K, B>>>,
+    net: &OutgoingSender,
+    sources: &[PeerAddress],
+    headers: &[Header],
+    max_block_size: usize,
+) -> Result<Vec<Block>, ChunkError> {
+    let chunk_size = headers.len().div_ceil(sources.len());
+    let chunks = headers.chunks(chunk_size).collect::<Vec<_>>();
+    let results = futures::future::join_all(
+        chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| fetch_chunk(net, sources[i % sources.len()], chunk, max_block_size)),
+    )
+    .await;
+
+    let mut blocks = Vec::new();
+    for (i, (chunk, mut result)) in chunks.iter().zip(results).enumerate() {
+        let helper = sources[i % sources.len()];
+        if helper != sources[0] {
+            match &result {
+                Ok(served) if served.len() == chunk.len() => {}
+                res => {
+                    match res {
+                        Err(ChunkError::BadData) => context.write().await.punish_bad_behavior(
+                            helper,
+                            Offense::InvalidBlock,
+                            "Served blocks not matching their headers!",
+                        ),
+                        Err(ChunkError::Network) => {
+                            context.write().await.punish_unresponsive(helper)
+                        }
+                        _ => {}
+                    }
+                    result = fetch_chunk(net, sources[0], chunk, max_block_size).await;
+                }
+            }
+        }
+        let served = result?;
+        let complete = served.len() == chunk.len();
+        blocks.extend(served);
+        if !complete {
+            break;
+        }
+    }
+    Ok(blocks)
+}
+
+/// Why syncing from a peer had to be stopped
+enum PeerFault {
+    Unresponsive,
+    Misbehaving(&'static str),
+    /// Not the fault of the peer, the local chain could not be read
+    Local(NodeError),
+}
+
+impl From<BlockchainError> for PeerFault {
+    fn from(e: BlockchainError) -> Self {
+        Self::Local(e.into())
+    }
+}
+
+async fn peer_header(
+    net: &OutgoingSender,
+    peer: PeerAddress,
+    index: u64,
+) -> Result<Header, PeerFault> {
+    let resp = net
+        .bincode_get::<GetHeadersRequest, GetHeadersResponse>(
+            format!("http://{}/bincode/headers", peer),
+            GetHeadersRequest {
+                since: index,
+                count: 1,
+            },
+            Limit::default().size(KB).time(3 * SECOND),
+        )
+        .await
+        .map_err(|_| PeerFault::Unresponsive)?;
+    match resp.headers.into_iter().next() {
+        Some(header) if header.number == index => Ok(header),
+        Some(_) => Err(PeerFault::Misbehaving("Bad header number!")),
+        None => Err(PeerFault::Misbehaving(
+            "Peer is not providing claimed headers!",
+        )),
+    }
+}
+
+async fn is_shared<K: KvStore, B: Blockchain<K>>(
+    context: &Arc<RwLock<NodeContext<K, B>>>,
+    net: &OutgoingSender,
+    peer: PeerAddress,
+    index: u64,
+) -> Result<bool, PeerFault> {
+    let peer_header = peer_header(net, peer, index).await?;
+    let local_header = context.read().await.blockchain.get_header(index)?;
+    Ok(peer_header.hash() == local_header.hash())
+}
+
+/// Latest block below `height` the local chain shares with the peer. Blocks
+/// `height - 1`, `height - 2`, `height - 4`... are probed until a shared one is found,
+/// then the fork is binary searched between the last two probes, so that a fork `n`
+/// blocks deep is found by fetching O(log n) headers. Blocks below `lowest` are not
+/// probed.
+async fn find_fork_point<K: KvStore, B: Blockchain<K>>(
+    context: &Arc<RwLock<NodeContext<K, B>>>,
+    net: &OutgoingSender,
+    peer: PeerAddress,
+    height: u64,
+    lowest: u64,
+) -> Result<u64, PeerFault> {
+    let mut step = 1;
+    let mut hi = height;
+    let mut lo = loop {
+        let probe = std::cmp::max(height.saturating_sub(step), lowest);
+        if is_shared(context, net, peer, probe).await? {
+            break probe;
+        }
+        if probe == lowest {
+            return Err(PeerFault::Misbehaving(if lowest > 0 {
+                "Peer is forked below a checkpoint!"
+            } else {
+                "Peer has a different genesis block!"
+            }));
+        }
+        hi = probe;
+        step *= 2;
+    };
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if is_shared(context, net, peer, mid).await? {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::MockBlockchain;
+    use crate::client::{NodeRequest, Peer};
+    use crate::config::blockchain::get_test_blockchain_config;
+    use crate::core::{Amount, Money, TokenId};
+    use crate::db::RamKvStore;
+    use crate::node::api::tests::test_context_with;
+    use crate::wallet::TxBuilder;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// Answers the requests sent through the returned sender with the API of `peer`
+    async fn serve(peer: Arc<RwLock<NodeContext<RamKvStore, MockBlockchain>>>) -> OutgoingSender {
+        let (streams_send, streams_recv) = mpsc::unbounded_channel();
+        peer.write().await.block_streams = Some(streams_send);
+        tokio::spawn(crate::node::api::serve_block_streams(
+            Arc::clone(&peer),
+            streams_recv,
+            futures::future::pending(),
+        ));
+        let (out_send, mut out_recv) = mpsc::unbounded_channel::<NodeRequest>();
+        tokio::spawn(async move {
+            while let Some(req) = out_recv.recv().await {
+                let resp = crate::node::node_service(None, Arc::clone(&peer), req.body).await;
+                let _ = req.resp.send(resp);
+            }
+        });
+        OutgoingSender::new(
+            TxBuilder::new(&Vec::from("ABC")).get_priv_key(),
+            "test".into(),
+            out_send,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_sync_blocks_reorgs_to_longer_fork() {
+        let conf = get_test_blockchain_config();
+        let validator = TxBuilder::new(&Vec::from("VALIDATOR"));
+        let other = TxBuilder::new(&Vec::from("VALIDATOR2"));
+        let abc = TxBuilder::new(&Vec::from("ABC"));
+        let tx = abc
+            .create_transaction(
+                "".into(),
+                abc.get_address(),
+                Money::ziesha(200),
+                Money::ziesha(0),
+                1,
+            )
+            .tx;
+
+        // Both chains share the first block, the peer is forked after it
+        let mut local = MockBlockchain::new(conf.clone());
+        let mut remote = MockBlockchain::new(conf);
+        let shared = local.next_block(60, &validator, vec![]);
+        local.push_block(shared.clone());
+        remote.push_block(shared);
+        local.push_block(local.next_block(120, &validator, vec![tx.clone()]));
+        local.push_block(local.next_block(180, &validator, vec![]));
+        for i in 2..6 {
+            remote.push_block(remote.next_block(i * 60 + 30, &other, vec![]));
+        }
+        let remote_tip = remote.get_tip().unwrap();
+        local.set_balance(
+            abc.get_address(),
+            TokenId::Ziesha,
+            Amount(1_000_000_000_000),
+        );
+
+        let ctx = test_context_with(local);
+        let peer = PeerAddress(SocketAddr::from(([1, 2, 3, 4], 8765)));
+        {
+            let mut ctx = ctx.write().await;
+            ctx.outgoing = Arc::new(serve(test_context_with(remote)).await);
+            let now = ctx.local_timestamp();
+            ctx.peer_manager.add_node(
+                now,
+                Peer {
+                    address: peer,
+                    pub_key: other.get_address(),
+                    height: 6,
+                    pruned_height: 0,
+                },
+                Duration::from_millis(10),
+            );
+            ctx.peer_manager.select_peers(1);
+        }
+
+        sync_blocks(Arc::clone(&ctx)).await.unwrap();
+
+        let ctx = ctx.read().await;
+        assert_eq!(ctx.blockchain.get_tip().unwrap(), remote_tip);
+        assert_eq!(ctx.blockchain.calls("reorg"), 1);
+        assert_eq!(ctx.blockchain.calls("extend"), 0);
+        // The transaction of the abandoned block is pending again
+        assert_eq!(ctx.mempool.len(), 1);
+        assert!(ctx.peer_manager.get_punished(0).is_empty());
+    }
+}
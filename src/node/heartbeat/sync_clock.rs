@@ -17,7 +17,7 @@ pub async fn sync_clock<K: KvStore, B: Blockchain<K>>(
     let peer_addresses = ctx.peer_manager.get_peers();
     drop(ctx);
 
-    log::info!("Syncing clocks...");
+    tracing::info!("Syncing clocks...");
     let peer_responses: Vec<(Peer, Result<(HandshakeResponse, Duration), NodeError>)> =
         http::group_request(&peer_addresses, move |peer| {
             let handshake_req = handshake_req.clone();
@@ -26,8 +26,8 @@ pub async fn sync_clock<K: KvStore, B: Blockchain<K>>(
             async move {
                 let timer = Instant::now();
                 let result = net
-                    .bincode_post::<HandshakeRequest, HandshakeResponse>(
-                        format!("http://{}/bincode/peers", peer.address),
+                    .handshake(
+                        peer.address,
                         handshake_req,
                         Limit::default().size(5 * KB).time(SECOND),
                     )
@@ -42,9 +42,13 @@ pub async fn sync_clock<K: KvStore, B: Blockchain<K>>(
         let resps = punish_non_responding(&mut ctx, &peer_responses)
             .into_iter()
             .collect::<Vec<_>>();
+        let now = ctx.local_timestamp();
         for (p, (resp, ping_time)) in resps.iter() {
             if *p == resp.peer.address {
-                ctx.peer_manager.add_node(resp.peer.clone(), *ping_time);
+                ctx.peer_manager
+                    .add_node(now, resp.peer.clone(), *ping_time);
+                ctx.peer_manager
+                    .set_uptime(p.ip(), resp.uptime, resp.restart.clone());
             }
         }
         let (timestamps, timestamp_offsets): (Vec<u32>, Vec<i32>) = resps
@@ -55,7 +59,7 @@ pub async fn sync_clock<K: KvStore, B: Blockchain<K>>(
             // Set timestamp_offset according to median timestamp of the network
             let median_timestamp = utils::median(&timestamps);
             let median_timestamp_offset = utils::median(&timestamp_offsets);
-            ctx.timestamp_offset = median_timestamp as i32 - utils::local_timestamp() as i32;
+            ctx.timestamp_offset = median_timestamp as i32 - ctx.local_timestamp() as i32;
             ctx.timestamp_offset -= median_timestamp_offset;
         }
 
@@ -12,7 +12,7 @@ pub async fn sync_mempool<K: KvStore, B: Blockchain<K>>(
     let peer_addresses = ctx.peer_manager.get_peers();
     drop(ctx);
 
-    log::info!("Syncing mempools...");
+    tracing::info!("Syncing mempools...");
     let peer_responses: Vec<(Peer, Result<GetMempoolResponse, NodeError>)> =
         http::group_request(&peer_addresses, |peer| {
             net.bincode_get::<GetMempoolRequest, GetMempoolResponse>(
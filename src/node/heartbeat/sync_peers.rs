@@ -1,6 +1,5 @@
 use super::*;
 use crate::common::*;
-use crate::utils::local_timestamp;
 use rand::prelude::IteratorRandom;
 
 pub async fn sync_peers<K: KvStore, B: Blockchain<K>>(
@@ -14,7 +13,7 @@ pub async fn sync_peers<K: KvStore, B: Blockchain<K>>(
     let peer_addresses = ctx.peer_manager.get_peers();
     drop(ctx);
 
-    log::info!("Syncing peers...");
+    tracing::info!("Syncing peers...");
     let peer_responses: Vec<(Peer, Result<GetPeersResponse, NodeError>)> =
         http::group_request(&peer_addresses, |peer| {
             net.json_get::<GetPeersRequest, GetPeersResponse>(
@@ -33,7 +32,7 @@ pub async fn sync_peers<K: KvStore, B: Blockchain<K>>(
             .map(|(_, r)| r.peers)
             .collect::<Vec<_>>();
 
-        let now = local_timestamp();
+        let now = ctx.local_timestamp();
 
         for peers in resps {
             for p in peers
@@ -41,7 +40,8 @@ pub async fn sync_peers<K: KvStore, B: Blockchain<K>>(
                 .choose_multiple(&mut rand::thread_rng(), ctx.opts.num_peers)
                 .into_iter()
             {
-                ctx.peer_manager.add_candidate(now, p);
+                ctx.peer_manager
+                    .add_candidate(now, p, PeerDirection::Outbound);
             }
         }
 
@@ -0,0 +1,74 @@
+use super::*;
+use crate::common::*;
+use crate::node::api::MAX_SNAPSHOT_CHUNK;
+
+/// Downloads the state of `peer` in chunks and imports it into a chain that only
+/// holds the genesis block. The download is restarted from the next heartbeat if
+/// the tip of the peer changes in between.
+pub async fn sync_snapshot<K: KvStore, B: Blockchain<K>>(
+    context: &Arc<RwLock<NodeContext<K, B>>>,
+    peer: &Peer,
+) -> Result<(), NodeError> {
+    let ctx = context.read().await;
+    let net = ctx.outgoing.clone();
+    let max_block_size = ctx.blockchain.config().max_block_size as u64;
+    drop(ctx);
+
+    tracing::info!(peer = %peer.address, peer_height = peer.height, "Downloading state snapshot");
+
+    let mut tip: Option<Header> = None;
+    let mut pairs = Vec::new();
+    loop {
+        let resp = match net
+            .bincode_get::<GetSnapshotRequest, GetSnapshotResponse>(
+                format!("http://{}/bincode/snapshot", peer.address),
+                GetSnapshotRequest {
+                    after: pairs.last().map(|(k, _): &(StringKey, Blob)| k.clone()),
+                    count: MAX_SNAPSHOT_CHUNK,
+                },
+                Limit::default()
+                    .size(max_block_size * 2 + MAX_SNAPSHOT_CHUNK as u64 * KB)
+                    .time(30 * SECOND),
+            )
+            .await
+        {
+            Ok(resp) => resp,
+            Err(_) => {
+                context.write().await.punish_unresponsive(peer.address);
+                return Ok(());
+            }
+        };
+        if let Some(tip) = &tip {
+            if tip != &resp.tip {
+                tracing::info!("Peer tip changed while downloading the snapshot!");
+                return Ok(());
+            }
+        }
+        tip = Some(resp.tip);
+        let done = resp.pairs.len() < MAX_SNAPSHOT_CHUNK;
+        pairs.extend(resp.pairs);
+        if done {
+            break;
+        }
+    }
+
+    let tip = match tip {
+        Some(tip) => tip,
+        None => return Ok(()),
+    };
+    let mut ctx = context.write().await;
+    if let Err(e) = ctx.blockchain.import_snapshot(&tip, &pairs) {
+        ctx.punish_bad_behavior(
+            peer.address,
+            Offense::InvalidBlock,
+            &format!("Invalid snapshot: {}", e),
+        );
+        return Ok(());
+    }
+    tracing::info!(
+        height = ctx.blockchain.get_height()?,
+        "Imported state snapshot"
+    );
+    ctx.on_update()?;
+    Ok(())
+}
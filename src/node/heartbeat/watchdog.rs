@@ -0,0 +1,13 @@
+use super::*;
+
+pub async fn watchdog<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+) -> Result<(), NodeError> {
+    // Only ping when the node context is accessible, so that systemd restarts the
+    // node if it ever gets stuck on a lock
+    let ctx = context.read().await;
+    if !ctx.shutdown {
+        systemd::ping_watchdog();
+    }
+    Ok(())
+}
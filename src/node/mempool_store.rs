@@ -0,0 +1,131 @@
+use crate::blockchain::TransactionStats;
+use crate::core::hash::Hash;
+use crate::core::{GeneralTransaction, Hasher};
+use crate::db::{Blob, KvStore, KvStoreError, StringKey, WriteOp};
+use std::collections::HashSet;
+
+mod keys {
+    use super::*;
+
+    pub fn prefix() -> StringKey {
+        "MPL-".into()
+    }
+
+    pub fn tx(hash: &str) -> StringKey {
+        format!("MPL-{}", hash).into()
+    }
+}
+
+fn tx_hash(tx: &GeneralTransaction) -> Result<String, KvStoreError> {
+    Ok(hex::encode(Hasher::hash(&bincode::serialize(tx)?)))
+}
+
+/// Pending transactions of the mempool, kept in a separate database so that they
+/// survive node restarts.
+pub struct MempoolStore {
+    database: Box<dyn KvStore + Send + Sync>,
+}
+
+impl MempoolStore {
+    pub fn new(database: Box<dyn KvStore + Send + Sync>) -> Self {
+        Self { database }
+    }
+
+    pub fn put(
+        &mut self,
+        tx: &GeneralTransaction,
+        stats: &TransactionStats,
+    ) -> Result<(), KvStoreError> {
+        self.database.update(&[WriteOp::Put(
+            keys::tx(&tx_hash(tx)?),
+            Blob::from(bincode::serialize(&(tx, stats))?),
+        )])
+    }
+
+    pub fn remove(&mut self, tx: &GeneralTransaction) -> Result<(), KvStoreError> {
+        self.database
+            .update(&[WriteOp::Remove(keys::tx(&tx_hash(tx)?))])
+    }
+
+    /// Removes the transactions which are not pending anymore
+    pub fn retain<'a>(
+        &mut self,
+        pending: impl Iterator<Item = &'a GeneralTransaction>,
+    ) -> Result<(), KvStoreError> {
+        let pending = pending
+            .map(|tx| tx_hash(tx).map(|h| keys::tx(&h)))
+            .collect::<Result<HashSet<_>, _>>()?;
+        let removed = self
+            .database
+            .pairs(keys::prefix())?
+            .into_iter()
+            .filter(|(k, _)| !pending.contains(k))
+            .map(|(k, _)| WriteOp::Remove(k))
+            .collect::<Vec<_>>();
+        self.database.update(&removed)
+    }
+
+    /// Stored transactions, except those which can't be decoded anymore
+    pub fn all(&self) -> Result<Vec<(GeneralTransaction, TransactionStats)>, KvStoreError> {
+        Ok(self
+            .database
+            .pairs(keys::prefix())?
+            .into_iter()
+            .filter_map(|(k, v)| match bincode::deserialize(&v.0) {
+                Ok(tx) => Some(tx),
+                Err(e) => {
+                    tracing::warn!("Dropped undecodable pending transaction {}: {}", k, e);
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Money;
+    use crate::db::RamKvStore;
+    use crate::wallet::TxBuilder;
+
+    #[test]
+    fn test_mempool_store() {
+        let wallet = TxBuilder::new(&Vec::from("ABC"));
+        let txs = (1..4)
+            .map(|nonce| {
+                GeneralTransaction::TransactionAndDelta(wallet.create_transaction(
+                    "".into(),
+                    wallet.get_address(),
+                    Money::ziesha(100),
+                    Money::ziesha(0),
+                    nonce,
+                ))
+            })
+            .collect::<Vec<_>>();
+        let mut store = MempoolStore::new(Box::new(RamKvStore::new()));
+        for (i, tx) in txs.iter().enumerate() {
+            store
+                .put(tx, &TransactionStats::new(i == 0, 100 + i as u32))
+                .unwrap();
+        }
+        assert_eq!(store.all().unwrap().len(), 3);
+
+        store.retain(txs[1..2].iter()).unwrap();
+        let all = store.all().unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].0, txs[1]);
+        assert_eq!(all[0].1.first_seen, 101);
+        assert!(!all[0].1.is_local);
+
+        store.remove(&txs[1]).unwrap();
+        assert!(store.all().unwrap().is_empty());
+
+        // Undecodable leftovers are skipped instead of failing the whole restore
+        store
+            .database
+            .update(&[WriteOp::Put(keys::tx("broken"), Blob::from(vec![1, 2, 3]))])
+            .unwrap();
+        assert!(store.all().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use crate::client::messages::MetricsSample;
+use crate::db::{Blob, KvStore, KvStoreError, LevelDbKvStore, RamKvStore, StringKey, WriteOp};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct MetricsOptions {
+    /// Where the samples are stored, kept in memory if `None`
+    pub db: Option<PathBuf>,
+    /// Time between two samples
+    pub interval: Duration,
+    /// Samples older than this many seconds are removed
+    pub retention: u32,
+}
+
+mod keys {
+    use super::*;
+
+    pub fn prefix() -> StringKey {
+        "SMP-".into()
+    }
+
+    pub fn sample(timestamp: u32) -> StringKey {
+        format!("SMP-{:010}", timestamp).into()
+    }
+}
+
+fn decode(blob: Blob) -> Result<MetricsSample, KvStoreError> {
+    Ok(bincode::deserialize(&blob.0)?)
+}
+
+/// Time-series of the node status, kept in a database separate from the chain so
+/// that operators can see what happened while they were not watching.
+pub struct Metrics {
+    opts: MetricsOptions,
+    database: Box<dyn KvStore + Send + Sync>,
+    last: Option<MetricsSample>,
+}
+
+impl Metrics {
+    pub fn new(opts: MetricsOptions) -> Result<Self, KvStoreError> {
+        let database: Box<dyn KvStore + Send + Sync> = match &opts.db {
+            Some(path) => Box::new(LevelDbKvStore::new(path, 64)?),
+            None => Box::new(RamKvStore::new()),
+        };
+        let last = database
+            .pairs(keys::prefix())?
+            .into_iter()
+            .last()
+            .map(|(_, v)| decode(v))
+            .transpose()?;
+        Ok(Self {
+            opts,
+            database,
+            last,
+        })
+    }
+
+    /// Stores a new sample and removes the ones older than the retention period.
+    /// The sync speed is measured since the previous sample.
+    pub fn record(
+        &mut self,
+        timestamp: u32,
+        height: u64,
+        peers: usize,
+        mempool_size: usize,
+    ) -> Result<MetricsSample, KvStoreError> {
+        let blocks_per_sec = match &self.last {
+            Some(last) if timestamp > last.timestamp => {
+                height.saturating_sub(last.height) as f64 / (timestamp - last.timestamp) as f64
+            }
+            _ => 0.0,
+        };
+        let sample = MetricsSample {
+            timestamp,
+            height,
+            peers,
+            mempool_size,
+            blocks_per_sec,
+        };
+
+        let cutoff = keys::sample(timestamp.saturating_sub(self.opts.retention));
+        let mut ops = self
+            .database
+            .pairs(keys::prefix())?
+            .into_iter()
+            .map(|(k, _)| k)
+            .take_while(|k| *k < cutoff)
+            .map(WriteOp::Remove)
+            .collect::<Vec<_>>();
+        ops.push(WriteOp::Put(
+            keys::sample(timestamp),
+            Blob::from(bincode::serialize(&sample).unwrap()),
+        ));
+        self.database.update(&ops)?;
+
+        self.last = Some(sample.clone());
+        Ok(sample)
+    }
+
+    /// Samples taken between `since` and `until` (Inclusive), oldest first
+    pub fn samples(
+        &self,
+        since: u32,
+        until: u32,
+        count: usize,
+    ) -> Result<Vec<MetricsSample>, KvStoreError> {
+        let (since, until) = (keys::sample(since), keys::sample(until));
+        self.database
+            .pairs(keys::prefix())?
+            .into_iter()
+            .skip_while(|(k, _)| *k < since)
+            .take_while(|(k, _)| *k <= until)
+            .take(count)
+            .map(|(_, v)| decode(v))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_retention_and_range() {
+        let mut metrics = Metrics::new(MetricsOptions {
+            db: None,
+            interval: Duration::from_secs(60),
+            retention: 300,
+        })
+        .unwrap();
+        for i in 0..10u32 {
+            metrics.record(i * 60, i as u64 * 30, 8, 0).unwrap();
+        }
+        let all = metrics.samples(0, u32::MAX, 100).unwrap();
+        assert_eq!(
+            all.iter().map(|s| s.timestamp).collect::<Vec<_>>(),
+            vec![240, 300, 360, 420, 480, 540]
+        );
+        assert_eq!(all[0].blocks_per_sec, 0.5);
+
+        let range = metrics.samples(300, 420, 100).unwrap();
+        assert_eq!(
+            range.iter().map(|s| s.timestamp).collect::<Vec<_>>(),
+            vec![300, 360, 420]
+        );
+        assert_eq!(metrics.samples(300, 420, 2).unwrap().len(), 2);
+    }
+}
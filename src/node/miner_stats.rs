@@ -0,0 +1,30 @@
+/// Counters describing the work this node has done as a block producer and
+/// as the coordinator of MPN provers.
+#[derive(Debug, Clone, Default)]
+pub struct MinerStats {
+    pub accepted_solutions: u64,
+    pub rejected_solutions: u64,
+    pub produced_blocks: u64,
+    /// Solutions for works that were already solved by another worker
+    pub stale_solutions: u64,
+    /// Blocks received for heights that were already taken by another block
+    pub stale_blocks: u64,
+}
+
+impl MinerStats {
+    pub fn solution_accepted(&mut self) {
+        self.accepted_solutions += 1;
+    }
+    pub fn solution_rejected(&mut self) {
+        self.rejected_solutions += 1;
+    }
+    pub fn solution_stale(&mut self) {
+        self.stale_solutions += 1;
+    }
+    pub fn block_stale(&mut self) {
+        self.stale_blocks += 1;
+    }
+    pub fn block_produced(&mut self) {
+        self.produced_blocks += 1;
+    }
+}
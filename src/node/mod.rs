@@ -1,34 +1,75 @@
 #[cfg(test)]
 mod test;
 
+pub mod simulation;
+pub mod systemd;
+
+mod access_list;
 mod api;
+mod ban_store;
+mod builder;
+mod clock;
+mod cluster;
 mod context;
+#[cfg(feature = "explorer")]
+mod explorer;
+mod faucet;
 mod firewall;
+mod follower;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+mod header_cache;
 mod heartbeat;
 mod http;
+mod mempool_store;
+mod metrics;
+mod miner_stats;
 mod peer_manager;
-use crate::blockchain::{Blockchain, Mempool};
+mod punish;
+mod telemetry;
+pub mod transport;
+#[cfg(feature = "upnp")]
+pub mod upnp;
+mod webhook;
+#[cfg(feature = "zmq")]
+pub mod zmq;
+use crate::blockchain::{Blockchain, ContractTxPolicy, Mempool};
 use crate::client::{
-    messages::*, Limit, NodeError, NodeRequest, OutgoingSender, Peer, PeerAddress, Timestamp,
-    NETWORK_HEADER, SIGNATURE_HEADER,
+    messages::*, protocol_version, signed_message, Limit, NodeError, NodeRequest, OutgoingSender,
+    Peer, PeerAddress, Timestamp, LEGACY_HANDSHAKE_VERSION, NETWORK_HEADER, PROTOCOL_HEADER,
+    PROTOCOL_VERSION, SIGNATURE_HEADER, SIGNATURE_TIMESTAMP_HEADER,
 };
 use crate::common::*;
-use crate::core::{Amount, Block};
-use crate::crypto::ed25519;
+use crate::core::{Address, Amount, Block, GeneralTransaction, Signer};
 use crate::crypto::SignatureScheme;
 use crate::db::KvStore;
-use crate::mpn::MpnWorker;
-use crate::utils::local_timestamp;
-use crate::wallet::TxBuilder;
+pub use access_list::{AccessListOptions, IpNetwork};
+pub use ban_store::BanStore;
+pub use builder::NodeBuilder;
+pub use clock::Clock;
+pub use cluster::ClusterOptions;
 use context::NodeContext;
-pub use firewall::Firewall;
+#[cfg(feature = "explorer")]
+pub use explorer::{Explorer, ExplorerOptions};
+pub use faucet::{Faucet, FaucetOptions};
+pub use firewall::{ConnectionGuard, ConnectionLimiter, Firewall};
+pub use follower::{ChainFollower, ChainUpdate, Reorg};
 use hyper::body::HttpBody;
 use hyper::{Body, Method, Request, Response, StatusCode};
+pub use mempool_store::MempoolStore;
+pub use metrics::{Metrics, MetricsOptions};
+use miner_stats::MinerStats;
 use peer_manager::PeerManager;
+pub use punish::{offense_of, PunishmentPolicy};
 use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+pub use telemetry::{TelemetryOptions, TelemetryReport};
+pub use webhook::{
+    WebhookEvent, WebhookEventKind, WebhookOptions, Webhooks, WEBHOOK_SIGNATURE_HEADER,
+};
 
 use tokio::sync::RwLock;
 use tokio::try_join;
@@ -50,22 +91,80 @@ pub struct NodeOptions {
     pub tx_max_time_alive: Option<u32>,
     pub heartbeat_intervals: HeartbeatIntervals,
     pub num_peers: usize,
+    /// Maximum number of requests sent to peers that may wait for a response at
+    /// the same time, the rest are queued
+    pub max_outgoing_requests: usize,
     pub max_blocks_fetch: u64,
-    pub default_punish: u32,
-    pub no_response_punish: u32,
-    pub invalid_data_punish: u32,
-    pub incorrect_chain_punish: u32,
-    pub max_punish: u32,
+    /// Headers of at most this many blocks are fetched and validated ahead of their bodies
+    pub max_pending_headers: usize,
+    /// Number of peers blocks are downloaded from concurrently while syncing
+    pub block_download_peers: usize,
+    pub punishments: PunishmentPolicy,
     pub candidate_remove_threshold: u32,
     pub mempool_max_fetch: usize,
+    /// Maximum number of pending transactions
+    pub mempool_max_size: usize,
+    /// Chain transactions paying less than this are neither accepted nor relayed
+    pub mempool_min_fee: Amount,
+    /// Added to the minimum fee for every byte of a chain transaction
+    pub mempool_min_fee_per_byte: Amount,
+    /// Contract transactions the node accepts and relays
+    pub mempool_contract_policy: ContractTxPolicy,
     pub max_block_time_difference: u32,
     pub automatic_block_generation: bool,
+    pub regtest: bool,
+    pub prune: Option<u64>,
+    /// Only keep rollback data of the given number of latest blocks. Forks deeper
+    /// than this are rejected, since the chain can no longer rewind to them
+    pub rollback_retention: Option<u64>,
+    /// A fresh node downloads the state of its highest peer instead of replaying
+    /// all blocks. The snapshot is trusted, only its headers are checked
+    pub snapshot_sync: bool,
+    /// Accepted transactions are pushed to the peers right away instead of waiting
+    /// for their mempool sync. Meant for transports that gossip them, since every peer
+    /// is sent a request otherwise
+    pub relay_txs: bool,
+    pub faucet: Option<FaucetOptions>,
+    #[cfg(feature = "explorer")]
+    pub explorer: Option<ExplorerOptions>,
+    /// Endpoints notified of chain and peer events
+    pub webhooks: Vec<WebhookOptions>,
+    /// Where block and transaction hashes are published, e.g `tcp://127.0.0.1:28332`
+    #[cfg(feature = "zmq")]
+    pub zmq: Option<String>,
+    /// Anonymous stats are periodically reported when enabled (Opt-in)
+    pub telemetry: Option<TelemetryOptions>,
+    /// Samples of the node status are recorded when enabled
+    pub metrics: Option<MetricsOptions>,
+    /// Other nodes of the operator, trusted with the admin endpoints
+    pub cluster: Option<ClusterOptions>,
+    /// Networks requests are accepted from, all of them if `None`
+    pub access_list: Option<AccessListOptions>,
+    /// Where pending transactions are kept across restarts, they are lost on
+    /// restart if `None`
+    pub mempool_db: Option<PathBuf>,
+    /// Start history of the node, unknown if it has no data directory
+    pub restart: Option<RestartInfo>,
+    /// Where bans of misbehaving peers are kept, they are lifted on restart if `None`
+    pub ban_db: Option<PathBuf>,
+    pub clock: Clock,
 }
 
+/// Signer, signature and signing time of the request. Requests without a signing
+/// time (Signed by older nodes) are treated as unsigned.
 fn fetch_signature(
     req: &Request<Body>,
-) -> Result<Option<(ed25519::PublicKey, ed25519::Signature)>, NodeError> {
-    if let Some(v) = req.headers().get(SIGNATURE_HEADER) {
+) -> Result<Option<(Address, <Signer as SignatureScheme>::Sig, Timestamp)>, NodeError> {
+    if let Some((v, ts)) = req
+        .headers()
+        .get(SIGNATURE_HEADER)
+        .zip(req.headers().get(SIGNATURE_TIMESTAMP_HEADER))
+    {
+        let timestamp = ts
+            .to_str()
+            .ok()
+            .and_then(|ts| ts.parse().ok())
+            .ok_or(NodeError::InvalidSignatureHeader)?;
         let s = v.to_str().map_err(|_| NodeError::InvalidSignatureHeader)?;
         let mut s = s.split('-');
         let (pub_hex, sig_hex) = s
@@ -73,14 +172,14 @@ fn fetch_signature(
             .zip(s.next())
             .ok_or(NodeError::InvalidSignatureHeader)?;
         let pub_key = hex::decode(pub_hex)
-            .map(|bytes| bincode::deserialize::<ed25519::PublicKey>(&bytes))
+            .map(|bytes| bincode::deserialize::<Address>(&bytes))
             .map_err(|_| NodeError::InvalidSignatureHeader)?
             .map_err(|_| NodeError::InvalidSignatureHeader)?;
         let sig = hex::decode(sig_hex)
-            .map(|bytes| bincode::deserialize::<ed25519::Signature>(&bytes))
+            .map(|bytes| bincode::deserialize::<<Signer as SignatureScheme>::Sig>(&bytes))
             .map_err(|_| NodeError::InvalidSignatureHeader)?
             .map_err(|_| NodeError::InvalidSignatureHeader)?;
-        return Ok(Some((pub_key, sig)));
+        return Ok(Some((pub_key, sig, timestamp)));
     }
     Ok(None)
 }
@@ -127,6 +226,28 @@ async fn promote_validator_claim<K: KvStore, B: Blockchain<K>>(
     });
 }
 
+async fn promote_tx<K: KvStore, B: Blockchain<K>>(
+    context: Arc<RwLock<NodeContext<K, B>>>,
+    tx: GeneralTransaction,
+) {
+    let context = context.read().await;
+    let net = context.outgoing.clone();
+    let peer_addresses = context.peer_manager.get_peers();
+    tokio::task::spawn(async move {
+        http::group_request(&peer_addresses, |peer| {
+            net.bincode_post::<TransactRequest, TransactResponse>(
+                format!("http://{}/bincode/transact", peer.address),
+                TransactRequest {
+                    tx: tx.clone(),
+                    timestamp_commit: None,
+                },
+                Limit::default().size(KB).time(3 * SECOND),
+            )
+        })
+        .await;
+    });
+}
+
 async fn node_service<K: KvStore, B: Blockchain<K>>(
     client: Option<SocketAddr>,
     context: Arc<RwLock<NodeContext<K, B>>>,
@@ -136,26 +257,42 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
     match async {
         let mut response = Response::builder()
             .header("Access-Control-Allow-Origin", "*")
+            .header(PROTOCOL_HEADER, PROTOCOL_VERSION)
             .body(Body::default())?;
 
         if let Some(client) = client {
             let mut ctx = context.write().await;
+            if let Some(access_list) = &ctx.opts.access_list {
+                if !access_list.permits(client.ip()) {
+                    tracing::debug!("{} -> Access list dropped request!", client);
+                    *response.status_mut() = StatusCode::FORBIDDEN;
+                    return Ok(response);
+                }
+            }
             let now = ctx.local_timestamp();
             if ctx.peer_manager.is_ip_punished(now, client.ip()) {
-                log::warn!("{} -> PeerManager dropped request!", client);
+                tracing::warn!("{} -> PeerManager dropped request!", client);
                 *response.status_mut() = StatusCode::FORBIDDEN;
                 return Ok(response);
             }
             if let Some(firewall) = &mut ctx.firewall {
                 if !firewall.incoming_permitted(client) {
-                    log::warn!("{} -> Firewall dropped request!", client);
+                    tracing::warn!("{} -> Firewall dropped request!", client);
                     *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
                     return Ok(response);
                 }
             }
+            let header = |name| req.headers().get(name).and_then(|v| v.to_str().ok());
+            ctx.peer_manager.observe(
+                now,
+                client.ip(),
+                protocol_version(req.headers()),
+                header(hyper::header::USER_AGENT.as_str()).map(String::from),
+            );
         }
 
         let method = req.method().clone();
+        let requester_version = protocol_version(req.headers());
 
         if method == Method::OPTIONS {
             return Ok(Response::builder()
@@ -166,10 +303,11 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
                 .body(Body::default())?);
         }
 
-        let path = req.uri().path().to_string();
-        let qs = req.uri().query().unwrap_or("").to_string();
+        let uri = req.uri().clone();
+        let path = uri.path().to_string();
+        let qs = uri.query().unwrap_or("").to_string();
 
-        log::info!(
+        tracing::info!(
             "{} -> {} {}",
             client
                 .map(|c| c.to_string())
@@ -188,7 +326,9 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
 
         let body = req.into_body();
 
-        if !is_local && network != context.read().await.network {
+        // Faucet is meant to be used by plain HTTP clients, which do not send the
+        // network header
+        if !is_local && path != "/faucet" && network != context.read().await.network {
             return Err(NodeError::WrongNetwork);
         }
 
@@ -196,6 +336,11 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
             if let Some(client) = client {
                 let mut ctx = context.write().await;
                 if let Some(firewall) = &mut ctx.firewall {
+                    if !firewall.body_permitted(client, req_sz) {
+                        tracing::warn!("{} -> Firewall dropped a large request!", client);
+                        *response.status_mut() = StatusCode::PAYLOAD_TOO_LARGE;
+                        return Ok(response);
+                    }
                     firewall.add_traffic(client.ip(), req_sz);
                 }
             }
@@ -206,17 +351,27 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
 
         let body_bytes = hyper::body::to_bytes(body).await?;
 
-        let needs_signature = false;
-
-        // TODO: This doesn't prevent replay attacks
-        let is_signed = creds
-            .map(|(pub_key, sig)| {
-                ed25519::Ed25519::<crate::core::Hasher>::verify(&pub_key, &body_bytes, &sig)
+        let now = crate::utils::local_timestamp();
+        let signer = creds
+            .filter(|(_, _, timestamp)| cluster::is_fresh(now, *timestamp))
+            .map(|(pub_key, sig, timestamp)| {
+                let msg = signed_message(&method, &uri, timestamp, &body_bytes)?;
+                Ok::<_, NodeError>(Signer::verify(&pub_key, &msg, &sig).then_some(pub_key))
             })
-            .unwrap_or(false);
-        if needs_signature && !is_signed {
-            return Err(NodeError::SignatureRequired);
-        }
+            .transpose()?
+            .flatten();
+
+        // Admin endpoints are only served to local clients and cluster members
+        let is_trusted = if let Some(cluster) = &context.read().await.opts.cluster {
+            let is_member = signer.map(|s| cluster.is_member(&s)).unwrap_or(false);
+            if !is_local && !is_member && cluster.restricts(&method, &path) {
+                *response.status_mut() = StatusCode::FORBIDDEN;
+                return Ok(response);
+            }
+            is_local || is_member
+        } else {
+            is_local
+        };
 
         match (method, &path[..]) {
             #[cfg(test)]
@@ -226,11 +381,36 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
                         .await?,
                 )?);
             }
+            (Method::POST, "/generate") => {
+                if is_trusted && context.read().await.opts.regtest {
+                    *response.body_mut() = Body::from(serde_json::to_vec(
+                        &api::generate(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
+                    )?);
+                } else {
+                    *response.status_mut() = StatusCode::FORBIDDEN;
+                }
+            }
+            (Method::POST, "/rpc") => {
+                if let Some(resp) = api::rpc(client, Arc::clone(&context), &body_bytes).await? {
+                    *response.body_mut() = Body::from(serde_json::to_vec(&resp)?);
+                }
+            }
             (Method::GET, "/stats") => {
                 *response.body_mut() = Body::from(serde_json::to_vec(
                     &api::get_stats(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
                 )?);
             }
+            (Method::GET, "/metrics/history") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_metrics_history(Arc::clone(&context), serde_qs::from_str(&qs)?)
+                        .await?,
+                )?);
+            }
+            (Method::GET, "/miner/stats") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_miner_stats(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
+                )?);
+            }
             (Method::GET, "/debug") => {
                 *response.body_mut() = Body::from(serde_json::to_vec(
                     &api::get_debug_data(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
@@ -241,6 +421,55 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
                     &api::get_account(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
                 )?);
             }
+            (Method::GET, tx_path) if tx_path.starts_with("/transactions/") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_transaction_status(
+                        Arc::clone(&context),
+                        GetTransactionStatusRequest {
+                            hash: tx_path["/transactions/".len()..].to_lowercase(),
+                        },
+                    )
+                    .await?,
+                )?);
+            }
+            (Method::GET, block_path) if block_path.starts_with("/blocks/") => {
+                let id = block_path["/blocks/".len()..].to_lowercase();
+                *response.body_mut() = Body::from(match id.strip_suffix("/transactions") {
+                    Some(id) => serde_json::to_vec(
+                        &api::get_block_transactions(
+                            Arc::clone(&context),
+                            GetBlockTransactionsRequest { id: id.into() },
+                        )
+                        .await?,
+                    )?,
+                    None => serde_json::to_vec(
+                        &api::get_block(Arc::clone(&context), GetBlockRequest { id }).await?,
+                    )?,
+                });
+            }
+            (Method::GET, account_path)
+                if account_path.starts_with("/accounts/")
+                    && account_path.ends_with("/transactions") =>
+            {
+                let address = account_path["/accounts/".len()..]
+                    .trim_end_matches("/transactions")
+                    .to_string();
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_account_transactions(
+                        Arc::clone(&context),
+                        GetAccountTransactionsRequest {
+                            address,
+                            ..serde_qs::from_str(&qs)?
+                        },
+                    )
+                    .await?,
+                )?);
+            }
+            (Method::GET, "/fees/estimate") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_fee_estimate(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
+                )?);
+            }
             (Method::GET, "/delegations") => {
                 *response.body_mut() = Body::from(serde_json::to_vec(
                     &api::get_delegations(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
@@ -251,6 +480,11 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
                     &api::get_balance(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
                 )?);
             }
+            (Method::GET, "/balances") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_balances(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
+                )?);
+            }
             (Method::GET, "/mpn/account") => {
                 *response.body_mut() = Body::from(serde_json::to_vec(
                     &api::get_mpn_account(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
@@ -261,6 +495,24 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
                     &api::get_peers(client, Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
                 )?);
             }
+            (Method::GET, "/peers/banned") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_banned_peers(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
+                )?);
+            }
+            (Method::POST, "/peers/unban") => {
+                if is_trusted {
+                    *response.body_mut() = Body::from(serde_json::to_vec(
+                        &api::unban_peer(
+                            Arc::clone(&context),
+                            serde_json::from_slice(&body_bytes)?,
+                        )
+                        .await?,
+                    )?);
+                } else {
+                    *response.status_mut() = StatusCode::FORBIDDEN;
+                }
+            }
             (Method::GET, "/explorer/stakers") => {
                 *response.body_mut() = Body::from(serde_json::to_vec(
                     &api::get_explorer_stakers(Arc::clone(&context), serde_qs::from_str(&qs)?)
@@ -273,17 +525,47 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
                 )?);
             }
             (Method::POST, "/bincode/peers") => {
-                *response.body_mut() = Body::from(bincode::serialize(
-                    &api::post_peer(
+                let resp = api::post_peer(
+                    client,
+                    Arc::clone(&context),
+                    bincode::deserialize(&body_bytes)?,
+                )
+                .await?;
+                *response.body_mut() = Body::from(
+                    if requester_version.unwrap_or(LEGACY_HANDSHAKE_VERSION)
+                        <= LEGACY_HANDSHAKE_VERSION
+                    {
+                        bincode::serialize(&LegacyHandshakeResponse::from(resp))?
+                    } else {
+                        bincode::serialize(&resp)?
+                    },
+                );
+            }
+            (Method::POST, "/config") => {
+                if is_trusted {
+                    *response.body_mut() = Body::from(serde_json::to_vec(
+                        &api::update_config(
+                            Arc::clone(&context),
+                            serde_json::from_slice(&body_bytes)?,
+                        )
+                        .await?,
+                    )?);
+                } else {
+                    *response.status_mut() = StatusCode::FORBIDDEN;
+                }
+            }
+            (Method::POST, "/faucet") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::faucet(
                         client,
                         Arc::clone(&context),
-                        bincode::deserialize(&body_bytes)?,
+                        serde_json::from_slice(&body_bytes)?,
                     )
                     .await?,
                 )?);
             }
             (Method::POST, "/shutdown") => {
-                if is_local {
+                if is_trusted {
                     *response.body_mut() = Body::from(serde_json::to_vec(
                         &api::shutdown(Arc::clone(&context), serde_json::from_slice(&body_bytes)?)
                             .await?,
@@ -333,6 +615,38 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
                         .await?,
                 )?);
             }
+            #[cfg(feature = "explorer")]
+            (Method::GET, "/explorer") => {
+                response.headers_mut().insert(
+                    hyper::header::CONTENT_TYPE,
+                    hyper::header::HeaderValue::from_static("text/html; charset=utf-8"),
+                );
+                *response.body_mut() = Body::from(explorer::EXPLORER_PAGE);
+            }
+            #[cfg(feature = "explorer")]
+            (Method::GET, "/explorer/index/blocks") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_explorer_block_stats(Arc::clone(&context), serde_qs::from_str(&qs)?)
+                        .await?,
+                )?);
+            }
+            #[cfg(feature = "explorer")]
+            (Method::GET, "/explorer/index/tx") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_explorer_transaction(Arc::clone(&context), serde_qs::from_str(&qs)?)
+                        .await?,
+                )?);
+            }
+            #[cfg(feature = "explorer")]
+            (Method::GET, "/explorer/index/address") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_explorer_address_activity(
+                        Arc::clone(&context),
+                        serde_qs::from_str(&qs)?,
+                    )
+                    .await?,
+                )?);
+            }
             (Method::GET, "/explorer/mempool") => {
                 *response.body_mut() = Body::from(serde_json::to_vec(
                     &api::get_explorer_mempool(Arc::clone(&context), serde_qs::from_str(&qs)?)
@@ -345,16 +659,44 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
                         .await?,
                 )?);
             }
+            (Method::GET, "/bincode/headers/meta") => {
+                *response.body_mut() = Body::from(bincode::serialize(
+                    &api::get_block_metas(Arc::clone(&context), bincode::deserialize(&body_bytes)?)
+                        .await?,
+                )?);
+            }
+            (Method::GET, "/headers/meta") => {
+                *response.body_mut() = Body::from(serde_json::to_vec(
+                    &api::get_block_metas(Arc::clone(&context), serde_qs::from_str(&qs)?).await?,
+                )?);
+            }
+            (Method::GET, "/bincode/snapshot") => {
+                *response.body_mut() = Body::from(bincode::serialize(
+                    &api::get_snapshot(Arc::clone(&context), bincode::deserialize(&body_bytes)?)
+                        .await?,
+                )?);
+            }
             (Method::GET, "/bincode/blocks") => {
                 *response.body_mut() = Body::from(bincode::serialize(
                     &api::get_blocks(Arc::clone(&context), bincode::deserialize(&body_bytes)?)
                         .await?,
                 )?);
             }
+            (Method::GET, "/bincode/blocks/stream") => {
+                *response.body_mut() = api::get_blocks_stream(
+                    Arc::clone(&context),
+                    bincode::deserialize(&body_bytes)?,
+                )
+                .await?;
+            }
             (Method::POST, "/bincode/blocks") => {
                 *response.body_mut() = Body::from(bincode::serialize(
-                    &api::post_block(Arc::clone(&context), bincode::deserialize(&body_bytes)?)
-                        .await?,
+                    &api::post_block(
+                        client,
+                        Arc::clone(&context),
+                        bincode::deserialize(&body_bytes)?,
+                    )
+                    .await?,
                 )?);
             }
             (Method::GET, "/mempool") => {
@@ -429,13 +771,15 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
             if let Some(client) = client {
                 if !is_local {
                     let mut ctx = context.write().await;
-                    let default_punish = ctx.opts.default_punish;
                     let now = ctx.local_timestamp();
-                    ctx.peer_manager
-                        .punish_ip_for(now, client.ip(), default_punish);
+                    let previous = ctx
+                        .peer_manager
+                        .record_offense(now, client.ip(), Offense::Spam);
+                    let secs = ctx.opts.punishments.duration(Offense::Spam, previous);
+                    ctx.punish_ip_for(client.ip(), secs);
                 }
             }
-            log::warn!(
+            tracing::warn!(
                 "{} -> Error: {}",
                 client
                     .map(|c| c.to_string())
@@ -449,53 +793,103 @@ async fn node_service<K: KvStore, B: Blockchain<K>>(
 
 use tokio::sync::mpsc;
 
-pub async fn node_create<K: KvStore, B: Blockchain<K>>(
-    opts: NodeOptions,
-    network: &str,
-    address: Option<PeerAddress>,
-    bootstrap: Vec<PeerAddress>,
-    blockchain: B,
-    timestamp_offset: i32,
-    validator_wallet: TxBuilder,
-    user_wallet: TxBuilder,
-    social_profiles: SocialProfiles,
+async fn node_create<K: KvStore, B: Blockchain<K>>(
+    builder: NodeBuilder<K, B>,
     mut incoming: mpsc::UnboundedReceiver<NodeRequest>,
     outgoing: mpsc::UnboundedSender<NodeRequest>,
-    firewall: Option<Firewall>,
-    mpn_workers: Vec<MpnWorker>,
 ) -> Result<(), NodeError> {
+    let NodeBuilder {
+        opts,
+        network,
+        address,
+        bootstrap,
+        blockchain,
+        timestamp_offset,
+        validator_wallet,
+        user_wallet,
+        social_profiles,
+        firewall,
+        mpn_workers,
+        _phantom,
+    } = builder;
+    #[cfg(feature = "zmq")]
+    let (zmq_send, zmq_recv) = mpsc::unbounded_channel();
+    let (streams_send, streams_recv) = mpsc::unbounded_channel();
     let context = Arc::new(RwLock::new(NodeContext {
         _phantom: std::marker::PhantomData,
         firewall,
         opts: opts.clone(),
-        network: network.into(),
+        default_punishments: opts.punishments.clone(),
+        network: network.clone(),
         social_profiles,
         address,
         shutdown: false,
-        outgoing: Arc::new(OutgoingSender {
-            network: network.into(),
-            chan: outgoing,
-            priv_key: validator_wallet.get_priv_key(),
-        }),
+        started_at: opts.clock.now(),
+        outgoing: Arc::new(
+            OutgoingSender::new(validator_wallet.get_priv_key(), network, outgoing)
+                .with_max_in_flight(opts.max_outgoing_requests),
+        ),
         mpn_workers: mpn_workers
             .into_iter()
             .map(|w| (w.mpn_address.clone(), w))
             .collect(),
         mpn_work_pool: None,
-        mempool: Mempool::new(Amount(1_000_000_000)),
+        miner_stats: Default::default(),
+        last_status: None,
+        faucet: opts.faucet.clone().map(Faucet::new),
+        webhooks: (!opts.webhooks.is_empty()).then(|| Webhooks::new(opts.webhooks.clone())),
+        #[cfg(feature = "zmq")]
+        publisher: opts.zmq.as_ref().map(|_| zmq::Publisher::new(zmq_send)),
+        block_streams: Some(streams_send),
+        #[cfg(feature = "explorer")]
+        explorer: opts
+            .explorer
+            .clone()
+            .map(Explorer::new)
+            .transpose()
+            .map_err(crate::blockchain::BlockchainError::from)?,
+        metrics: opts
+            .metrics
+            .clone()
+            .map(Metrics::new)
+            .transpose()
+            .map_err(crate::blockchain::BlockchainError::from)?,
+        mempool: Mempool::new(Amount(1_000_000_000), opts.mempool_max_size)
+            .with_min_fee(opts.mempool_min_fee)
+            .with_min_fee_per_byte(opts.mempool_min_fee_per_byte)
+            .with_contract_policy(opts.mempool_contract_policy.clone()),
+        mempool_store: opts
+            .mempool_db
+            .as_ref()
+            .map(|path| {
+                crate::db::LevelDbKvStore::new(path, 64).map(|db| MempoolStore::new(Box::new(db)))
+            })
+            .transpose()
+            .map_err(crate::blockchain::BlockchainError::from)?,
+        ban_store: opts
+            .ban_db
+            .as_ref()
+            .map(|path| {
+                crate::db::LevelDbKvStore::new(path, 64).map(|db| BanStore::new(Box::new(db)))
+            })
+            .transpose()
+            .map_err(crate::blockchain::BlockchainError::from)?,
         blockchain,
         validator_wallet,
         user_wallet,
         peer_manager: PeerManager::new(
             address,
             bootstrap,
-            local_timestamp(),
+            opts.clock.now(),
             opts.candidate_remove_threshold,
         ),
         timestamp_offset,
         validator_claim: None,
     }));
 
+    context.write().await.restore_mempool()?;
+    context.write().await.restore_bans()?;
+
     let server_future = async {
         loop {
             if context.read().await.shutdown {
@@ -506,7 +900,7 @@ pub async fn node_create<K: KvStore, B: Blockchain<K>>(
                     .resp
                     .send(node_service(msg.socket_addr, Arc::clone(&context), msg.body).await)
                 {
-                    log::error!("Request sender not receiving its answer: {}", e);
+                    tracing::error!("Request sender not receiving its answer: {}", e);
                 }
             } else {
                 break;
@@ -517,9 +911,32 @@ pub async fn node_create<K: KvStore, B: Blockchain<K>>(
 
     let heartbeat_future = heartbeat::heartbeater(Arc::clone(&context));
 
-    try_join!(server_future, heartbeat_future)?;
+    let zmq_future = async {
+        #[cfg(feature = "zmq")]
+        if let Some(endpoint) = &opts.zmq {
+            zmq::serve(endpoint, zmq_recv, async {
+                while !context.read().await.shutdown {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            })
+            .await?;
+        }
+        Ok::<(), NodeError>(())
+    };
+
+    let streams_future = async {
+        api::serve_block_streams(Arc::clone(&context), streams_recv, async {
+            while !context.read().await.shutdown {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        })
+        .await;
+        Ok::<(), NodeError>(())
+    };
+
+    try_join!(server_future, heartbeat_future, zmq_future, streams_future)?;
 
-    log::info!("Node stopped!");
+    tracing::info!("Node stopped!");
 
     Ok(())
 }
@@ -1,18 +1,37 @@
+use crate::client::messages::{BannedPeer, Offense, PeerDirection, PeerInfo, RestartInfo};
 use crate::client::{Peer, PeerAddress};
 use rand::prelude::IteratorRandom;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
 use std::time::Duration;
 
+/// Offenses are forgotten after this many seconds
+const PUNISHMENT_HISTORY_TTL: u32 = 24 * 3600;
+
+/// Addresses learned from other peers are ignored once there are this many candidates
+const MAX_CANDIDATES: usize = 1024;
+
+/// Candidates are forgotten after failing this many handshakes in a row, except the
+/// bootstrap nodes, which may be the only way back into the network after an outage
+const MAX_HANDSHAKE_FAILURES: u32 = 3;
+
 struct CandidateDetails {
     address: PeerAddress,
     candidated_since: u32,
+    direction: PeerDirection,
+    failures: u32,
 }
 
 #[derive(Clone)]
 struct NodeDetails {
     peer: Peer,
     ping_time: Duration,
+    direction: PeerDirection,
+    last_seen: u32,
+    protocol_version: Option<u32>,
+    user_agent: Option<String>,
+    uptime: Option<u32>,
+    restart: Option<RestartInfo>,
 }
 
 #[derive(Clone)]
@@ -20,12 +39,20 @@ struct PunishmentDetails {
     punished_till: u32,
 }
 
+#[derive(Clone)]
+struct PunishmentHistory {
+    offenses: HashMap<Offense, u32>,
+    last: u32,
+}
+
 pub struct PeerManager {
     candidate_remove_threshold: u32,
     self_addr: Option<PeerAddress>,
+    bootstrap: HashSet<IpAddr>,
     candidates: HashMap<IpAddr, CandidateDetails>,
     nodes: HashMap<IpAddr, NodeDetails>,
     punishments: HashMap<IpAddr, PunishmentDetails>,
+    punishment_history: HashMap<IpAddr, PunishmentHistory>,
     peers: Vec<IpAddr>,
 }
 
@@ -39,6 +66,7 @@ impl PeerManager {
         Self {
             candidate_remove_threshold,
             self_addr,
+            bootstrap: bootstrap.iter().map(|b| b.ip()).collect(),
             candidates: bootstrap
                 .into_iter()
                 .map(|b| {
@@ -47,11 +75,14 @@ impl PeerManager {
                         CandidateDetails {
                             address: b,
                             candidated_since: now,
+                            direction: PeerDirection::Outbound,
+                            failures: 0,
                         },
                     )
                 })
                 .collect(),
             punishments: HashMap::new(),
+            punishment_history: HashMap::new(),
             nodes: HashMap::new(),
             peers: Vec::new(),
         }
@@ -64,6 +95,8 @@ impl PeerManager {
                 self.punishments.remove(&ip);
             }
         }
+        self.punishment_history
+            .retain(|_, hist| now.saturating_sub(hist.last) < PUNISHMENT_HISTORY_TTL);
 
         // Remove candidates that are older than a certain time
         self.candidates
@@ -89,14 +122,69 @@ impl PeerManager {
         );
     }
 
+    /// Bans a peer until the given time, used for bringing back bans which were active
+    /// before a restart
+    pub fn restore_punishment(&mut self, ip: IpAddr, punished_till: u32) {
+        self.punishments
+            .insert(ip, PunishmentDetails { punished_till });
+    }
+
+    /// Lifts the ban of a peer and forgets its offenses, returns false if it was not
+    /// banned
+    pub fn unpunish_ip(&mut self, ip: IpAddr) -> bool {
+        self.punishment_history.remove(&ip);
+        self.punishments.remove(&ip).is_some()
+    }
+
+    pub fn punished_ips(&self) -> impl Iterator<Item = IpAddr> + '_ {
+        self.punishments.keys().cloned()
+    }
+
+    /// Banned peers along with their offenses, the longest bans first
+    pub fn get_punished(&self, now: u32) -> Vec<BannedPeer> {
+        let mut banned = self
+            .punishments
+            .iter()
+            .filter(|(_, det)| now < det.punished_till)
+            .map(|(ip, det)| BannedPeer {
+                ip: *ip,
+                banned_till: det.punished_till,
+                offenses: self
+                    .punishment_history
+                    .get(ip)
+                    .map(|h| h.offenses.clone())
+                    .unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        banned.sort_by_key(|b| std::cmp::Reverse(b.banned_till));
+        banned
+    }
+
+    /// Records an offense of a peer, returns the number of times it was recently
+    /// committed before
+    pub fn record_offense(&mut self, now: u32, ip: IpAddr, offense: Offense) -> u32 {
+        let hist = self
+            .punishment_history
+            .entry(ip)
+            .or_insert(PunishmentHistory {
+                offenses: HashMap::new(),
+                last: now,
+            });
+        hist.last = now;
+        let count = hist.offenses.entry(offense).or_default();
+        *count += 1;
+        *count - 1
+    }
+
     pub fn mark_as_candidate(&mut self, now: u32, addr: &PeerAddress) {
-        if self.nodes.contains_key(&addr.ip()) {
-            self.nodes.remove(&addr.ip());
+        if let Some(node) = self.nodes.remove(&addr.ip()) {
             self.candidates.insert(
                 addr.ip(),
                 CandidateDetails {
                     address: *addr,
                     candidated_since: now,
+                    direction: node.direction,
+                    failures: 0,
                 },
             );
         }
@@ -110,6 +198,58 @@ impl PeerManager {
         self.nodes.values().map(|n| &n.peer)
     }
 
+    /// Nodes along with what we know about them, most recently seen first
+    pub fn get_node_details(&self) -> Vec<PeerInfo> {
+        let mut details = self
+            .nodes
+            .values()
+            .map(|n| {
+                let hist = self.punishment_history.get(&n.peer.address.ip());
+                PeerInfo {
+                    address: n.peer.address,
+                    pub_key: n.peer.pub_key.to_string(),
+                    height: n.peer.height,
+                    pruned_height: n.peer.pruned_height,
+                    protocol_version: n.protocol_version,
+                    user_agent: n.user_agent.clone(),
+                    direction: n.direction,
+                    last_seen: n.last_seen,
+                    latency_ms: n.ping_time.as_millis() as u64,
+                    uptime: n.uptime,
+                    restart: n.restart.clone(),
+                    punishments: hist.map(|h| h.offenses.values().sum()).unwrap_or(0),
+                    last_punished: hist.map(|h| h.last),
+                    offenses: hist.map(|h| h.offenses.clone()).unwrap_or_default(),
+                }
+            })
+            .collect::<Vec<_>>();
+        details.sort_by_key(|d| std::cmp::Reverse(d.last_seen));
+        details
+    }
+
+    /// Records a request received from a node
+    pub fn observe(
+        &mut self,
+        now: u32,
+        ip: IpAddr,
+        protocol_version: Option<u32>,
+        user_agent: Option<String>,
+    ) {
+        if let Some(node) = self.nodes.get_mut(&ip) {
+            node.last_seen = now;
+            node.protocol_version = protocol_version;
+            node.user_agent = user_agent;
+        }
+    }
+
+    /// Records the uptime a node reported in its handshake
+    pub fn set_uptime(&mut self, ip: IpAddr, uptime: Option<u32>, restart: Option<RestartInfo>) {
+        if let Some(node) = self.nodes.get_mut(&ip) {
+            node.uptime = uptime;
+            node.restart = restart;
+        }
+    }
+
     pub fn random_candidates(&self, count: usize) -> Vec<PeerAddress> {
         self.candidates
             .values()
@@ -137,27 +277,204 @@ impl PeerManager {
             .collect()
     }
 
-    pub fn add_candidate(&mut self, now: u32, addr: PeerAddress) {
+    pub fn add_candidate(&mut self, now: u32, addr: PeerAddress, direction: PeerDirection) {
         if self.self_addr == Some(addr) {
             return;
         }
-        if !self.nodes.contains_key(&addr.ip()) {
-            self.candidates.insert(
-                addr.ip(),
-                CandidateDetails {
-                    address: addr,
-                    candidated_since: now,
-                },
-            );
+        if self.nodes.contains_key(&addr.ip()) {
+            return;
+        }
+        match self.candidates.get_mut(&addr.ip()) {
+            // Failures are kept, so that dead peers gossiped again are still dropped
+            Some(candidate) => {
+                candidate.address = addr;
+                candidate.candidated_since = now;
+                candidate.direction = direction;
+            }
+            None => {
+                if self.candidates.len() < MAX_CANDIDATES {
+                    self.candidates.insert(
+                        addr.ip(),
+                        CandidateDetails {
+                            address: addr,
+                            candidated_since: now,
+                            direction,
+                            failures: 0,
+                        },
+                    );
+                }
+            }
         }
     }
 
-    pub fn add_node(&mut self, peer: Peer, ping_time: Duration) {
+    /// Records a failed handshake with a candidate, which is removed after too many
+    pub fn candidate_failed(&mut self, addr: PeerAddress) {
+        if let Some(candidate) = self.candidates.get_mut(&addr.ip()) {
+            candidate.failures += 1;
+            if candidate.failures >= MAX_HANDSHAKE_FAILURES && !self.bootstrap.contains(&addr.ip())
+            {
+                self.candidates.remove(&addr.ip());
+            }
+        }
+    }
+
+    pub fn add_node(&mut self, now: u32, peer: Peer, ping_time: Duration) {
         if self.self_addr == Some(peer.address) {
             return;
         }
-        self.candidates.remove(&peer.address.ip());
-        self.nodes
-            .insert(peer.address.ip(), NodeDetails { peer, ping_time });
+        let ip = peer.address.ip();
+        let candidate = self.candidates.remove(&ip);
+        match self.nodes.get_mut(&ip) {
+            Some(node) => {
+                node.peer = peer;
+                node.ping_time = ping_time;
+                node.last_seen = now;
+            }
+            None => {
+                self.nodes.insert(
+                    ip,
+                    NodeDetails {
+                        peer,
+                        ping_time,
+                        direction: candidate
+                            .map(|c| c.direction)
+                            .unwrap_or(PeerDirection::Outbound),
+                        last_seen: now,
+                        protocol_version: None,
+                        user_agent: None,
+                        uptime: None,
+                        restart: None,
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::TxBuilder;
+
+    fn peer(addr: &str) -> Peer {
+        Peer {
+            address: addr.parse().unwrap(),
+            pub_key: TxBuilder::new(&Vec::from(addr)).get_address(),
+            height: 10,
+            pruned_height: 0,
+        }
+    }
+
+    #[test]
+    fn test_node_details() {
+        let inbound = peer("1.1.1.1:8765");
+        let outbound = peer("2.2.2.2:8765");
+        let mut pm = PeerManager::new(None, vec![outbound.address], 0, 3600);
+        pm.add_candidate(0, inbound.address, PeerDirection::Inbound);
+        pm.add_node(10, inbound.clone(), Duration::from_millis(120));
+        pm.add_node(20, outbound.clone(), Duration::from_millis(40));
+        pm.observe(
+            30,
+            inbound.address.ip(),
+            Some(1),
+            Some("bazuka/test".into()),
+        );
+
+        let details = pm.get_node_details();
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].address, inbound.address);
+        assert_eq!(details[0].direction, PeerDirection::Inbound);
+        assert_eq!(details[0].last_seen, 30);
+        assert_eq!(details[0].latency_ms, 120);
+        assert_eq!(details[0].protocol_version, Some(1));
+        assert_eq!(details[0].user_agent.as_deref(), Some("bazuka/test"));
+        assert_eq!(details[1].direction, PeerDirection::Outbound);
+        assert_eq!(details[1].user_agent, None);
+        assert_eq!(details[1].uptime, None);
+
+        pm.set_uptime(outbound.address.ip(), Some(500), None);
+        assert_eq!(pm.get_node_details()[1].uptime, Some(500));
+
+        assert_eq!(
+            pm.record_offense(40, outbound.address.ip(), Offense::Spam),
+            0
+        );
+        pm.punish_ip_for(40, outbound.address.ip(), 10);
+        pm.refresh(60);
+        assert_eq!(
+            pm.record_offense(60, outbound.address.ip(), Offense::Timeout),
+            0
+        );
+        pm.punish_ip_for(60, outbound.address.ip(), 10);
+        pm.refresh(80);
+        pm.add_node(80, outbound.clone(), Duration::from_millis(40));
+        let details = pm.get_node_details();
+        assert_eq!(details[0].address, outbound.address);
+        assert_eq!(details[0].punishments, 2);
+        assert_eq!(details[0].last_punished, Some(60));
+        assert_eq!(details[0].offenses.get(&Offense::Timeout), Some(&1));
+        assert_eq!(
+            pm.record_offense(70, outbound.address.ip(), Offense::Spam),
+            1
+        );
+        assert_eq!(pm.get_node_details()[0].punishments, 3);
+
+        pm.refresh(70 + PUNISHMENT_HISTORY_TTL);
+        assert_eq!(pm.get_node_details()[0].punishments, 0);
+    }
+
+    #[test]
+    fn test_punished() {
+        let a: IpAddr = "1.1.1.1".parse().unwrap();
+        let b: IpAddr = "2.2.2.2".parse().unwrap();
+        let mut pm = PeerManager::new(None, vec![], 0, 3600);
+        pm.record_offense(10, a, Offense::BadSignature);
+        pm.punish_ip_for(10, a, 100);
+        pm.restore_punishment(b, 500);
+        let banned = pm.get_punished(20);
+        assert_eq!(banned.len(), 2);
+        assert_eq!(banned[0].ip, b);
+        assert_eq!(banned[1].banned_till, 110);
+        assert_eq!(banned[1].offenses.get(&Offense::BadSignature), Some(&1));
+
+        assert!(pm.unpunish_ip(a));
+        assert!(!pm.unpunish_ip(a));
+        assert!(!pm.is_ip_punished(20, a));
+        assert_eq!(pm.record_offense(30, a, Offense::BadSignature), 0);
+
+        pm.refresh(501);
+        assert!(pm.get_punished(501).is_empty());
+        assert_eq!(pm.punished_ips().count(), 0);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let me: PeerAddress = "9.9.9.9:8765".parse().unwrap();
+        let bootstrap: PeerAddress = "8.8.8.8:8765".parse().unwrap();
+        let mut pm = PeerManager::new(Some(me), vec![bootstrap], 0, 3600);
+        pm.add_candidate(0, me, PeerDirection::Outbound);
+        for i in 0..MAX_CANDIDATES + 10 {
+            let addr = format!("10.0.{}.{}:8765", i / 256, i % 256);
+            pm.add_candidate(0, addr.parse().unwrap(), PeerDirection::Outbound);
+        }
+        assert_eq!(
+            pm.random_candidates(2 * MAX_CANDIDATES).len(),
+            MAX_CANDIDATES
+        );
+        assert!(!pm.random_candidates(2 * MAX_CANDIDATES).contains(&me));
+
+        let dead: PeerAddress = "10.0.0.1:8765".parse().unwrap();
+        for _ in 0..MAX_HANDSHAKE_FAILURES - 1 {
+            pm.candidate_failed(dead);
+            // Learning about it again does not reset the failures
+            pm.add_candidate(10, dead, PeerDirection::Outbound);
+        }
+        assert!(pm.random_candidates(2 * MAX_CANDIDATES).contains(&dead));
+        pm.candidate_failed(dead);
+        assert!(!pm.random_candidates(2 * MAX_CANDIDATES).contains(&dead));
+        assert_eq!(
+            pm.random_candidates(2 * MAX_CANDIDATES).len(),
+            MAX_CANDIDATES - 1
+        );
     }
 }
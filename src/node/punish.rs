@@ -0,0 +1,71 @@
+use crate::blockchain::BlockchainError;
+use crate::client::messages::Offense;
+use std::collections::HashMap;
+
+/// How long peers are banned for each kind of offense
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PunishmentPolicy {
+    /// Seconds a peer is banned for its first offense of a kind, doubled on every repeat.
+    /// Peers are only moved to the candidate list for offenses with no duration.
+    pub durations: HashMap<Offense, u32>,
+    /// Upper bound of a single punishment
+    pub max: u32,
+}
+
+impl PunishmentPolicy {
+    /// Ban duration of an offense, given the number of times the peer has recently
+    /// committed it before
+    pub fn duration(&self, offense: Offense, previous: u32) -> u32 {
+        self.durations
+            .get(&offense)
+            .cloned()
+            .unwrap_or_default()
+            .saturating_mul(2u32.saturating_pow(previous))
+            .min(self.max)
+    }
+}
+
+/// The offense of a peer which served a chain that could not be applied
+pub fn offense_of(err: &BlockchainError) -> Offense {
+    match err {
+        BlockchainError::SignatureError | BlockchainError::InvalidContractPaymentSignature => {
+            Offense::BadSignature
+        }
+        BlockchainError::IncorrectZkProof
+        | BlockchainError::ZkError(_)
+        | BlockchainError::UnelectedValidator => Offense::BadProof,
+        _ => Offense::InvalidBlock,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punishment_durations() {
+        let policy = PunishmentPolicy {
+            durations: [(Offense::Spam, 60), (Offense::InvalidBlock, 3600)]
+                .into_iter()
+                .collect(),
+            max: 7200,
+        };
+        assert_eq!(policy.duration(Offense::Spam, 0), 60);
+        assert_eq!(policy.duration(Offense::Spam, 3), 480);
+        assert_eq!(policy.duration(Offense::InvalidBlock, 1), 7200);
+        assert_eq!(policy.duration(Offense::InvalidBlock, 40), 7200);
+        assert_eq!(policy.duration(Offense::Timeout, 5), 0);
+        assert_eq!(
+            offense_of(&BlockchainError::UnelectedValidator),
+            Offense::BadProof
+        );
+        assert_eq!(
+            offense_of(&BlockchainError::SignatureError),
+            Offense::BadSignature
+        );
+        assert_eq!(
+            offense_of(&BlockchainError::InvalidMerkleRoot),
+            Offense::InvalidBlock
+        );
+    }
+}
@@ -1,12 +1,14 @@
 use super::*;
 
 use crate::blockchain::{BlockchainConfig, KvStoreChain};
-use crate::client::{messages::SocialProfiles, BazukaClient};
+use crate::client::BazukaClient;
 use crate::config;
 use crate::db::RamKvStore;
 use crate::mpn::MpnWorker;
 use crate::wallet::TxBuilder;
 
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{sleep, Duration};
@@ -25,10 +27,12 @@ pub struct NodeOpts {
     pub timestamp_offset: i32,
     pub auto_gen_block: bool,
     pub mpn_workers: Vec<MpnWorker>,
+    pub contract_policy: ContractTxPolicy,
 }
 
 fn create_test_node(
     opts: NodeOpts,
+    clock: Clock,
 ) -> (impl futures::Future<Output = Result<(), NodeError>>, Node) {
     let addr = PeerAddress(SocketAddr::from(([123, 234, 123, opts.addr as u8], 8765)));
     let chain = KvStoreChain::new(RamKvStore::new(), opts.config).unwrap();
@@ -36,35 +40,32 @@ fn create_test_node(
     let (out_send, out_recv) = mpsc::unbounded_channel::<NodeRequest>();
     let mut simulator_options = config::node::get_simulator_options();
     simulator_options.automatic_block_generation = opts.auto_gen_block;
-    let node = node_create(
-        simulator_options.clone(),
-        "simulator",
-        Some(addr),
-        opts.bootstrap
-            .iter()
-            .map(|p| PeerAddress(SocketAddr::from(([123, 234, 123, *p as u8], 8765))))
-            .collect(),
-        chain,
-        opts.timestamp_offset,
-        opts.wallet.clone(),
-        opts.wallet.clone(),
-        SocialProfiles::default(),
-        inc_recv,
-        out_send,
-        None,
-        opts.mpn_workers,
-    );
+    simulator_options.mempool_contract_policy = opts.contract_policy;
+    // Simulated nodes can be asked to generate blocks on demand
+    simulator_options.regtest = true;
+    simulator_options.clock = clock;
+    let node = NodeBuilder::new(simulator_options, "simulator", chain, opts.wallet.clone())
+        .address(Some(addr))
+        .bootstrap(
+            opts.bootstrap
+                .iter()
+                .map(|p| PeerAddress(SocketAddr::from(([123, 234, 123, *p as u8], 8765))))
+                .collect(),
+        )
+        .timestamp_offset(opts.timestamp_offset)
+        .mpn_workers(opts.mpn_workers)
+        .run(inc_recv, out_send);
     (
         node,
         Node {
             addr,
             incoming: BazukaClient {
                 peer: addr,
-                sender: Arc::new(OutgoingSender {
-                    chan: inc_send,
-                    network: "simulator".into(),
-                    priv_key: opts.wallet.get_priv_key(),
-                }),
+                sender: Arc::new(OutgoingSender::new(
+                    opts.wallet.get_priv_key(),
+                    "simulator".into(),
+                    inc_send,
+                )),
                 limit: None,
             },
             outgoing: out_recv,
@@ -77,8 +78,9 @@ async fn route(
     src: PeerAddress,
     mut outgoing: mpsc::UnboundedReceiver<NodeRequest>,
     incs: HashMap<PeerAddress, BazukaClient>,
+    mut rng: ChaChaRng,
 ) -> Result<(), NodeError> {
-    while let Some(req) = outgoing.recv().await {
+    'requests: while let Some(req) = outgoing.recv().await {
         let rules = rules.read().await.clone();
         let mut dst = PeerAddress(
             req.body
@@ -89,12 +91,19 @@ async fn route(
                 .parse()
                 .unwrap(),
         );
-        let rule = rules.iter().find(|r| r.applies(&req.body, src, dst));
-
-        if let Some(rule) = rule {
+        let matching = rules
+            .into_iter()
+            .filter(|r| r.applies(&req.body, src, dst))
+            .collect::<Vec<_>>();
+        for rule in matching {
             match rule.action {
                 Action::Drop => {
-                    continue;
+                    continue 'requests;
+                }
+                Action::Loss(probability) => {
+                    if rng.gen_bool(probability) {
+                        continue 'requests;
+                    }
                 }
                 Action::Delay(dur) => {
                     sleep(dur).await;
@@ -123,12 +132,13 @@ async fn route(
     Ok(())
 }
 
+/// What happens to a request matching a rule. All matching rules are applied in order.
 #[derive(Clone)]
 pub enum Action {
     Drop,
-    #[allow(dead_code)]
+    /// Drop with the given probability
+    Loss(f64),
     Delay(Duration),
-    #[allow(dead_code)]
     Redirect(u16),
 }
 
@@ -164,6 +174,22 @@ impl Rule {
             action: Action::Drop,
         }
     }
+    pub fn latency(dur: Duration) -> Self {
+        Rule {
+            from: Endpoint::Any,
+            to: Endpoint::Any,
+            url: "".into(),
+            action: Action::Delay(dur),
+        }
+    }
+    pub fn packet_loss(probability: f64) -> Self {
+        Rule {
+            from: Endpoint::Any,
+            to: Endpoint::Any,
+            url: "".into(),
+            action: Action::Loss(probability),
+        }
+    }
 }
 
 impl Rule {
@@ -180,6 +206,7 @@ impl Rule {
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn test_network(
     rules: Arc<RwLock<Vec<Rule>>>,
     node_opts: Vec<NodeOpts>,
@@ -187,20 +214,43 @@ pub fn test_network(
     impl futures::Future<Output = Result<Vec<()>, NodeError>>,
     impl futures::Future<Output = Result<Vec<()>, NodeError>>,
     Vec<BazukaClient>,
+) {
+    simulated_network(rules, Clock::System, 0, node_opts)
+}
+
+/// Like `test_network`, but all nodes share the given clock and random packet losses
+/// are drawn from `seed`. With a `Clock::Simulated` clock and a paused tokio runtime,
+/// runs are reproducible.
+#[allow(clippy::type_complexity)]
+pub fn simulated_network(
+    rules: Arc<RwLock<Vec<Rule>>>,
+    clock: Clock,
+    seed: u64,
+    node_opts: Vec<NodeOpts>,
+) -> (
+    impl futures::Future<Output = Result<Vec<()>, NodeError>>,
+    impl futures::Future<Output = Result<Vec<()>, NodeError>>,
+    Vec<BazukaClient>,
 ) {
     let (node_futs, nodes): (Vec<_>, Vec<Node>) = node_opts
         .into_iter()
-        .map(|node_opts| create_test_node(node_opts))
+        .map(|opts| create_test_node(opts, clock.clone()))
         .unzip();
     let incs: HashMap<_, _> = nodes.iter().map(|n| (n.addr, n.incoming.clone())).collect();
+    // Clients are returned in the same order as the given nodes
+    let clients = nodes.iter().map(|n| n.incoming.clone()).collect();
     let route_futs = nodes
         .into_iter()
-        .map(|n| route(Arc::clone(&rules), n.addr, n.outgoing, incs.clone()))
+        .enumerate()
+        .map(|(i, n)| {
+            let rng = ChaChaRng::seed_from_u64(seed.wrapping_add(i as u64));
+            route(Arc::clone(&rules), n.addr, n.outgoing, incs.clone(), rng)
+        })
         .collect::<Vec<_>>();
 
     (
         futures::future::try_join_all(node_futs),
         futures::future::try_join_all(route_futs),
-        incs.into_values().collect(),
+        clients,
     )
 }
@@ -0,0 +1,35 @@
+//! Readiness and watchdog notifications for nodes running as systemd services. All
+//! functions are no-ops when the node is not started by systemd.
+
+use sd_notify::NotifyState;
+use std::time::Duration;
+
+fn notify(state: NotifyState) {
+    if let Err(e) = sd_notify::notify(false, &[state]) {
+        tracing::warn!("Cannot notify systemd: {}", e);
+    }
+}
+
+/// Tells systemd the node has opened its database and is listening for requests
+pub fn notify_ready() {
+    notify(NotifyState::Ready);
+}
+
+pub fn notify_stopping() {
+    notify(NotifyState::Stopping);
+}
+
+pub fn ping_watchdog() {
+    notify(NotifyState::Watchdog);
+}
+
+/// Interval of the watchdog pings, half of the `WatchdogSec` of the service as
+/// recommended by systemd. `None` if the watchdog is not enabled.
+pub fn watchdog_interval() -> Option<Duration> {
+    let mut usec = 0;
+    if sd_notify::watchdog_enabled(false, &mut usec) {
+        Some(Duration::from_micros(usec) / 2)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,70 @@
+use super::NodeError;
+use hyper::{Body, Method, Request};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct TelemetryOptions {
+    /// Where the reports are POSTed to
+    pub url: String,
+    pub interval: Duration,
+}
+
+/// Anonymous stats of a node. Nothing identifying the node (Its addresses, wallet
+/// or social profiles) is ever included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryReport {
+    pub version: String,
+    pub network: String,
+    pub height: u64,
+    pub peers: usize,
+    pub os: String,
+    pub arch: String,
+}
+
+impl TelemetryReport {
+    pub fn new(network: String, height: u64, peers: usize) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").into(),
+            network,
+            height,
+            peers,
+            os: std::env::consts::OS.into(),
+            arch: std::env::consts::ARCH.into(),
+        }
+    }
+
+    pub fn request(&self, url: &str) -> Result<Request<Body>, NodeError> {
+        Ok(Request::builder()
+            .method(Method::POST)
+            .uri(url)
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(self)?))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_telemetry_request() {
+        let report = TelemetryReport::new("mainnet".into(), 123, 8);
+        let req = report.request("http://127.0.0.1:1234/report").unwrap();
+        assert_eq!(req.method(), Method::POST);
+        assert_eq!(req.uri(), "http://127.0.0.1:1234/report");
+        let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "network": "mainnet",
+                "height": 123,
+                "peers": 8,
+                "os": std::env::consts::OS,
+                "arch": std::env::consts::ARCH,
+            })
+        );
+    }
+}
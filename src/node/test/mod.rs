@@ -1,9 +1,11 @@
 use super::*;
 
-mod simulation;
+use super::simulation;
 use simulation::*;
 
 use crate::config::blockchain;
+use crate::mpn::MpnWorker;
+use crate::wallet::TxBuilder;
 use crate::zk;
 use std::sync::Arc;
 use std::time::Duration;
@@ -11,7 +13,10 @@ use tokio::sync::RwLock;
 use tokio::time::sleep;
 
 fn init() {
-    let _ = env_logger::builder().is_test(true).try_init();
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_test_writer()
+        .try_init();
 }
 
 const MAX_WAIT_FOR_CHANGE: usize = 20;
@@ -50,6 +55,7 @@ async fn test_peers_find_each_other() -> Result<(), NodeError> {
                 timestamp_offset: 5,
                 auto_gen_block: false,
                 mpn_workers: vec![],
+                contract_policy: Default::default(),
             },
             NodeOpts {
                 config: conf.clone(),
@@ -59,6 +65,7 @@ async fn test_peers_find_each_other() -> Result<(), NodeError> {
                 timestamp_offset: 10,
                 auto_gen_block: false,
                 mpn_workers: vec![],
+                contract_policy: Default::default(),
             },
             NodeOpts {
                 config: conf.clone(),
@@ -68,6 +75,7 @@ async fn test_peers_find_each_other() -> Result<(), NodeError> {
                 timestamp_offset: 15,
                 auto_gen_block: false,
                 mpn_workers: vec![],
+                contract_policy: Default::default(),
             },
         ],
     );
@@ -113,6 +121,7 @@ async fn test_timestamps_are_sync() -> Result<(), NodeError> {
                 timestamp_offset: 5,
                 auto_gen_block: false,
                 mpn_workers: vec![],
+                contract_policy: Default::default(),
             },
             NodeOpts {
                 config: conf.clone(),
@@ -122,6 +131,7 @@ async fn test_timestamps_are_sync() -> Result<(), NodeError> {
                 timestamp_offset: 10,
                 auto_gen_block: false,
                 mpn_workers: vec![],
+                contract_policy: Default::default(),
             },
             NodeOpts {
                 config: conf.clone(),
@@ -131,6 +141,7 @@ async fn test_timestamps_are_sync() -> Result<(), NodeError> {
                 timestamp_offset: 15,
                 auto_gen_block: false,
                 mpn_workers: vec![],
+                contract_policy: Default::default(),
             },
         ],
     );
@@ -166,8 +177,10 @@ async fn test_blocks_get_synced() -> Result<(), NodeError> {
     // Allow sync of clocks but no block transfer
     let rules = Arc::new(RwLock::new(vec![]));
 
-    let mut conf = blockchain::get_test_blockchain_config();
-    conf.slot_duration = 1;
+    let conf = blockchain::get_test_blockchain_config_with(blockchain::ConsensusParams {
+        slot_duration: 1,
+        ..Default::default()
+    });
 
     let (node_futs, route_futs, chans) = simulation::test_network(
         Arc::clone(&rules),
@@ -180,6 +193,7 @@ async fn test_blocks_get_synced() -> Result<(), NodeError> {
                 timestamp_offset: 5,
                 auto_gen_block: false,
                 mpn_workers: vec![],
+                contract_policy: Default::default(),
             },
             NodeOpts {
                 config: conf.clone(),
@@ -189,6 +203,7 @@ async fn test_blocks_get_synced() -> Result<(), NodeError> {
                 timestamp_offset: 10,
                 auto_gen_block: false,
                 mpn_workers: vec![],
+                contract_policy: Default::default(),
             },
         ],
     );
@@ -254,14 +269,81 @@ async fn test_blocks_get_synced() -> Result<(), NodeError> {
     Ok(())
 }
 
-#[tokio::test]
-async fn test_auto_block_production() -> Result<(), NodeError> {
+#[tokio::test(start_paused = true)]
+async fn test_blocks_get_synced_over_lossy_network() -> Result<(), NodeError> {
+    init();
+
+    let rules = Arc::new(RwLock::new(vec![
+        Rule::latency(Duration::from_millis(200)),
+        Rule::packet_loss(0.3),
+    ]));
+
+    let conf = blockchain::get_test_blockchain_config_with(blockchain::ConsensusParams {
+        slot_duration: 1,
+        ..Default::default()
+    });
+
+    let (node_futs, route_futs, chans) = simulation::simulated_network(
+        Arc::clone(&rules),
+        Clock::simulated(1000),
+        1234,
+        vec![
+            NodeOpts {
+                config: conf.clone(),
+                wallet: TxBuilder::new(&Vec::from("VALIDATOR")),
+                addr: 120,
+                bootstrap: vec![],
+                timestamp_offset: 0,
+                auto_gen_block: false,
+                mpn_workers: vec![],
+                contract_policy: Default::default(),
+            },
+            NodeOpts {
+                config: conf.clone(),
+                wallet: TxBuilder::new(&Vec::from("VALIDATOR2")),
+                addr: 121,
+                bootstrap: vec![120],
+                timestamp_offset: 0,
+                auto_gen_block: false,
+                mpn_workers: vec![],
+                contract_policy: Default::default(),
+            },
+        ],
+    );
+    let test_logic = async {
+        for i in 2..7 {
+            sleep(Duration::from_millis(1000)).await;
+            chans[0].mine().await?;
+            assert_eq!(chans[0].stats().await?.height, i);
+        }
+
+        assert!(
+            catch_change(
+                || async { Ok(chans[1].stats().await?.height == 6) },
+                MAX_WAIT_FOR_CHANGE
+            )
+            .await?,
+        );
+
+        for chan in chans.iter() {
+            chan.shutdown().await?;
+        }
+
+        Ok::<(), NodeError>(())
+    };
+    tokio::try_join!(node_futs, route_futs, test_logic)?;
+    Ok(())
+}
+
+async fn auto_block_production(contract_policy: ContractTxPolicy) -> Result<(), NodeError> {
     init();
 
     let rules = Arc::new(RwLock::new(vec![]));
 
-    let mut conf = blockchain::get_test_blockchain_config();
-    conf.slot_duration = 2;
+    let mut conf = blockchain::get_test_blockchain_config_with(blockchain::ConsensusParams {
+        slot_duration: 2,
+        ..Default::default()
+    });
     conf.mpn_config.mpn_num_deposit_batches = 1;
     conf.mpn_config.mpn_num_withdraw_batches = 1;
     conf.mpn_config.mpn_num_update_batches = 1;
@@ -284,6 +366,7 @@ async fn test_auto_block_production() -> Result<(), NodeError> {
                 mpn_workers: vec![MpnWorker {
                     mpn_address: abc.get_mpn_address(),
                 }],
+                contract_policy: contract_policy.clone(),
             },
             NodeOpts {
                 config: conf.clone(),
@@ -295,6 +378,7 @@ async fn test_auto_block_production() -> Result<(), NodeError> {
                 mpn_workers: vec![MpnWorker {
                     mpn_address: abc.get_mpn_address(),
                 }],
+                contract_policy: contract_policy.clone(),
             },
             NodeOpts {
                 config: conf.clone(),
@@ -306,6 +390,7 @@ async fn test_auto_block_production() -> Result<(), NodeError> {
                 mpn_workers: vec![MpnWorker {
                     mpn_address: abc.get_mpn_address(),
                 }],
+                contract_policy: contract_policy.clone(),
             },
         ],
     );
@@ -345,3 +430,93 @@ async fn test_auto_block_production() -> Result<(), NodeError> {
     tokio::try_join!(node_futs, route_futs, test_logic)?;
     Ok(())
 }
+
+#[tokio::test]
+async fn test_auto_block_production() -> Result<(), NodeError> {
+    auto_block_production(Default::default()).await
+}
+
+#[tokio::test]
+async fn test_auto_block_production_with_restrictive_contract_policy() -> Result<(), NodeError> {
+    // MPN updates are still accepted, the blocks need them
+    auto_block_production(ContractTxPolicy {
+        accept_creations: false,
+        accept_updates: false,
+        max_proof_size: Some(0),
+    })
+    .await
+}
+
+#[cfg(feature = "grpc")]
+#[tokio::test]
+async fn test_grpc_streams_blocks() -> Result<(), NodeError> {
+    use super::grpc::proto::{node_client::NodeClient, GetStatsRequest, StreamBlocksRequest};
+    init();
+
+    let rules = Arc::new(RwLock::new(vec![]));
+    let conf = blockchain::get_test_blockchain_config_with(blockchain::ConsensusParams {
+        slot_duration: 1,
+        ..Default::default()
+    });
+
+    let (node_futs, route_futs, chans) = simulation::test_network(
+        Arc::clone(&rules),
+        vec![NodeOpts {
+            config: conf.clone(),
+            wallet: TxBuilder::new(&Vec::from("VALIDATOR")),
+            addr: 120,
+            bootstrap: vec![],
+            timestamp_offset: 0,
+            auto_gen_block: false,
+            mpn_workers: vec![],
+            contract_policy: Default::default(),
+        }],
+    );
+    let addr: SocketAddr = "127.0.0.1:18790".parse()?;
+    let (stop_send, stop_recv) = tokio::sync::oneshot::channel::<()>();
+    let grpc_fut = grpc::serve(
+        addr,
+        "simulator".into(),
+        chans[0].sender.chan.clone(),
+        async move {
+            let _ = stop_recv.await;
+        },
+    );
+    let test_logic = async {
+        let generated = chans[0].generate(3).await?.generated;
+        assert_eq!(generated, vec![1, 2, 3]);
+
+        let mut client = NodeClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+        let stats = client
+            .get_stats(GetStatsRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(stats.height, 4);
+        assert_eq!(stats.network, "simulator");
+
+        let mut stream = client
+            .stream_blocks(StreamBlocksRequest { since: 2 })
+            .await
+            .unwrap()
+            .into_inner();
+        for expected in 2..4 {
+            let block = stream.message().await.unwrap().unwrap();
+            assert_eq!(block.header.unwrap().number, expected);
+        }
+
+        // New blocks are pushed as soon as they are produced
+        chans[0].generate(1).await?;
+        let block = stream.message().await.unwrap().unwrap();
+        assert_eq!(block.header.unwrap().number, 4);
+
+        drop(stream);
+        let _ = stop_send.send(());
+        chans[0].shutdown().await?;
+        Ok::<(), NodeError>(())
+    };
+    tokio::try_join!(node_futs, route_futs, grpc_fut, test_logic)?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! Ways the requests a node sends are carried to the outside world. Requests of the
+//! node are read from its outgoing channel and handed to a `Transport`, which pipes
+//! back the responses.
+
+#[cfg(feature = "libp2p")]
+mod p2p;
+#[cfg(feature = "libp2p")]
+pub use p2p::{Libp2pOptions, Libp2pService, Libp2pTransport};
+
+use crate::client::{NodeError, NodeRequest};
+use async_trait::async_trait;
+use hyper::{Body, Client, Request, Response};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, req: Request<Body>) -> Result<Response<Body>, NodeError>;
+}
+
+/// Sends every request as a plain HTTP request to its URL
+#[derive(Debug, Clone, Default)]
+pub struct HttpTransport;
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn send(&self, req: Request<Body>) -> Result<Response<Body>, NodeError> {
+        Ok(Client::new().request(req).await?)
+    }
+}
+
+/// Hands the requests of `outgoing` to the transport until `shutdown` resolves, each
+/// one within its own time limit
+pub async fn relay(
+    transport: Arc<dyn Transport>,
+    mut outgoing: mpsc::UnboundedReceiver<NodeRequest>,
+    shutdown: impl futures::Future<Output = ()>,
+) {
+    tokio::pin!(shutdown);
+    while let Some(req) = tokio::select! {
+        req = outgoing.recv() => req,
+        _ = &mut shutdown => None,
+    } {
+        let transport = Arc::clone(&transport);
+        tokio::spawn(async move {
+            let resp = async {
+                let resp = if let Some(time_limit) = req.limit.time {
+                    tokio::time::timeout(time_limit, transport.send(req.body)).await?
+                } else {
+                    transport.send(req.body).await
+                }?;
+                Ok::<_, NodeError>(resp)
+            }
+            .await;
+            if let Err(e) = req.resp.send(resp) {
+                tracing::debug!("Node not listening to its request answer: {}", e);
+            }
+        });
+    }
+}
@@ -0,0 +1,786 @@
+//! Carries the requests between the nodes over libp2p. Blocks, validator claims and
+//! transactions a node pushes to its peers are published once on a gossipsub topic,
+//! which spreads them across the network, while the rest of the requests are sent
+//! to the peer through request-response. Requests not meant for a peer, e.g. webhook
+//! deliveries, are still sent over HTTP.
+
+use super::{HttpTransport, Transport};
+use crate::client::messages::{PostBlockResponse, PostValidatorClaimResponse, TransactResponse};
+use crate::client::{Limit, NodeError, NodeRequest, DEFAULT_REQUEST_DEADLINE};
+use crate::common::*;
+use crate::core::{hash::Hash, Hasher};
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, StreamExt};
+use hyper::header::{HeaderMap, HeaderName, HeaderValue};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use libp2p::gossipsub::{self, IdentTopic, MessageAcceptance, MessageId, PublishError, TopicHash};
+use libp2p::multiaddr::Protocol;
+use libp2p::request_response::{self, OutboundRequestId, ProtocolSupport, ResponseChannel};
+use libp2p::swarm::{dial_opts::DialOpts, ConnectionId, NetworkBehaviour, SwarmEvent};
+use libp2p::{identity, noise, tcp, yamux, Multiaddr, PeerId, StreamProtocol, Swarm};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+const PROTOCOL: StreamProtocol = StreamProtocol::new("/bazuka/request/1");
+
+/// Snapshots and batches of blocks are the largest responses, the node applies its
+/// own, tighter, size limit on each of them
+const MAX_MESSAGE_SIZE: u64 = 256 * MB;
+
+/// Blocks are the largest messages gossiped
+const MAX_GOSSIP_SIZE: usize = 16 * MB as usize;
+
+/// Connections are kept open between the requests of the heartbeat
+const IDLE_CONNECTION_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone)]
+pub struct Libp2pOptions {
+    /// Where other nodes connect to, the external address of the node should point
+    /// to it
+    pub listen: SocketAddr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Topic {
+    Blocks,
+    Claims,
+    Txs,
+}
+
+impl Topic {
+    const ALL: [Topic; 3] = [Topic::Blocks, Topic::Claims, Topic::Txs];
+
+    /// Topic of the messages a node pushes to every peer through the given request
+    fn of(method: &Method, path: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| method == Method::POST && path == t.path())
+    }
+
+    fn from_hash(hash: &TopicHash) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.ident().hash() == *hash)
+    }
+
+    fn path(&self) -> &'static str {
+        match self {
+            Topic::Blocks => "/bincode/blocks",
+            Topic::Claims => "/claim",
+            Topic::Txs => "/bincode/transact",
+        }
+    }
+
+    fn ident(&self) -> IdentTopic {
+        IdentTopic::new(match self {
+            Topic::Blocks => "bazuka/blocks",
+            Topic::Claims => "bazuka/claims",
+            Topic::Txs => "bazuka/txs",
+        })
+    }
+
+    /// What the node expects back from a peer it has pushed the message to
+    fn ack(&self) -> Result<Vec<u8>, NodeError> {
+        Ok(match self {
+            Topic::Blocks => bincode::serialize(&PostBlockResponse {})?,
+            Topic::Claims => bincode::serialize(&PostValidatorClaimResponse {})?,
+            Topic::Txs => bincode::serialize(&TransactResponse { error: None })?,
+        })
+    }
+}
+
+/// Peer the request of the node is meant for, `None` if it is not a request of the
+/// peer protocol
+fn peer_of(req: &Request<Body>) -> Option<SocketAddr> {
+    let path = req.uri().path();
+    if path.starts_with("/bincode/") || path == "/peers" || path == "/claim" {
+        req.uri().authority()?.as_str().parse().ok()
+    } else {
+        None
+    }
+}
+
+fn multiaddr(addr: SocketAddr) -> Multiaddr {
+    Multiaddr::from(addr.ip()).with(Protocol::Tcp(addr.port()))
+}
+
+fn socket_addr(addr: &Multiaddr) -> Option<SocketAddr> {
+    let mut protocols = addr.iter();
+    let ip = match protocols.next()? {
+        Protocol::Ip4(ip) => IpAddr::from(ip),
+        Protocol::Ip6(ip) => IpAddr::from(ip),
+        _ => return None,
+    };
+    match protocols.next()? {
+        Protocol::Tcp(port) => Some(SocketAddr::new(ip, port)),
+        _ => None,
+    }
+}
+
+fn encode_headers(headers: &HeaderMap) -> Vec<(String, Vec<u8>)> {
+    headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.as_bytes().to_vec()))
+        .collect()
+}
+
+fn decode_headers(headers: Vec<(String, Vec<u8>)>) -> Result<HeaderMap, NodeError> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.append(
+            HeaderName::from_bytes(name.as_bytes()).map_err(hyper::http::Error::from)?,
+            HeaderValue::from_bytes(&value)?,
+        );
+    }
+    Ok(map)
+}
+
+/// A request of a node, as sent to one of its peers
+#[derive(Serialize, Deserialize, Debug)]
+struct WireRequest {
+    method: String,
+    /// Path and query of the request
+    uri: String,
+    headers: Vec<(String, Vec<u8>)>,
+    body: Vec<u8>,
+}
+
+impl WireRequest {
+    async fn from_request(req: Request<Body>) -> Result<Self, NodeError> {
+        let (parts, body) = req.into_parts();
+        Ok(Self {
+            method: parts.method.to_string(),
+            uri: parts
+                .uri
+                .path_and_query()
+                .map(|p| p.to_string())
+                .unwrap_or_default(),
+            headers: encode_headers(&parts.headers),
+            body: hyper::body::to_bytes(body).await?.to_vec(),
+        })
+    }
+
+    fn into_request(self) -> Result<Request<Body>, NodeError> {
+        let mut req = Request::builder()
+            .method(self.method.as_str())
+            .uri(self.uri)
+            .body(Body::from(self.body))?;
+        *req.headers_mut() = decode_headers(self.headers)?;
+        Ok(req)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct WireResponse {
+    status: u16,
+    headers: Vec<(String, Vec<u8>)>,
+    body: Vec<u8>,
+}
+
+impl WireResponse {
+    fn error(e: &NodeError) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            headers: Vec::new(),
+            body: format!("Error: {}", e).into_bytes(),
+        }
+    }
+
+    async fn from_response(resp: Result<Response<Body>, NodeError>) -> Self {
+        async {
+            let (parts, body) = resp?.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            Ok::<_, NodeError>(Self {
+                status: parts.status.as_u16(),
+                headers: encode_headers(&parts.headers),
+                body: body.to_vec(),
+            })
+        }
+        .await
+        .unwrap_or_else(|e| Self::error(&e))
+    }
+
+    fn into_response(self) -> Result<Response<Body>, NodeError> {
+        let mut resp = Response::builder()
+            .status(self.status)
+            .body(Body::from(self.body))?;
+        *resp.headers_mut() = decode_headers(self.headers)?;
+        Ok(resp)
+    }
+}
+
+/// A message published on a topic, the request it stands for is implied by the topic
+#[derive(Serialize, Deserialize, Debug)]
+struct GossipMessage {
+    headers: Vec<(String, Vec<u8>)>,
+    body: Vec<u8>,
+}
+
+impl GossipMessage {
+    fn into_request(self, topic: Topic) -> Result<Request<Body>, NodeError> {
+        let mut req = Request::builder()
+            .method(Method::POST)
+            .uri(topic.path())
+            .body(Body::from(self.body))?;
+        *req.headers_mut() = decode_headers(self.headers)?;
+        Ok(req)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct WireCodec;
+
+async fn read_message<T, M>(io: &mut T) -> io::Result<M>
+where
+    T: AsyncRead + Unpin + Send,
+    M: DeserializeOwned,
+{
+    let mut buf = Vec::new();
+    io.take(MAX_MESSAGE_SIZE).read_to_end(&mut buf).await?;
+    bincode::deserialize(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+async fn write_message<T, M>(io: &mut T, msg: &M) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+    M: Serialize,
+{
+    let buf = bincode::serialize(msg).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    io.write_all(&buf).await
+}
+
+#[async_trait]
+impl request_response::Codec for WireCodec {
+    type Protocol = StreamProtocol;
+    type Request = WireRequest;
+    type Response = WireResponse;
+
+    async fn read_request<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<WireRequest>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_message(io).await
+    }
+
+    async fn read_response<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<WireResponse>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_message(io).await
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &StreamProtocol,
+        io: &mut T,
+        req: WireRequest,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_message(io, &req).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &StreamProtocol,
+        io: &mut T,
+        resp: WireResponse,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_message(io, &resp).await
+    }
+}
+
+#[derive(NetworkBehaviour)]
+struct Behaviour {
+    gossipsub: gossipsub::Behaviour,
+    request_response: request_response::Behaviour<WireCodec>,
+}
+
+type Responder = oneshot::Sender<Result<WireResponse, NodeError>>;
+
+enum Command {
+    Request {
+        addr: SocketAddr,
+        req: WireRequest,
+        resp: Responder,
+    },
+    Publish {
+        topic: Topic,
+        data: Vec<u8>,
+        resp: oneshot::Sender<Result<(), NodeError>>,
+    },
+    Respond {
+        channel: ResponseChannel<WireResponse>,
+        resp: WireResponse,
+    },
+    Validate {
+        id: MessageId,
+        source: PeerId,
+        acceptance: MessageAcceptance,
+    },
+}
+
+/// Sends the requests of the node to its peers through a `Libp2pService`
+#[derive(Clone)]
+pub struct Libp2pTransport {
+    commands: mpsc::UnboundedSender<Command>,
+    http: HttpTransport,
+}
+
+impl Libp2pTransport {
+    /// Starts listening for other nodes, their requests are handed to `incoming`. The
+    /// transport works as long as the returned service is served.
+    pub fn new(
+        opts: Libp2pOptions,
+        incoming: mpsc::UnboundedSender<NodeRequest>,
+    ) -> Result<(Self, Libp2pService), NodeError> {
+        let keypair = identity::Keypair::generate_ed25519();
+        let gossipsub = gossipsub::Behaviour::new(
+            gossipsub::MessageAuthenticity::Signed(keypair.clone()),
+            gossipsub::ConfigBuilder::default()
+                .validation_mode(gossipsub::ValidationMode::Strict)
+                .validate_messages()
+                .max_transmit_size(MAX_GOSSIP_SIZE)
+                // The same block pushed to every peer is only published once
+                .message_id_fn(|msg| MessageId::new(&Hasher::hash(&msg.data)))
+                .build()
+                .map_err(|e| NodeError::Libp2pError(e.to_string()))?,
+        )
+        .map_err(|e| NodeError::Libp2pError(e.into()))?;
+        let request_response = request_response::Behaviour::with_codec(
+            WireCodec,
+            [(PROTOCOL, ProtocolSupport::Full)],
+            request_response::Config::default().with_request_timeout(DEFAULT_REQUEST_DEADLINE),
+        );
+        let mut swarm = libp2p::SwarmBuilder::with_existing_identity(keypair)
+            .with_tokio()
+            .with_tcp(
+                tcp::Config::default(),
+                noise::Config::new,
+                yamux::Config::default,
+            )
+            .map_err(|e| NodeError::Libp2pError(e.to_string()))?
+            .with_behaviour(|_| Behaviour {
+                gossipsub,
+                request_response,
+            })
+            .map_err(|e| NodeError::Libp2pError(e.to_string()))?
+            .with_swarm_config(|c| c.with_idle_connection_timeout(IDLE_CONNECTION_TIMEOUT))
+            .build();
+        swarm
+            .listen_on(multiaddr(opts.listen))
+            .map_err(|e| NodeError::Libp2pError(e.to_string()))?;
+        for topic in Topic::ALL {
+            swarm
+                .behaviour_mut()
+                .gossipsub
+                .subscribe(&topic.ident())
+                .map_err(|e| NodeError::Libp2pError(e.to_string()))?;
+        }
+
+        let (command_sender, commands) = mpsc::unbounded_channel();
+        Ok((
+            Self {
+                commands: command_sender.clone(),
+                http: HttpTransport,
+            },
+            Libp2pService {
+                swarm,
+                commands,
+                command_sender,
+                incoming,
+                connections: HashMap::new(),
+                remotes: HashMap::new(),
+                dialing: HashMap::new(),
+                queued: HashMap::new(),
+                pending: HashMap::new(),
+            },
+        ))
+    }
+
+    async fn publish(&self, topic: Topic, req: Request<Body>) -> Result<Vec<u8>, NodeError> {
+        let (parts, body) = req.into_parts();
+        let msg = GossipMessage {
+            headers: encode_headers(&parts.headers),
+            body: hyper::body::to_bytes(body).await?.to_vec(),
+        };
+        let (resp_snd, resp_rcv) = oneshot::channel();
+        self.commands
+            .send(Command::Publish {
+                topic,
+                data: bincode::serialize(&msg)?,
+                resp: resp_snd,
+            })
+            .map_err(|_| NodeError::NotListeningError)?;
+        resp_rcv.await.map_err(|_| NodeError::NotAnsweringError)??;
+        topic.ack()
+    }
+}
+
+#[async_trait]
+impl Transport for Libp2pTransport {
+    async fn send(&self, req: Request<Body>) -> Result<Response<Body>, NodeError> {
+        let addr = match peer_of(&req) {
+            Some(addr) => addr,
+            None => return self.http.send(req).await,
+        };
+        if let Some(topic) = Topic::of(req.method(), req.uri().path()) {
+            return Ok(Response::new(Body::from(self.publish(topic, req).await?)));
+        }
+        let (resp_snd, resp_rcv) = oneshot::channel();
+        self.commands
+            .send(Command::Request {
+                addr,
+                req: WireRequest::from_request(req).await?,
+                resp: resp_snd,
+            })
+            .map_err(|_| NodeError::NotListeningError)?;
+        resp_rcv
+            .await
+            .map_err(|_| NodeError::NotAnsweringError)??
+            .into_response()
+    }
+}
+
+/// Drives the libp2p swarm of a `Libp2pTransport`
+pub struct Libp2pService {
+    swarm: Swarm<Behaviour>,
+    commands: mpsc::UnboundedReceiver<Command>,
+    command_sender: mpsc::UnboundedSender<Command>,
+    incoming: mpsc::UnboundedSender<NodeRequest>,
+    /// Peers connected through the address they listen on
+    connections: HashMap<SocketAddr, PeerId>,
+    /// Where the connected peers are connecting from
+    remotes: HashMap<PeerId, SocketAddr>,
+    dialing: HashMap<ConnectionId, SocketAddr>,
+    /// Requests waiting for a connection to their peer
+    queued: HashMap<SocketAddr, Vec<(WireRequest, Responder)>>,
+    pending: HashMap<OutboundRequestId, Responder>,
+}
+
+impl Libp2pService {
+    /// Serves the requests of the node and of its peers until `shutdown` resolves
+    pub async fn serve(mut self, shutdown: impl futures::Future<Output = ()>) {
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                cmd = self.commands.recv() => {
+                    if let Some(cmd) = cmd {
+                        self.on_command(cmd);
+                    }
+                }
+                event = self.swarm.select_next_some() => self.on_event(event),
+                _ = &mut shutdown => break,
+            }
+        }
+    }
+
+    fn on_command(&mut self, cmd: Command) {
+        let behaviour = self.swarm.behaviour_mut();
+        match cmd {
+            Command::Request { addr, req, resp } => {
+                if let Some(peer) = self.connections.get(&addr) {
+                    let id = behaviour.request_response.send_request(peer, req);
+                    self.pending.insert(id, resp);
+                } else {
+                    let queue = self.queued.entry(addr).or_default();
+                    queue.push((req, resp));
+                    if queue.len() == 1 {
+                        self.dial(addr);
+                    }
+                }
+            }
+            Command::Publish { topic, data, resp } => {
+                let published = match behaviour.gossipsub.publish(topic.ident(), data) {
+                    Ok(_) | Err(PublishError::Duplicate) => Ok(()),
+                    Err(e) => Err(NodeError::Libp2pError(e.to_string())),
+                };
+                let _ = resp.send(published);
+            }
+            Command::Respond { channel, resp } => {
+                if behaviour
+                    .request_response
+                    .send_response(channel, resp)
+                    .is_err()
+                {
+                    tracing::debug!("Peer left before its request was answered");
+                }
+            }
+            Command::Validate {
+                id,
+                source,
+                acceptance,
+            } => {
+                let _ = behaviour
+                    .gossipsub
+                    .report_message_validation_result(&id, &source, acceptance);
+            }
+        }
+    }
+
+    fn dial(&mut self, addr: SocketAddr) {
+        let opts = DialOpts::unknown_peer_id().address(multiaddr(addr)).build();
+        let id = opts.connection_id();
+        match self.swarm.dial(opts) {
+            Ok(()) => {
+                self.dialing.insert(id, addr);
+            }
+            Err(e) => self.fail_queued(addr, e.to_string()),
+        }
+    }
+
+    fn fail_queued(&mut self, addr: SocketAddr, reason: String) {
+        for (_, resp) in self.queued.remove(&addr).unwrap_or_default() {
+            let _ = resp.send(Err(NodeError::Libp2pError(reason.clone())));
+        }
+    }
+
+    fn on_event(&mut self, event: SwarmEvent<BehaviourEvent>) {
+        match event {
+            SwarmEvent::ConnectionEstablished {
+                peer_id,
+                connection_id,
+                endpoint,
+                ..
+            } => {
+                if let Some(remote) = socket_addr(endpoint.get_remote_address()) {
+                    self.remotes.insert(peer_id, remote);
+                }
+                if let Some(addr) = self.dialing.remove(&connection_id) {
+                    self.connections.insert(addr, peer_id);
+                    for (req, resp) in self.queued.remove(&addr).unwrap_or_default() {
+                        let id = self
+                            .swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_request(&peer_id, req);
+                        self.pending.insert(id, resp);
+                    }
+                }
+            }
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                error,
+                ..
+            } => {
+                if let Some(addr) = self.dialing.remove(&connection_id) {
+                    self.fail_queued(addr, error.to_string());
+                }
+            }
+            SwarmEvent::ConnectionClosed {
+                peer_id,
+                num_established: 0,
+                ..
+            } => {
+                self.connections.retain(|_, p| *p != peer_id);
+                self.remotes.remove(&peer_id);
+            }
+            SwarmEvent::Behaviour(BehaviourEvent::RequestResponse(event)) => match event {
+                request_response::Event::Message {
+                    peer,
+                    message:
+                        request_response::Message::Request {
+                            request, channel, ..
+                        },
+                } => match request.into_request() {
+                    Ok(req) => self.forward(peer, req, |resp| Command::Respond { channel, resp }),
+                    Err(e) => {
+                        let _ = self
+                            .swarm
+                            .behaviour_mut()
+                            .request_response
+                            .send_response(channel, WireResponse::error(&e));
+                    }
+                },
+                request_response::Event::Message {
+                    message:
+                        request_response::Message::Response {
+                            request_id,
+                            response,
+                        },
+                    ..
+                } => {
+                    if let Some(resp) = self.pending.remove(&request_id) {
+                        let _ = resp.send(Ok(response));
+                    }
+                }
+                request_response::Event::OutboundFailure {
+                    request_id, error, ..
+                } => {
+                    if let Some(resp) = self.pending.remove(&request_id) {
+                        let _ = resp.send(Err(NodeError::Libp2pError(error.to_string())));
+                    }
+                }
+                _ => {}
+            },
+            SwarmEvent::Behaviour(BehaviourEvent::Gossipsub(gossipsub::Event::Message {
+                propagation_source,
+                message_id,
+                message,
+            })) => {
+                let req = Topic::from_hash(&message.topic)
+                    .ok_or_else(|| NodeError::Libp2pError("unknown topic".into()))
+                    .and_then(|topic| {
+                        bincode::deserialize::<GossipMessage>(&message.data)?.into_request(topic)
+                    });
+                match req {
+                    // Only messages the node accepts are spread further
+                    Ok(req) => {
+                        self.forward(propagation_source, req, move |resp| Command::Validate {
+                            id: message_id,
+                            source: propagation_source,
+                            acceptance: if resp.status == StatusCode::OK.as_u16() {
+                                MessageAcceptance::Accept
+                            } else {
+                                MessageAcceptance::Ignore
+                            },
+                        })
+                    }
+                    Err(_) => {
+                        let _ = self
+                            .swarm
+                            .behaviour_mut()
+                            .gossipsub
+                            .report_message_validation_result(
+                                &message_id,
+                                &propagation_source,
+                                MessageAcceptance::Reject,
+                            );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Hands a request of the peer to the node, the answer of the node is turned into
+    /// a command through `reply`
+    fn forward<F>(&self, peer: PeerId, req: Request<Body>, reply: F)
+    where
+        F: FnOnce(WireResponse) -> Command + Send + 'static,
+    {
+        // Requests without a sender address are taken as local ones by the node
+        let remote = match self.remotes.get(&peer) {
+            Some(remote) => *remote,
+            None => {
+                let _ = self
+                    .command_sender
+                    .send(reply(WireResponse::error(&NodeError::SenderIpUnknown)));
+                return;
+            }
+        };
+        let (resp_snd, mut resp_rcv) = mpsc::unbounded_channel();
+        let req = NodeRequest {
+            limit: Limit::default(),
+            socket_addr: Some(remote),
+            body: req,
+            resp: resp_snd,
+        };
+        if self.incoming.send(req).is_err() {
+            tracing::debug!("Node not listening to the requests of its peers");
+            return;
+        }
+        let commands = self.command_sender.clone();
+        tokio::spawn(async move {
+            let resp = match resp_rcv.recv().await {
+                Some(resp) => WireResponse::from_response(resp).await,
+                None => WireResponse::error(&NodeError::NotAnsweringError),
+            };
+            let _ = commands.send(reply(resp));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(
+        port: u16,
+    ) -> (
+        Libp2pTransport,
+        mpsc::UnboundedReceiver<NodeRequest>,
+        oneshot::Sender<()>,
+    ) {
+        let (inc_send, inc_recv) = mpsc::unbounded_channel();
+        let (transport, service) = Libp2pTransport::new(
+            Libp2pOptions {
+                listen: SocketAddr::from(([127, 0, 0, 1], port)),
+            },
+            inc_send,
+        )
+        .unwrap();
+        let (stop_send, stop_recv) = oneshot::channel::<()>();
+        tokio::spawn(service.serve(async {
+            let _ = stop_recv.await;
+        }));
+        (transport, inc_recv, stop_send)
+    }
+
+    #[tokio::test]
+    async fn test_requests_and_gossip_reach_the_peer() {
+        let (alice, _, _stop_alice) = start(28811);
+        let (_bob, mut bob_incoming, _stop_bob) = start(28812);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let answer = tokio::spawn(async move {
+            let req = bob_incoming.recv().await.unwrap();
+            assert_eq!(req.body.uri().path(), "/bincode/mempool");
+            assert!(req.socket_addr.unwrap().ip().is_loopback());
+            let _ = req.resp.send(Ok(Response::new(Body::from("mempool"))));
+            bob_incoming
+        });
+        let resp = alice
+            .send(
+                Request::get("http://127.0.0.1:28812/bincode/mempool")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            hyper::body::to_bytes(resp.into_body()).await.unwrap(),
+            "mempool"
+        );
+        let mut bob_incoming = answer.await.unwrap();
+
+        // Subscriptions are exchanged over the connection made by the request above
+        let block = || {
+            Request::post("http://127.0.0.1:28812/bincode/blocks")
+                .body(Body::from("block"))
+                .unwrap()
+        };
+        let mut published = alice.send(block()).await;
+        for _ in 0..50 {
+            if published.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            published = alice.send(block()).await;
+        }
+        assert_eq!(
+            hyper::body::to_bytes(published.unwrap().into_body())
+                .await
+                .unwrap(),
+            bincode::serialize(&PostBlockResponse {}).unwrap()
+        );
+        let req = bob_incoming.recv().await.unwrap();
+        assert_eq!(req.body.method(), Method::POST);
+        assert_eq!(req.body.uri().path(), "/bincode/blocks");
+        let _ = req.resp.send(Ok(Response::new(Body::empty())));
+        assert_eq!(hyper::body::to_bytes(req.body).await.unwrap(), "block");
+
+        // Pushing the same block to another peer does not publish it again
+        assert!(alice.send(block()).await.is_ok());
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(bob_incoming.try_recv().is_err());
+    }
+}
@@ -0,0 +1,133 @@
+//! Keeps the listening port of a node behind a home router forwarded to it through
+//! UPnP IGD, so that other peers are able to connect to it.
+
+use igd_next::aio::{tokio::Tokio, Gateway};
+use igd_next::{AddPortError, PortMappingProtocol, RemovePortError, SearchError, SearchOptions};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use thiserror::Error;
+
+const MAPPING_DESCRIPTION: &str = "Bazuka node";
+
+/// Permanent mappings are refreshed too, in case the router is restarted and forgets
+/// them
+const PERMANENT_REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Error, Debug)]
+pub enum UpnpError {
+    #[error("no upnp gateway found: {0}")]
+    Search(#[from] SearchError),
+    #[error("cannot map the port: {0}")]
+    AddPort(#[from] AddPortError),
+    #[error("cannot remove the port mapping: {0}")]
+    RemovePort(#[from] RemovePortError),
+    #[error("io error happened: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct UpnpOptions {
+    /// Port of the node on this machine
+    pub local_port: u16,
+    /// Port opened on the router, the one peers connect to
+    pub external_port: u16,
+    /// Seconds the router keeps the mapping alive, zero asks for a permanent one
+    pub lease_duration: u32,
+}
+
+impl UpnpOptions {
+    /// Mappings are renewed halfway through their lease, so that a slow router never
+    /// lets them expire
+    pub fn renewal_interval(&self) -> Duration {
+        if self.lease_duration == 0 {
+            PERMANENT_REFRESH_INTERVAL
+        } else {
+            Duration::from_secs(std::cmp::max(self.lease_duration as u64 / 2, 1))
+        }
+    }
+}
+
+/// Address of this machine on the network of the gateway. No packet is sent,
+/// connecting a UDP socket only picks the interface routing to it.
+async fn local_ip(gateway: SocketAddr) -> Result<IpAddr, UpnpError> {
+    let unspecified = match gateway {
+        SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+    let socket = tokio::net::UdpSocket::bind((unspecified, 0)).await?;
+    socket.connect(gateway).await?;
+    Ok(socket.local_addr()?.ip())
+}
+
+async fn map_port(opts: &UpnpOptions) -> Result<Gateway<Tokio>, UpnpError> {
+    let gateway = igd_next::aio::tokio::search_gateway(SearchOptions::default()).await?;
+    let local_addr = SocketAddr::new(local_ip(gateway.addr).await?, opts.local_port);
+    gateway
+        .add_port(
+            PortMappingProtocol::TCP,
+            opts.external_port,
+            local_addr,
+            opts.lease_duration,
+            MAPPING_DESCRIPTION,
+        )
+        .await?;
+    Ok(gateway)
+}
+
+/// Maps the port and renews the mapping before its lease expires, until `shutdown`
+/// resolves. The mapping is removed from the router on the way out. Failures are
+/// only logged and retried, a node without a UPnP capable router still works.
+pub async fn serve(opts: UpnpOptions, shutdown: impl futures::Future<Output = ()>) {
+    let mut gateway = None;
+    tokio::pin!(shutdown);
+    loop {
+        match map_port(&opts).await {
+            Ok(gw) => {
+                if gateway.is_none() {
+                    tracing::info!(
+                        "Port {} is mapped to {} on the UPnP gateway {}",
+                        opts.local_port,
+                        opts.external_port,
+                        gw.addr
+                    );
+                }
+                gateway = Some(gw);
+            }
+            Err(e) => {
+                tracing::warn!("UPnP port mapping failed: {}", e);
+            }
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(opts.renewal_interval()) => {}
+            _ = &mut shutdown => break,
+        }
+    }
+    if let Some(gateway) = gateway {
+        match gateway
+            .remove_port(PortMappingProtocol::TCP, opts.external_port)
+            .await
+        {
+            Ok(()) => tracing::info!("UPnP port mapping removed"),
+            Err(e) => tracing::warn!("{}", UpnpError::from(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renewal_interval() {
+        let mut opts = UpnpOptions {
+            local_port: 8765,
+            external_port: 8765,
+            lease_duration: 3600,
+        };
+        assert_eq!(opts.renewal_interval(), Duration::from_secs(1800));
+        opts.lease_duration = 1;
+        assert_eq!(opts.renewal_interval(), Duration::from_secs(1));
+        opts.lease_duration = 0;
+        assert_eq!(opts.renewal_interval(), PERMANENT_REFRESH_INTERVAL);
+    }
+}
@@ -0,0 +1,378 @@
+use super::{ChainFollower, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::core::{Block, TransactionData};
+use crate::db::KvStore;
+use hmac::{Hmac, Mac};
+use hyper::{Body, Method, Request};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::VecDeque;
+
+/// Hex encoded HMAC-SHA256 of the request body, keyed with the webhook secret
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-ZIESHA-WEBHOOK-SIGNATURE";
+
+/// Deliveries are dropped after this many failed attempts
+const MAX_ATTEMPTS: u32 = 5;
+/// Seconds to wait before the first retry, doubled on each following one
+const RETRY_DELAY: u32 = 10;
+/// Oldest deliveries are dropped when the queue gets longer than this
+const MAX_PENDING: usize = 10000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    NewBlock,
+    Reorg,
+    AddressActivity,
+    PeerBanned,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookOptions {
+    /// Where the events are POSTed to, the endpoint should answer with `200 OK`
+    pub url: String,
+    /// Deliveries are signed with this key when given
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Events sent to this endpoint, all of them if empty
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+    /// Addresses watched for `address_activity` events
+    #[serde(default)]
+    pub addresses: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    NewBlock {
+        number: u64,
+        hash: String,
+    },
+    /// Blocks since `fork_height` were replaced
+    Reorg {
+        fork_height: u64,
+        old_height: u64,
+        old_tip: String,
+    },
+    AddressActivity {
+        address: String,
+        block: u64,
+        tx: String,
+    },
+    PeerBanned {
+        peer: String,
+        seconds: u32,
+        reason: String,
+    },
+}
+
+impl WebhookEvent {
+    pub fn kind(&self) -> WebhookEventKind {
+        match self {
+            Self::NewBlock { .. } => WebhookEventKind::NewBlock,
+            Self::Reorg { .. } => WebhookEventKind::Reorg,
+            Self::AddressActivity { .. } => WebhookEventKind::AddressActivity,
+            Self::PeerBanned { .. } => WebhookEventKind::PeerBanned,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    timestamp: u32,
+    #[serde(flatten)]
+    event: &'a WebhookEvent,
+}
+
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub url: String,
+    pub body: Vec<u8>,
+    pub signature: Option<String>,
+    attempts: u32,
+    next_attempt: u32,
+}
+
+impl Delivery {
+    pub fn request(&self) -> Result<Request<Body>, NodeError> {
+        let mut req = Request::builder()
+            .method(Method::POST)
+            .uri(&self.url)
+            .header("content-type", "application/json");
+        if let Some(sig) = &self.signature {
+            req = req.header(WEBHOOK_SIGNATURE_HEADER, sig);
+        }
+        Ok(req.body(Body::from(self.body.clone()))?)
+    }
+}
+
+/// Queues events for the registered endpoints and keeps them until they are
+/// delivered, or until they fail too many times.
+pub struct Webhooks {
+    hooks: Vec<WebhookOptions>,
+    follower: ChainFollower,
+    pending: VecDeque<Delivery>,
+}
+
+impl Webhooks {
+    pub fn new(hooks: Vec<WebhookOptions>) -> Self {
+        Self {
+            hooks,
+            follower: ChainFollower::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn emit(&mut self, now: u32, event: WebhookEvent) {
+        let kind = event.kind();
+        let body = serde_json::to_vec(&WebhookPayload {
+            timestamp: now,
+            event: &event,
+        })
+        .expect("webhook events are serializable");
+        for hook in self.hooks.iter() {
+            if !hook.events.is_empty() && !hook.events.contains(&kind) {
+                continue;
+            }
+            if let WebhookEvent::AddressActivity { address, .. } = &event {
+                if !hook.addresses.contains(address) {
+                    continue;
+                }
+            }
+            self.pending.push_back(Delivery {
+                url: hook.url.clone(),
+                signature: hook.secret.as_ref().map(|s| sign(s, &body)),
+                body: body.clone(),
+                attempts: 0,
+                next_attempt: now,
+            });
+        }
+        while self.pending.len() > MAX_PENDING {
+            if let Some(dropped) = self.pending.pop_front() {
+                tracing::warn!("Webhook queue is full, dropping event for {}", dropped.url);
+            }
+        }
+    }
+
+    fn emit_block(&mut self, now: u32, block: &Block) {
+        self.emit(
+            now,
+            WebhookEvent::NewBlock {
+                number: block.header.number,
+                hash: hex::encode(block.header.hash()),
+            },
+        );
+        if self.hooks.iter().all(|h| h.addresses.is_empty()) {
+            return;
+        }
+        for tx in block.body.iter() {
+            let mut addresses = Vec::new();
+            if let Some(src) = &tx.src {
+                addresses.push(src.to_string());
+            }
+            if let TransactionData::RegularSend { entries } = &tx.data {
+                addresses.extend(entries.iter().map(|e| e.dst.to_string()));
+            }
+            addresses.sort();
+            addresses.dedup();
+            for address in addresses {
+                self.emit(
+                    now,
+                    WebhookEvent::AddressActivity {
+                        address,
+                        block: block.header.number,
+                        tx: hex::encode(tx.hash()),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Emits the events of the blocks added (or replaced) since the last call.
+    /// Nothing is reported on the first call, history is not replayed.
+    pub fn update<K: KvStore, B: Blockchain<K>>(
+        &mut self,
+        chain: &B,
+        now: u32,
+    ) -> Result<(), BlockchainError> {
+        let update = self.follower.update(chain)?;
+        if let Some(reorg) = update.reorg {
+            self.emit(
+                now,
+                WebhookEvent::Reorg {
+                    fork_height: reorg.fork_height,
+                    old_height: reorg.old_tip.0 + 1,
+                    old_tip: hex::encode(reorg.old_tip.1),
+                },
+            );
+        }
+        for block in update.blocks.iter() {
+            self.emit_block(now, block);
+        }
+        Ok(())
+    }
+
+    /// Takes the deliveries that should be attempted now
+    pub fn due(&mut self, now: u32) -> Vec<Delivery> {
+        let (due, later): (VecDeque<_>, VecDeque<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|d| d.next_attempt <= now);
+        self.pending = later;
+        due.into_iter().collect()
+    }
+
+    /// Puts a failed delivery back into the queue, with an exponential backoff
+    pub fn retry(&mut self, mut delivery: Delivery, now: u32) {
+        delivery.attempts += 1;
+        if delivery.attempts >= MAX_ATTEMPTS {
+            tracing::warn!(
+                "Giving up delivering a webhook event to {} after {} attempts",
+                delivery.url,
+                delivery.attempts
+            );
+            return;
+        }
+        delivery.next_attempt = now + (RETRY_DELAY << (delivery.attempts - 1));
+        self.pending.push_back(delivery);
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Amount;
+    use crate::test_utils::*;
+
+    fn hook(url: &str) -> WebhookOptions {
+        WebhookOptions {
+            url: url.into(),
+            secret: None,
+            events: vec![],
+            addresses: vec![],
+        }
+    }
+
+    fn events(webhooks: &mut Webhooks, now: u32) -> Vec<WebhookEvent> {
+        webhooks
+            .due(now)
+            .into_iter()
+            .map(|d| serde_json::from_slice(&d.body).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_webhooks_follow_the_chain() {
+        let validator = validator_wallet();
+        let alice = wallet("ALICE");
+        let mut chain = funded_chain(&validator, &[(&alice, Amount(1_000_000))]);
+        let mut webhooks = Webhooks::new(vec![WebhookOptions {
+            addresses: vec![alice.get_address().to_string()],
+            ..hook("http://127.0.0.1:1234/hook")
+        }]);
+        webhooks.update(&chain, 0).unwrap();
+        assert_eq!(webhooks.pending(), 0);
+
+        let txs = random_transactions(&mut rand::thread_rng(), &chain, &alice, 2).unwrap();
+        let blk1 = mine_block(&mut chain, &validator, &txs).unwrap();
+        let blk2 = mine_block(&mut chain, &validator, &[]).unwrap();
+        webhooks.update(&chain, 0).unwrap();
+        let evs = events(&mut webhooks, 0);
+        assert_eq!(
+            evs[0],
+            WebhookEvent::NewBlock {
+                number: 1,
+                hash: hex::encode(blk1.header.hash())
+            }
+        );
+        assert_eq!(
+            evs.iter()
+                .filter(|e| e.kind() == WebhookEventKind::AddressActivity)
+                .count(),
+            2
+        );
+        assert_eq!(
+            evs.last(),
+            Some(&WebhookEvent::NewBlock {
+                number: 2,
+                hash: hex::encode(blk2.header.hash())
+            })
+        );
+
+        chain.rollback().unwrap();
+        chain.rollback().unwrap();
+        let fork = mine_block(&mut chain, &validator, &[]).unwrap();
+        webhooks.update(&chain, 0).unwrap();
+        assert_eq!(
+            events(&mut webhooks, 0),
+            vec![
+                WebhookEvent::Reorg {
+                    fork_height: 1,
+                    old_height: 3,
+                    old_tip: hex::encode(blk2.header.hash())
+                },
+                WebhookEvent::NewBlock {
+                    number: 1,
+                    hash: hex::encode(fork.header.hash())
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_webhooks_filter_sign_and_retry() {
+        let mut webhooks = Webhooks::new(vec![
+            WebhookOptions {
+                secret: Some("secret".into()),
+                events: vec![WebhookEventKind::PeerBanned],
+                ..hook("http://127.0.0.1:1234/a")
+            },
+            WebhookOptions {
+                events: vec![WebhookEventKind::NewBlock],
+                ..hook("http://127.0.0.1:1234/b")
+            },
+        ]);
+        webhooks.emit(
+            100,
+            WebhookEvent::PeerBanned {
+                peer: "1.2.3.4:8765".into(),
+                seconds: 60,
+                reason: "test".into(),
+            },
+        );
+        let mut due = webhooks.due(100);
+        assert_eq!(due.len(), 1);
+        let delivery = due.pop().unwrap();
+        assert_eq!(delivery.url, "http://127.0.0.1:1234/a");
+        assert_eq!(delivery.signature, Some(sign("secret", &delivery.body)),);
+        let req = delivery.request().unwrap();
+        assert_eq!(
+            req.headers()[WEBHOOK_SIGNATURE_HEADER],
+            sign("secret", &delivery.body).as_str()
+        );
+
+        webhooks.retry(delivery, 100);
+        assert!(webhooks.due(109).is_empty());
+        let delivery = webhooks.due(110).pop().unwrap();
+        webhooks.retry(delivery, 110);
+        assert!(webhooks.due(129).is_empty());
+        let mut delivery = webhooks.due(130).pop().unwrap();
+        for now in [200, 300] {
+            webhooks.retry(delivery, now);
+            delivery = webhooks.due(now + 1000).pop().unwrap();
+        }
+        webhooks.retry(delivery, 400);
+        assert_eq!(webhooks.pending(), 0);
+    }
+}
@@ -0,0 +1,141 @@
+//! Publisher socket modeled after the ZeroMQ interface of bitcoind. Every message
+//! has three frames: the topic, the body and a little-endian `u32` sequence number
+//! counted separately for each topic.
+
+use super::{ChainFollower, NodeError};
+use crate::blockchain::{Blockchain, BlockchainError};
+use crate::core::hash::Hash;
+use crate::core::{GeneralTransaction, Hasher};
+use crate::db::KvStore;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use zeromq::{Socket, SocketSend, ZmqMessage};
+
+/// Hash of each new block on the best chain
+pub const TOPIC_HASHBLOCK: &str = "hashblock";
+/// Hash of each transaction accepted into the mempool
+pub const TOPIC_HASHTX: &str = "hashtx";
+/// Bincode encoded `Block`
+pub const TOPIC_RAWBLOCK: &str = "rawblock";
+/// Bincode encoded `GeneralTransaction`
+pub const TOPIC_RAWTX: &str = "rawtx";
+
+pub struct Publisher {
+    chan: mpsc::UnboundedSender<ZmqMessage>,
+    follower: ChainFollower,
+    sequences: HashMap<&'static str, u32>,
+}
+
+impl Publisher {
+    pub fn new(chan: mpsc::UnboundedSender<ZmqMessage>) -> Self {
+        Self {
+            chan,
+            follower: ChainFollower::new(),
+            sequences: HashMap::new(),
+        }
+    }
+
+    fn publish(&mut self, topic: &'static str, body: Vec<u8>) {
+        let seq = self.sequences.entry(topic).or_insert(0);
+        let mut msg = ZmqMessage::from(topic);
+        msg.push_back(body.into());
+        msg.push_back(seq.to_le_bytes().to_vec().into());
+        *seq = seq.wrapping_add(1);
+        if self.chan.send(msg).is_err() {
+            tracing::debug!("ZeroMQ publisher is not running!");
+        }
+    }
+
+    /// Publishes the blocks added to the chain since the last call, including the
+    /// ones replacing a fork
+    pub fn on_chain<K: KvStore, B: Blockchain<K>>(
+        &mut self,
+        chain: &B,
+    ) -> Result<(), BlockchainError> {
+        for block in self.follower.update(chain)?.blocks {
+            self.publish(TOPIC_HASHBLOCK, block.header.hash().to_vec());
+            self.publish(TOPIC_RAWBLOCK, bincode::serialize(&block).unwrap());
+        }
+        Ok(())
+    }
+
+    pub fn on_tx(&mut self, tx: &GeneralTransaction) {
+        let raw = bincode::serialize(tx).unwrap();
+        let hash = match tx {
+            GeneralTransaction::TransactionAndDelta(tx_delta) => tx_delta.tx.hash(),
+            _ => Hasher::hash(&raw),
+        };
+        self.publish(TOPIC_HASHTX, hash.to_vec());
+        self.publish(TOPIC_RAWTX, raw);
+    }
+}
+
+/// Binds a publisher socket on `endpoint` (E.g `tcp://127.0.0.1:28332`) and sends
+/// out whatever the `Publisher` queues, until `shutdown` resolves
+pub async fn serve(
+    endpoint: &str,
+    mut msgs: mpsc::UnboundedReceiver<ZmqMessage>,
+    shutdown: impl futures::Future<Output = ()>,
+) -> Result<(), NodeError> {
+    let mut socket = zeromq::PubSocket::new();
+    socket.bind(endpoint).await?;
+    tracing::info!("Publishing ZeroMQ notifications on {}", endpoint);
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            msg = msgs.recv() => match msg {
+                Some(msg) => socket.send(msg).await?,
+                None => break,
+            },
+            _ = &mut shutdown => break,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Amount;
+    use crate::test_utils::*;
+    use zeromq::SocketRecv;
+
+    #[tokio::test]
+    async fn test_publishes_new_blocks() {
+        let validator = validator_wallet();
+        let alice = wallet("ALICE");
+        let mut chain = funded_chain(&validator, &[(&alice, Amount(1_000_000))]);
+
+        let (send, recv) = mpsc::unbounded_channel();
+        let mut publisher = Publisher::new(send);
+        let (stop_send, stop_recv) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            serve("tcp://127.0.0.1:28790", recv, async {
+                let _ = stop_recv.await;
+            })
+            .await
+        });
+        let mut sub = zeromq::SubSocket::new();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        sub.connect("tcp://127.0.0.1:28790").await.unwrap();
+        sub.subscribe(TOPIC_HASHBLOCK).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        publisher.on_chain(&chain).unwrap();
+        let blocks = mine_blocks(&mut chain, &validator, 2).unwrap();
+        publisher.on_chain(&chain).unwrap();
+
+        for (seq, block) in blocks.iter().enumerate() {
+            let msg = sub.recv().await.unwrap();
+            assert_eq!(msg.get(0).unwrap().as_ref(), TOPIC_HASHBLOCK.as_bytes());
+            assert_eq!(msg.get(1).unwrap().as_ref(), block.header.hash().as_slice());
+            assert_eq!(
+                msg.get(2).unwrap().as_ref(),
+                (seq as u32).to_le_bytes().as_slice()
+            );
+        }
+
+        stop_send.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+}
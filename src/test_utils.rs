@@ -0,0 +1,192 @@
+//! Helpers for writing integration tests against an in-memory chain.
+//!
+//! ```ignore
+//! use bazuka::blockchain::Blockchain;
+//! use bazuka::core::Amount;
+//! use bazuka::test_utils::*;
+//!
+//! let validator = validator_wallet();
+//! let alice = wallet("ALICE");
+//! let mut chain = funded_chain(&validator, &[(&alice, Amount(1_000_000))]);
+//! let txs = random_transactions(&mut rand::thread_rng(), &chain, &alice, 10).unwrap();
+//! mine_block(&mut chain, &validator, &txs).unwrap();
+//! assert_eq!(chain.get_nonce(alice.get_address()).unwrap(), 10);
+//! ```
+
+use crate::blockchain::{Blockchain, BlockchainConfig, BlockchainError, KvStoreChain};
+use crate::config::blockchain::{get_regtest_blockchain_config, ConsensusParams};
+use crate::core::{
+    Amount, Block, Money, RegularSendEntry, Signature, TokenId, Transaction, TransactionAndDelta,
+    TransactionData,
+};
+use crate::db::{KvStore, RamKvStore};
+use crate::wallet::TxBuilder;
+use rand::Rng;
+
+/// Deterministic wallet derived from the given seed
+pub fn wallet(seed: &str) -> TxBuilder {
+    TxBuilder::new(&Vec::from(seed))
+}
+
+/// Wallet of the only validator of chains built by [`funded_chain`]
+pub fn validator_wallet() -> TxBuilder {
+    wallet("VALIDATOR")
+}
+
+/// Regtest config (validator proofs are not checked and MPN is disabled) in which
+/// `validator` is the only staker and each of the `funded` wallets owns the given
+/// amount of Ziesha in the genesis block.
+pub fn funded_config(validator: &TxBuilder, funded: &[(&TxBuilder, Amount)]) -> BlockchainConfig {
+    let mut conf = get_regtest_blockchain_config(validator, validator, ConsensusParams::default());
+    if !funded.is_empty() {
+        conf.genesis.body.push(Transaction {
+            memo: "Test balances".into(),
+            src: None,
+            data: TransactionData::RegularSend {
+                entries: funded
+                    .iter()
+                    .map(|(wallet, amount)| RegularSendEntry {
+                        dst: wallet.get_address(),
+                        amount: Money {
+                            token_id: TokenId::Ziesha,
+                            amount: *amount,
+                        },
+                    })
+                    .collect(),
+            },
+            nonce: 0,
+            fee: Money::ziesha(0),
+            sig: Signature::Unsigned,
+        });
+    }
+    conf
+}
+
+/// In-memory chain built from [`funded_config`]
+pub fn funded_chain(
+    validator: &TxBuilder,
+    funded: &[(&TxBuilder, Amount)],
+) -> KvStoreChain<RamKvStore> {
+    KvStoreChain::new(RamKvStore::new(), funded_config(validator, funded)).unwrap()
+}
+
+/// `count` signed payments from `from` to random addresses with consecutive nonces,
+/// all of them applicable on top of the current state of `chain`.
+pub fn random_transactions<R: Rng, K: KvStore, B: Blockchain<K>>(
+    rng: &mut R,
+    chain: &B,
+    from: &TxBuilder,
+    count: usize,
+) -> Result<Vec<TransactionAndDelta>, BlockchainError> {
+    let nonce = chain.get_nonce(from.get_address())?;
+    let balance = chain.get_balance(from.get_address(), TokenId::Ziesha)?;
+    // Split the balance so that every transaction (amount + fee) can be paid
+    let budget = balance.0 / std::cmp::max(count as u64, 1);
+    if budget < 2 {
+        return Err(BlockchainError::BalanceInsufficient);
+    }
+    Ok((0..count)
+        .map(|i| {
+            let fee = rng.gen_range(0..budget / 2);
+            let amount = rng.gen_range(1..=budget - fee);
+            let dst = TxBuilder::new(&rng.gen::<[u8; 32]>()).get_address();
+            from.create_transaction(
+                "Random payment".into(),
+                dst,
+                Money::ziesha(amount),
+                Money::ziesha(fee),
+                nonce + 1 + i as u32,
+            )
+        })
+        .collect())
+}
+
+/// Builds a block on the next slot with `validator` as its producer, including the
+/// valid ones among `txs`, and appends it to `chain`.
+pub fn mine_block<K: KvStore, B: Blockchain<K>>(
+    chain: &mut B,
+    validator: &TxBuilder,
+    txs: &[TransactionAndDelta],
+) -> Result<Block, BlockchainError> {
+    let tip = chain.get_tip()?;
+    let timestamp = std::cmp::max(
+        tip.proof_of_stake.timestamp,
+        chain.config().chain_start_timestamp,
+    ) + chain.config().slot_duration;
+    let block = chain
+        .draft_block(timestamp, txs, validator, true)?
+        .ok_or(BlockchainError::UnelectedValidator)?;
+    chain.extend(block.header.number, std::slice::from_ref(&block))?;
+    Ok(block)
+}
+
+/// Mines `count` empty blocks on `chain`
+pub fn mine_blocks<K: KvStore, B: Blockchain<K>>(
+    chain: &mut B,
+    validator: &TxBuilder,
+    count: usize,
+) -> Result<Vec<Block>, BlockchainError> {
+    (0..count)
+        .map(|_| mine_block(chain, validator, &[]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn test_funded_chain() {
+        let validator = validator_wallet();
+        let alice = wallet("ALICE");
+        let bob = wallet("BOB");
+        let chain = funded_chain(&validator, &[(&alice, Amount(1000)), (&bob, Amount(20))]);
+        assert_eq!(
+            chain
+                .get_balance(alice.get_address(), TokenId::Ziesha)
+                .unwrap(),
+            Amount(1000)
+        );
+        assert_eq!(
+            chain
+                .get_balance(bob.get_address(), TokenId::Ziesha)
+                .unwrap(),
+            Amount(20)
+        );
+    }
+
+    #[test]
+    fn test_mine_random_transactions() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let validator = validator_wallet();
+        let alice = wallet("ALICE");
+        let mut chain = funded_chain(&validator, &[(&alice, Amount(1_000_000))]);
+
+        for round in 1..=3 {
+            let txs = random_transactions(&mut rng, &chain, &alice, 10).unwrap();
+            let block = mine_block(&mut chain, &validator, &txs).unwrap();
+            assert_eq!(block.header.number, round);
+            assert!(txs.iter().all(|tx| block.body.contains(&tx.tx)));
+            assert_eq!(
+                chain.get_nonce(alice.get_address()).unwrap(),
+                round as u32 * 10
+            );
+        }
+
+        mine_blocks(&mut chain, &validator, 5).unwrap();
+        assert_eq!(chain.get_height().unwrap(), 9);
+    }
+
+    #[test]
+    fn test_random_transactions_insufficient_balance() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let alice = wallet("ALICE");
+        let chain = funded_chain(&validator_wallet(), &[(&alice, Amount(10))]);
+        assert!(matches!(
+            random_transactions(&mut rng, &chain, &alice, 10),
+            Err(BlockchainError::BalanceInsufficient)
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+use super::{WalletCollection, WalletError};
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const KEYSTORE_VERSION: u8 = 1;
+const EXTENSION: &str = "wallet";
+
+/// Default scrypt cost, about a second on a laptop
+const DEFAULT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedWallet {
+    version: u8,
+    log_n: u8,
+    salt: [u8; 16],
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+}
+
+/// A directory of passphrase-encrypted wallets, one file per wallet name. The key is
+/// derived from the passphrase with scrypt and the serialized wallet is sealed with
+/// AES-256-GCM, so a wrong passphrase is detected instead of yielding garbage.
+pub struct Keystore {
+    dir: PathBuf,
+    log_n: u8,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], log_n: u8) -> Result<[u8; 32], WalletError> {
+    let params = scrypt::Params::new(log_n, SCRYPT_R, SCRYPT_P, 32)
+        .map_err(|_| WalletError::KeystoreCorrupted)?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .map_err(|_| WalletError::KeystoreCorrupted)?;
+    Ok(key)
+}
+
+impl Keystore {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            log_n: DEFAULT_LOG_N,
+        }
+    }
+
+    /// File of the wallet with the given name
+    pub fn path(&self, name: &str) -> Result<PathBuf, WalletError> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(WalletError::InvalidWalletName(name.into()));
+        }
+        Ok(self.dir.join(format!("{}.{}", name, EXTENSION)))
+    }
+
+    pub fn exists(&self, name: &str) -> Result<bool, WalletError> {
+        Ok(self.path(name)?.exists())
+    }
+
+    /// Names of the wallets in the keystore, sorted
+    pub fn list(&self) -> Result<Vec<String>, WalletError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some(EXTENSION) {
+                if let Some(name) = path.file_stem().and_then(|n| n.to_str()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Encrypts and writes the wallet, overwriting a wallet with the same name
+    pub fn save(
+        &self,
+        name: &str,
+        wallet: &WalletCollection,
+        passphrase: &str,
+    ) -> Result<(), WalletError> {
+        let path = self.path(name)?;
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let key = derive_key(passphrase, &salt, self.log_n)?;
+        let ciphertext = Aes256Gcm::new(&key.into())
+            .encrypt(&Nonce::from(nonce), bincode::serialize(wallet)?.as_ref())
+            .map_err(|_| WalletError::KeystoreCorrupted)?;
+        let encrypted = EncryptedWallet {
+            version: KEYSTORE_VERSION,
+            log_n: self.log_n,
+            salt,
+            nonce,
+            ciphertext,
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        let mut opts = fs::OpenOptions::new();
+        opts.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+        opts.open(path)?
+            .write_all(&bincode::serialize(&encrypted)?)?;
+        Ok(())
+    }
+
+    /// Like `save`, but fails if a wallet with the same name already exists
+    pub fn create(
+        &self,
+        name: &str,
+        wallet: &WalletCollection,
+        passphrase: &str,
+    ) -> Result<(), WalletError> {
+        if self.exists(name)? {
+            return Err(WalletError::WalletAlreadyExists(name.into()));
+        }
+        self.save(name, wallet, passphrase)
+    }
+
+    /// Decrypts the wallet with the given name
+    pub fn unlock(&self, name: &str, passphrase: &str) -> Result<WalletCollection, WalletError> {
+        let path = self.path(name)?;
+        if !path.exists() {
+            return Err(WalletError::WalletNotFound(name.into()));
+        }
+        let encrypted: EncryptedWallet = bincode::deserialize(&fs::read(path)?)?;
+        if encrypted.version != KEYSTORE_VERSION {
+            return Err(WalletError::KeystoreCorrupted);
+        }
+        let key = derive_key(passphrase, &encrypted.salt, encrypted.log_n)?;
+        let plaintext = Aes256Gcm::new(&key.into())
+            .decrypt(&Nonce::from(encrypted.nonce), encrypted.ciphertext.as_ref())
+            .map_err(|_| WalletError::WrongPassphrase)?;
+        Ok(bincode::deserialize(&plaintext)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystore(dir: &Path) -> Keystore {
+        Keystore {
+            dir: dir.to_path_buf(),
+            log_n: 4,
+        }
+    }
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("bazuka-keystore-{}", std::process::id()));
+        let ks = keystore(&dir);
+        let mut rng = rand_mnemonic::rngs::OsRng::new().unwrap();
+        let mut wallet = WalletCollection::create(&mut rng, None);
+        let addr = wallet.user(0).tx_builder().get_address();
+
+        assert!(ks.list().unwrap().is_empty());
+        ks.create("alice", &wallet, "secret").unwrap();
+        assert!(matches!(
+            ks.create("alice", &wallet, "secret"),
+            Err(WalletError::WalletAlreadyExists(_))
+        ));
+        assert!(matches!(
+            ks.create("../alice", &wallet, "secret"),
+            Err(WalletError::InvalidWalletName(_))
+        ));
+        ks.create("bob", &wallet, "other").unwrap();
+        assert_eq!(ks.list().unwrap(), vec!["alice", "bob"]);
+
+        let mut unlocked = ks.unlock("alice", "secret").unwrap();
+        assert_eq!(unlocked.mnemonic(), wallet.mnemonic());
+        assert_eq!(unlocked.user(0).tx_builder().get_address(), addr);
+
+        assert!(matches!(
+            ks.unlock("alice", "wrong"),
+            Err(WalletError::WrongPassphrase)
+        ));
+        assert!(matches!(
+            ks.unlock("carol", "secret"),
+            Err(WalletError::WalletNotFound(_))
+        ));
+
+        // The seed is not stored in the clear
+        let raw = fs::read(dir.join("alice.wallet")).unwrap();
+        let phrase = wallet.mnemonic().to_string();
+        assert!(!raw.windows(phrase.len()).any(|w| w == phrase.as_bytes()));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
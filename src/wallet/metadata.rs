@@ -0,0 +1,146 @@
+use super::WalletError;
+use crate::core::{Amount, Decimal, TokenId};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Money received by a transaction which the user doesn't want to spend, e.g. because
+/// it comes from a tainted source
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExcludedInflow {
+    pub token_id: TokenId,
+    pub amount: Decimal,
+}
+
+/// User notes about the transactions of a wallet. Kept in a file next to the wallet,
+/// so that the format of the wallet file itself doesn't change.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletMetadata {
+    labels: BTreeMap<String, String>,
+    excluded: BTreeMap<String, ExcludedInflow>,
+}
+
+impl WalletMetadata {
+    /// Path of the metadata file of the wallet stored in `wallet_path`
+    pub fn path<P: AsRef<Path>>(wallet_path: P) -> PathBuf {
+        let mut path = wallet_path.as_ref().as_os_str().to_owned();
+        path.push(".meta");
+        path.into()
+    }
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WalletError> {
+        if let Ok(mut f) = File::open(&path) {
+            let mut bytes = Vec::new();
+            f.read_to_end(&mut bytes)?;
+            Ok(bincode::deserialize(&bytes)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), WalletError> {
+        File::create(path)?.write_all(&bincode::serialize(self)?)?;
+        Ok(())
+    }
+    pub fn set_label(&mut self, tx_hash: String, label: String) {
+        self.labels.insert(tx_hash, label);
+    }
+    pub fn remove_label(&mut self, tx_hash: &str) -> Option<String> {
+        self.labels.remove(tx_hash)
+    }
+    pub fn label(&self, tx_hash: &str) -> Option<&str> {
+        self.labels.get(tx_hash).map(|l| l.as_str())
+    }
+    pub fn labels(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.labels.iter()
+    }
+    pub fn exclude(&mut self, tx_hash: String, inflow: ExcludedInflow) {
+        self.excluded.insert(tx_hash, inflow);
+    }
+    pub fn include(&mut self, tx_hash: &str) -> Option<ExcludedInflow> {
+        self.excluded.remove(tx_hash)
+    }
+    pub fn excluded(&self) -> impl Iterator<Item = (&String, &ExcludedInflow)> {
+        self.excluded.iter()
+    }
+    /// Sum of the excluded inflows of a token, which new sends should leave untouched
+    pub fn excluded_amount(&self, token_id: TokenId, decimals: u8) -> Amount {
+        Amount(
+            self.excluded
+                .values()
+                .filter(|inflow| inflow.token_id == token_id)
+                .fold(0u64, |sum, inflow| {
+                    sum.saturating_add(inflow.amount.to_amount(decimals).0)
+                }),
+        )
+    }
+    /// Part of `balance` which can be spent without touching the excluded inflows
+    pub fn spendable(&self, balance: Amount, token_id: TokenId, decimals: u8) -> Amount {
+        Amount(
+            balance
+                .0
+                .saturating_sub(self.excluded_amount(token_id, decimals).0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallet_metadata() {
+        let other_token = TokenId::Custom(123.into());
+        let mut meta = WalletMetadata::default();
+        meta.set_label("aa".into(), "rent".into());
+        meta.exclude(
+            "bb".into(),
+            ExcludedInflow {
+                token_id: TokenId::Ziesha,
+                amount: "1.5".parse().unwrap(),
+            },
+        );
+        meta.exclude(
+            "cc".into(),
+            ExcludedInflow {
+                token_id: TokenId::Ziesha,
+                amount: "2".parse().unwrap(),
+            },
+        );
+        meta.exclude(
+            "dd".into(),
+            ExcludedInflow {
+                token_id: other_token,
+                amount: "7".parse().unwrap(),
+            },
+        );
+        assert_eq!(meta.label("aa"), Some("rent"));
+        assert_eq!(meta.label("bb"), None);
+        assert_eq!(meta.excluded_amount(TokenId::Ziesha, 2), Amount(350));
+        assert_eq!(meta.excluded_amount(other_token, 0), Amount(7));
+        assert_eq!(
+            meta.spendable(Amount(1000), TokenId::Ziesha, 2),
+            Amount(650)
+        );
+        assert_eq!(meta.spendable(Amount(5), other_token, 0), Amount(0));
+
+        let dir = std::env::temp_dir().join(format!("bazuka-metadata-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let meta_path = WalletMetadata::path(dir.join("wallet"));
+        assert_eq!(meta_path, dir.join("wallet.meta"));
+        assert_eq!(
+            WalletMetadata::open(&meta_path).unwrap(),
+            WalletMetadata::default()
+        );
+        meta.save(&meta_path).unwrap();
+        let mut meta = WalletMetadata::open(&meta_path).unwrap();
+
+        assert!(meta.include("cc").is_some());
+        assert!(meta.include("cc").is_none());
+        assert_eq!(meta.excluded_amount(TokenId::Ziesha, 2), Amount(150));
+        assert_eq!(meta.remove_label("aa"), Some("rent".into()));
+        assert_eq!(meta.labels().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
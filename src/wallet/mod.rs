@@ -1,7 +1,11 @@
+mod keystore;
+mod metadata;
 mod tx_builder;
-pub use tx_builder::TxBuilder;
+pub use keystore::Keystore;
+pub use metadata::{ExcludedInflow, WalletMetadata};
+pub use tx_builder::{TransactionBuilder, TxBuilder};
 
-use crate::core::{GeneralTransaction, NonceGroup, TokenId};
+use crate::core::{Address, GeneralTransaction, NonceGroup, TokenId};
 
 use bip39::Mnemonic;
 use rand_core_mnemonic::{CryptoRng, RngCore};
@@ -40,8 +44,23 @@ pub enum WalletError {
     BincodeError(#[from] bincode::Error),
     #[error("io error happened: {0}")]
     BlockchainError(#[from] io::Error),
+    #[error("wrong passphrase")]
+    WrongPassphrase,
+    #[error("keystore file is corrupted")]
+    KeystoreCorrupted,
+    #[error("invalid wallet name: {0}")]
+    InvalidWalletName(String),
+    #[error("wallet {0} already exists in the keystore")]
+    WalletAlreadyExists(String),
+    #[error("wallet {0} not found in the keystore")]
+    WalletNotFound(String),
+    #[error("invalid mnemonic phrase: {0}")]
+    InvalidMnemonic(#[from] bip39::Error),
 }
 
+/// Number of words in newly generated mnemonic phrases (256 bits of entropy)
+pub const MNEMONIC_WORDS: usize = 24;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WalletCollection {
     mnemonic: Mnemonic,
@@ -55,16 +74,38 @@ impl WalletCollection {
     pub fn create<R: RngCore + CryptoRng>(rng: &mut R, mnemonic: Option<Mnemonic>) -> Self {
         Self {
             mnemonic: mnemonic.unwrap_or_else(|| {
-                Mnemonic::generate_in_with(rng, bip39::Language::English, 12).unwrap()
+                Mnemonic::generate_in_with(rng, bip39::Language::English, MNEMONIC_WORDS).unwrap()
             }),
             wallets: Default::default(),
         }
     }
+    /// A new wallet with a random 24-word mnemonic phrase
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self::create(rng, None)
+    }
+    /// Restores the wallet backed up by the given mnemonic phrase. Every account derived
+    /// from it has the same keys as in the original wallet. Phrases of 12 words, which
+    /// older wallets were generated with, are accepted too.
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, WalletError> {
+        Ok(Self {
+            mnemonic: Mnemonic::parse(phrase)?,
+            wallets: Default::default(),
+        })
+    }
     pub fn user(&mut self, index: usize) -> &mut Wallet {
         self.wallets
             .entry(WalletType::User(index))
             .or_insert(Wallet::new(WalletType::User(index), self.mnemonic.clone()))
     }
+    /// Keys of the user account with the given index, all derived from the same mnemonic.
+    /// Unlike `user`, does not start tracking transactions of the account.
+    pub fn derive(&self, index: usize) -> TxBuilder {
+        Wallet::new(WalletType::User(index), self.mnemonic.clone()).tx_builder()
+    }
+    /// Addresses of the first `count` user accounts
+    pub fn derive_addresses(&self, count: usize) -> Vec<Address> {
+        (0..count).map(|i| self.derive(i).get_address()).collect()
+    }
     pub fn validator(&mut self) -> &mut Wallet {
         self.wallets
             .entry(WalletType::Validator)
@@ -136,3 +177,39 @@ impl Wallet {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mnemonic_recovery() {
+        let mut rng = rand_mnemonic::rngs::OsRng::new().unwrap();
+        let mut wallet = WalletCollection::generate(&mut rng);
+        let phrase = wallet.mnemonic().to_string();
+        assert_eq!(phrase.split_whitespace().count(), MNEMONIC_WORDS);
+
+        let mut restored = WalletCollection::from_mnemonic(&phrase).unwrap();
+        assert_eq!(restored.derive_addresses(3), wallet.derive_addresses(3));
+        assert_eq!(
+            restored.validator().tx_builder().get_address(),
+            wallet.validator().tx_builder().get_address()
+        );
+
+        // Older wallets have 12-word phrases
+        let mut legacy = WalletCollection::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        assert_eq!(legacy.mnemonic().to_string().split_whitespace().count(), 12);
+        assert_ne!(
+            legacy.user(0).tx_builder().get_address(),
+            wallet.user(0).tx_builder().get_address()
+        );
+
+        assert!(matches!(
+            WalletCollection::from_mnemonic("not a valid mnemonic phrase"),
+            Err(WalletError::InvalidMnemonic(_))
+        ));
+    }
+}
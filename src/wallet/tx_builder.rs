@@ -1,11 +1,12 @@
 #[cfg(feature = "client")]
 use crate::client::{messages::ValidatorClaim, PeerAddress};
+#[cfg(feature = "client")]
+use crate::core::ValidatorProof;
 
 use crate::core::{
     hash::Hash, Address, Amount, ContractDeposit, ContractId, ContractUpdate, ContractWithdraw,
     Hasher, Money, MpnAddress, MpnDeposit, MpnWithdraw, Ratio, RegularSendEntry, Signature, Signer,
-    Token, TokenId, Transaction, TransactionAndDelta, TransactionData, ValidatorProof, Vrf,
-    ZkSigner,
+    Token, TokenId, Transaction, TransactionAndDelta, TransactionData, Vrf, ZkSigner,
 };
 use crate::crypto::SignatureScheme;
 use crate::crypto::VerifiableRandomFunction;
@@ -25,6 +26,48 @@ pub struct TxBuilder {
     zk_address: <ZkSigner as ZkSignatureScheme>::Pub,
 }
 
+/// Builds and signs a `Transaction` of any kind, filling in the state delta that
+/// contract transactions carry alongside them. Memo defaults to empty, fee to zero.
+pub struct TransactionBuilder<'a> {
+    tx_builder: &'a TxBuilder,
+    memo: String,
+    data: TransactionData,
+    fee: Money,
+    nonce: u32,
+}
+
+impl<'a> TransactionBuilder<'a> {
+    pub fn memo(mut self, memo: String) -> Self {
+        self.memo = memo;
+        self
+    }
+    pub fn fee(mut self, fee: Money) -> Self {
+        self.fee = fee;
+        self
+    }
+    pub fn nonce(mut self, nonce: u32) -> Self {
+        self.nonce = nonce;
+        self
+    }
+    pub fn build(self) -> TransactionAndDelta {
+        let state_delta = match &self.data {
+            TransactionData::CreateContract { state, .. } => state.as_ref().map(|s| s.as_delta()),
+            TransactionData::UpdateContract { delta, .. } => delta.clone(),
+            _ => None,
+        };
+        let mut tx = Transaction {
+            memo: self.memo,
+            src: Some(self.tx_builder.get_address()),
+            data: self.data,
+            nonce: self.nonce,
+            fee: self.fee,
+            sig: Signature::Unsigned,
+        };
+        self.tx_builder.sign_tx(&mut tx);
+        TransactionAndDelta { tx, state_delta }
+    }
+}
+
 impl TxBuilder {
     pub fn new(seed: &[u8]) -> Self {
         let (pk, sk) = Signer::generate_keys(seed);
@@ -69,6 +112,15 @@ impl TxBuilder {
         let bytes = bincode::serialize(&tx.sig_state_excluded()).unwrap();
         tx.sig = Signature::Signed(Signer::sign(&self.private_key, &bytes));
     }
+    pub fn transaction(&self, data: TransactionData) -> TransactionBuilder<'_> {
+        TransactionBuilder {
+            tx_builder: self,
+            memo: String::new(),
+            data,
+            fee: Money::default(),
+            nonce: 0,
+        }
+    }
     pub fn delegate(
         &self,
         memo: String,
@@ -77,23 +129,14 @@ impl TxBuilder {
         fee: Money,
         nonce: u32,
     ) -> TransactionAndDelta {
-        let mut tx = Transaction {
-            memo,
-            src: Some(self.get_address()),
-            data: TransactionData::Delegate {
-                to: address,
-                amount,
-            },
-            nonce,
-            fee,
-            sig: Signature::Unsigned,
-        };
-        self.sign_tx(&mut tx);
-
-        TransactionAndDelta {
-            tx,
-            state_delta: None,
-        }
+        self.transaction(TransactionData::Delegate {
+            to: address,
+            amount,
+        })
+        .memo(memo)
+        .fee(fee)
+        .nonce(nonce)
+        .build()
     }
     pub fn undelegate(
         &self,
@@ -103,23 +146,14 @@ impl TxBuilder {
         fee: Money,
         nonce: u32,
     ) -> TransactionAndDelta {
-        let mut tx = Transaction {
-            memo,
-            src: Some(self.get_address()),
-            data: TransactionData::Undelegate {
-                from: address,
-                amount,
-            },
-            nonce,
-            fee,
-            sig: Signature::Unsigned,
-        };
-        self.sign_tx(&mut tx);
-
-        TransactionAndDelta {
-            tx,
-            state_delta: None,
-        }
+        self.transaction(TransactionData::Undelegate {
+            from: address,
+            amount,
+        })
+        .memo(memo)
+        .fee(fee)
+        .nonce(nonce)
+        .build()
     }
     pub fn auto_delegate(
         &self,
@@ -129,20 +163,11 @@ impl TxBuilder {
         fee: Money,
         nonce: u32,
     ) -> TransactionAndDelta {
-        let mut tx = Transaction {
-            memo,
-            src: Some(self.get_address()),
-            data: TransactionData::AutoDelegate { to, ratio },
-            nonce,
-            fee,
-            sig: Signature::Unsigned,
-        };
-        self.sign_tx(&mut tx);
-
-        TransactionAndDelta {
-            tx,
-            state_delta: None,
-        }
+        self.transaction(TransactionData::AutoDelegate { to, ratio })
+            .memo(memo)
+            .fee(fee)
+            .nonce(nonce)
+            .build()
     }
     pub fn generate_random(
         &self,
@@ -164,23 +189,14 @@ impl TxBuilder {
         fee: Money,
         nonce: u32,
     ) -> TransactionAndDelta {
-        let mut tx = Transaction {
-            memo,
-            src: Some(self.get_address()),
-            data: TransactionData::UpdateStaker {
-                vrf_pub_key: self.vrf_public_key.clone(),
-                commission,
-            },
-            nonce,
-            fee,
-            sig: Signature::Unsigned,
-        };
-        self.sign_tx(&mut tx);
-
-        TransactionAndDelta {
-            tx,
-            state_delta: None,
-        }
+        self.transaction(TransactionData::UpdateStaker {
+            vrf_pub_key: self.vrf_public_key.clone(),
+            commission,
+        })
+        .memo(memo)
+        .fee(fee)
+        .nonce(nonce)
+        .build()
     }
     #[cfg(feature = "client")]
     pub fn claim_validator(
@@ -221,10 +237,8 @@ impl TxBuilder {
         fee: Money,
         nonce: u32,
     ) -> (TransactionAndDelta, TokenId) {
-        let mut tx = Transaction {
-            memo,
-            src: Some(self.get_address()),
-            data: TransactionData::CreateToken {
+        let tx_delta = self
+            .transaction(TransactionData::CreateToken {
                 token: Token {
                     name,
                     symbol,
@@ -232,21 +246,13 @@ impl TxBuilder {
                     supply,
                     decimals,
                 },
-            },
-            nonce,
-            fee,
-            sig: Signature::Unsigned,
-        };
-        self.sign_tx(&mut tx);
-
-        let token_id = TokenId::new(&tx);
-        (
-            TransactionAndDelta {
-                tx,
-                state_delta: None,
-            },
-            token_id,
-        )
+            })
+            .memo(memo)
+            .fee(fee)
+            .nonce(nonce)
+            .build();
+        let token_id = TokenId::new(&tx_delta.tx);
+        (tx_delta, token_id)
     }
     pub fn create_multi_transaction(
         &self,
@@ -255,19 +261,11 @@ impl TxBuilder {
         fee: Money,
         nonce: u32,
     ) -> TransactionAndDelta {
-        let mut tx = Transaction {
-            memo,
-            src: Some(self.get_address()),
-            data: TransactionData::RegularSend { entries },
-            nonce,
-            fee,
-            sig: Signature::Unsigned,
-        };
-        self.sign_tx(&mut tx);
-        TransactionAndDelta {
-            tx,
-            state_delta: None,
-        }
+        self.transaction(TransactionData::RegularSend { entries })
+            .memo(memo)
+            .fee(fee)
+            .nonce(nonce)
+            .build()
     }
     pub fn create_mpn_transaction(
         &self,
@@ -297,22 +295,14 @@ impl TxBuilder {
         fee: Money,
         nonce: u32,
     ) -> TransactionAndDelta {
-        let mut tx = Transaction {
-            memo,
-            src: Some(self.get_address()),
-            data: TransactionData::CreateContract {
-                contract,
-                state: Some(initial_state.clone()),
-            },
-            nonce,
-            fee,
-            sig: Signature::Unsigned,
-        };
-        self.sign_tx(&mut tx);
-        TransactionAndDelta {
-            tx,
-            state_delta: Some(initial_state.as_delta()),
-        }
+        self.transaction(TransactionData::CreateContract {
+            contract,
+            state: Some(initial_state),
+        })
+        .memo(memo)
+        .fee(fee)
+        .nonce(nonce)
+        .build()
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -328,28 +318,20 @@ impl TxBuilder {
         miner_fee: Money,
         nonce: u32,
     ) -> TransactionAndDelta {
-        let mut tx = Transaction {
-            memo,
-            src: Some(self.get_address()),
-            data: TransactionData::UpdateContract {
-                contract_id,
-                updates: vec![ContractUpdate::FunctionCall {
-                    function_id,
-                    next_state,
-                    proof,
-                    fee: exec_fee,
-                }],
-                delta: Some(state_delta.clone()),
-            },
-            nonce,
-            fee: miner_fee,
-            sig: Signature::Unsigned,
-        };
-        self.sign_tx(&mut tx);
-        TransactionAndDelta {
-            tx,
-            state_delta: Some(state_delta),
-        }
+        self.transaction(TransactionData::UpdateContract {
+            contract_id,
+            updates: vec![ContractUpdate::FunctionCall {
+                function_id,
+                next_state,
+                proof,
+                fee: exec_fee,
+            }],
+            delta: Some(state_delta),
+        })
+        .memo(memo)
+        .fee(miner_fee)
+        .nonce(nonce)
+        .build()
     }
 
     #[allow(clippy::too_many_arguments)]
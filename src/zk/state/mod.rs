@@ -153,7 +153,11 @@ impl<H: ZkHasher> KvStoreStateManager<H> {
         indices.sort_unstable();
         indices.dedup();
         let mut accs = Vec::new();
-        for ind in indices.into_iter().skip(page_size * page).take(page_size) {
+        for ind in indices
+            .into_iter()
+            .skip(page_size.saturating_mul(page))
+            .take(page_size)
+        {
             accs.push((
                 ind,
                 KvStoreStateManager::<H>::get_mpn_account::<K>(db, mpn_contract_id, ind)?,